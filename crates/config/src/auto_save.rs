@@ -0,0 +1,254 @@
+//! 状態の自動保存
+//!
+//! 状態が変わったら `mark_dirty` で知らせると、最初の変更から自動保存の間隔だけ
+//! 待ってまとめて1回保存します。異常終了しても失われるのは最大で1間隔分の変更です。
+//! 終了時は `shutdown` で未保存の変更を書き出します。
+//! 操作のたびに変わる小さなファイルは `DeferredSave` で最新の内容だけを保存します。
+
+use crate::state_persistence::StatePersistenceConfig;
+use rust_explorer_utils::AppError;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// 保存処理
+type SaveFn = Box<dyn Fn() -> Result<(), AppError> + Send + Sync>;
+
+/// スケジューラーの状態
+#[derive(Debug, Default)]
+struct SchedulerState {
+    /// 未保存の変更があるか
+    dirty: bool,
+    /// 停止したか
+    stopped: bool,
+    /// 保存中か
+    saving: bool,
+}
+
+/// 保存スレッドと共有する部分
+struct Shared {
+    state: Mutex<SchedulerState>,
+    wake: Condvar,
+    save: SaveFn,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, SchedulerState> {
+        // 保存処理がパニックしても状態は壊れないため、そのまま使い続ける
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 未保存の変更があれば保存し、失敗したら次の機会に保存し直せるよう変更を残す
+    ///
+    /// 保存は同時に1つだけ行う。ほかの保存が書き込み中なら終わるまで待つため、
+    /// `flush` が保存スレッドの書き込み途中で戻ることはない。
+    fn save_dirty(&self, mut state: MutexGuard<'_, SchedulerState>) -> Result<(), AppError> {
+        while state.saving {
+            state = self.wake.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+        if !std::mem::take(&mut state.dirty) {
+            return Ok(());
+        }
+        state.saving = true;
+        drop(state);
+
+        let saving = SavingGuard { shared: self };
+        let result = (self.save)();
+        drop(saving);
+        if result.is_err() {
+            self.lock().dirty = true;
+        }
+        result
+    }
+}
+
+/// 保存が終わったことを知らせる（保存処理がパニックしても待っている側を止めない）
+struct SavingGuard<'a> {
+    shared: &'a Shared,
+}
+
+impl Drop for SavingGuard<'_> {
+    fn drop(&mut self) {
+        self.shared.lock().saving = false;
+        self.shared.wake.notify_all();
+    }
+}
+
+/// 変更を知らせるハンドル（状態変更のコールバックへ渡す）
+#[derive(Clone)]
+pub struct AutoSaveHandle {
+    shared: Arc<Shared>,
+}
+
+impl AutoSaveHandle {
+    /// 未保存の変更があることを知らせる
+    pub fn mark_dirty(&self) {
+        let mut state = self.shared.lock();
+        if !state.stopped {
+            state.dirty = true;
+            self.shared.wake.notify_all();
+        }
+    }
+}
+
+/// 自動保存のスケジューラー
+///
+/// 自動保存が無効な場合も変更は記録し、`flush` と `shutdown` で保存する。
+pub struct AutoSaveScheduler {
+    shared: Arc<Shared>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl AutoSaveScheduler {
+    /// 永続化設定の間隔で自動保存するスケジューラーを作成
+    pub fn new(
+        config: &StatePersistenceConfig,
+        save: impl Fn() -> Result<(), AppError> + Send + Sync + 'static,
+    ) -> Self {
+        let interval = config
+            .auto_save_enabled
+            .then(|| Duration::from_secs(config.auto_save_interval.max(1)));
+        Self::with_interval(interval, save)
+    }
+
+    /// 指定した間隔で自動保存するスケジューラーを作成（None なら自動保存しない）
+    pub fn with_interval(
+        interval: Option<Duration>,
+        save: impl Fn() -> Result<(), AppError> + Send + Sync + 'static,
+    ) -> Self {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(SchedulerState::default()),
+            wake: Condvar::new(),
+            save: Box::new(save),
+        });
+        let worker = interval.and_then(|interval| {
+            let shared = shared.clone();
+            std::thread::Builder::new()
+                .name("rust-explorer-auto-save".to_string())
+                .spawn(move || run_worker(&shared, interval))
+                .map_err(|e| eprintln!("自動保存スレッドの起動エラー: {}", e))
+                .ok()
+        });
+        Self { shared, worker }
+    }
+
+    /// 変更を知らせるハンドル
+    pub fn handle(&self) -> AutoSaveHandle {
+        AutoSaveHandle {
+            shared: self.shared.clone(),
+        }
+    }
+
+    /// 未保存の変更があることを知らせる
+    pub fn mark_dirty(&self) {
+        self.handle().mark_dirty();
+    }
+
+    /// 未保存の変更があるか
+    pub fn is_dirty(&self) -> bool {
+        self.shared.lock().dirty
+    }
+
+    /// 未保存の変更があればすぐに保存（保存スレッドが保存中なら終わるまで待つ）
+    pub fn flush(&self) -> Result<(), AppError> {
+        self.shared.save_dirty(self.shared.lock())
+    }
+
+    /// 自動保存を止め、未保存の変更を保存
+    pub fn shutdown(mut self) -> Result<(), AppError> {
+        self.stop();
+        self.flush()
+    }
+
+    /// 保存スレッドを止めて終了を待つ（保存中ならその保存は最後まで行う）
+    fn stop(&mut self) {
+        self.shared.lock().stopped = true;
+        self.shared.wake.notify_all();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Drop for AutoSaveScheduler {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// 最新の内容だけを間隔を空けてまとめて保存する
+///
+/// 呼び出し側は内容を預けるだけで、書き込みは保存スレッドで行う。
+/// 終了時は `flush` で預けたままの内容を書き出す。
+pub struct DeferredSave<T> {
+    latest: Arc<Mutex<Option<T>>>,
+    scheduler: AutoSaveScheduler,
+}
+
+impl<T: Send + 'static> DeferredSave<T> {
+    /// 最初に預けてから `interval` 後に保存する
+    pub fn new(
+        interval: Duration,
+        save: impl Fn(&T) -> Result<(), AppError> + Send + Sync + 'static,
+    ) -> Self {
+        let latest: Arc<Mutex<Option<T>>> = Arc::new(Mutex::new(None));
+        let pending = latest.clone();
+        let scheduler = AutoSaveScheduler::with_interval(Some(interval), move || {
+            let lock = || pending.lock().unwrap_or_else(|e| e.into_inner());
+            let Some(value) = lock().take() else {
+                return Ok(());
+            };
+            save(&value).inspect_err(|_| {
+                // 保存し直せるよう戻す（その間に預けられた新しい内容があればそちらを残す）
+                lock().get_or_insert(value);
+            })
+        });
+        Self { latest, scheduler }
+    }
+
+    /// 保存する内容を預ける（前に預けた未保存の内容は置き換える）
+    pub fn save(&self, value: T) {
+        *self.latest.lock().unwrap_or_else(|e| e.into_inner()) = Some(value);
+        self.scheduler.mark_dirty();
+    }
+
+    /// 預けたままの内容があればすぐに保存
+    ///
+    /// 保存スレッドが書き込み中なら、その書き込みが終わってから戻る。
+    pub fn flush(&self) -> Result<(), AppError> {
+        self.scheduler.flush()
+    }
+}
+
+/// 保存スレッドの本体
+fn run_worker(shared: &Shared, interval: Duration) {
+    let mut state = shared.lock();
+    loop {
+        while !state.dirty && !state.stopped {
+            state = shared.wake.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+
+        // 最初の変更から間隔分待ち、その間の変更をまとめて保存する
+        let deadline = Instant::now() + interval;
+        while !state.stopped {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            state = shared
+                .wake
+                .wait_timeout(state, deadline - now)
+                .unwrap_or_else(|e| e.into_inner())
+                .0;
+        }
+        // 停止時の保存は shutdown で行う
+        if state.stopped {
+            return;
+        }
+
+        if let Err(e) = shared.save_dirty(state) {
+            eprintln!("状態の自動保存エラー: {}", e);
+        }
+        state = shared.lock();
+    }
+}
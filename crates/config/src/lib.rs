@@ -4,11 +4,24 @@
 
 #![allow(clippy::result_large_err)]
 
+pub mod auto_save;
+pub mod paths;
 pub mod settings;
 pub mod state_persistence;
 
 #[cfg(test)]
 mod tests;
 
-pub use settings::Settings;
-pub use state_persistence::{StatePersistenceConfig, StatePersistenceManager, state_helpers};
+pub use auto_save::{AutoSaveHandle, AutoSaveScheduler, DeferredSave};
+pub use paths::{
+    AppPaths, PORTABLE_DATA_DIR, PORTABLE_FLAG, PORTABLE_MARKER_FILE, PathMode, app_paths,
+    init_app_paths,
+};
+pub use settings::{
+    CURRENT_SETTINGS_VERSION, SETTINGS_FILE_NAME, Settings, SettingsChange, SettingsWarning,
+};
+pub use state_persistence::{
+    BOOKMARKS_FILE, HISTORY_FILE, MAX_WORKSPACE_NAME_LEN, SEARCH_INDEX_FILE,
+    SESSION_RUNNING_MARKER, SESSION_STATE_FILE, SMART_FOLDERS_FILE, StateBackup,
+    StatePersistenceConfig, StatePersistenceManager, TAGS_FILE, UNDO_JOURNAL_FILE, state_helpers,
+};
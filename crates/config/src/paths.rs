@@ -0,0 +1,170 @@
+//! 設定・状態・ログの保存先
+//!
+//! 通常は XDG の設定ディレクトリなどを使います。ポータブルモードでは
+//! 実行ファイルの隣のディレクトリにすべてをまとめ、環境を汚さずに持ち運べます。
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// ポータブルモードを有効にするコマンドライン引数
+pub const PORTABLE_FLAG: &str = "--portable";
+
+/// 実行ファイルの隣に置くとポータブルモードになるマーカーファイル
+pub const PORTABLE_MARKER_FILE: &str = "rust-explorer.portable";
+
+/// ポータブルモードでデータを保存するディレクトリ名（実行ファイルの隣に作成）
+pub const PORTABLE_DATA_DIR: &str = "rust-explorer-data";
+
+/// 保存先の解決方法
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathMode {
+    /// XDG などのユーザーディレクトリを使う
+    Standard,
+    /// 指定したディレクトリの下にすべて保存する
+    Portable { root: PathBuf },
+}
+
+/// アプリケーションの保存先
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppPaths {
+    mode: PathMode,
+}
+
+impl AppPaths {
+    /// 通常モードの保存先
+    pub fn standard() -> Self {
+        Self {
+            mode: PathMode::Standard,
+        }
+    }
+
+    /// `root` の下にすべて保存するポータブルモードの保存先
+    pub fn portable(root: impl Into<PathBuf>) -> Self {
+        Self {
+            mode: PathMode::Portable { root: root.into() },
+        }
+    }
+
+    /// コマンドライン引数と実行ファイルの場所からモードを判定
+    pub fn detect() -> Self {
+        let exe_dir = std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf));
+        Self::detect_from(std::env::args().skip(1), exe_dir.as_deref())
+    }
+
+    /// 引数と実行ファイルのディレクトリからモードを判定
+    ///
+    /// `--portable` が指定されるか、実行ファイルの隣にマーカーファイルがあれば
+    /// ポータブルモードになる。
+    pub fn detect_from(
+        args: impl IntoIterator<Item = impl AsRef<str>>,
+        exe_dir: Option<&Path>,
+    ) -> Self {
+        let Some(exe_dir) = exe_dir else {
+            return Self::standard();
+        };
+        let flag = args.into_iter().any(|arg| arg.as_ref() == PORTABLE_FLAG);
+        if flag || exe_dir.join(PORTABLE_MARKER_FILE).is_file() {
+            Self::portable(exe_dir.join(PORTABLE_DATA_DIR))
+        } else {
+            Self::standard()
+        }
+    }
+
+    /// 保存先の解決方法
+    pub fn mode(&self) -> &PathMode {
+        &self.mode
+    }
+
+    /// ポータブルモードか
+    pub fn is_portable(&self) -> bool {
+        matches!(self.mode, PathMode::Portable { .. })
+    }
+
+    /// 設定ファイルのディレクトリ
+    pub fn config_dir(&self) -> PathBuf {
+        match &self.mode {
+            PathMode::Standard => dirs::config_dir()
+                .map(|dir| dir.join("rust-explorer"))
+                .unwrap_or_else(|| PathBuf::from(".")),
+            PathMode::Portable { root } => root.clone(),
+        }
+    }
+
+    /// 状態ファイルのディレクトリ
+    pub fn state_dir(&self) -> PathBuf {
+        self.config_dir().join("state")
+    }
+
+    /// ログのディレクトリ
+    pub fn log_dir(&self) -> PathBuf {
+        match &self.mode {
+            PathMode::Standard => PathBuf::from("./logs"),
+            PathMode::Portable { root } => root.join("logs"),
+        }
+    }
+
+    /// クラッシュレポートのディレクトリ
+    pub fn crash_reports_dir(&self) -> PathBuf {
+        match &self.mode {
+            PathMode::Standard => PathBuf::from("./crash_reports"),
+            PathMode::Portable { root } => root.join("crash_reports"),
+        }
+    }
+}
+
+/// プロセス全体で使う保存先
+static APP_PATHS: OnceLock<AppPaths> = OnceLock::new();
+
+/// プロセス全体の保存先を設定（最初の呼び出しのみ有効で、設定済みの値を返す）
+pub fn init_app_paths(paths: AppPaths) -> &'static AppPaths {
+    APP_PATHS.get_or_init(|| paths)
+}
+
+/// プロセス全体の保存先（未設定なら引数と実行ファイルの場所から判定する）
+pub fn app_paths() -> &'static AppPaths {
+    APP_PATHS.get_or_init(AppPaths::detect)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_portable_by_flag() {
+        let exe_dir = Path::new("/opt/rust-explorer");
+        let paths = AppPaths::detect_from(["--portable"], Some(exe_dir));
+        assert!(paths.is_portable());
+        assert_eq!(paths.config_dir(), exe_dir.join(PORTABLE_DATA_DIR));
+
+        let paths = AppPaths::detect_from(["--verbose"], Some(exe_dir));
+        assert_eq!(paths, AppPaths::standard());
+    }
+
+    #[test]
+    fn test_detect_portable_by_marker() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let no_args: [&str; 0] = [];
+        assert!(!AppPaths::detect_from(no_args, Some(temp_dir.path())).is_portable());
+
+        std::fs::write(temp_dir.path().join(PORTABLE_MARKER_FILE), "").unwrap();
+        assert!(AppPaths::detect_from(no_args, Some(temp_dir.path())).is_portable());
+    }
+
+    #[test]
+    fn test_portable_paths_share_one_root() {
+        let root = PathBuf::from("/media/usb/rust-explorer-data");
+        let paths = AppPaths::portable(&root);
+
+        for dir in [
+            paths.config_dir(),
+            paths.state_dir(),
+            paths.log_dir(),
+            paths.crash_reports_dir(),
+        ] {
+            assert!(dir.starts_with(&root), "{}", dir.display());
+        }
+        assert_eq!(paths.state_dir(), root.join("state"));
+    }
+}
@@ -1,12 +1,82 @@
 //! アプリケーション設定
+//!
+//! 設定は設定ディレクトリの `settings.json` に保存します。ファイルには
+//! スキーマのバージョンを記録し、古いファイルは読み込み時に移行します。
 
+use crate::paths::app_paths;
+use crate::state_persistence::{StatePersistenceConfig, StatePersistenceManager};
 use rust_explorer_utils::AppError;
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::path::PathBuf;
 
+/// 設定ファイル名
+pub const SETTINGS_FILE_NAME: &str = "settings.json";
+
+/// 現在の設定ファイルのスキーマバージョン
+pub const CURRENT_SETTINGS_VERSION: u32 = 4;
+
+/// バージョン `n` のファイルを `n + 1` へ移行する処理（添字がバージョン）
+const MIGRATIONS: &[fn(&mut Map<String, Value>)] = &[
+    migrate_v0_to_v1,
+    migrate_v1_to_v2,
+    migrate_v2_to_v3,
+    migrate_v3_to_v4,
+];
+
+/// 設定ファイルを読み込んだ際の警告
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettingsWarning {
+    /// 未知のフィールド（無視した）
+    UnknownField(String),
+    /// 欠けているフィールド（デフォルト値を使った）
+    MissingField(String),
+    /// 型の合わない値（デフォルト値を使った）
+    InvalidValue(String),
+    /// 対応しているより新しいバージョンのファイル
+    NewerVersion(u32),
+}
+
+impl std::fmt::Display for SettingsWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SettingsWarning::UnknownField(key) => {
+                write!(f, "Unknown setting '{}' was ignored", key)
+            }
+            SettingsWarning::MissingField(key) => {
+                write!(f, "Setting '{}' is missing, using the default", key)
+            }
+            SettingsWarning::InvalidValue(key) => {
+                write!(f, "Invalid value for setting '{}' was ignored", key)
+            }
+            SettingsWarning::NewerVersion(version) => write!(
+                f,
+                "Settings version {} is newer than supported version {}",
+                version, CURRENT_SETTINGS_VERSION
+            ),
+        }
+    }
+}
+
+/// 実行中に反映できる設定の変更
+#[derive(Debug, Clone, PartialEq)]
+pub enum SettingsChange {
+    /// テーマ（true ならダーク）
+    Theme { dark: bool },
+    /// 最小ウィンドウサイズ
+    MinWindowSize { width: u32, height: u32 },
+    /// デフォルトディレクトリ
+    DefaultDirectory(Option<PathBuf>),
+    /// 履歴に記録しない場所
+    HistoryExclusions(Vec<String>),
+}
+
 /// アプリケーション設定
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Settings {
+    /// 設定ファイルのスキーマバージョン
+    pub version: u32,
     /// ウィンドウ幅
     pub window_width: u32,
     /// ウィンドウ高さ
@@ -25,11 +95,18 @@ pub struct Settings {
     pub dark_theme: bool,
     /// デフォルトディレクトリ
     pub default_directory: Option<PathBuf>,
+    /// 高速検索の索引を作るディレクトリ（空なら索引を作らない）
+    pub search_roots: Vec<PathBuf>,
+    /// タグを拡張属性 `user.xdg.tags` にも書き出すか
+    pub mirror_tags_to_xattr: bool,
+    /// 履歴に記録しない場所（`*` などを含まなければそのパスと中身、含めばグロブ）
+    pub history_exclusions: Vec<String>,
 }
 
 impl Default for Settings {
     fn default() -> Self {
         Self {
+            version: CURRENT_SETTINGS_VERSION,
             window_width: 1200,
             window_height: 800,
             window_x: None,
@@ -39,25 +116,222 @@ impl Default for Settings {
             window_maximized: false,
             dark_theme: true,
             default_directory: None,
+            search_roots: Vec::new(),
+            mirror_tags_to_xattr: false,
+            history_exclusions: default_history_exclusions(),
         }
     }
 }
 
 impl Settings {
-    /// 設定を読み込み
+    /// 設定ディレクトリから設定を読み込み
     pub fn load() -> Result<Self, AppError> {
-        // 将来的にはJSONファイルから設定を読み込む
-        // 現在はデフォルト設定を返す
-        Ok(Self::default())
+        Self::load_from(&settings_manager()?)
     }
 
-    /// 設定を保存
+    /// 設定ディレクトリへ設定を保存（前回のファイルはバックアップされる）
     pub fn save(&self) -> Result<(), AppError> {
-        // 将来的にはJSONファイルに設定を保存する
-        // 現在は何もしない
+        self.save_to(&settings_manager()?)
+    }
+
+    /// 指定したマネージャーの保存先から設定を読み込み
+    ///
+    /// ファイルがなければデフォルト設定を返す。壊れている場合は最新のバックアップ、
+    /// それも読めなければデフォルト設定で起動できるよう警告を出して続行する。
+    pub fn load_from(manager: &StatePersistenceManager) -> Result<Self, AppError> {
+        if !manager.state_exists(SETTINGS_FILE_NAME) {
+            return Ok(Self::default());
+        }
+
+        let loaded = manager
+            .load_state::<Value>(SETTINGS_FILE_NAME)
+            .and_then(Self::from_value)
+            .or_else(|error| {
+                eprintln!("Warning: Failed to load settings, trying backup: {}", error);
+                manager
+                    .restore_from_backup::<Value>(SETTINGS_FILE_NAME)
+                    .and_then(Self::from_value)
+            });
+
+        match loaded {
+            Ok((settings, warnings)) => {
+                for warning in warnings {
+                    eprintln!("Warning: {}", warning);
+                }
+                Ok(settings)
+            }
+            Err(error) => {
+                eprintln!("Warning: Using default settings: {}", error);
+                Ok(Self::default())
+            }
+        }
+    }
+
+    /// 外部で編集された設定ファイルを読み直す
+    pub fn reload() -> Result<Self, AppError> {
+        Self::reload_from(&settings_manager()?)
+    }
+
+    /// 指定したマネージャーの保存先から設定を読み直す
+    ///
+    /// 起動時と異なり、壊れた内容や不正な値はエラーにする（呼び出し側は
+    /// 直前の設定を使い続ける）。未知のフィールドと欠けたフィールドは許容する。
+    pub fn reload_from(manager: &StatePersistenceManager) -> Result<Self, AppError> {
+        let (settings, warnings) = Self::from_value(manager.load_state(SETTINGS_FILE_NAME)?)?;
+        for warning in warnings {
+            if let SettingsWarning::InvalidValue(_) = warning {
+                return Err(AppError::Config(warning.to_string()));
+            }
+            eprintln!("Warning: {}", warning);
+        }
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    /// 値の範囲を検証
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.min_window_width == 0 || self.min_window_height == 0 {
+            return Err(AppError::Config(
+                "Minimum window size must be greater than zero".to_string(),
+            ));
+        }
+        if let Some(directory) = &self.default_directory
+            && !directory.is_absolute()
+        {
+            return Err(AppError::Config(format!(
+                "Default directory must be an absolute path: {}",
+                directory.display()
+            )));
+        }
+        if let Some(root) = self.search_roots.iter().find(|root| !root.is_absolute()) {
+            return Err(AppError::Config(format!(
+                "Search roots must be absolute paths: {}",
+                root.display()
+            )));
+        }
+        if self
+            .history_exclusions
+            .iter()
+            .any(|pattern| pattern.trim().is_empty())
+        {
+            return Err(AppError::Config(
+                "History exclusion patterns must not be empty".to_string(),
+            ));
+        }
         Ok(())
     }
 
+    /// 読み直した設定を取り込み、実行中に反映できる項目の変更点を返す
+    ///
+    /// ウィンドウの位置と大きさは実行中のウィンドウが管理するため取り込まない。
+    /// 実行中に反映できない項目も取り込み、終了時の保存で編集を戻さないようにする。
+    pub fn apply_reloaded(&mut self, reloaded: &Settings) -> Vec<SettingsChange> {
+        let mut changes = Vec::new();
+        if self.dark_theme != reloaded.dark_theme {
+            self.dark_theme = reloaded.dark_theme;
+            changes.push(SettingsChange::Theme {
+                dark: reloaded.dark_theme,
+            });
+        }
+        if self.min_window_size() != reloaded.min_window_size() {
+            (self.min_window_width, self.min_window_height) = reloaded.min_window_size();
+            changes.push(SettingsChange::MinWindowSize {
+                width: reloaded.min_window_width,
+                height: reloaded.min_window_height,
+            });
+        }
+        if self.default_directory != reloaded.default_directory {
+            self.default_directory = reloaded.default_directory.clone();
+            changes.push(SettingsChange::DefaultDirectory(
+                reloaded.default_directory.clone(),
+            ));
+        }
+        if self.history_exclusions != reloaded.history_exclusions {
+            changes.push(SettingsChange::HistoryExclusions(
+                reloaded.history_exclusions.clone(),
+            ));
+        }
+        *self = Settings {
+            window_width: self.window_width,
+            window_height: self.window_height,
+            window_x: self.window_x,
+            window_y: self.window_y,
+            window_maximized: self.window_maximized,
+            ..reloaded.clone()
+        };
+        changes
+    }
+
+    /// 指定したマネージャーの保存先へ設定を保存
+    pub fn save_to(&self, manager: &StatePersistenceManager) -> Result<(), AppError> {
+        let settings = Self {
+            version: CURRENT_SETTINGS_VERSION,
+            ..self.clone()
+        };
+        manager.save_state(&settings, SETTINGS_FILE_NAME)
+    }
+
+    /// JSON文字列から設定を読み込み、警告と共に返す
+    pub fn from_json(content: &str) -> Result<(Self, Vec<SettingsWarning>), AppError> {
+        Self::from_value(serde_json::from_str(content)?)
+    }
+
+    /// JSON値から設定を読み込み、警告と共に返す
+    ///
+    /// 古いバージョンは移行してから読み込む。未知のフィールドと型の合わない値は
+    /// 無視し、欠けているフィールドはデフォルト値で補う。
+    pub fn from_value(value: Value) -> Result<(Self, Vec<SettingsWarning>), AppError> {
+        let Value::Object(mut fields) = value else {
+            return Err(AppError::Config(
+                "Settings file must contain a JSON object".to_string(),
+            ));
+        };
+        let mut warnings = Vec::new();
+
+        // バージョン導入前のファイルはバージョン0として扱う
+        let version = match fields.get("version") {
+            None => 0,
+            Some(value) => value
+                .as_u64()
+                .and_then(|v| u32::try_from(v).ok())
+                .ok_or_else(|| AppError::Config(format!("Invalid settings version: {}", value)))?,
+        };
+        if version > CURRENT_SETTINGS_VERSION {
+            warnings.push(SettingsWarning::NewerVersion(version));
+        }
+        for migration in MIGRATIONS.iter().skip(version as usize) {
+            migration(&mut fields);
+        }
+        fields.insert("version".to_string(), CURRENT_SETTINGS_VERSION.into());
+
+        let Value::Object(defaults) = serde_json::to_value(Self::default())? else {
+            unreachable!("Settings always serializes to an object");
+        };
+        for key in defaults.keys() {
+            if !fields.contains_key(key) {
+                warnings.push(SettingsWarning::MissingField(key.clone()));
+            }
+        }
+
+        let mut merged = defaults.clone();
+        for (key, value) in fields {
+            if !defaults.contains_key(&key) {
+                warnings.push(SettingsWarning::UnknownField(key));
+                continue;
+            }
+            // 型が合わない値は他の設定を巻き込まないよう1つずつ確かめる
+            let mut candidate = defaults.clone();
+            candidate.insert(key.clone(), value.clone());
+            if serde_json::from_value::<Self>(Value::Object(candidate)).is_ok() {
+                merged.insert(key, value);
+            } else {
+                warnings.push(SettingsWarning::InvalidValue(key));
+            }
+        }
+
+        Ok((serde_json::from_value(Value::Object(merged))?, warnings))
+    }
+
     /// ウィンドウ幅を取得
     pub fn window_width(&self) -> u32 {
         self.window_width
@@ -105,6 +379,48 @@ impl Settings {
     }
 }
 
+/// 設定ディレクトリ（`~/.config/rust-explorer` やポータブルモードのデータディレクトリ）を
+/// 保存先とするマネージャー
+fn settings_manager() -> Result<StatePersistenceManager, AppError> {
+    StatePersistenceManager::new(StatePersistenceConfig {
+        state_dir: app_paths().config_dir(),
+        ..StatePersistenceConfig::default()
+    })
+}
+
+/// バージョン導入前のファイルを移行（フィールドの構成は同じ）
+fn migrate_v0_to_v1(fields: &mut Map<String, Value>) {
+    fields.insert("version".to_string(), 1.into());
+}
+
+/// 検索の索引を作るディレクトリを追加（既存の設定では索引を作らない）
+fn migrate_v1_to_v2(fields: &mut Map<String, Value>) {
+    fields
+        .entry("search_roots")
+        .or_insert_with(|| Value::Array(Vec::new()));
+    fields.insert("version".to_string(), 2.into());
+}
+
+/// タグを拡張属性へ書き出す設定を追加（既存の設定では書き出さない）
+fn migrate_v2_to_v3(fields: &mut Map<String, Value>) {
+    fields
+        .entry("mirror_tags_to_xattr")
+        .or_insert(Value::Bool(false));
+    fields.insert("version".to_string(), 3.into());
+}
+
+/// 履歴に記録しない場所を追加（既存の設定でも一時ディレクトリは記録しない）
+fn migrate_v3_to_v4(fields: &mut Map<String, Value>) {
+    fields
+        .entry("history_exclusions")
+        .or_insert_with(|| default_history_exclusions().into());
+    fields.insert("version".to_string(), 4.into());
+}
+
+fn default_history_exclusions() -> Vec<String> {
+    vec!["/tmp".to_string()]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -120,9 +436,18 @@ mod tests {
         assert!(settings.dark_theme);
     }
 
+    fn temp_manager(temp_dir: &tempfile::TempDir) -> StatePersistenceManager {
+        StatePersistenceManager::new(StatePersistenceConfig {
+            state_dir: temp_dir.path().to_path_buf(),
+            ..StatePersistenceConfig::default()
+        })
+        .unwrap()
+    }
+
     #[test]
     fn test_settings_load() {
-        let result = Settings::load();
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let result = Settings::load_from(&temp_manager(&temp_dir));
         assert!(result.is_ok());
         let settings = result.unwrap();
         assert_eq!(settings.window_width(), 1200);
@@ -131,9 +456,159 @@ mod tests {
 
     #[test]
     fn test_settings_save() {
-        let settings = Settings::default();
-        let result = settings.save();
-        assert!(result.is_ok());
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let manager = temp_manager(&temp_dir);
+        let mut settings = Settings::default();
+        settings.update_window_state(1024, 768, Some(10), Some(20), false);
+        settings.dark_theme = false;
+
+        assert!(settings.save_to(&manager).is_ok());
+        assert!(temp_dir.path().join(SETTINGS_FILE_NAME).exists());
+        assert_eq!(Settings::load_from(&manager).unwrap(), settings);
+    }
+
+    #[test]
+    fn test_settings_migrates_unversioned_file() {
+        let (settings, warnings) = Settings::from_json(
+            r#"{"window_width": 1000, "window_height": 700, "window_x": null,
+                "window_y": null, "min_window_width": 800, "min_window_height": 600,
+                "window_maximized": true, "dark_theme": false, "default_directory": null}"#,
+        )
+        .unwrap();
+
+        assert_eq!(settings.version, CURRENT_SETTINGS_VERSION);
+        assert_eq!(settings.window_width, 1000);
+        assert!(settings.window_maximized);
+        assert!(!settings.dark_theme);
+        assert!(warnings.is_empty(), "{:?}", warnings);
+    }
+
+    #[test]
+    fn test_settings_migrates_search_roots() {
+        let (settings, warnings) = Settings::from_json(
+            r#"{"version": 1, "window_width": 1000, "window_height": 700, "window_x": null,
+                "window_y": null, "min_window_width": 800, "min_window_height": 600,
+                "window_maximized": false, "dark_theme": true, "default_directory": null}"#,
+        )
+        .unwrap();
+
+        assert!(settings.search_roots.is_empty());
+        assert!(!settings.mirror_tags_to_xattr);
+        assert_eq!(settings.history_exclusions, vec!["/tmp".to_string()]);
+        assert_eq!(settings.version, CURRENT_SETTINGS_VERSION);
+        assert!(warnings.is_empty(), "{:?}", warnings);
+
+        let relative = Settings {
+            search_roots: vec![PathBuf::from("relative")],
+            ..Settings::default()
+        };
+        assert!(relative.validate().is_err());
+        let blank = Settings {
+            history_exclusions: vec![" ".to_string()],
+            ..Settings::default()
+        };
+        assert!(blank.validate().is_err());
+    }
+
+    #[test]
+    fn test_settings_tolerates_unknown_missing_and_invalid_fields() {
+        let (settings, warnings) = Settings::from_json(
+            r#"{"version": 1, "window_width": 900, "dark_theme": "yes", "future_option": 3}"#,
+        )
+        .unwrap();
+
+        assert_eq!(settings.window_width, 900);
+        assert!(settings.dark_theme);
+        assert_eq!(settings.window_height, 800);
+        assert!(warnings.contains(&SettingsWarning::UnknownField("future_option".to_string())));
+        assert!(warnings.contains(&SettingsWarning::InvalidValue("dark_theme".to_string())));
+        assert!(warnings.contains(&SettingsWarning::MissingField("window_height".to_string())));
+    }
+
+    #[test]
+    fn test_settings_reload_rejects_invalid_edits() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let manager = temp_manager(&temp_dir);
+        let path = temp_dir.path().join(SETTINGS_FILE_NAME);
+
+        std::fs::write(&path, "{ broken").unwrap();
+        assert!(Settings::reload_from(&manager).is_err());
+
+        std::fs::write(&path, r#"{"version": 1, "dark_theme": 1}"#).unwrap();
+        assert!(matches!(
+            Settings::reload_from(&manager),
+            Err(AppError::Config(_))
+        ));
+
+        std::fs::write(&path, r#"{"version": 1, "min_window_width": 0}"#).unwrap();
+        assert!(Settings::reload_from(&manager).is_err());
+
+        std::fs::write(&path, r#"{"version": 1, "dark_theme": false, "extra": 1}"#).unwrap();
+        assert!(!Settings::reload_from(&manager).unwrap().dark_theme);
+    }
+
+    #[test]
+    fn test_apply_reloaded_reports_live_changes() {
+        let mut running = Settings::default();
+        running.update_window_state(1400, 900, Some(5), Some(5), false);
+        let reloaded = Settings {
+            dark_theme: false,
+            min_window_width: 640,
+            default_directory: Some(PathBuf::from("/srv")),
+            history_exclusions: vec!["/tmp".to_string(), "**/target".to_string()],
+            window_width: 200,
+            ..Settings::default()
+        };
+
+        let changes = running.apply_reloaded(&reloaded);
+        assert_eq!(
+            changes,
+            vec![
+                SettingsChange::Theme { dark: false },
+                SettingsChange::MinWindowSize {
+                    width: 640,
+                    height: 600
+                },
+                SettingsChange::DefaultDirectory(Some(PathBuf::from("/srv"))),
+                SettingsChange::HistoryExclusions(vec![
+                    "/tmp".to_string(),
+                    "**/target".to_string()
+                ]),
+            ]
+        );
+        // ウィンドウの大きさは実行中の値を保つ
+        assert_eq!(running.window_width, 1400);
+        assert_eq!(running.window_position(), (Some(5), Some(5)));
+
+        // 実行中に反映しない項目も取り込む
+        let reloaded = Settings {
+            search_roots: vec![PathBuf::from("/data")],
+            ..reloaded
+        };
+        assert!(running.apply_reloaded(&reloaded).is_empty());
+        assert_eq!(running.search_roots, vec![PathBuf::from("/data")]);
+        assert!(running.apply_reloaded(&reloaded).is_empty());
+    }
+
+    #[test]
+    fn test_settings_load_falls_back_to_backup() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let manager = temp_manager(&temp_dir);
+        let settings = Settings {
+            window_width: 1600,
+            ..Settings::default()
+        };
+        settings.save_to(&manager).unwrap();
+        settings.save_to(&manager).unwrap();
+
+        std::fs::write(temp_dir.path().join(SETTINGS_FILE_NAME), "{ broken").unwrap();
+        assert_eq!(Settings::load_from(&manager).unwrap().window_width, 1600);
+
+        // バックアップもなければデフォルト設定で起動する
+        for backup in manager.list_backups(SETTINGS_FILE_NAME).unwrap() {
+            std::fs::remove_file(backup).unwrap();
+        }
+        assert_eq!(Settings::load_from(&manager).unwrap(), Settings::default());
     }
 
     #[test]
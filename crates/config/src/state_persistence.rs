@@ -1,9 +1,69 @@
 //! 状態の永続化・復元機能
 
+use chrono::{DateTime, NaiveDateTime, Utc};
 use rust_explorer_utils::AppError;
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// セッション（終了時の状態）のファイル名
+pub const SESSION_STATE_FILE: &str = "session_state.json";
+
+/// ワークスペースのファイル名の接頭辞（`workspace.<名前>.json`）
+const WORKSPACE_FILE_PREFIX: &str = "workspace.";
+
+/// 検索の索引のファイル名
+pub const SEARCH_INDEX_FILE: &str = "search_index.json";
+
+/// スマートフォルダの定義のファイル名（設定ディレクトリに置く）
+pub const SMART_FOLDERS_FILE: &str = "smart_folders.json";
+
+/// ブックマークのファイル名（設定ディレクトリに置く）
+pub const BOOKMARKS_FILE: &str = "bookmarks.json";
+
+/// タグの定義と割り当てのファイル
+pub const TAGS_FILE: &str = "tags.json";
+
+/// 訪れた場所の履歴のファイル名
+pub const HISTORY_FILE: &str = "history.json";
+
+/// 元に戻す履歴のファイル名
+pub const UNDO_JOURNAL_FILE: &str = "undo_journal.json";
+
+/// ワークスペース名の最大文字数
+pub const MAX_WORKSPACE_NAME_LEN: usize = 64;
+
+/// 実行中を示す印のファイル（正常に終了したら削除する）
+pub const SESSION_RUNNING_MARKER: &str = "session.running";
+
+/// バックアップのファイル名の時刻の書式（`<状態ファイル名>.backup.<時刻>`）
+const BACKUP_TIMESTAMP_FORMAT: &str = "%Y%m%d_%H%M%S%3f";
+
+/// 状態ファイルのバックアップ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateBackup {
+    /// バックアップファイルのパス
+    pub path: PathBuf,
+    /// 作成時刻（ファイル名から読み取れなければ None）
+    pub created: Option<DateTime<Utc>>,
+}
+
+impl StateBackup {
+    /// バックアップファイルのパスから作成
+    pub fn from_path(path: PathBuf) -> Self {
+        let created = path
+            .file_name()
+            .and_then(|name| {
+                let name = name.to_string_lossy();
+                let (_, timestamp) = name.rsplit_once(".backup.")?;
+                NaiveDateTime::parse_from_str(timestamp, BACKUP_TIMESTAMP_FORMAT).ok()
+            })
+            .map(|time| time.and_utc());
+        Self { path, created }
+    }
+}
 
 /// 状態の永続化設定
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -52,15 +112,30 @@ impl StatePersistenceManager {
 
     /// 状態をファイルに保存
     pub fn save_state<T: Serialize>(&self, state: &T, filename: &str) -> Result<(), AppError> {
-        let file_path = self.config.state_dir.join(filename);
+        let json = serde_json::to_vec_pretty(state).map_err(AppError::Json)?;
+        self.write_state(&json, filename)
+    }
+
+    /// 状態を整形せずにファイルへ保存（大きな状態を速く書き込むため）
+    pub fn save_state_compact<T: Serialize>(
+        &self,
+        state: &T,
+        filename: &str,
+    ) -> Result<(), AppError> {
+        let json = serde_json::to_vec(state).map_err(AppError::Json)?;
+        self.write_state(&json, filename)
+    }
 
-        // バックアップを作成
-        self.create_backup(&file_path)?;
+    fn write_state(&self, json: &[u8], filename: &str) -> Result<(), AppError> {
+        let file_path = self.config.state_dir.join(filename);
 
-        // JSONとして保存
-        let json = serde_json::to_string_pretty(state).map_err(AppError::Json)?;
+        // バックアップを作成（残さない設定ならコピーもしない）
+        if self.config.max_backups > 0 {
+            self.create_backup(&file_path)?;
+        }
 
-        fs::write(&file_path, json).map_err(AppError::FileSystem)?;
+        // 書き込み途中で終了しても元のファイルが壊れないようにする
+        write_atomically(&file_path, json)?;
 
         // 古いバックアップを清理
         self.cleanup_old_backups(&file_path)?;
@@ -128,6 +203,38 @@ impl StatePersistenceManager {
         Ok(files)
     }
 
+    /// 名前を付けてワークスペースを保存
+    pub fn save_workspace<T: Serialize>(&self, name: &str, state: &T) -> Result<(), AppError> {
+        self.save_state(state, &workspace_file_name(name)?)
+    }
+
+    /// ワークスペースを読み込む
+    pub fn load_workspace<T: for<'de> Deserialize<'de>>(&self, name: &str) -> Result<T, AppError> {
+        self.load_state(&workspace_file_name(name)?)
+    }
+
+    /// ワークスペースを削除（バックアップも削除する）
+    pub fn delete_workspace(&self, name: &str) -> Result<(), AppError> {
+        let filename = workspace_file_name(name)?;
+        for backup in self.list_backups(&filename)? {
+            fs::remove_file(backup).map_err(AppError::FileSystem)?;
+        }
+        self.delete_state(&filename)
+    }
+
+    /// 保存されたワークスペース名の一覧（名前順）
+    pub fn list_workspaces(&self) -> Result<Vec<String>, AppError> {
+        Ok(self
+            .list_state_files()?
+            .into_iter()
+            .filter_map(|file| {
+                file.strip_prefix(WORKSPACE_FILE_PREFIX)?
+                    .strip_suffix(".json")
+                    .map(str::to_string)
+            })
+            .collect())
+    }
+
     /// 最新のバックアップから状態を復元
     pub fn restore_from_backup<T: for<'de> Deserialize<'de>>(
         &self,
@@ -171,22 +278,75 @@ impl StatePersistenceManager {
             }
         }
 
-        // 新しい順にソート
-        backups.sort_by(|a, b| {
-            let a_metadata = a.metadata().ok();
-            let b_metadata = b.metadata().ok();
-            match (a_metadata, b_metadata) {
-                (Some(a_meta), Some(b_meta)) => b_meta
-                    .modified()
-                    .unwrap_or(std::time::UNIX_EPOCH)
-                    .cmp(&a_meta.modified().unwrap_or(std::time::UNIX_EPOCH)),
-                _ => std::cmp::Ordering::Equal,
-            }
-        });
+        // 新しい順にソート（名前のタイムスタンプは辞書順で時刻順になる）
+        backups.sort_by(|a, b| b.file_name().cmp(&a.file_name()));
 
         Ok(backups)
     }
 
+    /// 利用可能なバックアップを作成時刻付きで一覧取得（新しい順）
+    pub fn list_backup_entries(&self, filename: &str) -> Result<Vec<StateBackup>, AppError> {
+        Ok(self
+            .list_backups(filename)?
+            .into_iter()
+            .map(StateBackup::from_path)
+            .collect())
+    }
+
+    /// 指定したバックアップから状態を復元
+    pub fn load_backup<T: for<'de> Deserialize<'de>>(&self, backup: &Path) -> Result<T, AppError> {
+        let content = fs::read_to_string(backup).map_err(AppError::FileSystem)?;
+
+        serde_json::from_str(&content).map_err(AppError::Json)
+    }
+
+    /// 読めない状態ファイルを `<状態ファイル名>.corrupt.<時刻>` へ移して残す
+    ///
+    /// 次の保存で上書きされないようにするため。ファイルがなければ None を返す。
+    pub fn quarantine_state(&self, filename: &str) -> Result<Option<PathBuf>, AppError> {
+        let file_path = self.config.state_dir.join(filename);
+        if !file_path.exists() {
+            return Ok(None);
+        }
+
+        let timestamp = Utc::now().format(BACKUP_TIMESTAMP_FORMAT);
+        let quarantine_path = self
+            .config
+            .state_dir
+            .join(format!("{}.corrupt.{}", filename, timestamp));
+        fs::rename(&file_path, &quarantine_path).map_err(AppError::FileSystem)?;
+
+        Ok(Some(quarantine_path))
+    }
+
+    /// 実行中の印を置く（印にはこのプロセスのIDを書く）
+    pub fn mark_running(&self) -> Result<(), AppError> {
+        self.ensure_state_dir()?;
+        fs::write(
+            self.config.state_dir.join(SESSION_RUNNING_MARKER),
+            std::process::id().to_string(),
+        )
+        .map_err(AppError::FileSystem)
+    }
+
+    /// 前回の実行が正常に終了しなかったか（実行中の印が残っている）
+    ///
+    /// 印を書いたプロセスがまだ動いていれば、別に起動したものが実行中なので
+    /// 中断とはみなさない。IDを読めない印は中断として扱う。
+    pub fn was_interrupted(&self) -> Result<bool, AppError> {
+        let marker = self.config.state_dir.join(SESSION_RUNNING_MARKER);
+        let contents = match fs::read_to_string(&marker) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(AppError::FileSystem(e)),
+        };
+        let running = contents
+            .trim()
+            .parse::<u32>()
+            .is_ok_and(|pid| pid != std::process::id() && process_is_alive(pid));
+        Ok(!running)
+    }
+
     /// 状態ディレクトリを作成
     fn ensure_state_dir(&self) -> Result<(), AppError> {
         if !self.config.state_dir.exists() {
@@ -198,11 +358,12 @@ impl StatePersistenceManager {
     /// バックアップファイルを作成
     fn create_backup(&self, file_path: &Path) -> Result<(), AppError> {
         if file_path.exists() {
-            let timestamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
+            // list_backups の接頭辞と揃え、状態ファイルの一覧に混ざらないよう .json で終えない
+            let timestamp = Utc::now().format(BACKUP_TIMESTAMP_FORMAT);
             let backup_filename = format!(
-                "{}.backup.{}.json",
+                "{}.backup.{}",
                 file_path
-                    .file_stem()
+                    .file_name()
                     .ok_or_else(|| AppError::Internal("Invalid file path".to_string()))?
                     .to_string_lossy(),
                 timestamp
@@ -254,13 +415,80 @@ impl StatePersistenceManager {
     }
 }
 
-/// デフォルトの状態保存ディレクトリを取得
-fn default_state_dir() -> PathBuf {
-    if let Some(config_dir) = dirs::config_dir() {
-        config_dir.join("rust-explorer").join("state")
-    } else {
-        PathBuf::from(".").join("state")
+/// 一時ファイルへ書き込んでから置き換える
+///
+/// 一時ファイルは同じディレクトリに作るため、置き換えは rename 1回で済む。
+/// 名前は `.json` で終えず、状態ファイルの一覧に混ざらないようにする。
+/// 同時に書き込むスレッドやプロセスが互いの一時ファイルを壊さないよう、
+/// 名前にはプロセスIDと連番を付ける。
+fn write_atomically(path: &Path, contents: &[u8]) -> Result<(), AppError> {
+    static NEXT_TEMP_ID: AtomicU64 = AtomicU64::new(0);
+
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| AppError::Internal("Invalid file path".to_string()))?;
+    let temp_path = path.with_file_name(format!(
+        ".{}.{}.{}.tmp",
+        file_name.to_string_lossy(),
+        std::process::id(),
+        NEXT_TEMP_ID.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let result = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&temp_path)
+        .and_then(|mut file| {
+            file.write_all(contents)?;
+            file.sync_all()
+        })
+        .and_then(|()| fs::rename(&temp_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result.map_err(AppError::FileSystem)
+}
+
+/// プロセスが動いているか
+#[cfg(unix)]
+fn process_is_alive(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    if pid <= 0 {
+        return false;
     }
+    // SAFETY: シグナル 0 はプロセスの存在と権限を確かめるだけで、何も送らない
+    let result = unsafe { libc::kill(pid, 0) };
+    // 権限がなくて送れない場合もプロセスは存在する
+    result == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// プロセスが動いているか（確かめられないため、動いていないものとして扱う）
+#[cfg(not(unix))]
+fn process_is_alive(_pid: u32) -> bool {
+    false
+}
+
+/// ワークスペース名をファイル名へ変換（状態ディレクトリの外を指す名前は使えない）
+fn workspace_file_name(name: &str) -> Result<String, AppError> {
+    let valid = !name.trim().is_empty()
+        && name.chars().count() <= MAX_WORKSPACE_NAME_LEN
+        && !name.starts_with('.')
+        && !name.contains(['/', '\\'])
+        && !name.chars().any(char::is_control);
+    if !valid {
+        return Err(AppError::Config(format!(
+            "Invalid workspace name: {}",
+            name
+        )));
+    }
+    Ok(format!("{}{}.json", WORKSPACE_FILE_PREFIX, name))
+}
+
+/// デフォルトの状態保存ディレクトリを取得（ポータブルモードではデータディレクトリの下）
+fn default_state_dir() -> PathBuf {
+    crate::paths::app_paths().state_dir()
 }
 
 /// アプリケーション状態の永続化ヘルパー関数
@@ -300,12 +528,170 @@ pub mod state_helpers {
     /// セッション状態を保存
     pub fn save_session_state<T: Serialize>(state: &T) -> Result<(), AppError> {
         let manager = StatePersistenceManager::with_default_config()?;
-        manager.save_state(state, "session_state.json")
+        manager.save_state(state, SESSION_STATE_FILE)
     }
 
     /// セッション状態を復元
     pub fn load_session_state<T: for<'de> Deserialize<'de>>() -> Result<T, AppError> {
         let manager = StatePersistenceManager::with_default_config()?;
-        manager.load_state("session_state.json")
+        manager.load_state(SESSION_STATE_FILE)
+    }
+
+    /// セッション状態ファイルが存在するかチェック
+    pub fn session_state_exists() -> Result<bool, AppError> {
+        let manager = StatePersistenceManager::with_default_config()?;
+        Ok(manager.state_exists(SESSION_STATE_FILE))
+    }
+
+    /// セッション状態のバックアップの一覧（新しい順）
+    pub fn session_backups() -> Result<Vec<StateBackup>, AppError> {
+        StatePersistenceManager::with_default_config()?.list_backup_entries(SESSION_STATE_FILE)
+    }
+
+    /// 指定したバックアップからセッション状態を復元
+    pub fn load_session_backup<T: for<'de> Deserialize<'de>>(backup: &Path) -> Result<T, AppError> {
+        StatePersistenceManager::with_default_config()?.load_backup(backup)
+    }
+
+    /// 読めないセッション状態ファイルを隔離
+    pub fn quarantine_session_state() -> Result<Option<PathBuf>, AppError> {
+        StatePersistenceManager::with_default_config()?.quarantine_state(SESSION_STATE_FILE)
+    }
+
+    /// 実行中の印を置く
+    pub fn mark_session_running() -> Result<(), AppError> {
+        StatePersistenceManager::with_default_config()?.mark_running()
+    }
+
+    /// 正常に終了したので実行中の印を消す
+    pub fn mark_session_closed() -> Result<(), AppError> {
+        StatePersistenceManager::with_default_config()?.delete_state(SESSION_RUNNING_MARKER)
+    }
+
+    /// 前回の実行が正常に終了しなかったか（実行中の印が残り、書いたプロセスも動いていない）
+    pub fn session_was_interrupted() -> Result<bool, AppError> {
+        StatePersistenceManager::with_default_config()?.was_interrupted()
+    }
+
+    /// 名前を付けてワークスペースを保存
+    pub fn save_workspace<T: Serialize>(name: &str, state: &T) -> Result<(), AppError> {
+        StatePersistenceManager::with_default_config()?.save_workspace(name, state)
+    }
+
+    /// ワークスペースを読み込む
+    pub fn load_workspace<T: for<'de> Deserialize<'de>>(name: &str) -> Result<T, AppError> {
+        StatePersistenceManager::with_default_config()?.load_workspace(name)
+    }
+
+    /// ワークスペースを削除
+    pub fn delete_workspace(name: &str) -> Result<(), AppError> {
+        StatePersistenceManager::with_default_config()?.delete_workspace(name)
+    }
+
+    /// 保存されたワークスペース名の一覧
+    pub fn list_workspaces() -> Result<Vec<String>, AppError> {
+        StatePersistenceManager::with_default_config()?.list_workspaces()
+    }
+
+    /// 検索の索引を保存（大きくなるためバックアップは残さない）
+    pub fn save_search_index<T: Serialize>(index: &T) -> Result<(), AppError> {
+        unbacked_manager()?.save_state_compact(index, SEARCH_INDEX_FILE)
+    }
+
+    /// 検索の索引を復元
+    pub fn load_search_index<T: for<'de> Deserialize<'de>>() -> Result<T, AppError> {
+        unbacked_manager()?.load_state(SEARCH_INDEX_FILE)
+    }
+
+    /// バックアップを残さないマネージャー
+    fn unbacked_manager() -> Result<StatePersistenceManager, AppError> {
+        StatePersistenceManager::new(StatePersistenceConfig {
+            max_backups: 0,
+            ..StatePersistenceConfig::default()
+        })
+    }
+
+    /// スマートフォルダの定義を保存
+    pub fn save_smart_folders<T: Serialize>(folders: &T) -> Result<(), AppError> {
+        definitions_manager()?.save_state(folders, SMART_FOLDERS_FILE)
+    }
+
+    /// スマートフォルダの定義を復元
+    pub fn load_smart_folders<T: for<'de> Deserialize<'de>>() -> Result<T, AppError> {
+        definitions_manager()?.load_state(SMART_FOLDERS_FILE)
+    }
+
+    /// スマートフォルダの定義ファイルが存在するかチェック
+    pub fn smart_folders_exist() -> Result<bool, AppError> {
+        Ok(definitions_manager()?.state_exists(SMART_FOLDERS_FILE))
+    }
+
+    /// ブックマークを保存
+    pub fn save_bookmarks<T: Serialize>(bookmarks: &T) -> Result<(), AppError> {
+        definitions_manager()?.save_state(bookmarks, BOOKMARKS_FILE)
+    }
+
+    /// ブックマークを復元
+    pub fn load_bookmarks<T: for<'de> Deserialize<'de>>() -> Result<T, AppError> {
+        definitions_manager()?.load_state(BOOKMARKS_FILE)
+    }
+
+    /// ブックマークのファイルが存在するかチェック
+    pub fn bookmarks_exist() -> Result<bool, AppError> {
+        Ok(definitions_manager()?.state_exists(BOOKMARKS_FILE))
+    }
+
+    /// 設定ディレクトリに保存するマネージャー（スマートフォルダやブックマークは
+    /// 状態ではなく設定として扱う）
+    fn definitions_manager() -> Result<StatePersistenceManager, AppError> {
+        StatePersistenceManager::new(StatePersistenceConfig {
+            state_dir: crate::paths::app_paths().config_dir(),
+            ..StatePersistenceConfig::default()
+        })
+    }
+
+    /// 訪れた場所の履歴を保存（移動のたびに保存するためバックアップは残さない）
+    pub fn save_history<T: Serialize>(history: &T) -> Result<(), AppError> {
+        unbacked_manager()?.save_state(history, HISTORY_FILE)
+    }
+
+    /// 訪れた場所の履歴を復元
+    pub fn load_history<T: for<'de> Deserialize<'de>>() -> Result<T, AppError> {
+        unbacked_manager()?.load_state(HISTORY_FILE)
+    }
+
+    /// 履歴のファイルが存在するかチェック
+    pub fn history_exist() -> Result<bool, AppError> {
+        Ok(unbacked_manager()?.state_exists(HISTORY_FILE))
+    }
+
+    /// タグの定義と割り当てを保存
+    pub fn save_tags<T: Serialize>(tags: &T) -> Result<(), AppError> {
+        let manager = StatePersistenceManager::with_default_config()?;
+        manager.save_state(tags, TAGS_FILE)
+    }
+
+    /// タグの定義と割り当てを復元
+    pub fn load_tags<T: for<'de> Deserialize<'de>>() -> Result<T, AppError> {
+        let manager = StatePersistenceManager::with_default_config()?;
+        manager.load_state(TAGS_FILE)
+    }
+
+    /// タグのファイルが存在するかチェック
+    pub fn tags_exist() -> Result<bool, AppError> {
+        let manager = StatePersistenceManager::with_default_config()?;
+        Ok(manager.state_exists(TAGS_FILE))
+    }
+
+    /// 元に戻す履歴を保存
+    pub fn save_undo_journal<T: Serialize>(journal: &T) -> Result<(), AppError> {
+        let manager = StatePersistenceManager::with_default_config()?;
+        manager.save_state(journal, UNDO_JOURNAL_FILE)
+    }
+
+    /// 元に戻す履歴を復元
+    pub fn load_undo_journal<T: for<'de> Deserialize<'de>>() -> Result<T, AppError> {
+        let manager = StatePersistenceManager::with_default_config()?;
+        manager.load_state(UNDO_JOURNAL_FILE)
     }
 }
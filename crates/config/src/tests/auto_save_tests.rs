@@ -0,0 +1,205 @@
+//! 自動保存のテスト
+
+use crate::auto_save::{AutoSaveScheduler, DeferredSave};
+use crate::state_persistence::{StatePersistenceConfig, StatePersistenceManager};
+use rust_explorer_utils::AppError;
+use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tempfile::TempDir;
+
+/// 保存回数を数えるスケジューラーを作成
+fn counting_scheduler(interval: Option<Duration>) -> (AutoSaveScheduler, Arc<AtomicUsize>) {
+    let saves = Arc::new(AtomicUsize::new(0));
+    let counter = saves.clone();
+    let scheduler = AutoSaveScheduler::with_interval(interval, move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    });
+    (scheduler, saves)
+}
+
+#[test]
+fn test_auto_save_coalesces_changes() {
+    let (scheduler, saves) = counting_scheduler(Some(Duration::from_millis(100)));
+
+    // 間隔内の変更はまとめて1回だけ保存する
+    for _ in 0..10 {
+        scheduler.mark_dirty();
+    }
+    assert_eq!(saves.load(Ordering::SeqCst), 0);
+    std::thread::sleep(Duration::from_millis(400));
+    assert_eq!(saves.load(Ordering::SeqCst), 1);
+    assert!(!scheduler.is_dirty());
+
+    // 変更がなければ保存しない
+    scheduler.shutdown().unwrap();
+    assert_eq!(saves.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_shutdown_flushes_pending_changes() {
+    let (scheduler, saves) = counting_scheduler(Some(Duration::from_secs(3600)));
+    let handle = scheduler.handle();
+
+    handle.mark_dirty();
+    scheduler.shutdown().unwrap();
+    assert_eq!(saves.load(Ordering::SeqCst), 1);
+
+    // 停止後の変更は無視する
+    handle.mark_dirty();
+    assert_eq!(saves.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_disabled_auto_save_saves_on_flush_only() {
+    let config = StatePersistenceConfig {
+        auto_save_enabled: false,
+        ..StatePersistenceConfig::default()
+    };
+    let saves = Arc::new(AtomicUsize::new(0));
+    let counter = saves.clone();
+    let scheduler = AutoSaveScheduler::new(&config, move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    });
+
+    scheduler.mark_dirty();
+    std::thread::sleep(Duration::from_millis(50));
+    assert_eq!(saves.load(Ordering::SeqCst), 0);
+    scheduler.flush().unwrap();
+    assert_eq!(saves.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_failed_save_is_retried() {
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let counter = attempts.clone();
+    let scheduler = AutoSaveScheduler::with_interval(None, move || {
+        if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+            Err(AppError::Internal("disk full".to_string()))
+        } else {
+            Ok(())
+        }
+    });
+
+    scheduler.mark_dirty();
+    assert!(scheduler.flush().is_err());
+    assert!(scheduler.is_dirty());
+    scheduler.shutdown().unwrap();
+    assert_eq!(attempts.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_save_state_is_atomic_and_keeps_backups() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = StatePersistenceConfig {
+        state_dir: temp_dir.path().to_path_buf(),
+        auto_save_interval: 10,
+        auto_save_enabled: true,
+        max_backups: 2,
+    };
+    let manager = StatePersistenceManager::new(config).unwrap();
+
+    for value in 0..4 {
+        manager.save_state(&value, "session_state.json").unwrap();
+        std::thread::sleep(Duration::from_millis(5));
+    }
+
+    // 一時ファイルは残らず、バックアップは上限まで保持される
+    let names: Vec<String> = fs::read_dir(temp_dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    assert!(
+        names.iter().all(|name| !name.ends_with(".tmp")),
+        "{:?}",
+        names
+    );
+    assert_eq!(manager.list_backups("session_state.json").unwrap().len(), 2);
+    let loaded: i32 = manager.load_state("session_state.json").unwrap();
+    assert_eq!(loaded, 3);
+}
+
+#[test]
+fn test_concurrent_saves_do_not_share_temp_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = StatePersistenceConfig {
+        state_dir: temp_dir.path().to_path_buf(),
+        auto_save_interval: 10,
+        auto_save_enabled: true,
+        max_backups: 0,
+    };
+    let manager = Arc::new(StatePersistenceManager::new(config).unwrap());
+
+    let writers: Vec<_> = (0..8)
+        .map(|writer| {
+            let manager = manager.clone();
+            std::thread::spawn(move || {
+                for value in 0..20 {
+                    manager
+                        .save_state(&vec![writer; 100 + value], "session_state.json")
+                        .unwrap();
+                }
+            })
+        })
+        .collect();
+    for writer in writers {
+        writer.join().unwrap();
+    }
+
+    // 最後に置き換えたどれかの内容が壊れずに残る
+    let loaded: Vec<i32> = manager.load_state("session_state.json").unwrap();
+    assert_eq!(loaded.len(), 119);
+    assert!(loaded.iter().all(|value| *value == loaded[0]));
+    let names: Vec<String> = fs::read_dir(temp_dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    assert_eq!(names, vec!["session_state.json".to_string()]);
+}
+
+#[test]
+fn test_deferred_save_writes_latest_value() {
+    let saved = Arc::new(Mutex::new(Vec::new()));
+    let fail = Arc::new(AtomicUsize::new(1));
+    let record = saved.clone();
+    let failures = fail.clone();
+    let deferred = DeferredSave::new(Duration::from_secs(60), move |value: &u32| {
+        if failures.load(Ordering::SeqCst) > 0 {
+            failures.fetch_sub(1, Ordering::SeqCst);
+            return Err(AppError::Internal("disk full".to_string()));
+        }
+        record.lock().unwrap().push(*value);
+        Ok(())
+    });
+
+    // 間隔内に預けた内容は最新のものだけを保存する
+    deferred.save(1);
+    deferred.save(2);
+    assert!(deferred.flush().is_err());
+    // 失敗した内容は次の保存で書き出す
+    deferred.flush().unwrap();
+    assert_eq!(*saved.lock().unwrap(), vec![2]);
+
+    deferred.flush().unwrap();
+    assert_eq!(*saved.lock().unwrap(), vec![2]);
+}
+
+#[test]
+fn test_deferred_flush_waits_for_save_in_progress() {
+    let saved = Arc::new(Mutex::new(Vec::new()));
+    let record = saved.clone();
+    let deferred = DeferredSave::new(Duration::from_millis(10), move |value: &u32| {
+        std::thread::sleep(Duration::from_millis(300));
+        record.lock().unwrap().push(*value);
+        Ok(())
+    });
+
+    // 保存スレッドが書き込んでいる途中で flush しても、書き込みの完了を待つ
+    deferred.save(1);
+    std::thread::sleep(Duration::from_millis(100));
+    deferred.flush().unwrap();
+    assert_eq!(*saved.lock().unwrap(), vec![1]);
+}
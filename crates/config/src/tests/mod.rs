@@ -1 +1,2 @@
+mod auto_save_tests;
 mod state_persistence_tests;
@@ -1,6 +1,8 @@
 //! 状態永続化システムのテスト
 
-use crate::state_persistence::{StatePersistenceConfig, StatePersistenceManager, state_helpers};
+use crate::state_persistence::{
+    SESSION_RUNNING_MARKER, StatePersistenceConfig, StatePersistenceManager, state_helpers,
+};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use tempfile::TempDir;
@@ -207,6 +209,34 @@ fn test_backup_cleanup() {
     assert!(backups.len() <= 2);
 }
 
+#[test]
+fn test_compact_save_without_backups() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = StatePersistenceConfig {
+        state_dir: temp_dir.path().to_path_buf(),
+        auto_save_interval: 10,
+        auto_save_enabled: true,
+        max_backups: 0,
+    };
+
+    let manager = StatePersistenceManager::new(config).unwrap();
+    for i in 1..=3 {
+        let state = TestState {
+            value: i,
+            ..TestState::default()
+        };
+        manager.save_state_compact(&state, "index.json").unwrap();
+    }
+
+    // バックアップを残さない設定ではコピーも作らない
+    let entries = fs::read_dir(temp_dir.path()).unwrap().count();
+    assert_eq!(entries, 1);
+    let content = fs::read_to_string(temp_dir.path().join("index.json")).unwrap();
+    assert!(!content.contains('\n'));
+    let state: TestState = manager.load_state("index.json").unwrap();
+    assert_eq!(state.value, 3);
+}
+
 #[test]
 fn test_restore_from_backup() {
     let temp_dir = TempDir::new().unwrap();
@@ -308,3 +338,143 @@ fn test_invalid_json_handling() {
     let load_result: Result<TestState, _> = manager.load_state("invalid.json");
     assert!(load_result.is_err());
 }
+
+#[test]
+fn test_quarantine_and_load_backup() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = StatePersistenceConfig {
+        state_dir: temp_dir.path().to_path_buf(),
+        auto_save_interval: 10,
+        auto_save_enabled: true,
+        max_backups: 3,
+    };
+    let manager = StatePersistenceManager::new(config).unwrap();
+
+    let state = TestState::default();
+    manager.save_state(&state, "session.json").unwrap();
+    manager.save_state(&state, "session.json").unwrap();
+    fs::write(temp_dir.path().join("session.json"), "{ broken").unwrap();
+
+    // バックアップは作成時刻付きで一覧でき、個別に読み込める
+    let backups = manager.list_backup_entries("session.json").unwrap();
+    assert_eq!(backups.len(), 1);
+    assert!(backups[0].created.is_some());
+    let restored: TestState = manager.load_backup(&backups[0].path).unwrap();
+    assert_eq!(restored, state);
+
+    // 壊れたファイルは上書きされないよう別名で残す
+    let quarantined = manager.quarantine_state("session.json").unwrap().unwrap();
+    assert!(!manager.state_exists("session.json"));
+    assert_eq!(fs::read_to_string(&quarantined).unwrap(), "{ broken");
+    assert!(manager.list_state_files().unwrap().is_empty());
+    assert_eq!(manager.list_backups("session.json").unwrap().len(), 1);
+    assert_eq!(manager.quarantine_state("session.json").unwrap(), None);
+}
+
+#[test]
+fn test_workspaces() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = StatePersistenceConfig {
+        state_dir: temp_dir.path().to_path_buf(),
+        auto_save_interval: 10,
+        auto_save_enabled: true,
+        max_backups: 3,
+    };
+    let manager = StatePersistenceManager::new(config).unwrap();
+
+    let review = TestState {
+        name: "review".to_string(),
+        ..TestState::default()
+    };
+    manager.save_workspace("レビュー", &review).unwrap();
+    manager
+        .save_workspace("photos", &TestState::default())
+        .unwrap();
+    manager
+        .save_state(&TestState::default(), "session_state.json")
+        .unwrap();
+
+    // セッションなど他の状態ファイルは含めない
+    assert_eq!(
+        manager.list_workspaces().unwrap(),
+        vec!["photos".to_string(), "レビュー".to_string()]
+    );
+    let loaded: TestState = manager.load_workspace("レビュー").unwrap();
+    assert_eq!(loaded, review);
+
+    // 上書き保存で作られたバックアップも一緒に削除する
+    manager.save_workspace("photos", &review).unwrap();
+    manager.delete_workspace("photos").unwrap();
+    assert_eq!(
+        manager.list_workspaces().unwrap(),
+        vec!["レビュー".to_string()]
+    );
+    assert!(
+        manager
+            .list_backups("workspace.photos.json")
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_invalid_workspace_names() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = StatePersistenceConfig {
+        state_dir: temp_dir.path().to_path_buf(),
+        auto_save_interval: 10,
+        auto_save_enabled: true,
+        max_backups: 3,
+    };
+    let manager = StatePersistenceManager::new(config).unwrap();
+
+    for name in ["", "  ", "../escape", "a/b", ".hidden"] {
+        assert!(
+            manager.save_workspace(name, &TestState::default()).is_err(),
+            "{:?}",
+            name
+        );
+    }
+    assert!(fs::read_dir(temp_dir.path()).unwrap().next().is_none());
+}
+
+#[test]
+fn test_session_interrupted_checks_marker_process() {
+    let temp_dir = TempDir::new().unwrap();
+    let config = StatePersistenceConfig {
+        state_dir: temp_dir.path().join("state"),
+        auto_save_interval: 10,
+        auto_save_enabled: true,
+        max_backups: 3,
+    };
+    let manager = StatePersistenceManager::new(config).unwrap();
+    let marker = temp_dir.path().join("state").join(SESSION_RUNNING_MARKER);
+
+    assert!(!manager.was_interrupted().unwrap());
+
+    // 同じIDの印は以前の実行のもの（IDが再利用された）として扱う
+    manager.mark_running().unwrap();
+    assert_eq!(
+        fs::read_to_string(&marker).unwrap(),
+        std::process::id().to_string()
+    );
+    assert!(manager.was_interrupted().unwrap());
+
+    fs::write(&marker, "not a pid").unwrap();
+    assert!(manager.was_interrupted().unwrap());
+
+    // 印を書いたプロセスが動いている間は中断とはみなさない
+    #[cfg(unix)]
+    {
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        fs::write(&marker, child.id().to_string()).unwrap();
+        assert!(!manager.was_interrupted().unwrap());
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert!(manager.was_interrupted().unwrap());
+    }
+}
@@ -0,0 +1,346 @@
+//! ブックマーク
+//!
+//! よく使う場所を名前付きで登録し、フォルダにまとめて表示順を管理する。
+//! 表示順の先頭から9件はキーボードのショートカットで開ける。
+
+use crate::state::state_utils;
+use rust_explorer_utils::AppError;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// ショートカット（1〜9）で開けるブックマークの数
+pub const MAX_BOOKMARK_SHORTCUTS: usize = 9;
+
+/// ブックマーク
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bookmark {
+    /// ブックマークID
+    pub id: String,
+    /// 表示名
+    pub name: String,
+    /// 開く場所（ディレクトリまたはファイル）
+    pub path: PathBuf,
+}
+
+/// ブックマークのフォルダ（入れ子にはできない）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookmarkFolder {
+    /// フォルダID
+    pub id: String,
+    /// 表示名
+    pub name: String,
+    /// 表示順のブックマーク
+    #[serde(default)]
+    pub bookmarks: Vec<Bookmark>,
+}
+
+/// 一覧の1項目
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BookmarkEntry {
+    Bookmark(Bookmark),
+    Folder(BookmarkFolder),
+}
+
+impl BookmarkEntry {
+    /// 項目のID
+    pub fn id(&self) -> &str {
+        match self {
+            BookmarkEntry::Bookmark(bookmark) => &bookmark.id,
+            BookmarkEntry::Folder(folder) => &folder.id,
+        }
+    }
+}
+
+/// ブックマークの一覧（表示順）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bookmarks {
+    #[serde(default)]
+    entries: Vec<BookmarkEntry>,
+}
+
+impl Bookmarks {
+    /// 空の一覧を作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 最上位の項目（表示順）
+    pub fn entries(&self) -> &[BookmarkEntry] {
+        &self.entries
+    }
+
+    /// フォルダの中も含めたすべてのブックマーク（表示順）
+    pub fn bookmarks(&self) -> Vec<&Bookmark> {
+        self.entries
+            .iter()
+            .flat_map(|entry| match entry {
+                BookmarkEntry::Bookmark(bookmark) => std::slice::from_ref(bookmark).iter(),
+                BookmarkEntry::Folder(folder) => folder.bookmarks.iter(),
+            })
+            .collect()
+    }
+
+    /// IDでブックマークを検索
+    pub fn get(&self, id: &str) -> Option<&Bookmark> {
+        self.bookmarks()
+            .into_iter()
+            .find(|bookmark| bookmark.id == id)
+    }
+
+    /// IDでフォルダを検索
+    pub fn folder(&self, id: &str) -> Option<&BookmarkFolder> {
+        self.entries.iter().find_map(|entry| match entry {
+            BookmarkEntry::Folder(folder) if folder.id == id => Some(folder),
+            _ => None,
+        })
+    }
+
+    /// パスのブックマーク
+    pub fn find(&self, path: &Path) -> Option<&Bookmark> {
+        self.bookmarks()
+            .into_iter()
+            .find(|bookmark| bookmark.path == path)
+    }
+
+    /// ショートカットの番号（1〜9）のブックマーク
+    pub fn shortcut(&self, number: usize) -> Option<&Bookmark> {
+        if !(1..=MAX_BOOKMARK_SHORTCUTS).contains(&number) {
+            return None;
+        }
+        self.bookmarks().into_iter().nth(number - 1)
+    }
+
+    /// ブックマークのショートカットの番号（10件目以降は None）
+    pub fn shortcut_number(&self, id: &str) -> Option<usize> {
+        self.bookmarks()
+            .iter()
+            .take(MAX_BOOKMARK_SHORTCUTS)
+            .position(|bookmark| bookmark.id == id)
+            .map(|index| index + 1)
+    }
+
+    /// パスを登録する（`folder` を指定するとそのフォルダの末尾、なければ最上位の末尾）
+    ///
+    /// 表示名はパスの名前にする。登録済みのパスは追加しない。
+    pub fn add(&mut self, path: &Path, folder: Option<&str>) -> Result<&Bookmark, AppError> {
+        if !path.is_absolute() {
+            return Err(AppError::InvalidPath(path.to_path_buf()));
+        }
+        if self.find(path).is_some() {
+            return Err(AppError::InvalidInput(format!(
+                "Already bookmarked: {}",
+                path.display()
+            )));
+        }
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string());
+        let bookmark = Bookmark {
+            id: state_utils::generate_bookmark_id(),
+            name,
+            path: path.to_path_buf(),
+        };
+        match folder {
+            Some(folder_id) => {
+                let folder = self.folder_mut(folder_id)?;
+                folder.bookmarks.push(bookmark);
+                Ok(&folder.bookmarks[folder.bookmarks.len() - 1])
+            }
+            None => {
+                self.entries.push(BookmarkEntry::Bookmark(bookmark));
+                match &self.entries[self.entries.len() - 1] {
+                    BookmarkEntry::Bookmark(bookmark) => Ok(bookmark),
+                    BookmarkEntry::Folder(_) => unreachable!(),
+                }
+            }
+        }
+    }
+
+    /// 最上位の末尾にフォルダを作成
+    pub fn add_folder(&mut self, name: &str) -> Result<&BookmarkFolder, AppError> {
+        let folder = BookmarkFolder {
+            id: state_utils::generate_bookmark_id(),
+            name: validate_name(name)?,
+            bookmarks: Vec::new(),
+        };
+        self.entries.push(BookmarkEntry::Folder(folder));
+        match &self.entries[self.entries.len() - 1] {
+            BookmarkEntry::Folder(folder) => Ok(folder),
+            BookmarkEntry::Bookmark(_) => unreachable!(),
+        }
+    }
+
+    /// ブックマークまたはフォルダの名前を変更
+    pub fn rename(&mut self, id: &str, name: &str) -> Result<(), AppError> {
+        let name = validate_name(name)?;
+        for entry in &mut self.entries {
+            match entry {
+                BookmarkEntry::Bookmark(bookmark) if bookmark.id == id => {
+                    bookmark.name = name;
+                    return Ok(());
+                }
+                BookmarkEntry::Folder(folder) if folder.id == id => {
+                    folder.name = name;
+                    return Ok(());
+                }
+                BookmarkEntry::Folder(folder) => {
+                    if let Some(bookmark) = folder.bookmarks.iter_mut().find(|b| b.id == id) {
+                        bookmark.name = name;
+                        return Ok(());
+                    }
+                }
+                BookmarkEntry::Bookmark(_) => {}
+            }
+        }
+        Err(unknown_bookmark(id))
+    }
+
+    /// ブックマークまたはフォルダを削除（フォルダは中のブックマークごと削除）
+    pub fn remove(&mut self, id: &str) -> bool {
+        match self.locate(id) {
+            Some((None, index)) => {
+                self.entries.remove(index);
+                true
+            }
+            Some((Some(folder), index)) => {
+                if let BookmarkEntry::Folder(folder) = &mut self.entries[folder] {
+                    folder.bookmarks.remove(index);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// `folder` の `index` 番目へ移動（`folder` が None なら最上位、範囲外なら末尾）
+    ///
+    /// フォルダは最上位でだけ並べ替えられる。
+    pub fn move_to(
+        &mut self,
+        id: &str,
+        folder: Option<&str>,
+        index: usize,
+    ) -> Result<(), AppError> {
+        if let Some(folder_id) = folder {
+            self.folder_mut(folder_id)?;
+            if self.folder(id).is_some() {
+                return Err(AppError::InvalidInput(
+                    "Bookmark folders cannot be nested".to_string(),
+                ));
+            }
+        }
+        let entry = self.take(id).ok_or_else(|| unknown_bookmark(id))?;
+        match (folder, entry) {
+            (Some(folder_id), BookmarkEntry::Bookmark(bookmark)) => {
+                let folder = self.folder_mut(folder_id)?;
+                let index = index.min(folder.bookmarks.len());
+                folder.bookmarks.insert(index, bookmark);
+            }
+            (_, entry) => {
+                let index = index.min(self.entries.len());
+                self.entries.insert(index, entry);
+            }
+        }
+        Ok(())
+    }
+
+    /// 同じフォルダ（または最上位）の中で `offset` だけ前後へ移動（端で止まる）
+    pub fn move_by(&mut self, id: &str, offset: isize) -> Result<(), AppError> {
+        let (folder, index) = self.locate(id).ok_or_else(|| unknown_bookmark(id))?;
+        let folder_id = folder.map(|folder| self.entries[folder].id().to_string());
+        let target = index.saturating_add_signed(offset);
+        self.move_to(id, folder_id.as_deref(), target)
+    }
+
+    /// 名前変更・移動に合わせて `from` とその中を指すブックマークを `to` へ付け替える
+    ///
+    /// 付け替えたブックマークがあれば true を返す。名前がパスの名前のままなら追従させる。
+    pub fn relocate(&mut self, from: &Path, to: &Path) -> bool {
+        if from == to {
+            return false;
+        }
+        let mut moved = false;
+        for bookmark in self.bookmarks_mut() {
+            let Ok(suffix) = bookmark.path.strip_prefix(from) else {
+                continue;
+            };
+            let target = if suffix.as_os_str().is_empty() {
+                to.to_path_buf()
+            } else {
+                to.join(suffix)
+            };
+            let default_name = bookmark
+                .path
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy() == bookmark.name);
+            if default_name && let Some(name) = target.file_name() {
+                bookmark.name = name.to_string_lossy().to_string();
+            }
+            bookmark.path = target;
+            moved = true;
+        }
+        moved
+    }
+
+    fn bookmarks_mut(&mut self) -> impl Iterator<Item = &mut Bookmark> {
+        self.entries.iter_mut().flat_map(|entry| match entry {
+            BookmarkEntry::Bookmark(bookmark) => std::slice::from_mut(bookmark).iter_mut(),
+            BookmarkEntry::Folder(folder) => folder.bookmarks.iter_mut(),
+        })
+    }
+
+    /// 項目の位置（フォルダの中なら最上位でのフォルダの位置と、フォルダの中での位置）
+    fn locate(&self, id: &str) -> Option<(Option<usize>, usize)> {
+        for (position, entry) in self.entries.iter().enumerate() {
+            if entry.id() == id {
+                return Some((None, position));
+            }
+            if let BookmarkEntry::Folder(folder) = entry
+                && let Some(index) = folder.bookmarks.iter().position(|b| b.id == id)
+            {
+                return Some((Some(position), index));
+            }
+        }
+        None
+    }
+
+    /// 一覧から取り出す
+    fn take(&mut self, id: &str) -> Option<BookmarkEntry> {
+        match self.locate(id)? {
+            (None, index) => Some(self.entries.remove(index)),
+            (Some(folder), index) => match &mut self.entries[folder] {
+                BookmarkEntry::Folder(folder) => {
+                    Some(BookmarkEntry::Bookmark(folder.bookmarks.remove(index)))
+                }
+                BookmarkEntry::Bookmark(_) => None,
+            },
+        }
+    }
+
+    fn folder_mut(&mut self, id: &str) -> Result<&mut BookmarkFolder, AppError> {
+        self.entries
+            .iter_mut()
+            .find_map(|entry| match entry {
+                BookmarkEntry::Folder(folder) if folder.id == id => Some(folder),
+                _ => None,
+            })
+            .ok_or_else(|| unknown_bookmark(id))
+    }
+}
+
+/// 表示名を確かめて前後の空白を除く
+fn validate_name(name: &str) -> Result<String, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::InvalidInput(
+            "Bookmark name must not be empty".to_string(),
+        ));
+    }
+    Ok(name.to_string())
+}
+
+fn unknown_bookmark(id: &str) -> AppError {
+    AppError::InvalidInput(format!("Unknown bookmark: {}", id))
+}
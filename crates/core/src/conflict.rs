@@ -0,0 +1,315 @@
+//! ファイル転送時の競合解決
+//!
+//! コピー・移動先に同名の項目が既に存在する場合の解決方法と、
+//! 「すべてに適用」の記憶を扱います。
+
+use rust_explorer_utils::AppError;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// 競合の解決方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ConflictResolution {
+    /// 転送しない
+    Skip,
+    /// 既存の項目を置き換える
+    Overwrite,
+    /// 「file (2).txt」のように別名で転送する
+    Rename,
+    /// 更新日時が新しい方を残す
+    KeepNewer,
+    /// サイズが大きい方を残す
+    KeepLarger,
+}
+
+impl ConflictResolution {
+    /// すべての解決方法（ダイアログの表示順）
+    pub const ALL: [ConflictResolution; 5] = [
+        ConflictResolution::Skip,
+        ConflictResolution::Overwrite,
+        ConflictResolution::Rename,
+        ConflictResolution::KeepNewer,
+        ConflictResolution::KeepLarger,
+    ];
+
+    /// 表示用ラベル
+    pub fn label(&self) -> &'static str {
+        match self {
+            ConflictResolution::Skip => "スキップ",
+            ConflictResolution::Overwrite => "上書き",
+            ConflictResolution::Rename => "名前を変更",
+            ConflictResolution::KeepNewer => "新しい方を残す",
+            ConflictResolution::KeepLarger => "大きい方を残す",
+        }
+    }
+}
+
+/// ジョブ全体の競合ポリシー
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ConflictPolicy {
+    /// 競合のたびにユーザーに確認する
+    #[default]
+    Ask,
+    /// 常に指定の方法で解決する
+    Always(ConflictResolution),
+}
+
+/// ユーザーの選択
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConflictDecision {
+    pub resolution: ConflictResolution,
+    /// 同じジョブの残りの競合にも適用する
+    pub apply_to_all: bool,
+}
+
+/// 競合の内容
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub source: PathBuf,
+    pub destination: PathBuf,
+    pub source_size: u64,
+    pub destination_size: u64,
+    pub source_modified: Option<SystemTime>,
+    pub destination_modified: Option<SystemTime>,
+    pub source_is_dir: bool,
+    pub destination_is_dir: bool,
+}
+
+impl Conflict {
+    /// 転送元と転送先のメタデータから競合情報を作成
+    pub fn inspect(source: &Path, destination: &Path) -> Result<Self, AppError> {
+        let source_meta = std::fs::symlink_metadata(source)
+            .map_err(|_| AppError::NotFound(source.to_path_buf()))?;
+        let destination_meta = std::fs::symlink_metadata(destination)
+            .map_err(|_| AppError::NotFound(destination.to_path_buf()))?;
+
+        Ok(Self {
+            source: source.to_path_buf(),
+            destination: destination.to_path_buf(),
+            source_size: source_meta.len(),
+            destination_size: destination_meta.len(),
+            source_modified: source_meta.modified().ok(),
+            destination_modified: destination_meta.modified().ok(),
+            source_is_dir: source_meta.is_dir(),
+            destination_is_dir: destination_meta.is_dir(),
+        })
+    }
+}
+
+/// 解決方法を適用した結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConflictAction {
+    /// 転送しない
+    Skip,
+    /// 既存の項目を削除してから転送する
+    Overwrite,
+    /// 指定のパスへ転送する
+    WriteTo(PathBuf),
+}
+
+/// 解決方法を競合に適用する
+pub fn apply_resolution(conflict: &Conflict, resolution: ConflictResolution) -> ConflictAction {
+    match resolution {
+        ConflictResolution::Skip => ConflictAction::Skip,
+        ConflictResolution::Overwrite => ConflictAction::Overwrite,
+        ConflictResolution::Rename => ConflictAction::WriteTo(auto_rename(&conflict.destination)),
+        ConflictResolution::KeepNewer => {
+            match (conflict.source_modified, conflict.destination_modified) {
+                (Some(source), Some(destination)) if source > destination => {
+                    ConflictAction::Overwrite
+                }
+                (Some(_), None) => ConflictAction::Overwrite,
+                _ => ConflictAction::Skip,
+            }
+        }
+        ConflictResolution::KeepLarger => {
+            if conflict.source_size > conflict.destination_size {
+                ConflictAction::Overwrite
+            } else {
+                ConflictAction::Skip
+            }
+        }
+    }
+}
+
+/// 既存の項目と重ならない「name (n).ext」形式のパスを作成
+pub fn auto_rename(path: &Path) -> PathBuf {
+    let parent = path.parent().unwrap_or_else(|| Path::new(""));
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    (2..)
+        .map(|n| parent.join(numbered_name(&file_name, n)))
+        .find(|candidate| std::fs::symlink_metadata(candidate).is_err())
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// 「name (n).ext」形式の名前を作成
+pub(crate) fn numbered_name(file_name: &str, n: u32) -> String {
+    // 隠しファイル（.bashrc など）は全体を名前として扱う
+    let (stem, extension) = match file_name.rfind('.') {
+        Some(index) if index > 0 => (&file_name[..index], &file_name[index..]),
+        _ => (file_name, ""),
+    };
+    let stem = strip_copy_suffix(stem);
+    format!("{stem} ({n}){extension}")
+}
+
+/// 既に「 (n)」が付いている名前から番号を取り除く
+fn strip_copy_suffix(stem: &str) -> &str {
+    if let Some(open) = stem.rfind(" (")
+        && let Some(number) = stem[open + 2..].strip_suffix(')')
+        && !number.is_empty()
+        && number.chars().all(|c| c.is_ascii_digit())
+    {
+        return &stem[..open];
+    }
+    stem
+}
+
+/// ジョブ単位で競合の解決方法を決定する
+#[derive(Debug, Clone, Default)]
+pub struct ConflictResolver {
+    policy: ConflictPolicy,
+    remembered: Option<ConflictResolution>,
+}
+
+impl ConflictResolver {
+    /// 新しいリゾルバーを作成
+    pub fn new(policy: ConflictPolicy) -> Self {
+        Self {
+            policy,
+            remembered: None,
+        }
+    }
+
+    /// 確認なしで決まる解決方法を返す（ユーザーへの確認が必要なら None）
+    pub fn preset(&self) -> Option<ConflictResolution> {
+        match self.policy {
+            ConflictPolicy::Always(resolution) => Some(resolution),
+            ConflictPolicy::Ask => self.remembered,
+        }
+    }
+
+    /// ユーザーの選択を記録し、今回の解決方法を返す
+    pub fn record(&mut self, decision: ConflictDecision) -> ConflictResolution {
+        if decision.apply_to_all {
+            self.remembered = Some(decision.resolution);
+        }
+        decision.resolution
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    fn conflict(source_size: u64, destination_size: u64, newer_source: bool) -> Conflict {
+        let older = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+        let newer = SystemTime::UNIX_EPOCH + Duration::from_secs(2_000);
+        Conflict {
+            source: PathBuf::from("/src/file.txt"),
+            destination: PathBuf::from("/nonexistent-dest/file.txt"),
+            source_size,
+            destination_size,
+            source_modified: Some(if newer_source { newer } else { older }),
+            destination_modified: Some(if newer_source { older } else { newer }),
+            source_is_dir: false,
+            destination_is_dir: false,
+        }
+    }
+
+    #[test]
+    fn test_auto_rename() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("file.txt");
+        fs::write(&path, "a").unwrap();
+
+        assert_eq!(auto_rename(&path), temp_dir.path().join("file (2).txt"));
+
+        fs::write(temp_dir.path().join("file (2).txt"), "b").unwrap();
+        assert_eq!(auto_rename(&path), temp_dir.path().join("file (3).txt"));
+
+        // 番号付きの名前からは連番を続ける
+        assert_eq!(
+            auto_rename(&temp_dir.path().join("file (2).txt")),
+            temp_dir.path().join("file (3).txt")
+        );
+    }
+
+    #[test]
+    fn test_auto_rename_without_extension() {
+        assert_eq!(
+            auto_rename(Path::new("/nonexistent-dir/.bashrc")),
+            PathBuf::from("/nonexistent-dir/.bashrc (2)")
+        );
+        assert_eq!(
+            auto_rename(Path::new("/nonexistent-dir/folder")),
+            PathBuf::from("/nonexistent-dir/folder (2)")
+        );
+    }
+
+    #[test]
+    fn test_apply_resolution() {
+        let c = conflict(10, 20, true);
+        assert_eq!(
+            apply_resolution(&c, ConflictResolution::Skip),
+            ConflictAction::Skip
+        );
+        assert_eq!(
+            apply_resolution(&c, ConflictResolution::Overwrite),
+            ConflictAction::Overwrite
+        );
+        assert_eq!(
+            apply_resolution(&c, ConflictResolution::KeepNewer),
+            ConflictAction::Overwrite
+        );
+        assert_eq!(
+            apply_resolution(&c, ConflictResolution::KeepLarger),
+            ConflictAction::Skip
+        );
+        assert_eq!(
+            apply_resolution(&c, ConflictResolution::Rename),
+            ConflictAction::WriteTo(PathBuf::from("/nonexistent-dest/file (2).txt"))
+        );
+
+        let c = conflict(30, 20, false);
+        assert_eq!(
+            apply_resolution(&c, ConflictResolution::KeepNewer),
+            ConflictAction::Skip
+        );
+        assert_eq!(
+            apply_resolution(&c, ConflictResolution::KeepLarger),
+            ConflictAction::Overwrite
+        );
+    }
+
+    #[test]
+    fn test_resolver_apply_to_all() {
+        let mut resolver = ConflictResolver::new(ConflictPolicy::Ask);
+        assert_eq!(resolver.preset(), None);
+
+        let once = ConflictDecision {
+            resolution: ConflictResolution::Overwrite,
+            apply_to_all: false,
+        };
+        assert_eq!(resolver.record(once), ConflictResolution::Overwrite);
+        assert_eq!(resolver.preset(), None);
+
+        let all = ConflictDecision {
+            resolution: ConflictResolution::Skip,
+            apply_to_all: true,
+        };
+        resolver.record(all);
+        assert_eq!(resolver.preset(), Some(ConflictResolution::Skip));
+
+        let fixed = ConflictResolver::new(ConflictPolicy::Always(ConflictResolution::Rename));
+        assert_eq!(fixed.preset(), Some(ConflictResolution::Rename));
+    }
+}
@@ -0,0 +1,512 @@
+//! ファイルの内容の検索
+//!
+//! ディレクトリ以下のファイルの内容を文字列または正規表現で検索し、見つかった行を
+//! 前後の行とともにファイルごとに届けます。`.gitignore` と対象・除外のグロブに従って
+//! ファイルを選び、バイナリファイルは読み飛ばし、文字コードは内容から判定します。
+//! 検索はジョブとして実行するため、ジョブの一覧に表示され、そこから中止できます。
+
+use crate::filesystem::{FileEntry, FileType};
+use crate::job::{Interrupt, JobContext, JobId, JobKind, JobManager};
+use crate::listing::ListingCanceller;
+use crossbeam_channel::{Receiver, Sender};
+use encoding_rs::{EUC_JP, Encoding, SHIFT_JIS, UTF_8, WINDOWS_1252};
+use ignore::WalkBuilder;
+use ignore::overrides::{Override, OverrideBuilder};
+use regex::{Regex, RegexBuilder};
+use rust_explorer_utils::AppError;
+use std::borrow::Cow;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// バイナリかどうかを判定するために調べる先頭のバイト数
+const BINARY_SNIFF_LEN: usize = 8 * 1024;
+
+/// 進捗を通知する最小間隔
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 大文字と小文字の区別
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CaseSensitivity {
+    /// 区別する
+    Sensitive,
+    /// 区別しない
+    Insensitive,
+    /// 検索文字列に大文字が含まれる場合だけ区別する
+    #[default]
+    Smart,
+}
+
+/// 内容の検索の条件
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContentSearchOptions {
+    /// 検索する文字列
+    pub pattern: String,
+    /// `pattern` を正規表現として扱うか
+    pub regex: bool,
+    /// 大文字と小文字の区別
+    pub case: CaseSensitivity,
+    /// 単語全体に一致する場合だけ見つけるか
+    pub whole_word: bool,
+    /// 対象にするファイルのグロブ（空ならすべて）
+    pub include: Vec<String>,
+    /// 除外するファイル・ディレクトリのグロブ
+    pub exclude: Vec<String>,
+    /// `.gitignore` などの除外設定に従うか
+    pub respect_gitignore: bool,
+    /// 隠しファイル・フォルダも検索するか
+    pub include_hidden: bool,
+    /// 見つかった行の前後に含める行数
+    pub context_lines: usize,
+    /// これより大きいファイルは読み飛ばす（バイト）
+    pub max_file_size: u64,
+    /// 見つかった行がこの数に達したら検索を打ち切る
+    pub max_matches: usize,
+}
+
+impl Default for ContentSearchOptions {
+    fn default() -> Self {
+        Self {
+            pattern: String::new(),
+            regex: false,
+            case: CaseSensitivity::default(),
+            whole_word: false,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            respect_gitignore: true,
+            include_hidden: false,
+            context_lines: 2,
+            max_file_size: 16 * 1024 * 1024,
+            max_matches: 10_000,
+        }
+    }
+}
+
+impl ContentSearchOptions {
+    /// 検索する文字列を指定して既定の条件を作成
+    pub fn new(pattern: impl Into<String>) -> Self {
+        Self {
+            pattern: pattern.into(),
+            ..Self::default()
+        }
+    }
+
+    /// 行を照合する正規表現を作成
+    pub fn matcher(&self) -> Result<Regex, AppError> {
+        if self.pattern.is_empty() {
+            return Err(AppError::InvalidInput(
+                "Search pattern is empty".to_string(),
+            ));
+        }
+
+        let mut pattern = if self.regex {
+            self.pattern.clone()
+        } else {
+            regex::escape(&self.pattern)
+        };
+        if self.whole_word {
+            pattern = format!(r"\b(?:{})\b", pattern);
+        }
+        let case_insensitive = match self.case {
+            CaseSensitivity::Sensitive => false,
+            CaseSensitivity::Insensitive => true,
+            CaseSensitivity::Smart => !self.pattern.chars().any(char::is_uppercase),
+        };
+
+        RegexBuilder::new(&pattern)
+            .case_insensitive(case_insensitive)
+            .build()
+            .map_err(|e| AppError::InvalidInput(format!("Invalid search pattern: {}", e)))
+    }
+
+    /// 対象・除外のグロブを `root` からの相対パスに対する条件にする
+    fn overrides(&self, root: &Path) -> Result<Override, AppError> {
+        let mut builder = OverrideBuilder::new(root);
+        let globs = self
+            .include
+            .iter()
+            .map(|glob| (glob, glob.to_string()))
+            .chain(self.exclude.iter().map(|glob| (glob, format!("!{}", glob))));
+        for (glob, line) in globs {
+            builder
+                .add(&line)
+                .map_err(|e| AppError::InvalidInput(format!("Invalid glob '{}': {}", glob, e)))?;
+        }
+        builder
+            .build()
+            .map_err(|e| AppError::InvalidInput(format!("Invalid glob: {}", e)))
+    }
+}
+
+/// 見つかった1行
+#[derive(Debug, Clone)]
+pub struct ContentMatch {
+    /// 見つかったファイル
+    pub entry: FileEntry,
+    /// 行番号（1始まり）
+    pub line_number: usize,
+    /// 見つかった行
+    pub line: String,
+    /// 行の中で一致した範囲（バイト位置）
+    pub ranges: Vec<Range<usize>>,
+    /// 前の行（古い順）
+    pub before: Vec<String>,
+    /// 後の行
+    pub after: Vec<String>,
+    /// ファイルの文字コード
+    pub encoding: &'static str,
+}
+
+/// 検索の進み具合
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ContentSearchProgress {
+    /// 内容を調べたファイル数
+    pub searched_files: usize,
+    /// 見つかったファイル数
+    pub matched_files: usize,
+    /// 見つかった行数
+    pub matches: usize,
+    /// バイナリ・大きすぎる・読めないなどの理由で読み飛ばしたファイル数
+    pub skipped_files: usize,
+    /// 見つかった行が上限に達して打ち切ったか
+    pub truncated: bool,
+}
+
+/// 検索中に届くイベント
+#[derive(Debug)]
+pub enum ContentSearchEvent {
+    /// 1つのファイルで見つかった行
+    Matches(Vec<ContentMatch>),
+    /// 進み具合
+    Progress(ContentSearchProgress),
+    /// 検索を始められなかった
+    Failed(AppError),
+    /// ジョブの一覧などから中止された
+    Cancelled(ContentSearchProgress),
+    /// すべて検索した
+    Finished(ContentSearchProgress),
+}
+
+/// バックグラウンドで進む内容の検索（破棄すると検索を中断する）
+pub struct ContentSearch {
+    job: JobId,
+    root: PathBuf,
+    receiver: Receiver<ContentSearchEvent>,
+    canceller: ListingCanceller,
+}
+
+impl ContentSearch {
+    /// `root` 以下の検索を `jobs` のジョブとして開始
+    ///
+    /// 条件が正しくない場合は検索を始めずに `AppError::InvalidInput` を返す。
+    pub fn start(
+        jobs: &JobManager,
+        root: &Path,
+        options: ContentSearchOptions,
+    ) -> Result<Self, AppError> {
+        if !root.is_dir() {
+            return Err(AppError::InvalidPath(root.to_path_buf()));
+        }
+        let matcher = options.matcher()?;
+        let overrides = options.overrides(root)?;
+
+        // 表示側が追いつかない場合に検索を待たせるため上限付きにする
+        let (sender, receiver) = crossbeam_channel::bounded(64);
+        let canceller = ListingCanceller::default();
+        let worker = SearchWorker {
+            matcher,
+            options,
+            sender,
+            canceller: canceller.clone(),
+            progress: ContentSearchProgress::default(),
+            last_progress: Instant::now(),
+        };
+        let kind = JobKind::ContentSearch {
+            root: root.to_path_buf(),
+            pattern: worker.options.pattern.clone(),
+        };
+        let walk_root = root.to_path_buf();
+        let job = jobs.submit_task(
+            kind,
+            Box::new(move |context| worker.run(&walk_root, overrides, context)),
+        );
+
+        Ok(Self {
+            job,
+            root: root.to_path_buf(),
+            receiver,
+            canceller,
+        })
+    }
+
+    /// 検索を実行しているジョブ
+    pub fn job_id(&self) -> JobId {
+        self.job
+    }
+
+    /// 検索しているディレクトリ
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// イベントの受信側（UIスレッドへ渡す場合などに使う）
+    pub fn receiver(&self) -> &Receiver<ContentSearchEvent> {
+        &self.receiver
+    }
+
+    /// 中断用のハンドル
+    pub fn canceller(&self) -> ListingCanceller {
+        self.canceller.clone()
+    }
+
+    /// 検索を中断
+    pub fn cancel(&self) {
+        self.canceller.cancel();
+    }
+
+    /// 次のイベントを待つ（検索が終わっていれば None）
+    pub fn recv(&self) -> Option<ContentSearchEvent> {
+        self.receiver.recv().ok()
+    }
+
+    /// 検索を最後まで待ち、見つかった行と結果をまとめて返す
+    pub fn collect(self) -> Result<(Vec<ContentMatch>, ContentSearchProgress), AppError> {
+        let mut matches = Vec::new();
+        while let Some(event) = self.recv() {
+            match event {
+                ContentSearchEvent::Matches(batch) => matches.extend(batch),
+                ContentSearchEvent::Progress(_) => {}
+                ContentSearchEvent::Failed(error) => return Err(error),
+                ContentSearchEvent::Cancelled(_) => {
+                    return Err(AppError::InvalidOperation(
+                        "Content search was cancelled".to_string(),
+                    ));
+                }
+                ContentSearchEvent::Finished(progress) => return Ok((matches, progress)),
+            }
+        }
+        Err(AppError::Internal(
+            "Content search stopped unexpectedly".to_string(),
+        ))
+    }
+}
+
+impl Drop for ContentSearch {
+    fn drop(&mut self) {
+        self.canceller.cancel();
+    }
+}
+
+/// 検索ジョブの本体（受信側が破棄されるか中断されたら終了する）
+struct SearchWorker {
+    matcher: Regex,
+    options: ContentSearchOptions,
+    sender: Sender<ContentSearchEvent>,
+    canceller: ListingCanceller,
+    progress: ContentSearchProgress,
+    last_progress: Instant,
+}
+
+impl SearchWorker {
+    fn run(
+        mut self,
+        root: &Path,
+        overrides: Override,
+        context: &JobContext,
+    ) -> Result<(), Interrupt> {
+        let respect_gitignore = self.options.respect_gitignore;
+        let walker = WalkBuilder::new(root)
+            .hidden(!self.options.include_hidden)
+            .git_ignore(respect_gitignore)
+            .git_global(respect_gitignore)
+            .git_exclude(respect_gitignore)
+            .ignore(respect_gitignore)
+            .parents(respect_gitignore)
+            // Git の管理下でなくても .gitignore に従う
+            .require_git(false)
+            .overrides(overrides)
+            .sort_by_file_name(|a, b| a.cmp(b))
+            .build();
+
+        for result in walker {
+            if self.canceller.is_cancelled() {
+                return Err(Interrupt::Cancelled);
+            }
+            if let Err(interrupt) = context.checkpoint() {
+                // 中止を知らせて表示側の検索中の状態を終わらせる
+                let _ = self
+                    .sender
+                    .send(ContentSearchEvent::Cancelled(self.progress));
+                return Err(interrupt);
+            }
+            let Ok(entry) = result else {
+                self.progress.skipped_files += 1;
+                continue;
+            };
+            if !entry.file_type().is_some_and(|t| t.is_file()) {
+                continue;
+            }
+
+            let matches = self.search_file(entry.path());
+            let processed = (self.progress.searched_files + self.progress.skipped_files) as u64;
+            context.update(|progress| {
+                progress.processed_files = processed;
+                progress.current_path = Some(entry.path().to_path_buf());
+            });
+            if !matches.is_empty() {
+                self.progress.matched_files += 1;
+                self.progress.matches += matches.len();
+                if self
+                    .sender
+                    .send(ContentSearchEvent::Matches(matches))
+                    .is_err()
+                {
+                    return Err(Interrupt::Cancelled);
+                }
+            }
+            if self.progress.matches >= self.options.max_matches {
+                self.progress.truncated = true;
+                break;
+            }
+            if self.last_progress.elapsed() >= PROGRESS_INTERVAL {
+                self.last_progress = Instant::now();
+                if self
+                    .sender
+                    .send(ContentSearchEvent::Progress(self.progress))
+                    .is_err()
+                {
+                    return Err(Interrupt::Cancelled);
+                }
+            }
+        }
+
+        let _ = self
+            .sender
+            .send(ContentSearchEvent::Finished(self.progress));
+        Ok(())
+    }
+
+    /// 1つのファイルを検索（読めないファイルは読み飛ばした数に数える）
+    fn search_file(&mut self, path: &Path) -> Vec<ContentMatch> {
+        let Some(entry) = FileEntry::from_path(path) else {
+            self.progress.skipped_files += 1;
+            return Vec::new();
+        };
+        if entry.file_type != FileType::File || entry.size > self.options.max_file_size {
+            self.progress.skipped_files += 1;
+            return Vec::new();
+        }
+        let Ok(bytes) = std::fs::read(path) else {
+            self.progress.skipped_files += 1;
+            return Vec::new();
+        };
+        let Some((text, encoding)) = decode(&bytes) else {
+            self.progress.skipped_files += 1;
+            return Vec::new();
+        };
+        self.progress.searched_files += 1;
+
+        let remaining = self.options.max_matches - self.progress.matches;
+        let lines: Vec<&str> = text.lines().collect();
+        let context = self.options.context_lines;
+        let mut matches = Vec::new();
+        for (index, line) in lines.iter().enumerate() {
+            let ranges: Vec<Range<usize>> = self
+                .matcher
+                .find_iter(line)
+                .map(|found| found.range())
+                .filter(|range| !range.is_empty())
+                .collect();
+            if ranges.is_empty() {
+                continue;
+            }
+            let after_end = (index + 1 + context).min(lines.len());
+            matches.push(ContentMatch {
+                entry: entry.clone(),
+                line_number: index + 1,
+                line: line.to_string(),
+                ranges,
+                before: to_strings(&lines[index.saturating_sub(context)..index]),
+                after: to_strings(&lines[index + 1..after_end]),
+                encoding: encoding.name(),
+            });
+            if matches.len() >= remaining {
+                break;
+            }
+        }
+        matches
+    }
+}
+
+fn to_strings(lines: &[&str]) -> Vec<String> {
+    lines.iter().map(|line| line.to_string()).collect()
+}
+
+/// ファイルの内容を文字列にする（バイナリと判定した場合は None）
+///
+/// BOM があればそれに従い、なければ UTF-8、Shift_JIS、EUC-JP の順に誤りなく
+/// 読めるものを選ぶ。どれでも読めない場合は Windows-1252 として読む。
+pub(crate) fn decode(bytes: &[u8]) -> Option<(Cow<'_, str>, &'static Encoding)> {
+    if let Some((encoding, bom_length)) = Encoding::for_bom(bytes) {
+        let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_length..]);
+        return Some((text, encoding));
+    }
+    if bytes[..bytes.len().min(BINARY_SNIFF_LEN)].contains(&0) {
+        return None;
+    }
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return Some((Cow::Borrowed(text), UTF_8));
+    }
+    for encoding in [SHIFT_JIS, EUC_JP] {
+        if let Some(text) = encoding.decode_without_bom_handling_and_without_replacement(bytes) {
+            return Some((text, encoding));
+        }
+    }
+    let (text, _) = WINDOWS_1252.decode_without_bom_handling(bytes);
+    Some((text, WINDOWS_1252))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_detects_encoding() {
+        assert_eq!(decode(b"plain").unwrap().1, UTF_8);
+        assert!(decode(b"ELF\0\x01\x02").is_none());
+
+        let (text, encoding) = decode(&[0xFF, 0xFE, b'h', 0, b'i', 0]).unwrap();
+        assert_eq!((text.as_ref(), encoding), ("hi", encoding_rs::UTF_16LE));
+
+        let (bytes, _, _) = SHIFT_JIS.encode("日本語のテキスト");
+        let (text, encoding) = decode(&bytes).unwrap();
+        assert_eq!((text.as_ref(), encoding), ("日本語のテキスト", SHIFT_JIS));
+    }
+
+    #[test]
+    fn test_matcher_options() {
+        let matcher = |options: ContentSearchOptions| options.matcher().unwrap();
+
+        let literal = matcher(ContentSearchOptions::new("a.b"));
+        assert!(literal.is_match("A.B") && !literal.is_match("axb"));
+
+        let smart = matcher(ContentSearchOptions::new("Foo"));
+        assert!(!smart.is_match("foo"));
+
+        let word = matcher(ContentSearchOptions {
+            whole_word: true,
+            ..ContentSearchOptions::new("cat")
+        });
+        assert!(word.is_match("a cat.") && !word.is_match("concatenate"));
+
+        assert!(
+            ContentSearchOptions {
+                regex: true,
+                ..ContentSearchOptions::new("(")
+            }
+            .matcher()
+            .is_err()
+        );
+        assert!(ContentSearchOptions::default().matcher().is_err());
+    }
+}
@@ -1,6 +1,8 @@
 //! イベント管理システム
 
+use crate::watcher::DirectoryDelta;
 use rust_explorer_utils::AppError;
+use std::sync::{Arc, RwLock};
 
 /// イベントタイプ
 #[derive(Debug, Clone)]
@@ -13,21 +15,49 @@ pub enum Event {
     TabCreated(String),
     /// タブが閉じられた
     TabClosed(String),
+    /// 監視中のディレクトリの内容が変化した
+    DirectoryUpdated(DirectoryDelta),
 }
 
-/// イベント管理
+/// イベントコールバック
+type EventCallback = Box<dyn Fn(&Event) + Send + Sync>;
+
+/// イベント管理（クローンは同じ購読者を共有する）
+#[derive(Clone)]
 pub struct EventManager {
-    // 将来的にはイベントハンドラーのリストを保持
+    callbacks: Arc<RwLock<Vec<EventCallback>>>,
 }
 
 impl EventManager {
     /// 新しいイベントマネージャーを作成
     pub fn new() -> Self {
-        Self {}
+        Self {
+            callbacks: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+
+    /// イベントを購読
+    pub fn on_event<F>(&self, callback: F) -> Result<(), AppError>
+    where
+        F: Fn(&Event) + Send + Sync + 'static,
+    {
+        let mut callbacks = self
+            .callbacks
+            .write()
+            .map_err(|e| AppError::Internal(format!("Failed to write callbacks: {}", e)))?;
+
+        callbacks.push(Box::new(callback));
+        Ok(())
     }
 
     /// イベントを処理
     pub fn handle_event(&self, event: Event) -> Result<(), AppError> {
+        if let Ok(callbacks) = self.callbacks.read() {
+            for callback in callbacks.iter() {
+                callback(&event);
+            }
+        }
+
         match event {
             Event::FileSelected(path) => {
                 // ファイル選択処理
@@ -45,6 +75,7 @@ impl EventManager {
                 // タブ閉じる処理
                 println!("Tab closed: {}", name);
             }
+            Event::DirectoryUpdated(_) => {}
         }
         Ok(())
     }
@@ -2,12 +2,15 @@
 //!
 //! ファイルエントリのソートとフィルタ機能を提供します。
 
+use crate::query::FileQuery;
 use crate::{FileEntry, FileType};
+use rust_explorer_utils::AppError;
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::path::Path;
 
 /// ソート条件
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SortCriteria {
     /// 名前順
     Name,
@@ -26,7 +29,7 @@ impl Default for SortCriteria {
 }
 
 /// ソート方向
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SortDirection {
     /// 昇順
     Ascending,
@@ -41,7 +44,7 @@ impl Default for SortDirection {
 }
 
 /// ソート設定
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SortConfig {
     /// ソート条件
     pub criteria: SortCriteria,
@@ -62,7 +65,7 @@ impl Default for SortConfig {
 }
 
 /// フィルタ条件
-#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub struct FilterCriteria {
     /// 隠しファイル・フォルダを表示するか
     pub show_hidden: bool,
@@ -74,12 +77,17 @@ pub struct FilterCriteria {
     pub min_size: Option<u64>,
     /// 最大サイズ（バイト）
     pub max_size: Option<u64>,
+    /// クエリ（`ext:rs size:>10MB` など）
+    #[serde(default)]
+    pub query: Option<String>,
 }
 
 /// ファイルソート・フィルタマネージャー
 pub struct FileSortFilterManager {
     sort_config: SortConfig,
     filter_criteria: FilterCriteria,
+    /// `filter_criteria.query` を解析したもの
+    query: Option<FileQuery>,
 }
 
 impl FileSortFilterManager {
@@ -88,6 +96,7 @@ impl FileSortFilterManager {
         Self {
             sort_config: SortConfig::default(),
             filter_criteria: FilterCriteria::default(),
+            query: None,
         }
     }
 
@@ -97,10 +106,32 @@ impl FileSortFilterManager {
     }
 
     /// フィルタ条件を更新
+    ///
+    /// 保存されていたクエリが解析できない場合は、クエリなしとして扱う。
     pub fn update_filter_criteria(&mut self, criteria: FilterCriteria) {
+        self.query = criteria
+            .query
+            .as_deref()
+            .and_then(|query| FileQuery::parse(query).ok())
+            .filter(|query| !query.is_empty());
         self.filter_criteria = criteria;
     }
 
+    /// クエリを設定（空文字列で解除）
+    ///
+    /// 解析できない場合は現在のクエリを変更せずにエラーを返す。
+    pub fn set_query(&mut self, text: &str) -> Result<(), AppError> {
+        let query = FileQuery::parse(text)?;
+        if query.is_empty() {
+            self.query = None;
+            self.filter_criteria.query = None;
+        } else {
+            self.filter_criteria.query = Some(text.to_string());
+            self.query = Some(query);
+        }
+        Ok(())
+    }
+
     /// 現在のソート設定を取得
     pub fn sort_config(&self) -> SortConfig {
         self.sort_config
@@ -172,6 +203,15 @@ impl FileSortFilterManager {
             }
         }
 
+        // クエリ
+        if self
+            .query
+            .as_ref()
+            .is_some_and(|query| !query.matches(entry))
+        {
+            return false;
+        }
+
         true
     }
 
@@ -232,6 +272,7 @@ impl FileSortFilterManager {
     /// フィルタをクリア
     pub fn clear_filters(&mut self) {
         self.filter_criteria = FilterCriteria::default();
+        self.query = None;
     }
 
     /// フィルタが適用されているかチェック
@@ -240,6 +281,7 @@ impl FileSortFilterManager {
             || self.filter_criteria.extension_filter.is_some()
             || self.filter_criteria.min_size.is_some()
             || self.filter_criteria.max_size.is_some()
+            || self.query.is_some()
             || self.filter_criteria.show_hidden
     }
 }
@@ -603,6 +645,7 @@ mod tests {
             min_size: Some(100),
             max_size: Some(1000),
             show_hidden: true,
+            query: None,
         });
 
         assert!(manager.has_active_filters());
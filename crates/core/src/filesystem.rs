@@ -2,8 +2,10 @@
 
 use rust_explorer_utils::AppError;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::time::SystemTime;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
 use tokio::fs;
 
 /// ファイルタイプ
@@ -25,6 +27,30 @@ pub struct FileEntry {
     pub modified: Option<SystemTime>,
 }
 
+impl FileEntry {
+    /// パスの現在の状態からエントリを作成（存在しなければ None）
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let metadata = std::fs::symlink_metadata(path).ok()?;
+        let file_type = if metadata.is_dir() {
+            FileType::Directory
+        } else if metadata.is_file() {
+            FileType::File
+        } else if metadata.file_type().is_symlink() {
+            FileType::SymLink
+        } else {
+            FileType::Other
+        };
+
+        Some(Self {
+            name: path.file_name()?.to_string_lossy().to_string(),
+            path: path.to_path_buf(),
+            file_type,
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+}
+
 /// ファイル情報
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileInfo {
@@ -46,6 +72,21 @@ pub trait FileSystemApi {
 
     /// アクセス可能かチェック
     fn is_accessible(&self, path: &Path) -> bool;
+
+    /// ファイル・ディレクトリをコピー（`to` は既存であってはならない）
+    async fn copy(&self, from: &Path, to: &Path) -> Result<(), AppError>;
+
+    /// ファイル・ディレクトリを移動（`to` は既存であってはならない）
+    async fn move_item(&self, from: &Path, to: &Path) -> Result<(), AppError>;
+
+    /// ファイル・ディレクトリを削除（`recursive` が false の場合は空ディレクトリのみ）
+    async fn delete(&self, path: &Path, recursive: bool) -> Result<(), AppError>;
+
+    /// 同じディレクトリ内で名前を変更し、新しいパスを返す
+    async fn rename(&self, path: &Path, new_name: &str) -> Result<PathBuf, AppError>;
+
+    /// ディレクトリを作成（親ディレクトリは既存である必要がある）
+    async fn create_directory(&self, path: &Path) -> Result<(), AppError>;
 }
 
 /// ファイルシステム管理
@@ -77,6 +118,81 @@ impl FileSystemManager {
         }
     }
 
+    /// 操作元が存在し、操作先が存在しないことを確認
+    async fn check_transfer(from: &Path, to: &Path) -> Result<std::fs::Metadata, AppError> {
+        let metadata = fs::symlink_metadata(from)
+            .await
+            .map_err(|_| AppError::NotFound(from.to_path_buf()))?;
+
+        if fs::symlink_metadata(to).await.is_ok() {
+            return Err(AppError::AlreadyExists(to.to_path_buf()));
+        }
+
+        if metadata.is_dir() && to.starts_with(from) {
+            return Err(AppError::InvalidOperation(format!(
+                "Cannot copy or move {} into itself",
+                from.display()
+            )));
+        }
+
+        Ok(metadata)
+    }
+
+    /// ディレクトリを再帰的にコピー（深い階層でもスタックを消費しないよう反復で処理）
+    async fn copy_recursive(from: &Path, to: &Path) -> Result<(), AppError> {
+        let copy_err = |source| AppError::CopyFailed {
+            from: from.to_path_buf(),
+            to: to.to_path_buf(),
+            source,
+        };
+
+        let mut pending = vec![(from.to_path_buf(), to.to_path_buf())];
+        while let Some((src, dst)) = pending.pop() {
+            let metadata = fs::symlink_metadata(&src).await.map_err(copy_err)?;
+
+            if metadata.file_type().is_symlink() {
+                copy_symlink(&src, &dst).await.map_err(copy_err)?;
+            } else if metadata.is_dir() {
+                fs::create_dir(&dst).await.map_err(copy_err)?;
+                let mut dir = fs::read_dir(&src).await.map_err(copy_err)?;
+                while let Some(entry) = dir.next_entry().await.map_err(copy_err)? {
+                    pending.push((entry.path(), dst.join(entry.file_name())));
+                }
+            } else {
+                fs::copy(&src, &dst).await.map_err(copy_err)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 削除処理の本体
+    async fn remove_path(path: &Path, recursive: bool) -> Result<(), AppError> {
+        let metadata = fs::symlink_metadata(path)
+            .await
+            .map_err(|_| AppError::NotFound(path.to_path_buf()))?;
+
+        let result = if metadata.is_dir() {
+            if recursive {
+                fs::remove_dir_all(path).await
+            } else {
+                fs::remove_dir(path).await
+            }
+        } else {
+            fs::remove_file(path).await
+        };
+
+        result.map_err(|source| match source.kind() {
+            std::io::ErrorKind::DirectoryNotEmpty => {
+                AppError::DirectoryNotEmpty(path.to_path_buf())
+            }
+            _ => AppError::DeleteFailed {
+                path: path.to_path_buf(),
+                source,
+            },
+        })
+    }
+
     /// ディレクトリの内容を一覧取得（将来の実装）
     pub async fn list_directory_sync<P: AsRef<Path>>(
         &self,
@@ -166,6 +282,86 @@ impl FileSystemApi for FileSystemManager {
     fn is_accessible(&self, path: &Path) -> bool {
         path.exists() && (path.is_file() || path.is_dir())
     }
+
+    async fn copy(&self, from: &Path, to: &Path) -> Result<(), AppError> {
+        Self::check_transfer(from, to).await?;
+        Self::copy_recursive(from, to).await
+    }
+
+    async fn move_item(&self, from: &Path, to: &Path) -> Result<(), AppError> {
+        Self::check_transfer(from, to).await?;
+
+        match fs::rename(from, to).await {
+            Ok(()) => Ok(()),
+            // 別デバイス間の移動はコピー後に削除する
+            Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {
+                Self::copy_recursive(from, to).await?;
+                Self::remove_path(from, true).await
+            }
+            Err(source) => Err(AppError::MoveFailed {
+                from: from.to_path_buf(),
+                to: to.to_path_buf(),
+                source,
+            }),
+        }
+    }
+
+    async fn delete(&self, path: &Path, recursive: bool) -> Result<(), AppError> {
+        Self::remove_path(path, recursive).await
+    }
+
+    async fn rename(&self, path: &Path, new_name: &str) -> Result<PathBuf, AppError> {
+        if new_name.is_empty()
+            || new_name == "."
+            || new_name == ".."
+            || new_name.contains(std::path::is_separator)
+        {
+            return Err(AppError::InvalidInput(format!(
+                "Invalid file name: {new_name}"
+            )));
+        }
+
+        if fs::symlink_metadata(path).await.is_err() {
+            return Err(AppError::NotFound(path.to_path_buf()));
+        }
+
+        let target = path
+            .parent()
+            .ok_or_else(|| AppError::InvalidPath(path.to_path_buf()))?
+            .join(new_name);
+
+        if fs::symlink_metadata(&target).await.is_ok() {
+            return Err(AppError::AlreadyExists(target));
+        }
+
+        fs::rename(path, &target)
+            .await
+            .map_err(|source| AppError::RenameFailed {
+                from: path.to_path_buf(),
+                to: target.clone(),
+                source,
+            })?;
+
+        Ok(target)
+    }
+
+    async fn create_directory(&self, path: &Path) -> Result<(), AppError> {
+        if fs::symlink_metadata(path).await.is_ok() {
+            return Err(AppError::AlreadyExists(path.to_path_buf()));
+        }
+
+        fs::create_dir(path)
+            .await
+            .map_err(|source| match source.kind() {
+                std::io::ErrorKind::NotFound => {
+                    AppError::NotFound(path.parent().unwrap_or(path).to_path_buf())
+                }
+                _ => AppError::CreateFailed {
+                    path: path.to_path_buf(),
+                    source,
+                },
+            })
+    }
 }
 
 impl Default for FileSystemManager {
@@ -174,16 +370,231 @@ impl Default for FileSystemManager {
     }
 }
 
-/// キャッシュ付きファイルシステム管理（将来の実装）
+/// シンボリックリンクはリンク先ではなくリンク自体を複製する
+#[cfg(unix)]
+pub(crate) async fn copy_symlink(src: &Path, dst: &Path) -> std::io::Result<()> {
+    let target = fs::read_link(src).await?;
+    fs::symlink(target, dst).await
+}
+
+/// シンボリックリンクはリンク先ではなくリンク自体を複製する
+#[cfg(not(unix))]
+pub(crate) async fn copy_symlink(src: &Path, dst: &Path) -> std::io::Result<()> {
+    fs::copy(src, dst).await.map(|_| ())
+}
+
+/// ファイルまたはディレクトリを完全に削除（ディレクトリは中身ごと）
+pub(crate) async fn remove_tree(path: &Path) -> Result<(), AppError> {
+    let is_dir = fs::symlink_metadata(path)
+        .await
+        .map(|m| m.is_dir())
+        .unwrap_or(false);
+    let result = if is_dir {
+        fs::remove_dir_all(path).await
+    } else {
+        fs::remove_file(path).await
+    };
+    result.map_err(|source| AppError::DeleteFailed {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// ディレクトリキャッシュの設定
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheConfig {
+    /// 保持するディレクトリ数の上限
+    pub max_entries: usize,
+    /// 更新日時が変わっていなくても再取得するまでの時間
+    pub ttl: Duration,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            max_entries: 256,
+            ttl: Duration::from_secs(30),
+        }
+    }
+}
+
+/// キャッシュの統計情報
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// 上限を超えて追い出された数
+    pub evictions: u64,
+    /// ディレクトリの更新日時の変化で破棄された数
+    pub stale: u64,
+    /// 現在保持しているディレクトリ数
+    pub entries: usize,
+}
+
+impl CacheStats {
+    /// ヒット率（0.0〜1.0）
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            self.hits as f64 / total as f64
+        }
+    }
+}
+
+/// キャッシュされたディレクトリ一覧
+struct CachedListing {
+    entries: Vec<FileEntry>,
+    /// 取得時点のディレクトリの更新日時
+    modified: Option<SystemTime>,
+    fetched_at: Instant,
+    /// 最後に使われた順番（LRU）
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct CacheState {
+    listings: HashMap<PathBuf, CachedListing>,
+    clock: u64,
+    stats: CacheStats,
+}
+
+/// キャッシュ付きファイルシステム管理
+///
+/// ディレクトリ一覧をパスごとに LRU で保持し、ディレクトリの更新日時と TTL で
+/// 再検証します。API 経由の変更操作は影響するエントリを自動的に無効化します。
 pub struct CachedFileSystemManager {
     inner: FileSystemManager,
-    // cache: HashMap<PathBuf, (Vec<FileEntry>, SystemTime)>,
+    config: CacheConfig,
+    state: Mutex<CacheState>,
 }
 
 impl CachedFileSystemManager {
     pub fn new() -> Self {
+        Self::with_config(CacheConfig::default())
+    }
+
+    /// 設定を指定してキャッシュ付きマネージャーを作成
+    pub fn with_config(config: CacheConfig) -> Self {
         Self {
             inner: FileSystemManager::new(),
+            config,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    /// キャッシュの設定
+    pub fn config(&self) -> &CacheConfig {
+        &self.config
+    }
+
+    /// 統計情報を取得
+    pub fn stats(&self) -> CacheStats {
+        self.state
+            .lock()
+            .map(|state| CacheStats {
+                entries: state.listings.len(),
+                ..state.stats.clone()
+            })
+            .unwrap_or_default()
+    }
+
+    /// 統計情報をリセット
+    pub fn reset_stats(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.stats = CacheStats::default();
+        }
+    }
+
+    /// 指定ディレクトリのキャッシュを破棄
+    pub fn invalidate(&self, path: &Path) {
+        if let Ok(mut state) = self.state.lock() {
+            state.listings.remove(path);
+        }
+    }
+
+    /// 指定パス以下のすべてのキャッシュを破棄
+    pub fn invalidate_subtree(&self, path: &Path) {
+        if let Ok(mut state) = self.state.lock() {
+            state.listings.retain(|key, _| !key.starts_with(path));
+        }
+    }
+
+    /// すべてのキャッシュを破棄
+    pub fn clear(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.listings.clear();
+        }
+    }
+
+    /// 項目の変更に伴い、項目自身以下と親ディレクトリのキャッシュを破棄
+    fn invalidate_item(&self, path: &Path) {
+        self.invalidate_subtree(path);
+        if let Some(parent) = path.parent() {
+            self.invalidate(parent);
+        }
+    }
+
+    /// 有効なキャッシュがあれば返す
+    fn lookup(&self, path: &Path, modified: Option<SystemTime>) -> Option<Vec<FileEntry>> {
+        let mut state = self.state.lock().ok()?;
+        state.clock += 1;
+        let clock = state.clock;
+
+        let fresh = match state.listings.get_mut(path) {
+            Some(listing)
+                if listing.modified.is_some()
+                    && listing.modified == modified
+                    && listing.fetched_at.elapsed() < self.config.ttl =>
+            {
+                listing.last_used = clock;
+                Some(listing.entries.clone())
+            }
+            Some(_) => {
+                state.listings.remove(path);
+                state.stats.stale += 1;
+                None
+            }
+            None => None,
+        };
+
+        if fresh.is_some() {
+            state.stats.hits += 1;
+        } else {
+            state.stats.misses += 1;
+        }
+        fresh
+    }
+
+    /// 一覧をキャッシュに追加し、上限を超えた分を古い順に追い出す
+    fn store(&self, path: &Path, entries: &[FileEntry], modified: Option<SystemTime>) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        state.clock += 1;
+        let clock = state.clock;
+        state.listings.insert(
+            path.to_path_buf(),
+            CachedListing {
+                entries: entries.to_vec(),
+                modified,
+                fetched_at: Instant::now(),
+                last_used: clock,
+            },
+        );
+
+        while state.listings.len() > self.config.max_entries.max(1) {
+            let Some(oldest) = state
+                .listings
+                .iter()
+                .min_by_key(|(_, listing)| listing.last_used)
+                .map(|(key, _)| key.clone())
+            else {
+                break;
+            };
+            state.listings.remove(&oldest);
+            state.stats.evictions += 1;
         }
     }
 }
@@ -191,8 +602,18 @@ impl CachedFileSystemManager {
 #[async_trait::async_trait]
 impl FileSystemApi for CachedFileSystemManager {
     async fn list_directory(&self, path: &Path) -> Result<Vec<FileEntry>, AppError> {
-        // 将来的にキャッシュ機能を実装
-        self.inner.list_directory(path).await
+        // 一覧の取得中に変更された場合に次回再取得されるよう、更新日時は先に取得する
+        let modified = fs::metadata(path)
+            .await
+            .ok()
+            .and_then(|m| m.modified().ok());
+        if let Some(entries) = self.lookup(path, modified) {
+            return Ok(entries);
+        }
+
+        let entries = self.inner.list_directory(path).await?;
+        self.store(path, &entries, modified);
+        Ok(entries)
     }
 
     async fn get_file_info(&self, path: &Path) -> Result<FileInfo, AppError> {
@@ -202,6 +623,40 @@ impl FileSystemApi for CachedFileSystemManager {
     fn is_accessible(&self, path: &Path) -> bool {
         self.inner.is_accessible(path)
     }
+
+    async fn copy(&self, from: &Path, to: &Path) -> Result<(), AppError> {
+        let result = self.inner.copy(from, to).await;
+        self.invalidate_item(to);
+        result
+    }
+
+    async fn move_item(&self, from: &Path, to: &Path) -> Result<(), AppError> {
+        let result = self.inner.move_item(from, to).await;
+        self.invalidate_item(from);
+        self.invalidate_item(to);
+        result
+    }
+
+    async fn delete(&self, path: &Path, recursive: bool) -> Result<(), AppError> {
+        let result = self.inner.delete(path, recursive).await;
+        self.invalidate_item(path);
+        result
+    }
+
+    async fn rename(&self, path: &Path, new_name: &str) -> Result<PathBuf, AppError> {
+        let result = self.inner.rename(path, new_name).await;
+        self.invalidate_item(path);
+        if let Ok(new_path) = &result {
+            self.invalidate_item(new_path);
+        }
+        result
+    }
+
+    async fn create_directory(&self, path: &Path) -> Result<(), AppError> {
+        let result = self.inner.create_directory(path).await;
+        self.invalidate_item(path);
+        result
+    }
 }
 
 impl Default for CachedFileSystemManager {
@@ -0,0 +1,289 @@
+//! 訪れた場所の履歴
+//!
+//! タブをまたいでディレクトリの移動とファイルを開いた記録を残し、訪れた回数と
+//! 新しさを合わせた度合い（frecency）で最近の場所を並べる。タブの戻る・進むの
+//! 履歴と違い、アプリを閉じても残る。
+
+use crate::search::glob_match;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// 履歴に残す場所の最大数（超えたら度合いの低い場所から忘れる）
+pub const MAX_HISTORY_ENTRIES: usize = 500;
+
+/// 度合いの計算に使う直近の訪問の数
+const MAX_SAMPLED_VISITS: usize = 10;
+
+/// 訪れた場所の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VisitKind {
+    /// 開いたディレクトリ
+    Directory,
+    /// 開いたファイル
+    File,
+}
+
+/// 履歴の1項目
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub path: PathBuf,
+    pub kind: VisitKind,
+    /// 訪れた回数
+    pub visit_count: u32,
+    /// 最後に訪れた日時
+    pub last_visited: DateTime<Utc>,
+    /// 直近の訪問日時（古い順、最大 `MAX_SAMPLED_VISITS` 件）
+    #[serde(default)]
+    pub recent_visits: Vec<DateTime<Utc>>,
+}
+
+impl HistoryEntry {
+    /// 訪れた回数と新しさを合わせた度合い（大きいほどよく使う）
+    ///
+    /// 直近の訪問の重みの平均に訪れた回数を掛ける。
+    pub fn frecency(&self, now: DateTime<Utc>) -> f64 {
+        let weights: Vec<f64> = if self.recent_visits.is_empty() {
+            vec![age_weight(now, self.last_visited)]
+        } else {
+            self.recent_visits
+                .iter()
+                .map(|visited| age_weight(now, *visited))
+                .collect()
+        };
+        let average = weights.iter().sum::<f64>() / weights.len() as f64;
+        f64::from(self.visit_count) * average / 100.0
+    }
+
+    fn visit(&mut self, kind: VisitKind, at: DateTime<Utc>) {
+        self.kind = kind;
+        self.visit_count = self.visit_count.saturating_add(1);
+        self.last_visited = self.last_visited.max(at);
+        self.recent_visits.push(at);
+        self.trim_visits();
+    }
+
+    /// 同じ場所になった項目の記録を合わせる
+    fn merge(&mut self, other: HistoryEntry) {
+        self.visit_count = self.visit_count.saturating_add(other.visit_count);
+        self.last_visited = self.last_visited.max(other.last_visited);
+        self.recent_visits.extend(other.recent_visits);
+        self.trim_visits();
+    }
+
+    fn trim_visits(&mut self) {
+        self.recent_visits.sort();
+        let excess = self.recent_visits.len().saturating_sub(MAX_SAMPLED_VISITS);
+        self.recent_visits.drain(..excess);
+    }
+}
+
+/// 訪問の経過日数による重み
+fn age_weight(now: DateTime<Utc>, visited: DateTime<Utc>) -> f64 {
+    match (now - visited).num_days() {
+        ..=4 => 100.0,
+        5..=14 => 70.0,
+        15..=31 => 50.0,
+        32..=90 => 30.0,
+        _ => 10.0,
+    }
+}
+
+/// 履歴に記録しない場所
+///
+/// `*` `?` `[` を含まないパターンはそのパスと中身、含むパターンはグロブとして
+/// パスまたはその親ディレクトリのいずれかに一致すれば除外する。
+#[derive(Debug, Clone, Default)]
+pub struct HistoryExclusions {
+    prefixes: Vec<PathBuf>,
+    globs: Vec<Vec<char>>,
+}
+
+impl HistoryExclusions {
+    /// パターンの一覧から作成（空のパターンは無視する）
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> Self {
+        let mut exclusions = Self::default();
+        for pattern in patterns {
+            let pattern = pattern.as_ref().trim();
+            if pattern.is_empty() {
+                continue;
+            }
+            if pattern.contains(['*', '?', '[']) {
+                exclusions.globs.push(pattern.chars().collect());
+            } else {
+                exclusions.prefixes.push(PathBuf::from(pattern));
+            }
+        }
+        exclusions
+    }
+
+    /// 記録しない場所か
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        if self.prefixes.iter().any(|prefix| path.starts_with(prefix)) {
+            return true;
+        }
+        !self.globs.is_empty()
+            && path.ancestors().any(|ancestor| {
+                let text = ancestor.to_string_lossy();
+                self.globs.iter().any(|glob| glob_match(glob, &text))
+            })
+    }
+}
+
+/// 訪れた場所の履歴
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LocationHistory {
+    #[serde(default)]
+    entries: Vec<HistoryEntry>,
+}
+
+impl LocationHistory {
+    /// 空の履歴を作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// すべての項目（記録した順）
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    /// パスの項目
+    pub fn get(&self, path: &Path) -> Option<&HistoryEntry> {
+        self.entries.iter().find(|entry| entry.path == path)
+    }
+
+    /// 訪問を記録する（相対パスと除外する場所は記録せず false を返す）
+    pub fn record(
+        &mut self,
+        path: &Path,
+        kind: VisitKind,
+        at: DateTime<Utc>,
+        exclusions: &HistoryExclusions,
+    ) -> bool {
+        if !path.is_absolute() || exclusions.is_excluded(path) {
+            return false;
+        }
+        match self.entries.iter_mut().find(|entry| entry.path == path) {
+            Some(entry) => entry.visit(kind, at),
+            None => {
+                self.entries.push(HistoryEntry {
+                    path: path.to_path_buf(),
+                    kind,
+                    visit_count: 1,
+                    last_visited: at,
+                    recent_visits: vec![at],
+                });
+                self.prune(at);
+            }
+        }
+        true
+    }
+
+    /// 度合いの高い順に最大 `limit` 件（`kind` を指定するとその種類だけ）
+    ///
+    /// 度合いが同じなら最後に訪れた日時の新しい順にする。
+    pub fn top(
+        &self,
+        limit: usize,
+        kind: Option<VisitKind>,
+        now: DateTime<Utc>,
+    ) -> Vec<&HistoryEntry> {
+        let mut ranked: Vec<(&HistoryEntry, f64)> = self
+            .entries
+            .iter()
+            .filter(|entry| kind.is_none_or(|kind| entry.kind == kind))
+            .map(|entry| (entry, entry.frecency(now)))
+            .collect();
+        ranked.sort_by(|(a, a_score), (b, b_score)| {
+            b_score
+                .total_cmp(a_score)
+                .then_with(|| b.last_visited.cmp(&a.last_visited))
+        });
+        ranked
+            .into_iter()
+            .take(limit)
+            .map(|(entry, _)| entry)
+            .collect()
+    }
+
+    /// パスの記録を削除
+    pub fn remove(&mut self, path: &Path) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|entry| entry.path != path);
+        self.entries.len() != before
+    }
+
+    /// すべての記録を削除
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// 除外する場所になった記録を削除し、削除したものがあれば true を返す
+    pub fn forget_excluded(&mut self, exclusions: &HistoryExclusions) -> bool {
+        let before = self.entries.len();
+        self.entries
+            .retain(|entry| !exclusions.is_excluded(&entry.path));
+        self.entries.len() != before
+    }
+
+    /// 名前変更・移動に合わせて `from` とその中の記録を `to` へ付け替える
+    ///
+    /// 付け替え先の記録が既にあれば訪問を合わせる。付け替えたものがあれば true を返す。
+    pub fn relocate(&mut self, from: &Path, to: &Path) -> bool {
+        if from == to {
+            return false;
+        }
+        let mut moved = false;
+        let mut index = 0;
+        while index < self.entries.len() {
+            let Ok(suffix) = self.entries[index].path.strip_prefix(from) else {
+                index += 1;
+                continue;
+            };
+            let target = if suffix.as_os_str().is_empty() {
+                to.to_path_buf()
+            } else {
+                to.join(suffix)
+            };
+            moved = true;
+            match self.entries.iter().position(|entry| entry.path == target) {
+                Some(existing) => {
+                    let entry = self.entries.remove(index);
+                    let existing = if existing > index {
+                        existing - 1
+                    } else {
+                        existing
+                    };
+                    self.entries[existing].merge(entry);
+                }
+                None => {
+                    self.entries[index].path = target;
+                    index += 1;
+                }
+            }
+        }
+        moved
+    }
+
+    /// 上限を超えた分を度合いの低い場所から忘れる
+    fn prune(&mut self, now: DateTime<Utc>) {
+        while self.entries.len() > MAX_HISTORY_ENTRIES {
+            let Some(lowest) = self
+                .entries
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| {
+                    a.frecency(now)
+                        .total_cmp(&b.frecency(now))
+                        .then_with(|| a.last_visited.cmp(&b.last_visited))
+                })
+                .map(|(index, _)| index)
+            else {
+                break;
+            };
+            self.entries.remove(lowest);
+        }
+    }
+}
@@ -0,0 +1,1153 @@
+//! バックグラウンドファイル操作ジョブ
+//!
+//! コピー・移動・削除・ゴミ箱への移動を tokio ランタイム上で順番に実行し、
+//! 進捗の通知と一時停止・再開・キャンセルを提供します。ファイルの内容の検索も
+//! ジョブとして別スレッドで実行しますが、ファイルを変更しないため順番は待ちません。
+
+use crate::conflict::{
+    Conflict, ConflictAction, ConflictDecision, ConflictPolicy, ConflictResolver, apply_resolution,
+};
+use crate::filesystem::{copy_symlink, remove_tree};
+use crate::trash::TrashManager;
+use rust_explorer_utils::AppError;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tokio::fs;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::runtime::{Handle, Runtime};
+use tokio::sync::{Semaphore, oneshot, watch};
+
+/// ジョブID
+pub type JobId = u64;
+
+/// コピー時の読み書き単位
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// 進捗イベントの最小通知間隔
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// ジョブの種類
+#[derive(Debug, Clone, PartialEq)]
+pub enum JobKind {
+    /// `sources` を `destination` ディレクトリへコピー
+    Copy {
+        sources: Vec<PathBuf>,
+        destination: PathBuf,
+    },
+    /// `sources` を `destination` ディレクトリへ移動
+    Move {
+        sources: Vec<PathBuf>,
+        destination: PathBuf,
+    },
+    /// `paths` を完全に削除
+    Delete { paths: Vec<PathBuf> },
+    /// `paths` をゴミ箱へ移動
+    Trash { paths: Vec<PathBuf> },
+    /// `root` 以下のファイルの内容から `pattern` を検索
+    ContentSearch { root: PathBuf, pattern: String },
+}
+
+impl JobKind {
+    /// 表示用の短い説明
+    pub fn description(&self) -> String {
+        match self {
+            JobKind::Copy { sources, .. } => format!("{} 項目をコピー", sources.len()),
+            JobKind::Move { sources, .. } => format!("{} 項目を移動", sources.len()),
+            JobKind::Delete { paths } => format!("{} 項目を削除", paths.len()),
+            JobKind::Trash { paths } => format!("{} 項目をゴミ箱へ移動", paths.len()),
+            JobKind::ContentSearch { pattern, .. } => format!("「{}」を内容検索", pattern),
+        }
+    }
+}
+
+/// ジョブの状態
+#[derive(Debug, Clone, PartialEq)]
+pub enum JobStatus {
+    /// 実行待ち
+    Queued,
+    /// 実行中
+    Running,
+    /// 一時停止中
+    Paused,
+    /// 競合の解決方法の選択待ち
+    WaitingForDecision,
+    /// 完了
+    Completed,
+    /// キャンセル済み
+    Cancelled,
+    /// 失敗（エラーメッセージ）
+    Failed(String),
+}
+
+impl JobStatus {
+    /// 終了状態かどうか
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            JobStatus::Completed | JobStatus::Cancelled | JobStatus::Failed(_)
+        )
+    }
+}
+
+/// ジョブの進捗
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JobProgress {
+    /// 総バイト数
+    pub total_bytes: u64,
+    /// 処理済みバイト数
+    pub processed_bytes: u64,
+    /// 総ファイル数（ディレクトリを含む）
+    pub total_files: u64,
+    /// 処理済みファイル数
+    pub processed_files: u64,
+    /// 処理中のパス
+    pub current_path: Option<PathBuf>,
+    /// 一時停止時間を除いた経過時間
+    pub elapsed: Duration,
+}
+
+impl JobProgress {
+    /// 進捗率（0.0〜1.0）
+    pub fn fraction(&self) -> f64 {
+        if self.total_bytes > 0 {
+            self.processed_bytes as f64 / self.total_bytes as f64
+        } else if self.total_files > 0 {
+            self.processed_files as f64 / self.total_files as f64
+        } else {
+            0.0
+        }
+    }
+
+    /// スループット（バイト/秒）
+    pub fn bytes_per_second(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.processed_bytes as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    /// 残り時間の見積もり
+    pub fn eta(&self) -> Option<Duration> {
+        let fraction = self.fraction();
+        if fraction <= 0.0 || self.elapsed.is_zero() {
+            return None;
+        }
+        let remaining = self.elapsed.as_secs_f64() * (1.0 - fraction) / fraction;
+        Some(Duration::from_secs_f64(remaining.max(0.0)))
+    }
+}
+
+/// ジョブの概要
+#[derive(Debug, Clone)]
+pub struct JobInfo {
+    pub id: JobId,
+    pub kind: JobKind,
+    pub status: JobStatus,
+    pub progress: JobProgress,
+}
+
+/// ジョブイベント
+#[derive(Debug, Clone)]
+pub enum JobEvent {
+    /// ジョブがキューに追加された
+    Queued { id: JobId, kind: JobKind },
+    /// ジョブの実行が開始された
+    Started(JobId),
+    /// 進捗が更新された
+    Progress { id: JobId, progress: JobProgress },
+    /// 転送先の競合が見つかり、`JobManager::resolve_conflict` での選択を待っている
+    ConflictDetected { id: JobId, conflict: Conflict },
+    /// 一時停止された
+    Paused(JobId),
+    /// 再開された
+    Resumed(JobId),
+    /// 移動ジョブで項目を移動し終えた（競合で名前を変えた場合は `to` が変更後の名前）
+    Moved {
+        id: JobId,
+        from: PathBuf,
+        to: PathBuf,
+    },
+    /// 完了した
+    Completed(JobId),
+    /// キャンセルされた
+    Cancelled(JobId),
+    /// 失敗した
+    Failed { id: JobId, error: String },
+}
+
+impl JobEvent {
+    /// イベント対象のジョブID
+    pub fn job_id(&self) -> JobId {
+        match self {
+            JobEvent::Queued { id, .. }
+            | JobEvent::Progress { id, .. }
+            | JobEvent::ConflictDetected { id, .. }
+            | JobEvent::Moved { id, .. }
+            | JobEvent::Failed { id, .. } => *id,
+            JobEvent::Started(id)
+            | JobEvent::Paused(id)
+            | JobEvent::Resumed(id)
+            | JobEvent::Completed(id)
+            | JobEvent::Cancelled(id) => *id,
+        }
+    }
+}
+
+/// ジョブイベントコールバックの型エイリアス
+type JobEventCallback = Box<dyn Fn(&JobEvent) + Send + Sync>;
+
+/// 実行制御の状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ControlState {
+    Running,
+    Paused,
+    Cancelled,
+}
+
+/// ジョブごとの制御情報
+struct JobControl {
+    kind: JobKind,
+    control: watch::Sender<ControlState>,
+    status: watch::Sender<JobStatus>,
+    progress: Mutex<JobProgress>,
+    pending_decision: Mutex<Option<oneshot::Sender<ConflictDecision>>>,
+}
+
+impl JobControl {
+    fn info(&self, id: JobId) -> JobInfo {
+        JobInfo {
+            id,
+            kind: self.kind.clone(),
+            status: self.status.borrow().clone(),
+            progress: self.progress.lock().map(|p| p.clone()).unwrap_or_default(),
+        }
+    }
+}
+
+/// ジョブ実行の中断理由
+pub(crate) enum Interrupt {
+    Cancelled,
+    Failed(AppError),
+}
+
+impl From<AppError> for Interrupt {
+    fn from(error: AppError) -> Self {
+        Interrupt::Failed(error)
+    }
+}
+
+/// 別スレッドで実行するジョブの処理
+pub(crate) type JobTask = Box<dyn FnOnce(&JobContext) -> Result<(), Interrupt> + Send + Sync>;
+
+/// 別スレッドで実行するジョブの処理から一時停止・キャンセルと進捗を扱う
+pub(crate) struct JobContext {
+    control: Arc<JobControl>,
+    handle: Handle,
+}
+
+impl JobContext {
+    /// 一時停止中なら再開まで待機し、キャンセルされていれば中断する
+    pub(crate) fn checkpoint(&self) -> Result<(), Interrupt> {
+        let mut rx = self.control.control.subscribe();
+        if *rx.borrow() == ControlState::Paused {
+            let _ = self
+                .handle
+                .block_on(rx.wait_for(|s| *s != ControlState::Paused));
+        }
+
+        if *rx.borrow() == ControlState::Cancelled {
+            Err(Interrupt::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// 進捗を更新（通知はジョブの実行側が一定間隔で行う）
+    pub(crate) fn update(&self, f: impl FnOnce(&mut JobProgress)) {
+        if let Ok(mut progress) = self.control.progress.lock() {
+            f(&mut progress);
+        }
+    }
+}
+
+/// ジョブ管理マネージャー
+pub struct JobManager {
+    /// 専用ランタイム（`with_handle` の場合は None）
+    _runtime: Option<Runtime>,
+    handle: Handle,
+    jobs: Arc<RwLock<HashMap<JobId, Arc<JobControl>>>>,
+    next_id: AtomicU64,
+    slots: Arc<Semaphore>,
+    callbacks: Arc<RwLock<Vec<JobEventCallback>>>,
+    trash: Arc<TrashManager>,
+}
+
+impl JobManager {
+    /// 専用ランタイムを持つジョブマネージャーを作成（同時実行数1）
+    pub fn new() -> Result<Self, AppError> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .thread_name("rust-explorer-jobs")
+            .enable_all()
+            .build()
+            .map_err(AppError::FileSystem)?;
+        let handle = runtime.handle().clone();
+
+        let mut manager = Self::with_handle(handle, 1);
+        manager._runtime = Some(runtime);
+        Ok(manager)
+    }
+
+    /// 既存のランタイム上でジョブを実行するマネージャーを作成
+    pub fn with_handle(handle: Handle, max_concurrent: usize) -> Self {
+        Self {
+            _runtime: None,
+            handle,
+            jobs: Arc::new(RwLock::new(HashMap::new())),
+            next_id: AtomicU64::new(1),
+            slots: Arc::new(Semaphore::new(max_concurrent.max(1))),
+            callbacks: Arc::new(RwLock::new(Vec::new())),
+            trash: Arc::new(TrashManager::new()),
+        }
+    }
+
+    /// ゴミ箱ジョブで使うゴミ箱を指定
+    pub fn with_trash_manager(mut self, trash: TrashManager) -> Self {
+        self.trash = Arc::new(trash);
+        self
+    }
+
+    /// ゴミ箱ジョブで使うゴミ箱
+    pub fn trash_manager(&self) -> &TrashManager {
+        &self.trash
+    }
+
+    /// ジョブをキューに追加（競合はユーザーに確認する）
+    pub fn submit(&self, kind: JobKind) -> JobId {
+        self.submit_with_policy(kind, ConflictPolicy::Ask)
+    }
+
+    /// 競合ポリシーを指定してジョブをキューに追加
+    pub fn submit_with_policy(&self, kind: JobKind, policy: ConflictPolicy) -> JobId {
+        self.enqueue(kind, policy, None)
+    }
+
+    /// 別スレッドで実行する処理をジョブとしてキューに追加
+    ///
+    /// 転送などとは同時実行数を分け合わず、すぐに実行する。
+    pub(crate) fn submit_task(&self, kind: JobKind, task: JobTask) -> JobId {
+        self.enqueue(kind, ConflictPolicy::Ask, Some(task))
+    }
+
+    fn enqueue(&self, kind: JobKind, policy: ConflictPolicy, task: Option<JobTask>) -> JobId {
+        let exclusive = task.is_none();
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let control = Arc::new(JobControl {
+            kind: kind.clone(),
+            control: watch::Sender::new(ControlState::Running),
+            status: watch::Sender::new(JobStatus::Queued),
+            progress: Mutex::new(JobProgress::default()),
+            pending_decision: Mutex::new(None),
+        });
+
+        if let Ok(mut jobs) = self.jobs.write() {
+            jobs.insert(id, Arc::clone(&control));
+        }
+        emit(&self.callbacks, JobEvent::Queued { id, kind });
+
+        let runner = JobRunner {
+            id,
+            control,
+            callbacks: Arc::clone(&self.callbacks),
+            trash: Arc::clone(&self.trash),
+            resolver: ConflictResolver::new(policy),
+            started: Instant::now(),
+            paused_for: Duration::ZERO,
+            last_emit: None,
+            task,
+        };
+        let slots = Arc::clone(&self.slots);
+        self.handle.spawn(async move {
+            let _permit = if exclusive {
+                let Ok(permit) = slots.acquire_owned().await else {
+                    return;
+                };
+                Some(permit)
+            } else {
+                None
+            };
+            runner.run().await;
+        });
+
+        id
+    }
+
+    /// ジョブを一時停止
+    pub fn pause(&self, id: JobId) -> Result<(), AppError> {
+        let control = self.control(id)?;
+        let changed = control.control.send_if_modified(|state| {
+            if *state == ControlState::Running {
+                *state = ControlState::Paused;
+                true
+            } else {
+                false
+            }
+        });
+
+        if changed {
+            control.status.send_if_modified(|status| {
+                if *status == JobStatus::Running {
+                    *status = JobStatus::Paused;
+                    true
+                } else {
+                    false
+                }
+            });
+            emit(&self.callbacks, JobEvent::Paused(id));
+        }
+        Ok(())
+    }
+
+    /// ジョブを再開
+    pub fn resume(&self, id: JobId) -> Result<(), AppError> {
+        let control = self.control(id)?;
+        let changed = control.control.send_if_modified(|state| {
+            if *state == ControlState::Paused {
+                *state = ControlState::Running;
+                true
+            } else {
+                false
+            }
+        });
+
+        if changed {
+            control.status.send_if_modified(|status| {
+                if *status == JobStatus::Paused {
+                    *status = JobStatus::Running;
+                    true
+                } else {
+                    false
+                }
+            });
+            emit(&self.callbacks, JobEvent::Resumed(id));
+        }
+        Ok(())
+    }
+
+    /// ジョブをキャンセル（処理中のファイルは削除される）
+    pub fn cancel(&self, id: JobId) -> Result<(), AppError> {
+        let control = self.control(id)?;
+        control.control.send_replace(ControlState::Cancelled);
+        Ok(())
+    }
+
+    /// 選択待ちの競合に解決方法を伝える
+    pub fn resolve_conflict(&self, id: JobId, decision: ConflictDecision) -> Result<(), AppError> {
+        let control = self.control(id)?;
+        let sender = control
+            .pending_decision
+            .lock()
+            .map_err(|e| AppError::Internal(format!("Failed to lock job: {}", e)))?
+            .take()
+            .ok_or_else(|| AppError::Internal(format!("Job {} has no pending conflict", id)))?;
+
+        sender
+            .send(decision)
+            .map_err(|_| AppError::Internal(format!("Job {} is no longer waiting", id)))
+    }
+
+    /// ジョブ用ランタイム上で非同期処理を完了まで実行する
+    ///
+    /// 名前の変更など短い操作をUIスレッドから呼び出すためのもの。非同期コンテキスト内では呼び出さないこと。
+    pub fn block_on<F: std::future::Future>(&self, future: F) -> F::Output {
+        self.handle.block_on(future)
+    }
+
+    /// ジョブの状態を取得
+    pub fn status(&self, id: JobId) -> Option<JobStatus> {
+        self.control(id).ok().map(|c| c.status.borrow().clone())
+    }
+
+    /// ジョブの進捗を取得
+    pub fn progress(&self, id: JobId) -> Option<JobProgress> {
+        self.control(id).ok().map(|c| c.info(id).progress)
+    }
+
+    /// すべてのジョブの概要をID順で取得
+    pub fn jobs(&self) -> Vec<JobInfo> {
+        let Ok(jobs) = self.jobs.read() else {
+            return Vec::new();
+        };
+        let mut infos: Vec<JobInfo> = jobs.iter().map(|(id, c)| c.info(*id)).collect();
+        infos.sort_by_key(|info| info.id);
+        infos
+    }
+
+    /// 終了したジョブを一覧から削除
+    pub fn clear_finished(&self) {
+        if let Ok(mut jobs) = self.jobs.write() {
+            jobs.retain(|_, c| !c.status.borrow().is_finished());
+        }
+    }
+
+    /// ジョブの終了を待機し、最終状態を返す
+    pub async fn wait(&self, id: JobId) -> Option<JobStatus> {
+        let mut rx = self.control(id).ok()?.status.subscribe();
+        let status = rx.wait_for(|s| s.is_finished()).await.ok()?.clone();
+        Some(status)
+    }
+
+    /// ジョブイベントのコールバックを登録
+    pub fn on_job_event<F>(&self, callback: F) -> Result<(), AppError>
+    where
+        F: Fn(&JobEvent) + Send + Sync + 'static,
+    {
+        let mut callbacks = self
+            .callbacks
+            .write()
+            .map_err(|e| AppError::Internal(format!("Failed to write callbacks: {}", e)))?;
+
+        callbacks.push(Box::new(callback));
+        Ok(())
+    }
+
+    fn control(&self, id: JobId) -> Result<Arc<JobControl>, AppError> {
+        self.jobs
+            .read()
+            .map_err(|e| AppError::Internal(format!("Failed to read jobs: {}", e)))?
+            .get(&id)
+            .cloned()
+            .ok_or_else(|| AppError::Internal(format!("Job not found: {}", id)))
+    }
+}
+
+/// コールバックにイベントを通知
+fn emit(callbacks: &RwLock<Vec<JobEventCallback>>, event: JobEvent) {
+    if let Ok(callbacks) = callbacks.read() {
+        for callback in callbacks.iter() {
+            callback(&event);
+        }
+    }
+}
+
+/// 走査で見つかった項目
+struct ScannedItem {
+    path: PathBuf,
+    is_dir: bool,
+}
+
+/// 競合を解決した転送先
+enum TransferTarget {
+    /// まだ存在しない場所
+    New(PathBuf),
+    /// 上書きする既存の場所
+    Existing(PathBuf),
+}
+
+/// 1つのジョブを実行する
+struct JobRunner {
+    id: JobId,
+    control: Arc<JobControl>,
+    callbacks: Arc<RwLock<Vec<JobEventCallback>>>,
+    trash: Arc<TrashManager>,
+    resolver: ConflictResolver,
+    started: Instant,
+    paused_for: Duration,
+    last_emit: Option<Instant>,
+    /// 別スレッドで実行する処理（`submit_task` の場合）
+    task: Option<JobTask>,
+}
+
+impl JobRunner {
+    async fn run(mut self) {
+        // キュー待ちの間にキャンセルされた場合は実行しない
+        // （別スレッドの処理は、中止を呼び出し元へ知らせるため実行して中断させる）
+        if *self.control.control.borrow() == ControlState::Cancelled && self.task.is_none() {
+            self.finish(JobStatus::Cancelled, JobEvent::Cancelled(self.id));
+            return;
+        }
+
+        self.started = Instant::now();
+        let initial = if *self.control.control.borrow() == ControlState::Paused {
+            JobStatus::Paused
+        } else {
+            JobStatus::Running
+        };
+        self.control.status.send_replace(initial);
+        emit(&self.callbacks, JobEvent::Started(self.id));
+
+        let kind = self.control.kind.clone();
+        let result = match &kind {
+            JobKind::Copy {
+                sources,
+                destination,
+            } => self.run_transfer(sources, destination, false).await,
+            JobKind::Move {
+                sources,
+                destination,
+            } => self.run_transfer(sources, destination, true).await,
+            JobKind::Delete { paths } => self.run_delete(paths).await,
+            JobKind::Trash { paths } => self.run_trash(paths).await,
+            JobKind::ContentSearch { .. } => self.run_task().await,
+        };
+
+        self.report(true);
+        match result {
+            Ok(()) => self.finish(JobStatus::Completed, JobEvent::Completed(self.id)),
+            Err(Interrupt::Cancelled) => {
+                self.finish(JobStatus::Cancelled, JobEvent::Cancelled(self.id))
+            }
+            Err(Interrupt::Failed(error)) => {
+                let message = error.to_string();
+                self.finish(
+                    JobStatus::Failed(message.clone()),
+                    JobEvent::Failed {
+                        id: self.id,
+                        error: message,
+                    },
+                )
+            }
+        }
+    }
+
+    fn finish(&self, status: JobStatus, event: JobEvent) {
+        self.control.status.send_replace(status);
+        emit(&self.callbacks, event);
+    }
+
+    /// 一時停止中なら再開まで待機し、キャンセルされていれば中断する
+    async fn checkpoint(&mut self) -> Result<(), Interrupt> {
+        let mut rx = self.control.control.subscribe();
+        if *rx.borrow() == ControlState::Paused {
+            let paused_at = Instant::now();
+            let _ = rx.wait_for(|s| *s != ControlState::Paused).await;
+            self.paused_for += paused_at.elapsed();
+        }
+
+        if *rx.borrow() == ControlState::Cancelled {
+            Err(Interrupt::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// 進捗を更新して必要に応じて通知する
+    fn update(&mut self, f: impl FnOnce(&mut JobProgress)) {
+        if let Ok(mut progress) = self.control.progress.lock() {
+            f(&mut progress);
+            progress.elapsed = self.started.elapsed().saturating_sub(self.paused_for);
+        }
+        self.report(false);
+    }
+
+    fn report(&mut self, force: bool) {
+        let due = self
+            .last_emit
+            .is_none_or(|last| last.elapsed() >= PROGRESS_INTERVAL);
+        if !force && !due {
+            return;
+        }
+        self.last_emit = Some(Instant::now());
+
+        if let Ok(progress) = self.control.progress.lock() {
+            emit(
+                &self.callbacks,
+                JobEvent::Progress {
+                    id: self.id,
+                    progress: progress.clone(),
+                },
+            );
+        }
+    }
+
+    /// 別スレッドで処理を実行し、終わるまで一定間隔で進捗を通知する
+    async fn run_task(&mut self) -> Result<(), Interrupt> {
+        let task = self
+            .task
+            .take()
+            .ok_or_else(|| AppError::Internal(format!("Job {} has no task", self.id)))?;
+        let context = JobContext {
+            control: Arc::clone(&self.control),
+            handle: Handle::current(),
+        };
+        let mut worker = tokio::task::spawn_blocking(move || task(&context));
+
+        loop {
+            tokio::select! {
+                result = &mut worker => {
+                    return result.unwrap_or_else(|e| {
+                        Err(AppError::Internal(format!("Job task panicked: {}", e)).into())
+                    });
+                }
+                _ = tokio::time::sleep(PROGRESS_INTERVAL) => {
+                    // 一時停止中の時間を経過時間に含めないよう、再開まで待つ
+                    // （キャンセルは処理側が検知して終了する）
+                    let _ = self.checkpoint().await;
+                    self.update(|_| {});
+                }
+            }
+        }
+    }
+
+    /// 配下の項目を親から順に列挙し、合計を進捗に反映する
+    async fn scan(&mut self, roots: &[PathBuf]) -> Result<Vec<ScannedItem>, Interrupt> {
+        let mut items = Vec::new();
+        let mut total_bytes = 0;
+
+        for root in roots {
+            let mut pending = vec![root.clone()];
+            while let Some(path) = pending.pop() {
+                self.checkpoint().await?;
+                let metadata = fs::symlink_metadata(&path)
+                    .await
+                    .map_err(|_| AppError::NotFound(path.clone()))?;
+
+                if metadata.is_dir() {
+                    let mut dir = fs::read_dir(&path).await.map_err(AppError::FileSystem)?;
+                    while let Some(entry) = dir.next_entry().await.map_err(AppError::FileSystem)? {
+                        pending.push(entry.path());
+                    }
+                } else {
+                    total_bytes += metadata.len();
+                }
+                items.push(ScannedItem {
+                    is_dir: metadata.is_dir(),
+                    path,
+                });
+            }
+        }
+
+        let total_files = items.len() as u64;
+        self.update(|p| {
+            p.total_bytes = total_bytes;
+            p.total_files = total_files;
+        });
+        Ok(items)
+    }
+
+    async fn run_transfer(
+        &mut self,
+        sources: &[PathBuf],
+        destination: &Path,
+        remove_source: bool,
+    ) -> Result<(), Interrupt> {
+        self.scan(sources).await?;
+
+        for source in sources {
+            let name = source
+                .file_name()
+                .ok_or_else(|| AppError::InvalidPath(source.clone()))?;
+            let target = destination.join(name);
+
+            if source.is_dir() && target.starts_with(source) && target != *source {
+                return Err(AppError::InvalidOperation(format!(
+                    "Cannot copy or move {} into itself",
+                    source.display()
+                ))
+                .into());
+            }
+
+            let target = match self.resolve_target(source, target).await? {
+                Some(TransferTarget::New(target)) => target,
+                Some(TransferTarget::Existing(target)) => {
+                    self.overwrite(source, &target, remove_source).await?;
+                    if remove_source {
+                        self.moved(source, target);
+                    }
+                    continue;
+                }
+                None => {
+                    // スキップした項目も処理済みとして数える
+                    let (bytes, files) = self.measure(source).await;
+                    self.update(|p| {
+                        p.processed_bytes += bytes;
+                        p.processed_files += files;
+                    });
+                    continue;
+                }
+            };
+
+            self.transfer_new(source, &target, remove_source).await?;
+            if remove_source {
+                self.moved(source, target);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// まだ存在しない転送先へ転送する（移動は名前の変更を試し、別デバイスならコピー後に削除する）
+    ///
+    /// コピーは一時的な名前へ書き込み終えてから名前を変えるため、失敗やキャンセルで
+    /// 転送先に途中までの内容を残さない。
+    async fn transfer_new(
+        &mut self,
+        source: &Path,
+        target: &Path,
+        remove_source: bool,
+    ) -> Result<(), Interrupt> {
+        if remove_source {
+            self.checkpoint().await?;
+            match fs::rename(source, target).await {
+                Ok(()) => {
+                    let (bytes, files) = self.measure(target).await;
+                    self.update(|p| {
+                        p.processed_bytes += bytes;
+                        p.processed_files += files;
+                        p.current_path = Some(source.to_path_buf());
+                    });
+                    return Ok(());
+                }
+                // 別デバイス間はコピー後に削除する
+                Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => {}
+                Err(source_err) => {
+                    return Err(AppError::MoveFailed {
+                        from: source.to_path_buf(),
+                        to: target.to_path_buf(),
+                        source: source_err,
+                    }
+                    .into());
+                }
+            }
+        }
+
+        let staging = self.copy_to_staging(source, target).await?;
+        if let Err(source_err) = fs::rename(&staging, target).await {
+            let _ = remove_tree(&staging).await;
+            return Err(AppError::MoveFailed {
+                from: staging,
+                to: target.to_path_buf(),
+                source: source_err,
+            }
+            .into());
+        }
+        if remove_source {
+            remove_tree(source).await?;
+        }
+        Ok(())
+    }
+
+    /// 既存の転送先を上書きする
+    ///
+    /// ディレクトリ同士は中身を統合する。それ以外は一時的な名前へ転送し終えてから
+    /// 置き換えるため、失敗やキャンセルで既存の転送先を失わない。
+    async fn overwrite(
+        &mut self,
+        source: &Path,
+        target: &Path,
+        remove_source: bool,
+    ) -> Result<(), Interrupt> {
+        let source_is_dir = fs::symlink_metadata(source)
+            .await
+            .map_err(|_| AppError::NotFound(source.to_path_buf()))?
+            .is_dir();
+        let target_is_dir = fs::symlink_metadata(target).await.is_ok_and(|m| m.is_dir());
+
+        if source_is_dir && target_is_dir {
+            let mut names = Vec::new();
+            let mut dir = fs::read_dir(source).await.map_err(AppError::FileSystem)?;
+            while let Some(entry) = dir.next_entry().await.map_err(AppError::FileSystem)? {
+                names.push(entry.file_name());
+            }
+            self.update(|p| p.processed_files += 1);
+            for name in names {
+                let (from, to) = (source.join(&name), target.join(&name));
+                if fs::symlink_metadata(&to).await.is_ok() {
+                    Box::pin(self.overwrite(&from, &to, remove_source)).await?;
+                } else {
+                    self.transfer_new(&from, &to, remove_source).await?;
+                }
+            }
+            if remove_source {
+                remove_tree(source).await?;
+            }
+            return Ok(());
+        }
+
+        // 同じデバイス内のファイル同士の移動は名前の変更で置き換える
+        if remove_source && !source_is_dir && !target_is_dir {
+            self.checkpoint().await?;
+            if fs::rename(source, target).await.is_ok() {
+                let (bytes, files) = self.measure(target).await;
+                self.update(|p| {
+                    p.processed_bytes += bytes;
+                    p.processed_files += files;
+                    p.current_path = Some(source.to_path_buf());
+                });
+                return Ok(());
+            }
+        }
+
+        let staging = self.copy_to_staging(source, target).await?;
+        // 置き換えられるのはディレクトリ以外同士だけなので、どちらかがディレクトリなら先に消す
+        if source_is_dir || target_is_dir {
+            remove_tree(target).await?;
+        }
+        fs::rename(&staging, target)
+            .await
+            .map_err(|source_err| AppError::MoveFailed {
+                from: staging.clone(),
+                to: target.to_path_buf(),
+                source: source_err,
+            })?;
+        if remove_source {
+            remove_tree(source).await?;
+        }
+        Ok(())
+    }
+
+    /// `target` と同じディレクトリの一時的な名前へコピーし、その名前を返す
+    ///
+    /// 失敗やキャンセルの場合は途中までコピーした内容を削除する。
+    async fn copy_to_staging(
+        &mut self,
+        source: &Path,
+        target: &Path,
+    ) -> Result<PathBuf, Interrupt> {
+        let staging = self.staging_path(target)?;
+        if let Err(interrupt) = self.copy_tree(source, &staging).await {
+            let _ = remove_tree(&staging).await;
+            return Err(interrupt);
+        }
+        Ok(staging)
+    }
+
+    /// 転送先へ名前を変える前に書き込む、転送先と同じディレクトリの一時的な名前
+    fn staging_path(&self, target: &Path) -> Result<PathBuf, AppError> {
+        let name = target
+            .file_name()
+            .ok_or_else(|| AppError::InvalidPath(target.to_path_buf()))?;
+        Ok(target.with_file_name(format!(
+            ".{}.job-{}.partial",
+            name.to_string_lossy(),
+            self.id
+        )))
+    }
+
+    fn moved(&self, from: &Path, to: PathBuf) {
+        emit(
+            &self.callbacks,
+            JobEvent::Moved {
+                id: self.id,
+                from: from.to_path_buf(),
+                to,
+            },
+        );
+    }
+
+    /// 転送先が既に存在する場合は競合を解決し、実際の転送先を返す（スキップなら None）
+    async fn resolve_target(
+        &mut self,
+        source: &Path,
+        target: PathBuf,
+    ) -> Result<Option<TransferTarget>, Interrupt> {
+        if fs::symlink_metadata(&target).await.is_err() {
+            return Ok(Some(TransferTarget::New(target)));
+        }
+
+        let conflict = Conflict::inspect(source, &target)?;
+        let resolution = match self.resolver.preset() {
+            Some(resolution) => resolution,
+            None => {
+                let decision = self.ask(conflict.clone()).await?;
+                self.resolver.record(decision)
+            }
+        };
+
+        match apply_resolution(&conflict, resolution) {
+            ConflictAction::Skip => Ok(None),
+            // 同じ場所へのコピーで上書きすると転送元を失うためスキップする
+            ConflictAction::Overwrite if target == source => Ok(None),
+            ConflictAction::Overwrite => Ok(Some(TransferTarget::Existing(target))),
+            ConflictAction::WriteTo(path) => Ok(Some(TransferTarget::New(path))),
+        }
+    }
+
+    /// 競合をイベントで通知し、選択されるまで待機する
+    async fn ask(&mut self, conflict: Conflict) -> Result<ConflictDecision, Interrupt> {
+        let (sender, receiver) = oneshot::channel();
+        if let Ok(mut pending) = self.control.pending_decision.lock() {
+            *pending = Some(sender);
+        }
+        self.control
+            .status
+            .send_replace(JobStatus::WaitingForDecision);
+        emit(
+            &self.callbacks,
+            JobEvent::ConflictDetected {
+                id: self.id,
+                conflict,
+            },
+        );
+
+        let waiting_since = Instant::now();
+        let mut control = self.control.control.subscribe();
+        let decision = tokio::select! {
+            decision = receiver => decision.ok(),
+            _ = control.wait_for(|s| *s == ControlState::Cancelled) => None,
+        };
+        self.paused_for += waiting_since.elapsed();
+
+        if let Ok(mut pending) = self.control.pending_decision.lock() {
+            pending.take();
+        }
+        let decision = decision.ok_or(Interrupt::Cancelled)?;
+
+        let status = if *self.control.control.borrow() == ControlState::Paused {
+            JobStatus::Paused
+        } else {
+            JobStatus::Running
+        };
+        self.control.status.send_replace(status);
+        Ok(decision)
+    }
+
+    /// 項目のバイト数と項目数を数える
+    async fn measure(&self, root: &Path) -> (u64, u64) {
+        let mut bytes = 0;
+        let mut files = 0;
+        let mut pending = vec![root.to_path_buf()];
+        while let Some(path) = pending.pop() {
+            let Ok(metadata) = fs::symlink_metadata(&path).await else {
+                continue;
+            };
+            files += 1;
+            if metadata.is_dir() {
+                if let Ok(mut dir) = fs::read_dir(&path).await {
+                    while let Ok(Some(entry)) = dir.next_entry().await {
+                        pending.push(entry.path());
+                    }
+                }
+            } else {
+                bytes += metadata.len();
+            }
+        }
+        (bytes, files)
+    }
+
+    async fn copy_tree(&mut self, from: &Path, to: &Path) -> Result<(), Interrupt> {
+        let mut pending = vec![(from.to_path_buf(), to.to_path_buf())];
+        while let Some((src, dst)) = pending.pop() {
+            self.checkpoint().await?;
+            self.update(|p| p.current_path = Some(src.clone()));
+
+            let copy_err = |source| AppError::CopyFailed {
+                from: src.clone(),
+                to: dst.clone(),
+                source,
+            };
+            let metadata = fs::symlink_metadata(&src).await.map_err(copy_err)?;
+
+            if metadata.file_type().is_symlink() {
+                copy_symlink(&src, &dst).await.map_err(copy_err)?;
+            } else if metadata.is_dir() {
+                fs::create_dir(&dst).await.map_err(copy_err)?;
+                let mut dir = fs::read_dir(&src).await.map_err(copy_err)?;
+                while let Some(entry) = dir.next_entry().await.map_err(copy_err)? {
+                    pending.push((entry.path(), dst.join(entry.file_name())));
+                }
+            } else {
+                let result = self.copy_file(&src, &dst, metadata.permissions()).await;
+                if result.is_err() {
+                    // 途中まで書き込んだファイルは残さない
+                    let _ = fs::remove_file(&dst).await;
+                }
+                result?;
+            }
+
+            self.update(|p| p.processed_files += 1);
+        }
+        Ok(())
+    }
+
+    async fn copy_file(
+        &mut self,
+        src: &Path,
+        dst: &Path,
+        permissions: std::fs::Permissions,
+    ) -> Result<(), Interrupt> {
+        let copy_err = |source| AppError::CopyFailed {
+            from: src.to_path_buf(),
+            to: dst.to_path_buf(),
+            source,
+        };
+
+        let mut reader = fs::File::open(src).await.map_err(copy_err)?;
+        let mut writer = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(dst)
+            .await
+            .map_err(copy_err)?;
+
+        let mut buffer = vec![0u8; CHUNK_SIZE];
+        loop {
+            self.checkpoint().await?;
+            let read = reader.read(&mut buffer).await.map_err(copy_err)?;
+            if read == 0 {
+                break;
+            }
+            writer.write_all(&buffer[..read]).await.map_err(copy_err)?;
+            self.update(|p| p.processed_bytes += read as u64);
+        }
+
+        writer.flush().await.map_err(copy_err)?;
+        fs::set_permissions(dst, permissions)
+            .await
+            .map_err(copy_err)?;
+        Ok(())
+    }
+
+    async fn run_delete(&mut self, paths: &[PathBuf]) -> Result<(), Interrupt> {
+        let items = self.scan(paths).await?;
+
+        // 子から先に削除する
+        for item in items.iter().rev() {
+            self.checkpoint().await?;
+            self.update(|p| p.current_path = Some(item.path.clone()));
+
+            let bytes = if item.is_dir {
+                0
+            } else {
+                fs::symlink_metadata(&item.path)
+                    .await
+                    .map(|m| m.len())
+                    .unwrap_or(0)
+            };
+            let result = if item.is_dir {
+                fs::remove_dir(&item.path).await
+            } else {
+                fs::remove_file(&item.path).await
+            };
+            result.map_err(|source| AppError::DeleteFailed {
+                path: item.path.clone(),
+                source,
+            })?;
+
+            self.update(|p| {
+                p.processed_files += 1;
+                p.processed_bytes += bytes;
+            });
+        }
+        Ok(())
+    }
+
+    async fn run_trash(&mut self, paths: &[PathBuf]) -> Result<(), Interrupt> {
+        // 同じデバイス内の移動なので項目数だけを数える
+        let total_files = paths.len() as u64;
+        self.update(|p| p.total_files = total_files);
+
+        for path in paths {
+            self.checkpoint().await?;
+            self.update(|p| p.current_path = Some(path.clone()));
+            self.trash.trash(path).await?;
+            self.update(|p| p.processed_files += 1);
+        }
+        Ok(())
+    }
+}
@@ -4,24 +4,72 @@
 
 #![allow(clippy::result_large_err)]
 
+pub mod bookmarks;
+pub mod conflict;
+pub mod content_search;
 pub mod event;
 pub mod file_sorting;
 pub mod filesystem;
+pub mod history;
+pub mod job;
+pub mod listing;
+pub mod query;
+pub mod quick_open;
+pub mod search;
+pub mod smart_folder;
 pub mod state;
 pub mod system_integration;
+pub mod tags;
+pub mod trash;
+pub mod undo;
+pub mod watcher;
 
 #[cfg(test)]
 mod tests;
 
+pub use bookmarks::{Bookmark, BookmarkEntry, BookmarkFolder, Bookmarks, MAX_BOOKMARK_SHORTCUTS};
+pub use conflict::{
+    Conflict, ConflictAction, ConflictDecision, ConflictPolicy, ConflictResolution,
+    ConflictResolver, apply_resolution, auto_rename,
+};
+pub use content_search::{
+    CaseSensitivity, ContentMatch, ContentSearch, ContentSearchEvent, ContentSearchOptions,
+    ContentSearchProgress,
+};
 pub use event::{Event, EventManager};
 pub use file_sorting::{
     FileSortFilterManager, FilterCriteria, SortConfig, SortCriteria, SortDirection,
 };
 pub use filesystem::{
-    CachedFileSystemManager, FileEntry, FileInfo, FileSystemApi, FileSystemManager, FileType,
+    CacheConfig, CacheStats, CachedFileSystemManager, FileEntry, FileInfo, FileSystemApi,
+    FileSystemManager, FileType,
+};
+pub use history::{
+    HistoryEntry, HistoryExclusions, LocationHistory, MAX_HISTORY_ENTRIES, VisitKind,
+};
+pub use job::{JobEvent, JobId, JobInfo, JobKind, JobManager, JobProgress, JobStatus};
+pub use listing::{
+    DirectoryStream, ListingCanceller, ListingEvent, ListingOptions, merge_metadata,
 };
+pub use query::FileQuery;
+pub use quick_open::{
+    FuzzyMatch, MAX_INDEXED_CANDIDATES, QuickOpen, QuickOpenCandidate, QuickOpenMatch,
+    QuickOpenSource, fuzzy_match,
+};
+pub use search::{
+    INDEX_FORMAT_VERSION, IndexSnapshot, IndexerStatus, SearchIndex, SearchIndexer, SearchQuery,
+};
+pub use smart_folder::{SmartFolder, SmartFolderResults, SmartFolderUpdate, SmartFolders};
 pub use state::{
-    AppState, PanePosition, PaneSize, PaneState, PaneType, StateChangeEvent, StateManager,
-    TabState, UiState, WindowState, state_utils,
+    AppState, MAX_CLOSED_TABS, MIN_PANE_FLEX, PanePosition, PaneSize, PaneState, PaneTree,
+    PaneType, SplitDirection, StateChangeEvent, StateDiff, StateManager, TabState, UiState,
+    WindowState, state_utils,
 };
 pub use system_integration::{DefaultSystemIntegration, FileNavigationManager, SystemIntegration};
+pub use tags::{
+    MAX_TAG_NAME_LEN, Tag, TagColor, TagStore, XDG_TAGS_ATTRIBUTE, read_xattr_tags,
+    write_xattr_tags,
+};
+pub use trash::{TrashItem, TrashManager};
+pub use undo::{Fingerprint, JournalEntry, JournalOperation, UndoJournal};
+pub use watcher::{DirectoryChange, DirectoryDelta, DirectoryWatcher};
@@ -0,0 +1,234 @@
+//! 大きなディレクトリの段階的な読み込み
+//!
+//! ディレクトリの項目を読み込んだ順にバッチで届け、サイズや更新日時などの
+//! 取得に時間がかかる情報は名前の一覧を届けた後で別に届けます。
+
+use crate::filesystem::{FileEntry, FileSystemManager, FileType};
+use crossbeam_channel::{Receiver, Sender};
+use rust_explorer_utils::AppError;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// 段階的な読み込みの設定
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListingOptions {
+    /// 最初のバッチの項目数（最初の1画面分を素早く表示するため小さめにする）
+    pub initial_batch_size: usize,
+    /// バッチの最大項目数（バッチごとに倍に増やす）
+    pub max_batch_size: usize,
+    /// 名前の一覧の後にメタデータを読み込むか
+    pub load_metadata: bool,
+}
+
+impl Default for ListingOptions {
+    fn default() -> Self {
+        Self {
+            initial_batch_size: 200,
+            max_batch_size: 10_000,
+            load_metadata: true,
+        }
+    }
+}
+
+/// 読み込み中に届くイベント
+#[derive(Debug)]
+pub enum ListingEvent {
+    /// 名前と種類だけのエントリ（サイズと更新日時は後続の `Metadata` で届く）
+    Entries(Vec<FileEntry>),
+    /// メタデータを読み込んだエントリ（既に届いたエントリをパスで置き換える）
+    Metadata(Vec<FileEntry>),
+    /// 読み込みに失敗した
+    Failed(AppError),
+    /// すべて読み込んだ
+    Finished { total: usize },
+}
+
+/// 読み込みを中断するためのハンドル
+#[derive(Debug, Clone, Default)]
+pub struct ListingCanceller {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl ListingCanceller {
+    /// 読み込みを中断
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// 中断されたか
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// 段階的に読み込まれるディレクトリ一覧（破棄すると読み込みを中断する）
+pub struct DirectoryStream {
+    path: PathBuf,
+    receiver: Receiver<ListingEvent>,
+    canceller: ListingCanceller,
+}
+
+impl DirectoryStream {
+    /// 読み込み中のディレクトリ
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// イベントの受信側（UIスレッドへ渡す場合などに使う）
+    pub fn receiver(&self) -> &Receiver<ListingEvent> {
+        &self.receiver
+    }
+
+    /// 中断用のハンドル
+    pub fn canceller(&self) -> ListingCanceller {
+        self.canceller.clone()
+    }
+
+    /// 読み込みを中断
+    pub fn cancel(&self) {
+        self.canceller.cancel();
+    }
+
+    /// 次のイベントを待つ（読み込みが終わっていれば None）
+    pub fn recv(&self) -> Option<ListingEvent> {
+        self.receiver.recv().ok()
+    }
+
+    /// 残りをすべて読み込み、メタデータを反映した一覧を返す
+    pub fn collect(self) -> Result<Vec<FileEntry>, AppError> {
+        let mut entries = Vec::new();
+        while let Some(event) = self.recv() {
+            match event {
+                ListingEvent::Entries(batch) => entries.extend(batch),
+                ListingEvent::Metadata(batch) => merge_metadata(&mut entries, batch),
+                ListingEvent::Failed(error) => return Err(error),
+                ListingEvent::Finished { .. } => break,
+            }
+        }
+        Ok(entries)
+    }
+}
+
+impl Drop for DirectoryStream {
+    fn drop(&mut self) {
+        self.canceller.cancel();
+    }
+}
+
+/// メタデータを読み込んだエントリで一覧の同じパスの項目を置き換える
+pub fn merge_metadata(entries: &mut [FileEntry], batch: Vec<FileEntry>) {
+    let mut updates: std::collections::HashMap<PathBuf, FileEntry> =
+        batch.into_iter().map(|e| (e.path.clone(), e)).collect();
+    for entry in entries.iter_mut() {
+        if updates.is_empty() {
+            break;
+        }
+        if let Some(updated) = updates.remove(&entry.path) {
+            *entry = updated;
+        }
+    }
+}
+
+impl FileSystemManager {
+    /// ディレクトリをバックグラウンドで読み込み、バッチごとに届けるストリームを返す
+    pub fn list_directory_streaming(
+        &self,
+        path: &Path,
+        options: ListingOptions,
+    ) -> Result<DirectoryStream, AppError> {
+        if !path.is_dir() {
+            return Err(AppError::InvalidPath(path.to_path_buf()));
+        }
+        let read_dir = std::fs::read_dir(path).map_err(AppError::FileSystem)?;
+
+        // 表示側が追いつかない場合に読み込みを待たせるため上限付きにする
+        let (sender, receiver) = crossbeam_channel::bounded(16);
+        let canceller = ListingCanceller::default();
+        let thread_canceller = canceller.clone();
+        std::thread::Builder::new()
+            .name("rust-explorer-listing".to_string())
+            .spawn(move || read_entries(read_dir, options, sender, thread_canceller))
+            .map_err(AppError::FileSystem)?;
+
+        Ok(DirectoryStream {
+            path: path.to_path_buf(),
+            receiver,
+            canceller,
+        })
+    }
+}
+
+/// 読み込みスレッドの本体（受信側が破棄されるか中断されたら終了する）
+fn read_entries(
+    read_dir: std::fs::ReadDir,
+    options: ListingOptions,
+    sender: Sender<ListingEvent>,
+    canceller: ListingCanceller,
+) {
+    let max_batch_size = options.max_batch_size.max(1);
+    let mut batch_size = options.initial_batch_size.clamp(1, max_batch_size);
+    let mut batch = Vec::with_capacity(batch_size);
+    let mut paths = Vec::new();
+    let mut total = 0;
+
+    for entry in read_dir {
+        if canceller.is_cancelled() {
+            return;
+        }
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(error) => {
+                let _ = sender.send(ListingEvent::Failed(AppError::FileSystem(error)));
+                return;
+            }
+        };
+
+        // 種類は readdir の結果から分かるため stat しない
+        let file_type = match entry.file_type() {
+            Ok(t) if t.is_dir() => FileType::Directory,
+            Ok(t) if t.is_file() => FileType::File,
+            Ok(t) if t.is_symlink() => FileType::SymLink,
+            _ => FileType::Other,
+        };
+        let path = entry.path();
+        total += 1;
+        if options.load_metadata {
+            paths.push(path.clone());
+        }
+        batch.push(FileEntry {
+            name: entry.file_name().to_string_lossy().to_string(),
+            path,
+            file_type,
+            size: 0,
+            modified: None,
+        });
+
+        if batch.len() >= batch_size {
+            let full = std::mem::replace(&mut batch, Vec::with_capacity(batch_size));
+            if sender.send(ListingEvent::Entries(full)).is_err() {
+                return;
+            }
+            batch_size = (batch_size * 2).min(max_batch_size);
+        }
+    }
+
+    if !batch.is_empty() && sender.send(ListingEvent::Entries(batch)).is_err() {
+        return;
+    }
+    if options.load_metadata {
+        for chunk in paths.chunks(max_batch_size) {
+            if canceller.is_cancelled() {
+                return;
+            }
+            let entries = chunk
+                .iter()
+                .filter_map(|path| FileEntry::from_path(path))
+                .collect();
+            if sender.send(ListingEvent::Metadata(entries)).is_err() {
+                return;
+            }
+        }
+    }
+    let _ = sender.send(ListingEvent::Finished { total });
+}
@@ -0,0 +1,616 @@
+//! 検索・フィルタのクエリ
+//!
+//! `ext:rs,toml size:>10MB modified:<7d type:dir name:/regex/ -path:target` のような
+//! クエリを解析し、`FileEntry` に対する条件へ変換します。条件は空白区切りで AND になり、
+//! `OR`（`|`）、`NOT`（`-`）、括弧、`"` で囲んだ語句を使えます。
+//! 解析したクエリは `FileSortFilterManager` と検索索引の両方で使います。
+
+use crate::filesystem::{FileEntry, FileType};
+use chrono::{Local, NaiveDate, TimeZone};
+use regex::{Regex, RegexBuilder};
+use rust_explorer_utils::AppError;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+/// 1日の長さ
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// 比較の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Compare {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+}
+
+impl Compare {
+    fn test<T: PartialOrd>(self, value: T, bound: T) -> bool {
+        match self {
+            Compare::Less => value < bound,
+            Compare::LessOrEqual => value <= bound,
+            Compare::Greater => value > bound,
+            Compare::GreaterOrEqual => value >= bound,
+            Compare::Equal => value == bound,
+        }
+    }
+
+    /// 大小を入れ替える（経過時間と時刻の変換用）
+    fn reversed(self) -> Self {
+        match self {
+            Compare::Less => Compare::Greater,
+            Compare::LessOrEqual => Compare::GreaterOrEqual,
+            Compare::Greater => Compare::Less,
+            Compare::GreaterOrEqual => Compare::LessOrEqual,
+            Compare::Equal => Compare::Equal,
+        }
+    }
+
+    /// 値の先頭の比較演算子を読み取る（なければ `default`）
+    fn split(value: &str, default: Compare) -> (Compare, &str) {
+        for (prefix, compare) in [
+            (">=", Compare::GreaterOrEqual),
+            ("<=", Compare::LessOrEqual),
+            (">", Compare::Greater),
+            ("<", Compare::Less),
+            ("=", Compare::Equal),
+        ] {
+            if let Some(rest) = value.strip_prefix(prefix) {
+                return (compare, rest);
+            }
+        }
+        (default, value)
+    }
+}
+
+/// 1つの条件
+#[derive(Debug, Clone)]
+enum Term {
+    /// 名前の部分一致（小文字）
+    Name(String),
+    /// 名前の正規表現
+    NameRegex(Regex),
+    /// フルパスの部分一致（小文字）
+    Path(String),
+    /// 拡張子のいずれか（小文字、`.` なし）
+    Extension(Vec<String>),
+    /// ファイルのサイズ（ディレクトリは一致しない）
+    Size(Compare, u64),
+    /// ファイルのサイズがこの範囲内（両端を含む）
+    SizeBetween(u64, u64),
+    /// 更新日時
+    Modified(Compare, SystemTime),
+    /// 更新日時がこの範囲内（日付の指定用、終わりは含まない）
+    ModifiedBetween(SystemTime, SystemTime),
+    /// 種類
+    Type(FileType),
+}
+
+impl Term {
+    fn matches(&self, entry: &FileEntry) -> bool {
+        match self {
+            Term::Name(text) => entry.name.to_lowercase().contains(text),
+            Term::NameRegex(regex) => regex.is_match(&entry.name),
+            Term::Path(text) => entry.path.to_string_lossy().to_lowercase().contains(text),
+            Term::Extension(extensions) => entry
+                .path
+                .extension()
+                .map(|ext| ext.to_string_lossy().to_lowercase())
+                .is_some_and(|ext| extensions.contains(&ext)),
+            Term::Size(compare, size) => {
+                entry.file_type == FileType::File && compare.test(entry.size, *size)
+            }
+            Term::SizeBetween(min, max) => {
+                entry.file_type == FileType::File && (*min..=*max).contains(&entry.size)
+            }
+            Term::Modified(compare, time) => entry
+                .modified
+                .is_some_and(|modified| compare.test(modified, *time)),
+            Term::ModifiedBetween(start, end) => entry
+                .modified
+                .is_some_and(|modified| *start <= modified && modified < *end),
+            Term::Type(file_type) => entry.file_type == *file_type,
+        }
+    }
+}
+
+/// 条件の組み合わせ
+#[derive(Debug, Clone)]
+enum Expr {
+    And(Vec<Expr>),
+    Or(Vec<Expr>),
+    Not(Box<Expr>),
+    Term(Term),
+}
+
+impl Expr {
+    fn matches(&self, entry: &FileEntry) -> bool {
+        match self {
+            Expr::And(exprs) => exprs.iter().all(|expr| expr.matches(entry)),
+            Expr::Or(exprs) => exprs.iter().any(|expr| expr.matches(entry)),
+            Expr::Not(expr) => !expr.matches(entry),
+            Expr::Term(term) => term.matches(entry),
+        }
+    }
+}
+
+/// 解析したクエリ
+#[derive(Debug, Clone)]
+pub struct FileQuery {
+    source: String,
+    expr: Expr,
+}
+
+impl FileQuery {
+    /// クエリを解析（経過時間は現在時刻を基準にする）
+    ///
+    /// 解析できない場合は問題のある語の位置（1始まりの文字数）を含む
+    /// `AppError::InvalidInput` を返す。
+    pub fn parse(source: &str) -> Result<Self, AppError> {
+        Self::parse_at(source, SystemTime::now())
+    }
+
+    /// 経過時間の基準の時刻を指定してクエリを解析
+    pub fn parse_at(source: &str, now: SystemTime) -> Result<Self, AppError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens,
+            position: 0,
+            end_column: source.chars().count() + 1,
+            now,
+        };
+        let expr = parser.parse_query()?;
+        Ok(Self {
+            source: source.to_string(),
+            expr,
+        })
+    }
+
+    /// エントリが条件を満たすか
+    pub fn matches(&self, entry: &FileEntry) -> bool {
+        self.expr.matches(entry)
+    }
+
+    /// 解析前のクエリ
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// 条件がない（すべてに一致する）か
+    pub fn is_empty(&self) -> bool {
+        matches!(&self.expr, Expr::And(exprs) if exprs.is_empty())
+    }
+}
+
+impl PartialEq for FileQuery {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl FromStr for FileQuery {
+    type Err = AppError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        Self::parse(source)
+    }
+}
+
+/// 条件の値の書き方
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Text(String),
+    /// `"` で囲んだ語句
+    Phrase(String),
+    /// `/` で囲んだ正規表現
+    Regex(String),
+}
+
+/// 字句の種類
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    LeftParen,
+    RightParen,
+    And,
+    Or,
+    Not,
+    Term { field: Option<String>, value: Value },
+}
+
+/// 字句と位置
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    /// 1始まりの文字の位置
+    column: usize,
+    /// 元の文字列
+    text: String,
+}
+
+/// 位置を示す入力エラー
+fn invalid(column: usize, text: &str, message: &str) -> AppError {
+    AppError::InvalidInput(format!("{} at column {}: '{}'", message, column, text))
+}
+
+/// `/` で始まる値の閉じる `/` の位置（語の終わりにある最初の `/`）
+///
+/// `/tmp` や `/home/me` のように語の途中にしか `/` がなければ正規表現ではない。
+fn closing_slash(chars: &[char], open: usize) -> Option<usize> {
+    (open + 1..chars.len()).find(|&k| {
+        chars[k] == '/'
+            && chars
+                .get(k + 1)
+                .is_none_or(|next| next.is_whitespace() || *next == ')')
+    })
+}
+
+/// クエリを字句に分ける
+///
+/// `/…/` は名前（`name:` か項目名なし）の正規表現としてだけ扱い、`path:` の値は
+/// `/` で始まってもそのままの文字列として扱う。
+fn tokenize(source: &str) -> Result<Vec<Token>, AppError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        let simple = match c {
+            _ if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '(' => Some(TokenKind::LeftParen),
+            ')' => Some(TokenKind::RightParen),
+            '|' => Some(TokenKind::Or),
+            // 語の先頭の `-` は否定
+            '-' if chars.get(i + 1).is_some_and(|c| !c.is_whitespace()) => Some(TokenKind::Not),
+            _ => None,
+        };
+        if let Some(kind) = simple {
+            i += 1;
+            tokens.push(Token {
+                kind,
+                column: start + 1,
+                text: c.to_string(),
+            });
+            continue;
+        }
+
+        // `field:` の部分
+        let mut field = None;
+        if c != '"' && c != '/' {
+            let mut j = i;
+            while j < chars.len() && (chars[j].is_alphanumeric() || chars[j] == '_') {
+                j += 1;
+            }
+            if j > i && chars.get(j) == Some(&':') {
+                field = Some(chars[i..j].iter().collect::<String>().to_lowercase());
+                i = j + 1;
+            }
+        }
+
+        let regex_end = match field.as_deref() {
+            None | Some("name") if chars.get(i) == Some(&'/') => closing_slash(&chars, i),
+            _ => None,
+        };
+        let value = match chars.get(i) {
+            Some('"') => {
+                let Some(length) = chars[i + 1..].iter().position(|&c| c == '"') else {
+                    let text: String = chars[start..].iter().collect();
+                    return Err(invalid(start + 1, &text, "Unterminated quoted phrase"));
+                };
+                let inner: String = chars[i + 1..i + 1 + length].iter().collect();
+                i += length + 2;
+                Value::Phrase(inner)
+            }
+            _ if let Some(end) = regex_end => {
+                let inner: String = chars[i + 1..end].iter().collect();
+                i = end + 1;
+                Value::Regex(inner)
+            }
+            _ => {
+                let begin = i;
+                while i < chars.len() && !chars[i].is_whitespace() && !matches!(chars[i], '(' | ')')
+                {
+                    i += 1;
+                }
+                Value::Text(chars[begin..i].iter().collect())
+            }
+        };
+
+        let text: String = chars[start..i].iter().collect();
+        let kind = match (&field, &value) {
+            (None, Value::Text(word)) if word == "AND" => TokenKind::And,
+            (None, Value::Text(word)) if word == "OR" => TokenKind::Or,
+            (None, Value::Text(word)) if word == "NOT" => TokenKind::Not,
+            _ => TokenKind::Term { field, value },
+        };
+        tokens.push(Token {
+            kind,
+            column: start + 1,
+            text,
+        });
+    }
+
+    Ok(tokens)
+}
+
+/// 字句から条件を組み立てる（優先順位は NOT、AND、OR の順）
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    /// クエリの終わりの位置（エラー表示用）
+    end_column: usize,
+    now: SystemTime,
+}
+
+impl Parser {
+    fn parse_query(&mut self) -> Result<Expr, AppError> {
+        if self.tokens.is_empty() {
+            return Ok(Expr::And(Vec::new()));
+        }
+        let expr = self.parse_or()?;
+        match self.tokens.get(self.position) {
+            Some(token) => Err(invalid(token.column, &token.text, "Unexpected token")),
+            None => Ok(expr),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, AppError> {
+        let mut exprs = vec![self.parse_and()?];
+        while self.eat(&TokenKind::Or) {
+            exprs.push(self.parse_and()?);
+        }
+        Ok(if exprs.len() == 1 {
+            exprs.remove(0)
+        } else {
+            Expr::Or(exprs)
+        })
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, AppError> {
+        let mut exprs = vec![self.parse_unary()?];
+        loop {
+            if self.eat(&TokenKind::And) {
+                exprs.push(self.parse_unary()?);
+                continue;
+            }
+            // 並べた条件は AND
+            match self.tokens.get(self.position).map(|token| &token.kind) {
+                Some(TokenKind::Or | TokenKind::RightParen) | None => break,
+                Some(_) => exprs.push(self.parse_unary()?),
+            }
+        }
+        Ok(if exprs.len() == 1 {
+            exprs.remove(0)
+        } else {
+            Expr::And(exprs)
+        })
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, AppError> {
+        if self.eat(&TokenKind::Not) {
+            return Ok(Expr::Not(Box::new(self.parse_unary()?)));
+        }
+
+        let Some(token) = self.tokens.get(self.position).cloned() else {
+            return Err(invalid(self.end_column, "", "Unexpected end of query"));
+        };
+        self.position += 1;
+        match token.kind {
+            TokenKind::LeftParen => {
+                let expr = self.parse_or()?;
+                if !self.eat(&TokenKind::RightParen) {
+                    return Err(invalid(token.column, &token.text, "Unclosed parenthesis"));
+                }
+                Ok(expr)
+            }
+            TokenKind::Term {
+                ref field,
+                ref value,
+            } => Ok(Expr::Term(self.parse_term(
+                &token,
+                field.as_deref(),
+                value,
+            )?)),
+            _ => Err(invalid(token.column, &token.text, "Expected a search term")),
+        }
+    }
+
+    /// 1つの条件を解釈
+    fn parse_term(
+        &self,
+        token: &Token,
+        field: Option<&str>,
+        value: &Value,
+    ) -> Result<Term, AppError> {
+        let error = |message: &str| invalid(token.column, &token.text, message);
+        let regex = |pattern: &str| {
+            RegexBuilder::new(pattern)
+                .case_insensitive(true)
+                .build()
+                .map_err(|_| error("Invalid regular expression"))
+        };
+        let text = match value {
+            Value::Text(text) | Value::Phrase(text) => text.as_str(),
+            // 字句に分けるときに名前の条件だけを正規表現にしている
+            Value::Regex(pattern) => return Ok(Term::NameRegex(regex(pattern)?)),
+        };
+        if text.is_empty() {
+            return Err(error("Missing value"));
+        }
+
+        match field {
+            None | Some("name") => Ok(Term::Name(text.to_lowercase())),
+            Some("path") => Ok(Term::Path(text.to_lowercase())),
+            Some("ext") => {
+                let extensions: Vec<String> = text
+                    .split(',')
+                    .map(|ext| ext.trim().trim_start_matches('.').to_lowercase())
+                    .collect();
+                if extensions.iter().any(String::is_empty) {
+                    return Err(error("Empty extension"));
+                }
+                Ok(Term::Extension(extensions))
+            }
+            Some("size") if text.contains("..") => {
+                // `1KB..10MB`、`..10MB`、`1KB..`
+                let (min, max) = text.split_once("..").unwrap_or_default();
+                let bound = |size: &str, open: u64| match size {
+                    "" => Some(open),
+                    _ => parse_size(size),
+                };
+                match (bound(min, 0), bound(max, u64::MAX)) {
+                    (Some(min), Some(max)) if min <= max => Ok(Term::SizeBetween(min, max)),
+                    _ => Err(error("Invalid size range")),
+                }
+            }
+            Some("size") => {
+                let (compare, size) = Compare::split(text, Compare::Equal);
+                let size = parse_size(size).ok_or_else(|| error("Invalid size"))?;
+                Ok(Term::Size(compare, size))
+            }
+            Some("modified") => self
+                .parse_modified(text)
+                .ok_or_else(|| error("Invalid date or age")),
+            Some("type") => match text.to_lowercase().as_str() {
+                "file" | "f" => Ok(Term::Type(FileType::File)),
+                "dir" | "directory" | "folder" | "d" => Ok(Term::Type(FileType::Directory)),
+                "symlink" | "link" | "l" => Ok(Term::Type(FileType::SymLink)),
+                "other" => Ok(Term::Type(FileType::Other)),
+                _ => Err(error("Unknown type (expected file, dir, symlink or other)")),
+            },
+            Some(_) => Err(error("Unknown field")),
+        }
+    }
+
+    /// 更新日時の条件（`<7d` のような経過時間、`>2024-01-31` のような日付）
+    fn parse_modified(&self, text: &str) -> Option<Term> {
+        if let Some(age) = parse_age(Compare::split(text, Compare::Less).1) {
+            // 経過時間が短い＝更新日時が新しい
+            let (compare, _) = Compare::split(text, Compare::Less);
+            let threshold = self.now.checked_sub(age)?;
+            return Some(Term::Modified(compare.reversed(), threshold));
+        }
+
+        let (compare, date) = Compare::split(text, Compare::Equal);
+        let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+        let start: SystemTime = Local
+            .from_local_datetime(&date.and_hms_opt(0, 0, 0)?)
+            .earliest()?
+            .into();
+        let end = start + DAY;
+        Some(match compare {
+            Compare::Equal => Term::ModifiedBetween(start, end),
+            Compare::Less | Compare::GreaterOrEqual => Term::Modified(compare, start),
+            Compare::LessOrEqual => Term::Modified(Compare::Less, end),
+            Compare::Greater => Term::Modified(Compare::GreaterOrEqual, end),
+        })
+    }
+
+    /// 次の字句が `kind` なら読み進める
+    fn eat(&mut self, kind: &TokenKind) -> bool {
+        let matched = self
+            .tokens
+            .get(self.position)
+            .is_some_and(|token| token.kind == *kind);
+        if matched {
+            self.position += 1;
+        }
+        matched
+    }
+}
+
+/// `10MB` のようなサイズをバイト数にする（単位は1024倍）
+fn parse_size(text: &str) -> Option<u64> {
+    let split = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let multiplier: u64 = match unit.to_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" => 1 << 10,
+        "m" | "mb" => 1 << 20,
+        "g" | "gb" => 1 << 30,
+        "t" | "tb" => 1 << 40,
+        _ => return None,
+    };
+    Some((number * multiplier as f64) as u64)
+}
+
+/// `7d` のような経過時間（s, m/min, h, d, w, y）
+fn parse_age(text: &str) -> Option<Duration> {
+    let split = text
+        .find(|c: char| !c.is_ascii_digit())
+        .filter(|&split| split > 0)?;
+    let (number, unit) = text.split_at(split);
+    let number: u64 = number.parse().ok()?;
+    let seconds: u64 = match unit.to_lowercase().as_str() {
+        "s" => 1,
+        "m" | "min" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        "y" => 365 * 24 * 60 * 60,
+        _ => return None,
+    };
+    Some(Duration::from_secs(number.checked_mul(seconds)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("10"), Some(10));
+        assert_eq!(parse_size("2KB"), Some(2048));
+        assert_eq!(parse_size("1.5m"), Some(1_572_864));
+        assert_eq!(parse_size("10XB"), None);
+        assert_eq!(parse_size("MB"), None);
+    }
+
+    #[test]
+    fn test_parse_age() {
+        assert_eq!(parse_age("7d"), Some(DAY * 7));
+        assert_eq!(parse_age("30min"), Some(Duration::from_secs(1800)));
+        assert_eq!(parse_age("d"), None);
+        assert_eq!(parse_age("2024-01-01"), None);
+    }
+
+    #[test]
+    fn test_tokenize_fields_and_operators() {
+        let tokens = tokenize(r#"-path:target (a OR "b c") name:/x y/"#).unwrap();
+        let kinds: Vec<_> = tokens.iter().map(|t| t.kind.clone()).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Not,
+                TokenKind::Term {
+                    field: Some("path".to_string()),
+                    value: Value::Text("target".to_string()),
+                },
+                TokenKind::LeftParen,
+                TokenKind::Term {
+                    field: None,
+                    value: Value::Text("a".to_string()),
+                },
+                TokenKind::Or,
+                TokenKind::Term {
+                    field: None,
+                    value: Value::Phrase("b c".to_string()),
+                },
+                TokenKind::RightParen,
+                TokenKind::Term {
+                    field: Some("name".to_string()),
+                    value: Value::Regex("x y".to_string()),
+                },
+            ]
+        );
+        assert_eq!(tokens[2].column, 14);
+    }
+}
@@ -0,0 +1,445 @@
+//! パスのクイックオープン
+//!
+//! 入力した断片を最近開いた場所・ブックマーク・索引のパスにあいまいに照合し、
+//! よく使う場所と名前（ベースネーム）での一致を上位に並べる。
+
+use crate::bookmarks::Bookmarks;
+use crate::filesystem::FileType;
+use crate::history::{LocationHistory, VisitKind};
+use crate::search::SearchIndex;
+use crate::state::AppState;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// 一致した1文字の点数
+const SCORE_MATCH: i32 = 16;
+/// 単語の先頭（区切り文字の直後・パスの先頭）での一致
+const BONUS_BOUNDARY: i32 = 32;
+/// 小文字から大文字に変わる位置での一致
+const BONUS_CAMEL: i32 = 24;
+/// 直前の文字に続く一致
+const BONUS_CONSECUTIVE: i32 = 24;
+/// 一致の間に飛ばした1文字ごとの減点
+const PENALTY_GAP: i32 = 1;
+/// 断片が名前の中で一致した場合の加点
+const BONUS_BASENAME: i32 = 48;
+/// 入力が名前と完全に一致した場合の加点
+const BONUS_EXACT_NAME: i32 = 100;
+/// よく使う度合い 1.0 あたりの加点
+const FRECENCY_WEIGHT: f64 = 40.0;
+/// 最近開いた場所の重みの減り方（1つ前に開くごとに掛ける）
+const RECENT_DECAY: f64 = 0.9;
+
+/// 索引から照合する候補の上限（これを超える一致は入力を絞ってもらう）
+pub const MAX_INDEXED_CANDIDATES: usize = 1000;
+
+/// 候補の出どころ
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QuickOpenSource {
+    /// 最近開いた場所
+    Recent,
+    /// ブックマーク
+    Bookmark,
+    /// 検索の索引
+    Indexed,
+}
+
+impl QuickOpenSource {
+    /// 同じパスが複数の出どころにある場合の優先度と加点
+    fn bonus(self) -> i32 {
+        match self {
+            QuickOpenSource::Bookmark => 30,
+            QuickOpenSource::Recent => 10,
+            QuickOpenSource::Indexed => 0,
+        }
+    }
+}
+
+/// 照合する候補
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuickOpenCandidate {
+    pub path: PathBuf,
+    pub source: QuickOpenSource,
+    pub is_dir: bool,
+    /// よく使う度合い（大きいほど上位）
+    pub frecency: f64,
+}
+
+/// 照合の結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuickOpenMatch {
+    pub path: PathBuf,
+    pub source: QuickOpenSource,
+    pub is_dir: bool,
+    pub score: i32,
+    /// 一致した文字の位置（パスを文字列にしたときの文字単位の位置、昇順）
+    pub positions: Vec<usize>,
+}
+
+/// あいまい照合の結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    pub score: i32,
+    /// 一致した文字の位置（文字単位、昇順）
+    pub positions: Vec<usize>,
+}
+
+/// `pattern` の文字を順に含むか照合し、単語の先頭や連続した一致ほど高い点数を付ける
+///
+/// 大文字小文字は区別しない。空白で区切った断片はそれぞれ照合する。
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<FuzzyMatch> {
+    let text: Vec<char> = text.chars().collect();
+    let lower = lowercase(&text);
+    let bonuses = bonuses(&text);
+    let mut total = FuzzyMatch {
+        score: 0,
+        positions: Vec::new(),
+    };
+    for fragment in pattern.split_whitespace() {
+        let fragment: Vec<char> = fragment.to_lowercase().chars().collect();
+        let found = best_match(&fragment, &lower, &bonuses)?;
+        total.score += found.score;
+        total.positions.extend(found.positions);
+    }
+    total.positions.sort_unstable();
+    total.positions.dedup();
+    Some(total)
+}
+
+/// クイックオープンの候補
+#[derive(Debug, Clone, Default)]
+pub struct QuickOpen {
+    candidates: Vec<QuickOpenCandidate>,
+    by_path: HashMap<PathBuf, usize>,
+}
+
+impl QuickOpen {
+    /// 候補のない状態で作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// タブの現在のパスと履歴から最近開いたディレクトリを候補にする
+    ///
+    /// 最後に開いた場所ほど重く、何度も開いた場所は重みを足し合わせる。
+    /// アクティブなタブの現在のパスを最も新しいものとして扱う。
+    pub fn from_state(state: &AppState) -> Self {
+        let mut visits: Vec<&Path> = Vec::new();
+        let active = state.active_tab_id.as_deref();
+        let tabs = state
+            .closed_tabs
+            .iter()
+            .chain(
+                state
+                    .tabs
+                    .iter()
+                    .filter(|tab| Some(tab.id.as_str()) != active),
+            )
+            .chain(
+                state
+                    .tabs
+                    .iter()
+                    .filter(|tab| Some(tab.id.as_str()) == active),
+            );
+        for tab in tabs {
+            if tab.smart_folder.is_some() || tab.tag.is_some() {
+                continue;
+            }
+            visits.extend(tab.history_back.iter().map(PathBuf::as_path));
+            visits.push(&tab.current_path);
+        }
+
+        let mut frecency: Vec<(&Path, f64)> = Vec::new();
+        let mut weight = 1.0;
+        for path in visits.into_iter().rev() {
+            match frecency.iter_mut().find(|(known, _)| *known == path) {
+                Some((_, total)) => *total += weight,
+                None => frecency.push((path, weight)),
+            }
+            weight *= RECENT_DECAY;
+        }
+
+        let mut quick_open = Self::new();
+        for (path, frecency) in frecency {
+            quick_open.add(QuickOpenCandidate {
+                path: path.to_path_buf(),
+                source: QuickOpenSource::Recent,
+                is_dir: true,
+                frecency,
+            });
+        }
+        quick_open
+    }
+
+    /// ブックマークを候補に加える
+    pub fn add_bookmarks(&mut self, bookmarks: &Bookmarks) {
+        for bookmark in bookmarks.bookmarks() {
+            self.add(QuickOpenCandidate {
+                path: bookmark.path.clone(),
+                source: QuickOpenSource::Bookmark,
+                is_dir: !bookmark.path.is_file(),
+                frecency: 0.0,
+            });
+        }
+    }
+
+    /// 訪れた場所の履歴を候補に加える（度合いは対数で縮めて他の候補と釣り合わせる）
+    pub fn add_history(&mut self, history: &LocationHistory, now: DateTime<Utc>) {
+        for entry in history.entries() {
+            self.add(QuickOpenCandidate {
+                path: entry.path.clone(),
+                source: QuickOpenSource::Recent,
+                is_dir: entry.kind == VisitKind::Directory,
+                frecency: entry.frecency(now).ln_1p(),
+            });
+        }
+    }
+
+    /// 候補を追加（同じパスがあれば出どころの優先度が高い方を残し、よく使う度合いは大きい方）
+    pub fn add(&mut self, candidate: QuickOpenCandidate) {
+        match self.by_path.get(&candidate.path) {
+            Some(&index) => {
+                let existing = &mut self.candidates[index];
+                existing.frecency = existing.frecency.max(candidate.frecency);
+                if candidate.source.bonus() > existing.source.bonus() {
+                    existing.source = candidate.source;
+                    existing.is_dir = candidate.is_dir;
+                }
+            }
+            None => {
+                self.by_path
+                    .insert(candidate.path.clone(), self.candidates.len());
+                self.candidates.push(candidate);
+            }
+        }
+    }
+
+    /// 候補の一覧
+    pub fn candidates(&self) -> &[QuickOpenCandidate] {
+        &self.candidates
+    }
+
+    /// 入力に一致する候補を点数の高い順に最大 `limit` 件返す
+    ///
+    /// `index` を渡すと索引のパスも照合する。索引のパスは最後の断片が名前に含まれる
+    /// ものを最大 `MAX_INDEXED_CANDIDATES` 件まで照合する。入力が空なら索引は使わず、
+    /// 最近開いた場所とブックマークをよく使う順に返す。
+    pub fn search(
+        &self,
+        pattern: &str,
+        index: Option<&SearchIndex>,
+        limit: usize,
+    ) -> Vec<QuickOpenMatch> {
+        let fragments: Vec<Vec<char>> = pattern
+            .split_whitespace()
+            .map(|fragment| fragment.to_lowercase().chars().collect())
+            .collect();
+
+        let mut matches: Vec<QuickOpenMatch> = self
+            .candidates
+            .iter()
+            .filter_map(|candidate| {
+                let (score, positions) = score_path(&fragments, &candidate.path)?;
+                Some(QuickOpenMatch {
+                    path: candidate.path.clone(),
+                    source: candidate.source,
+                    is_dir: candidate.is_dir,
+                    score: score
+                        + candidate.source.bonus()
+                        + (candidate.frecency * FRECENCY_WEIGHT).round() as i32,
+                    positions,
+                })
+            })
+            .collect();
+
+        if let Some(index) = index
+            && let Some(last) = fragments.last()
+        {
+            let mut indexed = 0;
+            for (entry, name_lower) in index.named_entries() {
+                if indexed == MAX_INDEXED_CANDIDATES {
+                    break;
+                }
+                // 小文字にした名前で割り当てずに除いてから照合する
+                if !contains_in_order(last, name_lower) || self.by_path.contains_key(&entry.path) {
+                    continue;
+                }
+                if let Some((score, positions)) = score_path(&fragments, &entry.path) {
+                    indexed += 1;
+                    matches.push(QuickOpenMatch {
+                        path: entry.path.clone(),
+                        source: QuickOpenSource::Indexed,
+                        is_dir: entry.file_type == FileType::Directory,
+                        score,
+                        positions,
+                    });
+                }
+            }
+        }
+
+        matches.sort_by(|a, b| {
+            b.score
+                .cmp(&a.score)
+                .then_with(|| a.path.as_os_str().len().cmp(&b.path.as_os_str().len()))
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        matches.truncate(limit);
+        matches
+    }
+}
+
+/// パスを照合する（断片ごとに名前での一致を優先し、なければパス全体で照合）
+fn score_path(fragments: &[Vec<char>], path: &Path) -> Option<(i32, Vec<usize>)> {
+    if fragments.is_empty() {
+        return Some((0, Vec::new()));
+    }
+    // 安い判定で明らかに一致しないパスを割り当てる前に除く
+    let path_text = path.to_string_lossy();
+    if !fragments
+        .iter()
+        .all(|fragment| contains_in_order(fragment, &path_text))
+    {
+        return None;
+    }
+
+    let text: Vec<char> = path_text.chars().collect();
+    let lower = lowercase(&text);
+    let bonuses = bonuses(&text);
+    let name_start = text
+        .iter()
+        .rposition(|&c| is_separator(c))
+        .map_or(0, |i| i + 1);
+    let mut score = 0;
+    let mut positions = Vec::new();
+    for fragment in fragments {
+        let in_name = best_match(fragment, &lower[name_start..], &bonuses[name_start..]);
+        let found = match in_name {
+            Some(found) => FuzzyMatch {
+                score: found.score + BONUS_BASENAME,
+                positions: found.positions.iter().map(|p| p + name_start).collect(),
+            },
+            None => best_match(fragment, &lower, &bonuses)?,
+        };
+        score += found.score;
+        positions.extend(found.positions);
+    }
+    if fragments.len() == 1 && fragments[0].as_slice() == &lower[name_start..] {
+        score += BONUS_EXACT_NAME;
+    }
+    positions.sort_unstable();
+    positions.dedup();
+    Some((score, positions))
+}
+
+/// 最も点数の高い一致を探す
+///
+/// `pattern` の i 文字目を `text` の j 文字目に合わせた場合の最高点を順に求め、
+/// 最後の文字の最高点の位置からたどって一致した位置を返す。
+fn best_match(pattern: &[char], text: &[char], bonuses: &[i32]) -> Option<FuzzyMatch> {
+    const NONE: i32 = i32::MIN / 2;
+    let (m, n) = (pattern.len(), text.len());
+    if m == 0 {
+        return Some(FuzzyMatch {
+            score: 0,
+            positions: Vec::new(),
+        });
+    }
+    if m > n || !is_subsequence(pattern, text) {
+        return None;
+    }
+
+    let mut score = vec![NONE; m * n];
+    let mut from = vec![usize::MAX; m * n];
+    for i in 0..m {
+        // j - 2 以前の位置から飛んでくる場合の最高点（飛ばした文字数の減点は後で引く）
+        let mut best_gap = NONE;
+        let mut best_gap_at = usize::MAX;
+        for j in i..n {
+            if i > 0 && j >= 2 {
+                let k = j - 2;
+                let previous = score[(i - 1) * n + k];
+                if previous > NONE && previous + PENALTY_GAP * k as i32 > best_gap {
+                    best_gap = previous + PENALTY_GAP * k as i32;
+                    best_gap_at = k;
+                }
+            }
+            if text[j] != pattern[i] {
+                continue;
+            }
+            let gained = SCORE_MATCH + bonuses[j];
+            if i == 0 {
+                score[j] = gained;
+                continue;
+            }
+            let mut best = NONE;
+            let mut best_at = usize::MAX;
+            let adjacent = score[(i - 1) * n + j - 1];
+            if adjacent > NONE {
+                best = adjacent + BONUS_CONSECUTIVE;
+                best_at = j - 1;
+            }
+            if best_gap > NONE && best_gap - PENALTY_GAP * (j - 1) as i32 > best {
+                best = best_gap - PENALTY_GAP * (j - 1) as i32;
+                best_at = best_gap_at;
+            }
+            if best > NONE {
+                score[i * n + j] = best + gained;
+                from[i * n + j] = best_at;
+            }
+        }
+    }
+
+    let last = (m - 1) * n;
+    let end = (0..n)
+        .filter(|&j| score[last + j] > NONE)
+        .max_by_key(|&j| (score[last + j], std::cmp::Reverse(j)))?;
+    let mut positions = vec![0; m];
+    let mut j = end;
+    for i in (0..m).rev() {
+        positions[i] = j;
+        j = from[i * n + j];
+    }
+    Some(FuzzyMatch {
+        score: score[last + end],
+        positions,
+    })
+}
+
+/// `pattern` の文字を順に含むか
+fn is_subsequence(pattern: &[char], text: &[char]) -> bool {
+    let mut remaining = text.iter();
+    pattern.iter().all(|c| remaining.by_ref().any(|t| t == c))
+}
+
+/// `pattern` の文字を順に含むか（`text` は1文字ずつ小文字にして比べる）
+fn contains_in_order(pattern: &[char], text: &str) -> bool {
+    let mut remaining = text.chars().map(lower_char);
+    pattern.iter().all(|c| remaining.by_ref().any(|t| t == *c))
+}
+
+fn lowercase(text: &[char]) -> Vec<char> {
+    text.iter().copied().map(lower_char).collect()
+}
+
+fn lower_char(c: char) -> char {
+    // 1文字が複数文字になる変換は位置がずれるため先頭の1文字だけ使う
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// 文字ごとの位置の加点（単語の先頭と大文字の始まり）
+fn bonuses(text: &[char]) -> Vec<i32> {
+    text.iter()
+        .enumerate()
+        .map(|(i, &c)| match i.checked_sub(1).map(|p| text[p]) {
+            None => BONUS_BOUNDARY,
+            Some(previous) if is_separator(previous) || "_-. ".contains(previous) => BONUS_BOUNDARY,
+            Some(previous) if previous.is_lowercase() && c.is_uppercase() => BONUS_CAMEL,
+            _ => 0,
+        })
+        .collect()
+}
+
+fn is_separator(c: char) -> bool {
+    c == '/' || c == std::path::MAIN_SEPARATOR
+}
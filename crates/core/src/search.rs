@@ -0,0 +1,996 @@
+//! 高速検索のための索引
+//!
+//! 指定したルート以下のパスを低い優先度のバックグラウンドスレッドで巡回して索引を作り、
+//! 以降は OS の変更通知で索引を最新に保ちます。索引はファイル名の前方一致・部分一致・
+//! グロブ、`FileQuery` のクエリで検索でき、結果は `FileEntry` として返します。
+
+use crate::filesystem::{FileEntry, FileType};
+use crate::query::FileQuery;
+use crate::watcher::{ChangeCoalescer, DirectoryChange, RawChange};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use rust_explorer_utils::AppError;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// 保存する索引の形式のバージョン
+pub const INDEX_FORMAT_VERSION: u32 = 1;
+
+/// 変更通知をまとめる間隔
+const CHANGE_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// 巡回中に一休みするまでに読むディレクトリ数
+const CRAWL_BATCH_DIRS: usize = 64;
+
+/// 巡回の一休みの長さ（操作中の処理を妨げないため）
+const CRAWL_PAUSE: Duration = Duration::from_millis(2);
+
+/// 検索条件（大文字小文字は区別しない）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchQuery {
+    /// 名前の前方一致
+    Prefix(String),
+    /// 名前の部分一致
+    Substring(String),
+    /// グロブ（`/` を含む場合はフルパス、それ以外は名前に対して照合）
+    Glob(String),
+}
+
+impl SearchQuery {
+    /// 入力された文字列から検索条件を作る（ワイルドカードを含めばグロブ、それ以外は部分一致）
+    pub fn parse(text: &str) -> Self {
+        let text = text.trim();
+        if text.contains(['*', '?', '[']) {
+            SearchQuery::Glob(text.to_string())
+        } else {
+            SearchQuery::Substring(text.to_string())
+        }
+    }
+}
+
+/// 保存用の索引の内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexSnapshot {
+    pub version: u32,
+    pub roots: Vec<PathBuf>,
+    pub entries: Vec<FileEntry>,
+}
+
+impl Default for IndexSnapshot {
+    fn default() -> Self {
+        Self {
+            version: INDEX_FORMAT_VERSION,
+            roots: Vec::new(),
+            entries: Vec::new(),
+        }
+    }
+}
+
+/// 検索結果の並び順（完全一致、前方一致、名前の長さ）
+type Rank = (bool, bool, usize);
+
+/// 名前の連続した3文字（部分一致とグロブの絞り込みに使う）
+type Trigram = [char; 3];
+
+/// 索引の1件
+#[derive(Debug, Clone)]
+struct IndexRecord {
+    entry: FileEntry,
+    /// 小文字にした名前（照合用）
+    name_lower: Box<str>,
+    /// 最後に巡回または変更通知で確認した世代
+    generation: u64,
+}
+
+/// パスの索引
+///
+/// 削除した件は空きとして再利用し、名前の前方一致は並べ替えた名前から探す。
+/// 部分一致とグロブは名前の3文字組の索引で候補を絞ってから照合し、
+/// 絞り込めない短い条件のときだけ全件を走査する。
+#[derive(Debug, Default)]
+pub struct SearchIndex {
+    roots: Vec<PathBuf>,
+    records: Vec<Option<IndexRecord>>,
+    free: Vec<usize>,
+    by_path: BTreeMap<PathBuf, usize>,
+    by_name: BTreeSet<(Box<str>, usize)>,
+    by_trigram: HashMap<Trigram, BTreeSet<usize>>,
+    generation: u64,
+    /// 項目を追加・更新・削除するたびに増える番号
+    revision: u64,
+}
+
+impl SearchIndex {
+    /// 空の索引を作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 保存した内容から索引を作成（形式が異なれば空の索引）
+    pub fn from_snapshot(snapshot: IndexSnapshot) -> Self {
+        let mut index = Self::new();
+        if snapshot.version != INDEX_FORMAT_VERSION {
+            return index;
+        }
+        index.roots = snapshot.roots;
+        for entry in snapshot.entries {
+            index.upsert(entry);
+        }
+        index
+    }
+
+    /// 保存用の内容を作成
+    pub fn snapshot(&self) -> IndexSnapshot {
+        IndexSnapshot {
+            version: INDEX_FORMAT_VERSION,
+            roots: self.roots.clone(),
+            entries: self.entries().cloned().collect(),
+        }
+    }
+
+    /// 索引の対象のルート
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    /// 索引の件数
+    pub fn len(&self) -> usize {
+        self.by_path.len()
+    }
+
+    /// 索引が空か
+    pub fn is_empty(&self) -> bool {
+        self.by_path.is_empty()
+    }
+
+    /// 内容の更新番号（変わっていなければ前回の検索結果をそのまま使える）
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// パスが索引にあるか
+    pub fn contains(&self, path: &Path) -> bool {
+        self.by_path.contains_key(path)
+    }
+
+    /// 項目を追加または更新
+    pub fn upsert(&mut self, entry: FileEntry) {
+        let name_lower: Box<str> = entry.name.to_lowercase().into();
+        let record = IndexRecord {
+            entry,
+            name_lower,
+            generation: self.generation,
+        };
+        self.revision += 1;
+
+        if let Some(&id) = self.by_path.get(&record.entry.path) {
+            if let Some(old) = self.records[id].take() {
+                self.unlink_name(&old.name_lower, id);
+            }
+            self.link_name(&record.name_lower, id);
+            self.records[id] = Some(record);
+            return;
+        }
+
+        let id = match self.free.pop() {
+            Some(id) => id,
+            None => {
+                self.records.push(None);
+                self.records.len() - 1
+            }
+        };
+        self.by_path.insert(record.entry.path.clone(), id);
+        self.link_name(&record.name_lower, id);
+        self.records[id] = Some(record);
+    }
+
+    /// 項目とその配下を削除し、削除した件数を返す
+    pub fn remove_tree(&mut self, path: &Path) -> usize {
+        let removed = self.paths_under(path);
+        for path in &removed {
+            self.remove_one(path);
+        }
+        removed.len()
+    }
+
+    /// 検索して最大 `limit` 件を返す
+    ///
+    /// 名前が条件と一致するもの、条件で始まるもの、名前の短いものの順に並べる。
+    pub fn search(&self, query: &SearchQuery, limit: usize) -> Vec<FileEntry> {
+        if limit == 0 {
+            return Vec::new();
+        }
+
+        match query {
+            SearchQuery::Prefix(prefix) => {
+                let prefix: Box<str> = prefix.to_lowercase().into();
+                self.by_name
+                    .range((prefix.clone(), 0)..)
+                    .take_while(|(name, _)| name.starts_with(&*prefix))
+                    .filter_map(|(_, id)| self.records[*id].as_ref())
+                    .take(limit)
+                    .map(|record| record.entry.clone())
+                    .collect()
+            }
+            SearchQuery::Substring(text) => {
+                let text = text.to_lowercase();
+                self.ranked(&text, limit, &required_trigrams(query), |record| {
+                    record.name_lower.contains(&text)
+                })
+            }
+            SearchQuery::Glob(pattern) => {
+                let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+                let full_path = pattern.contains(&'/');
+                self.ranked("", limit, &required_trigrams(query), |record| {
+                    if full_path {
+                        glob_match_folded(&pattern, &record.entry.path.to_string_lossy())
+                    } else {
+                        glob_match(&pattern, &record.name_lower)
+                    }
+                })
+            }
+        }
+    }
+
+    /// 検索で条件と照合する項目の数
+    ///
+    /// 前方一致は名前が前方一致する項目だけを、部分一致とグロブは3文字組の索引で
+    /// 絞り込んだ候補だけを照合する。
+    pub fn candidate_count(&self, query: &SearchQuery) -> usize {
+        match query {
+            SearchQuery::Prefix(prefix) => {
+                let prefix: Box<str> = prefix.to_lowercase().into();
+                self.by_name
+                    .range((prefix.clone(), 0)..)
+                    .take_while(|(name, _)| name.starts_with(&*prefix))
+                    .count()
+            }
+            SearchQuery::Substring(_) | SearchQuery::Glob(_) => self
+                .candidates(&required_trigrams(query))
+                .map_or(self.len(), BTreeSet::len),
+        }
+    }
+
+    /// クエリに一致する項目を最大 `limit` 件返す（名前の短いものから）
+    pub fn query(&self, query: &FileQuery, limit: usize) -> Vec<FileEntry> {
+        if limit == 0 {
+            return Vec::new();
+        }
+        self.ranked("", limit, &[], |record| query.matches(&record.entry))
+    }
+
+    /// `scope` の配下でクエリに一致する項目を最大 `limit` 件返す（`scope` 自身は含まない）
+    ///
+    /// パスの順序で配下だけをたどるため、索引全体は走査しない。
+    pub fn query_under(&self, scope: &Path, query: &FileQuery, limit: usize) -> Vec<FileEntry> {
+        if limit == 0 {
+            return Vec::new();
+        }
+        let records = self
+            .by_path
+            .range(scope.to_path_buf()..)
+            .take_while(|(path, _)| path.starts_with(scope))
+            .filter(|(path, _)| path.as_path() != scope)
+            .filter_map(|(_, id)| self.records[*id].as_ref())
+            .filter(|record| query.matches(&record.entry));
+        Self::rank(records, "", limit)
+    }
+
+    /// 条件に合う項目を並べて上位 `limit` 件を返す
+    ///
+    /// `required` は一致する名前が必ず含む3文字組で、空でなければ最も件数の少ない
+    /// 3文字組を持つ項目だけを照合する。
+    fn ranked(
+        &self,
+        text: &str,
+        limit: usize,
+        required: &[Trigram],
+        matches: impl Fn(&IndexRecord) -> bool,
+    ) -> Vec<FileEntry> {
+        let Some(candidates) = self.candidates(required) else {
+            let records = self
+                .records
+                .iter()
+                .flatten()
+                .filter(|record| matches(record));
+            return Self::rank(records, text, limit);
+        };
+
+        let records = candidates
+            .iter()
+            .filter_map(|id| self.records[*id].as_ref())
+            .filter(|record| matches(record));
+        Self::rank(records, text, limit)
+    }
+
+    /// `required` の3文字組をすべて含む名前の候補（最も件数の少ない3文字組の項目）
+    ///
+    /// `required` が空で絞り込めない場合は None を返す。
+    fn candidates(&self, required: &[Trigram]) -> Option<&BTreeSet<usize>> {
+        static NONE: BTreeSet<usize> = BTreeSet::new();
+
+        let mut candidates: Option<&BTreeSet<usize>> = None;
+        for trigram in required {
+            // 索引にない3文字組を含む条件にはどの項目も一致しない
+            let Some(ids) = self.by_trigram.get(trigram) else {
+                return Some(&NONE);
+            };
+            if candidates.is_none_or(|smallest| ids.len() < smallest.len()) {
+                candidates = Some(ids);
+            }
+        }
+        candidates
+    }
+
+    /// 項目を並べて上位 `limit` 件を返す
+    fn rank<'a>(
+        records: impl Iterator<Item = &'a IndexRecord>,
+        text: &str,
+        limit: usize,
+    ) -> Vec<FileEntry> {
+        let mut hits: Vec<_> = records
+            .map(|record| {
+                let name = &*record.name_lower;
+                let rank = (name != text, !name.starts_with(text), name.len());
+                (rank, record)
+            })
+            .collect();
+
+        let order = |a: &(Rank, &IndexRecord), b: &(Rank, &IndexRecord)| {
+            a.0.cmp(&b.0)
+                .then_with(|| a.1.entry.path.cmp(&b.1.entry.path))
+        };
+        if hits.len() > limit {
+            hits.select_nth_unstable_by(limit - 1, order);
+            hits.truncate(limit);
+        }
+        hits.sort_by(order);
+        hits.into_iter()
+            .map(|(_, record)| record.entry.clone())
+            .collect()
+    }
+
+    /// 索引のすべての項目
+    pub(crate) fn entries(&self) -> impl Iterator<Item = &FileEntry> {
+        self.records.iter().flatten().map(|record| &record.entry)
+    }
+
+    /// 索引のすべての項目と小文字にした名前
+    pub(crate) fn named_entries(&self) -> impl Iterator<Item = (&FileEntry, &str)> {
+        self.records
+            .iter()
+            .flatten()
+            .map(|record| (&record.entry, &*record.name_lower))
+    }
+
+    /// パスとその配下のパス（パスの順序では配下が連続して並ぶ）
+    fn paths_under(&self, path: &Path) -> Vec<PathBuf> {
+        self.by_path
+            .range(path.to_path_buf()..)
+            .map(|(p, _)| p)
+            .take_while(|p| p.starts_with(path))
+            .cloned()
+            .collect()
+    }
+
+    fn remove_one(&mut self, path: &Path) {
+        if let Some(id) = self.by_path.remove(path)
+            && let Some(record) = self.records[id].take()
+        {
+            self.unlink_name(&record.name_lower, id);
+            self.free.push(id);
+            self.revision += 1;
+        }
+    }
+
+    /// 名前を前方一致と3文字組の索引に加える
+    fn link_name(&mut self, name_lower: &str, id: usize) {
+        self.by_name.insert((name_lower.into(), id));
+        for trigram in trigrams(&name_lower.chars().collect::<Vec<_>>()) {
+            self.by_trigram.entry(trigram).or_default().insert(id);
+        }
+    }
+
+    /// 名前を前方一致と3文字組の索引から除く
+    fn unlink_name(&mut self, name_lower: &str, id: usize) {
+        self.by_name.remove(&(name_lower.into(), id));
+        for trigram in trigrams(&name_lower.chars().collect::<Vec<_>>()) {
+            if let Some(ids) = self.by_trigram.get_mut(&trigram) {
+                ids.remove(&id);
+                if ids.is_empty() {
+                    self.by_trigram.remove(&trigram);
+                }
+            }
+        }
+    }
+
+    /// 新しい世代を始める（以降に追加・更新した項目はこの世代になる）
+    fn next_generation(&mut self) -> u64 {
+        self.generation += 1;
+        self.generation
+    }
+
+    /// ルート配下で指定した世代より前に確認した項目（巡回で見つからなかったもの）を削除
+    fn sweep(&mut self, root: &Path, generation: u64) {
+        let stale: Vec<_> = self
+            .paths_under(root)
+            .into_iter()
+            .filter(|path| {
+                self.by_path
+                    .get(path)
+                    .and_then(|id| self.records[*id].as_ref())
+                    .is_some_and(|record| record.generation < generation)
+            })
+            .collect();
+        for path in stale {
+            self.remove_one(&path);
+        }
+    }
+}
+
+/// 文字列の連続した3文字組（重複は除く）
+fn trigrams(chars: &[char]) -> Vec<Trigram> {
+    let mut trigrams: Vec<Trigram> = chars
+        .windows(3)
+        .map(|window| [window[0], window[1], window[2]])
+        .collect();
+    trigrams.sort_unstable();
+    trigrams.dedup();
+    trigrams
+}
+
+/// 一致する名前が必ず含む3文字組（前方一致は名前の順序で探すため使わない）
+fn required_trigrams(query: &SearchQuery) -> Vec<Trigram> {
+    match query {
+        SearchQuery::Prefix(_) => Vec::new(),
+        SearchQuery::Substring(text) => trigrams(&text.to_lowercase().chars().collect::<Vec<_>>()),
+        SearchQuery::Glob(pattern) => {
+            let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+            glob_name_literals(&pattern)
+                .iter()
+                .flat_map(|literal| trigrams(literal))
+                .collect()
+        }
+    }
+}
+
+/// グロブに一致する名前が必ずそのまま含む文字の並び
+///
+/// 名前は最後の `/` より後なので、`/` や `**`（`/` を越えうる）より前の並びは捨てる。
+fn glob_name_literals(pattern: &[char]) -> Vec<Vec<char>> {
+    let mut literals = vec![Vec::new()];
+    let mut i = 0;
+    while i < pattern.len() {
+        match pattern[i] {
+            '/' => literals = vec![Vec::new()],
+            '*' if pattern.get(i + 1) == Some(&'*') => {
+                literals = vec![Vec::new()];
+                i += 1;
+            }
+            '*' | '?' => literals.push(Vec::new()),
+            '[' => {
+                let Some(end) = pattern[i + 1..]
+                    .iter()
+                    .skip(1)
+                    .position(|&c| c == ']')
+                    .map(|end| i + 2 + end)
+                else {
+                    literals.push(Vec::new());
+                    i += 1;
+                    continue;
+                };
+                if pattern[i + 1..end].contains(&'/') {
+                    literals = vec![Vec::new()];
+                } else {
+                    literals.push(Vec::new());
+                }
+                i = end;
+            }
+            c => literals.last_mut().expect("never empty").push(c),
+        }
+        i += 1;
+    }
+    literals
+}
+
+/// グロブの照合（`*` は `/` を越えない任意の文字列、`**` は任意の文字列、`?` は1文字、
+/// `[abc]` `[a-z]` `[!a]` は文字の集合）
+pub(crate) fn glob_match(pattern: &[char], text: &str) -> bool {
+    glob_match_with(pattern, text, |c| c)
+}
+
+/// 文字列を1文字ずつ小文字にしながらグロブと照合（パターンは小文字にしておく）
+fn glob_match_folded(pattern: &[char], text: &str) -> bool {
+    glob_match_with(pattern, text, |c| c.to_lowercase().next().unwrap_or(c))
+}
+
+/// グロブの照合の本体
+///
+/// 一致しなければ直前の `*` を1文字伸ばしてやり直し、`*` が `/` に当たったら
+/// その前の `**` を伸ばす。再帰しないため、照合の手間はパターンと文字列の長さの積に収まる。
+fn glob_match_with(pattern: &[char], text: &str, fold: impl Fn(char) -> char) -> bool {
+    // やり直す位置（パターンの位置, 文字列の位置, `**` か）
+    let mut star: Option<(usize, usize, bool)> = None;
+    let mut any_depth_star: Option<(usize, usize)> = None;
+    let (mut p, mut t) = (0, 0);
+
+    while let Some(c) = text[t..].chars().next() {
+        if pattern.get(p) == Some(&'*') {
+            let any_depth = pattern.get(p + 1) == Some(&'*');
+            p += if any_depth { 2 } else { 1 };
+            star = Some((p, t, any_depth));
+            if any_depth {
+                any_depth_star = Some((p, t));
+            }
+            continue;
+        }
+        if let Some(next) = glob_step(pattern, p, fold(c)) {
+            p = next;
+            t += c.len_utf8();
+            continue;
+        }
+
+        let retry = match star {
+            Some((sp, st, any_depth)) if any_depth || !text[st..].starts_with('/') => {
+                Some((sp, st, any_depth))
+            }
+            _ => any_depth_star.map(|(sp, st)| (sp, st, true)),
+        };
+        let Some((sp, st, any_depth)) = retry else {
+            return false;
+        };
+        let width = text[st..].chars().next().map_or(1, char::len_utf8);
+        star = Some((sp, st + width, any_depth));
+        if any_depth {
+            any_depth_star = Some((sp, st + width));
+        }
+        p = sp;
+        t = st + width;
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// パターンの `p` の1文字分が `c` に一致すれば、パターンの次の位置を返す
+fn glob_step(pattern: &[char], p: usize, c: char) -> Option<usize> {
+    match *pattern.get(p)? {
+        '?' => (c != '/').then_some(p + 1),
+        '[' => {
+            let Some(end) = pattern[p + 1..]
+                .iter()
+                .skip(1)
+                .position(|&c| c == ']')
+                .map(|end| p + 2 + end)
+            else {
+                // 閉じていない括弧は文字として扱う
+                return (c == '[').then_some(p + 1);
+            };
+            class_matches(&pattern[p + 1..end], c).then_some(end + 1)
+        }
+        literal => (c == literal).then_some(p + 1),
+    }
+}
+
+/// 文字の集合 `[...]` の中身と照合
+fn class_matches(class: &[char], c: char) -> bool {
+    let (negated, class) = match class.split_first() {
+        Some(('!' | '^', class)) => (true, class),
+        _ => (false, class),
+    };
+
+    let mut matched = false;
+    let mut i = 0;
+    while i < class.len() {
+        if i + 2 < class.len() && class[i + 1] == '-' {
+            matched |= (class[i]..=class[i + 2]).contains(&c);
+            i += 3;
+        } else {
+            matched |= class[i] == c;
+            i += 1;
+        }
+    }
+    matched != negated
+}
+
+/// 索引の作成状況
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexerStatus {
+    /// 巡回中か
+    pub crawling: bool,
+    /// 索引の件数
+    pub indexed: usize,
+    /// 索引の更新番号
+    pub revision: u64,
+}
+
+/// 索引スレッドへの指示
+enum IndexerMessage {
+    Notify(notify::Event),
+    SetRoots(Vec<PathBuf>),
+    Shutdown,
+}
+
+/// 索引を作成・更新するバックグラウンドサービス
+///
+/// 破棄するとスレッドと変更の監視を終了する。
+pub struct SearchIndexer {
+    index: Arc<RwLock<SearchIndex>>,
+    /// 索引の対象のルート（スレッドが受け取る前から答えられるよう別に持つ）
+    roots: RwLock<Vec<PathBuf>>,
+    crawling: Arc<AtomicBool>,
+    sender: mpsc::Sender<IndexerMessage>,
+}
+
+impl SearchIndexer {
+    /// 索引を作成・更新するスレッドを開始する
+    ///
+    /// 保存していた索引はスレッドで `load` から読み込み（起動を遅らせないため）、
+    /// その後 `roots` を巡回し直す。
+    pub fn start(
+        roots: Vec<PathBuf>,
+        load: impl FnOnce() -> SearchIndex + Send + 'static,
+    ) -> Result<Self, AppError> {
+        let (sender, receiver) = mpsc::channel();
+        let notify_sender = sender.clone();
+        let watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+            if let Ok(event) = result {
+                let _ = notify_sender.send(IndexerMessage::Notify(event));
+            }
+        })
+        // 監視できなくても巡回した索引は使えるため続行する
+        .inspect_err(|e| eprintln!("Warning: Search index will not follow changes: {}", e))
+        .ok();
+
+        let index = Arc::new(RwLock::new(SearchIndex::new()));
+        let crawling = Arc::new(AtomicBool::new(false));
+        let worker = IndexWorker {
+            index: Arc::clone(&index),
+            crawling: Arc::clone(&crawling),
+            watcher,
+            watched: Vec::new(),
+            coalescer: ChangeCoalescer::default(),
+            pending_roots: VecDeque::new(),
+            crawl: None,
+        };
+        std::thread::Builder::new()
+            .name("rust-explorer-indexer".to_string())
+            .spawn(move || worker.run(load, receiver))
+            .map_err(AppError::FileSystem)?;
+
+        let indexer = Self {
+            index,
+            roots: RwLock::new(Vec::new()),
+            crawling,
+            sender,
+        };
+        indexer.set_roots(roots)?;
+        Ok(indexer)
+    }
+
+    /// 索引の対象のルートを変更（対象外になったルートの項目は削除する）
+    pub fn set_roots(&self, roots: Vec<PathBuf>) -> Result<(), AppError> {
+        // スレッドが受け取る前に状況を問い合わされても巡回中と答える
+        self.crawling.store(!roots.is_empty(), Ordering::SeqCst);
+        if let Ok(mut current) = self.roots.write() {
+            current.clone_from(&roots);
+        }
+        self.sender
+            .send(IndexerMessage::SetRoots(roots))
+            .map_err(|_| AppError::Internal("Search indexer has stopped".to_string()))
+    }
+
+    /// 索引を検索
+    pub fn search(&self, query: &SearchQuery, limit: usize) -> Result<Vec<FileEntry>, AppError> {
+        Ok(self.read_index()?.search(query, limit))
+    }
+
+    /// 索引をクエリで検索
+    pub fn query(&self, query: &FileQuery, limit: usize) -> Result<Vec<FileEntry>, AppError> {
+        Ok(self.read_index()?.query(query, limit))
+    }
+
+    /// 索引をクエリで検索（`scope` の配下だけ）
+    pub fn query_under(
+        &self,
+        scope: &Path,
+        query: &FileQuery,
+        limit: usize,
+    ) -> Result<Vec<FileEntry>, AppError> {
+        Ok(self.read_index()?.query_under(scope, query, limit))
+    }
+
+    /// パスが索引の対象のルートのいずれかの配下か
+    pub fn covers(&self, path: &Path) -> bool {
+        self.roots
+            .read()
+            .is_ok_and(|roots| roots.iter().any(|root| path.starts_with(root)))
+    }
+
+    /// 索引を読み取る（読み取り中は索引の更新を待たせるため短い処理に使う）
+    pub fn with_index<R>(&self, f: impl FnOnce(&SearchIndex) -> R) -> Result<R, AppError> {
+        let index = self.read_index()?;
+        Ok(f(&index))
+    }
+
+    /// 作成状況
+    pub fn status(&self) -> IndexerStatus {
+        let (indexed, revision) = self
+            .index
+            .read()
+            .map(|index| (index.len(), index.revision()))
+            .unwrap_or_default();
+        IndexerStatus {
+            crawling: self.crawling.load(Ordering::SeqCst),
+            indexed,
+            revision,
+        }
+    }
+
+    /// 保存用の内容を作成
+    pub fn snapshot(&self) -> Result<IndexSnapshot, AppError> {
+        Ok(self.read_index()?.snapshot())
+    }
+
+    fn read_index(&self) -> Result<std::sync::RwLockReadGuard<'_, SearchIndex>, AppError> {
+        self.index
+            .read()
+            .map_err(|e| AppError::Internal(format!("Failed to lock search index: {}", e)))
+    }
+}
+
+impl Drop for SearchIndexer {
+    fn drop(&mut self) {
+        // 監視の通知用の送信側はスレッドが持っているため明示的に止める
+        let _ = self.sender.send(IndexerMessage::Shutdown);
+    }
+}
+
+/// 巡回中のルート
+struct Crawl {
+    root: PathBuf,
+    generation: u64,
+    directories: VecDeque<PathBuf>,
+}
+
+/// 索引スレッドの状態
+struct IndexWorker {
+    index: Arc<RwLock<SearchIndex>>,
+    crawling: Arc<AtomicBool>,
+    watcher: Option<RecommendedWatcher>,
+    watched: Vec<PathBuf>,
+    coalescer: ChangeCoalescer,
+    pending_roots: VecDeque<PathBuf>,
+    crawl: Option<Crawl>,
+}
+
+impl IndexWorker {
+    fn run(mut self, load: impl FnOnce() -> SearchIndex, receiver: mpsc::Receiver<IndexerMessage>) {
+        lower_thread_priority();
+        let loaded = load();
+        if let Ok(mut index) = self.index.write() {
+            *index = loaded;
+        }
+        let mut last_change: Option<Instant> = None;
+
+        loop {
+            // 巡回中は待たずに通知を取り込み、合間に少しずつ巡回する
+            let timeout = if self.crawl.is_some() || !self.pending_roots.is_empty() {
+                Duration::ZERO
+            } else if let Some(last) = last_change {
+                CHANGE_DEBOUNCE.saturating_sub(last.elapsed())
+            } else {
+                Duration::MAX
+            };
+
+            match receiver.recv_timeout(timeout) {
+                Ok(IndexerMessage::Notify(event)) => {
+                    for change in RawChange::from_notify(event) {
+                        self.coalescer.push(change);
+                    }
+                    last_change.get_or_insert_with(Instant::now);
+                }
+                Ok(IndexerMessage::SetRoots(roots)) => self.set_roots(roots),
+                Ok(IndexerMessage::Shutdown) | Err(RecvTimeoutError::Disconnected) => break,
+                Err(RecvTimeoutError::Timeout) => {}
+            }
+
+            if last_change.is_some_and(|last| last.elapsed() >= CHANGE_DEBOUNCE) {
+                last_change = None;
+                self.apply_changes();
+            }
+            self.crawl_batch();
+        }
+    }
+
+    /// ルートを入れ替え、新しいルートの監視と巡回を始める
+    fn set_roots(&mut self, roots: Vec<PathBuf>) {
+        if let Some(watcher) = &mut self.watcher {
+            for root in self.watched.drain(..) {
+                let _ = watcher.unwatch(&root);
+            }
+            for root in &roots {
+                match watcher.watch(root, RecursiveMode::Recursive) {
+                    Ok(()) => self.watched.push(root.clone()),
+                    Err(e) => eprintln!("Warning: Failed to watch {}: {}", root.display(), e),
+                }
+            }
+        }
+
+        if let Ok(mut index) = self.index.write() {
+            let removed: Vec<_> = index
+                .roots
+                .iter()
+                .filter(|root| !roots.contains(root))
+                .cloned()
+                .collect();
+            for root in removed {
+                index.remove_tree(&root);
+            }
+            index.roots = roots.clone();
+        }
+
+        self.crawl = None;
+        self.pending_roots = roots.into();
+        self.crawling
+            .store(!self.pending_roots.is_empty(), Ordering::SeqCst);
+    }
+
+    /// まとめた変更通知を索引へ反映
+    fn apply_changes(&mut self) {
+        let deltas = self
+            .coalescer
+            .finish(|path| std::fs::symlink_metadata(path).is_ok());
+        let Ok(mut index) = self.index.write() else {
+            return;
+        };
+        let mut new_directories = Vec::new();
+        let mut add = |index: &mut SearchIndex, path: &Path| {
+            if let Some(entry) = FileEntry::from_path(path) {
+                if entry.file_type == FileType::Directory {
+                    new_directories.push(entry.path.clone());
+                }
+                index.upsert(entry);
+            }
+        };
+
+        for change in deltas.into_iter().flat_map(|delta| delta.changes) {
+            match change {
+                DirectoryChange::Added(path) | DirectoryChange::Modified(path) => {
+                    add(&mut index, &path)
+                }
+                DirectoryChange::Removed(path) => {
+                    index.remove_tree(&path);
+                }
+                DirectoryChange::Renamed { from, to } => {
+                    index.remove_tree(&from);
+                    add(&mut index, &to);
+                }
+            }
+        }
+        drop(index);
+
+        // 移動してきたディレクトリの中身は通知されないため巡回する
+        for directory in new_directories {
+            match &mut self.crawl {
+                Some(crawl) => crawl.directories.push_back(directory),
+                None => {
+                    self.crawl = Some(Crawl {
+                        root: directory.clone(),
+                        generation: 0,
+                        directories: VecDeque::from([directory]),
+                    })
+                }
+            }
+        }
+    }
+
+    /// 巡回を少し進める
+    fn crawl_batch(&mut self) {
+        if self.crawl.is_none() {
+            let Some(root) = self.pending_roots.pop_front() else {
+                self.crawling.store(false, Ordering::SeqCst);
+                return;
+            };
+            let generation = match self.index.write() {
+                Ok(mut index) => {
+                    let generation = index.next_generation();
+                    if let Some(entry) = FileEntry::from_path(&root) {
+                        index.upsert(entry);
+                    }
+                    generation
+                }
+                Err(_) => return,
+            };
+            self.crawl = Some(Crawl {
+                directories: VecDeque::from([root.clone()]),
+                root,
+                generation,
+            });
+        }
+        let Some(crawl) = &mut self.crawl else {
+            return;
+        };
+
+        for _ in 0..CRAWL_BATCH_DIRS {
+            let Some(directory) = crawl.directories.pop_front() else {
+                break;
+            };
+            let entries = read_directory(&directory);
+            let Ok(mut index) = self.index.write() else {
+                return;
+            };
+            for entry in entries {
+                // シンボリックリンク先は巡回しない（ループを避ける）
+                if entry.file_type == FileType::Directory {
+                    crawl.directories.push_back(entry.path.clone());
+                }
+                index.upsert(entry);
+            }
+        }
+
+        if crawl.directories.is_empty() {
+            // 変更通知で始めた巡回（世代 0）では古い項目を消さない
+            if crawl.generation > 0
+                && let Ok(mut index) = self.index.write()
+            {
+                index.sweep(&crawl.root, crawl.generation);
+            }
+            self.crawl = None;
+        }
+        std::thread::sleep(CRAWL_PAUSE);
+    }
+}
+
+/// ディレクトリの中身を読む（読めない項目は飛ばす）
+fn read_directory(directory: &Path) -> Vec<FileEntry> {
+    let Ok(entries) = std::fs::read_dir(directory) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| FileEntry::from_path(&entry.path()))
+        .collect()
+}
+
+/// 索引スレッドの優先度を下げる
+#[cfg(target_os = "linux")]
+fn lower_thread_priority() {
+    // SAFETY: Linux では呼び出したスレッドの nice 値だけを変更する
+    unsafe {
+        libc::setpriority(libc::PRIO_PROCESS, 0, 10);
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn lower_thread_priority() {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, text: &str) -> bool {
+        glob_match(&pattern.chars().collect::<Vec<_>>(), text)
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(matches("*.rs", "main.rs"));
+        assert!(!matches("*.rs", "main.rsx"));
+        assert!(matches("ma?n.*", "main.rs"));
+        assert!(matches("[a-c]at.txt", "bat.txt"));
+        assert!(!matches("[!a-c]at.txt", "bat.txt"));
+        assert!(matches("[abc", "[abc"));
+    }
+
+    #[test]
+    fn test_glob_path_separators() {
+        // `*` はディレクトリを越えず、`**` は越える
+        assert!(matches("/src/*.rs", "/src/lib.rs"));
+        assert!(!matches("/src/*.rs", "/src/a/lib.rs"));
+        assert!(matches("/src/**.rs", "/src/a/lib.rs"));
+        assert!(matches("**/target/*", "/home/u/project/target/debug"));
+    }
+}
@@ -0,0 +1,317 @@
+//! スマートフォルダ
+//!
+//! 名前を付けて保存した検索（クエリと検索範囲）と、その結果を索引の変化に
+//! 合わせて届けるストリーム。
+
+use crate::filesystem::FileEntry;
+use crate::listing::ListingCanceller;
+use crate::query::FileQuery;
+use crate::search::{SearchIndex, SearchIndexer};
+use crate::state::state_utils;
+use crossbeam_channel::{Receiver, SendTimeoutError, Sender};
+use rust_explorer_utils::AppError;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 索引の変化を確かめる間隔
+const REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+
+/// 索引の作成中に結果を作り直す間隔（巡回中は索引が絶えず変わるため間引く）
+const CRAWLING_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 保存した検索
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SmartFolder {
+    /// スマートフォルダID
+    pub id: String,
+    /// 表示名
+    pub name: String,
+    /// クエリ（`FileQuery` の書式）
+    pub query: String,
+    /// 検索範囲のディレクトリ
+    pub scope: PathBuf,
+}
+
+impl SmartFolder {
+    /// 名前・クエリ・検索範囲を確かめて作成
+    pub fn new(name: &str, query: &str, scope: PathBuf) -> Result<Self, AppError> {
+        let name = validate_name(name)?;
+        validate_definition(query, &scope)?;
+        Ok(Self {
+            id: state_utils::generate_smart_folder_id(),
+            name,
+            query: query.trim().to_string(),
+            scope,
+        })
+    }
+
+    /// クエリを解析
+    pub fn compile(&self) -> Result<FileQuery, AppError> {
+        FileQuery::parse(&self.query)
+    }
+}
+
+/// 表示名を確かめて前後の空白を除く
+fn validate_name(name: &str) -> Result<String, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::InvalidInput(
+            "Smart folder name is empty".to_string(),
+        ));
+    }
+    Ok(name.to_string())
+}
+
+/// クエリと検索範囲を確かめる
+fn validate_definition(query: &str, scope: &Path) -> Result<(), AppError> {
+    if query.trim().is_empty() {
+        return Err(AppError::InvalidInput(
+            "Smart folder query is empty".to_string(),
+        ));
+    }
+    FileQuery::parse(query)?;
+    if !scope.is_absolute() {
+        return Err(AppError::InvalidPath(scope.to_path_buf()));
+    }
+    Ok(())
+}
+
+/// スマートフォルダの一覧（表示順）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SmartFolders {
+    #[serde(default)]
+    folders: Vec<SmartFolder>,
+}
+
+impl SmartFolders {
+    /// 空の一覧を作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 表示順のスマートフォルダ
+    pub fn folders(&self) -> &[SmartFolder] {
+        &self.folders
+    }
+
+    /// IDで検索
+    pub fn get(&self, id: &str) -> Option<&SmartFolder> {
+        self.folders.iter().find(|folder| folder.id == id)
+    }
+
+    /// 末尾に追加（同じ名前があれば追加しない）
+    pub fn add(
+        &mut self,
+        name: &str,
+        query: &str,
+        scope: PathBuf,
+    ) -> Result<&SmartFolder, AppError> {
+        let folder = SmartFolder::new(name, query, scope)?;
+        self.ensure_unique_name(&folder.name, None)?;
+        self.folders.push(folder);
+        Ok(&self.folders[self.folders.len() - 1])
+    }
+
+    /// クエリと検索範囲を変更
+    pub fn update(&mut self, id: &str, query: &str, scope: PathBuf) -> Result<(), AppError> {
+        validate_definition(query, &scope)?;
+        let folder = self.folder_mut(id)?;
+        folder.query = query.trim().to_string();
+        folder.scope = scope;
+        Ok(())
+    }
+
+    /// 名前を変更（他と同じ名前にはできない）
+    pub fn rename(&mut self, id: &str, name: &str) -> Result<(), AppError> {
+        let name = validate_name(name)?;
+        self.ensure_unique_name(&name, Some(id))?;
+        self.folder_mut(id)?.name = name;
+        Ok(())
+    }
+
+    /// 削除
+    pub fn remove(&mut self, id: &str) -> Option<SmartFolder> {
+        let position = self.folders.iter().position(|folder| folder.id == id)?;
+        Some(self.folders.remove(position))
+    }
+
+    /// 表示順の `index` 番目へ移動（範囲外なら末尾）
+    pub fn move_to(&mut self, id: &str, index: usize) -> Result<(), AppError> {
+        let position = self
+            .folders
+            .iter()
+            .position(|folder| folder.id == id)
+            .ok_or_else(|| unknown_folder(id))?;
+        let folder = self.folders.remove(position);
+        let index = index.min(self.folders.len());
+        self.folders.insert(index, folder);
+        Ok(())
+    }
+
+    fn folder_mut(&mut self, id: &str) -> Result<&mut SmartFolder, AppError> {
+        self.folders
+            .iter_mut()
+            .find(|folder| folder.id == id)
+            .ok_or_else(|| unknown_folder(id))
+    }
+
+    /// 名前の重複を確かめる（大文字小文字は区別しない、`except` のIDは除く）
+    fn ensure_unique_name(&self, name: &str, except: Option<&str>) -> Result<(), AppError> {
+        let lower = name.to_lowercase();
+        let taken = self.folders.iter().any(|folder| {
+            Some(folder.id.as_str()) != except && folder.name.to_lowercase() == lower
+        });
+        if taken {
+            return Err(AppError::InvalidInput(format!(
+                "A smart folder named '{}' already exists",
+                name
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn unknown_folder(id: &str) -> AppError {
+    AppError::InvalidInput(format!("Unknown smart folder: {}", id))
+}
+
+/// スマートフォルダの結果
+#[derive(Debug, Clone)]
+pub struct SmartFolderUpdate {
+    /// クエリに一致した項目（検索範囲のディレクトリ自身は含まない）
+    pub entries: Vec<FileEntry>,
+    /// 索引を作成中か（作成中は結果が増えていく）
+    pub indexing: bool,
+}
+
+/// 検索範囲の索引の変化に合わせて結果を届けるストリーム
+///
+/// 破棄すると配信を終了する（検索範囲だけの索引を作っていればその作成と監視も終了する）。
+pub struct SmartFolderResults {
+    scope: PathBuf,
+    receiver: Receiver<SmartFolderUpdate>,
+    canceller: ListingCanceller,
+}
+
+impl SmartFolderResults {
+    /// 結果の配信を開始（最大 `limit` 件）
+    ///
+    /// 共有の索引 `shared` が検索範囲を含んでいればそれを検索し、含まなければ
+    /// 検索範囲だけの索引を作る。
+    pub fn start(
+        folder: &SmartFolder,
+        shared: Option<&Arc<SearchIndexer>>,
+        limit: usize,
+    ) -> Result<Self, AppError> {
+        if !folder.scope.is_dir() {
+            return Err(AppError::InvalidPath(folder.scope.clone()));
+        }
+        let query = folder.compile()?;
+        let indexer = match shared {
+            Some(shared) if shared.covers(&folder.scope) => Arc::clone(shared),
+            _ => Arc::new(SearchIndexer::start(
+                vec![folder.scope.clone()],
+                SearchIndex::new,
+            )?),
+        };
+
+        // 最新の結果だけに意味があるため、表示側が追いつかなければ待たせる
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        let canceller = ListingCanceller::default();
+        let worker = ResultsWorker {
+            indexer,
+            query,
+            scope: folder.scope.clone(),
+            limit,
+            sender,
+            canceller: canceller.clone(),
+        };
+        std::thread::Builder::new()
+            .name("rust-explorer-smart-folder".to_string())
+            .spawn(move || worker.run())
+            .map_err(AppError::FileSystem)?;
+
+        Ok(Self {
+            scope: folder.scope.clone(),
+            receiver,
+            canceller,
+        })
+    }
+
+    /// 検索範囲のディレクトリ
+    pub fn scope(&self) -> &Path {
+        &self.scope
+    }
+
+    /// 結果の受信側（UIスレッドへ渡す場合などに使う）
+    pub fn receiver(&self) -> &Receiver<SmartFolderUpdate> {
+        &self.receiver
+    }
+
+    /// 中断用のハンドル
+    pub fn canceller(&self) -> ListingCanceller {
+        self.canceller.clone()
+    }
+
+    /// 配信を終了
+    pub fn cancel(&self) {
+        self.canceller.cancel();
+    }
+
+    /// 次の結果を待つ（終了していれば None）
+    pub fn recv(&self) -> Option<SmartFolderUpdate> {
+        self.receiver.recv().ok()
+    }
+}
+
+impl Drop for SmartFolderResults {
+    fn drop(&mut self) {
+        self.canceller.cancel();
+    }
+}
+
+/// 索引の更新番号を見て結果を作り直すスレッド
+struct ResultsWorker {
+    indexer: Arc<SearchIndexer>,
+    query: FileQuery,
+    scope: PathBuf,
+    limit: usize,
+    sender: Sender<SmartFolderUpdate>,
+    canceller: ListingCanceller,
+}
+
+impl ResultsWorker {
+    fn run(self) {
+        let mut delivered = None;
+        while !self.canceller.is_cancelled() {
+            let status = self.indexer.status();
+            let key = (status.revision, status.crawling);
+            if delivered != Some(key) {
+                delivered = Some(key);
+                let Ok(entries) = self
+                    .indexer
+                    .query_under(&self.scope, &self.query, self.limit)
+                else {
+                    return;
+                };
+                let update = SmartFolderUpdate {
+                    entries,
+                    indexing: status.crawling,
+                };
+                match self.sender.send_timeout(update, REFRESH_INTERVAL) {
+                    Ok(()) => {}
+                    // 表示側が受け取らなかった結果は捨て、次に最新の結果を作り直す
+                    Err(SendTimeoutError::Timeout(_)) => delivered = None,
+                    Err(SendTimeoutError::Disconnected(_)) => return,
+                }
+            }
+            std::thread::sleep(if status.crawling {
+                CRAWLING_REFRESH_INTERVAL
+            } else {
+                REFRESH_INTERVAL
+            });
+        }
+    }
+}
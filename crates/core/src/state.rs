@@ -2,10 +2,13 @@
 //!
 //! アプリケーション全体の状態を管理するシステム
 
+use crate::file_sorting::{FilterCriteria, SortConfig};
+use crate::smart_folder::SmartFolder;
+use crate::tags::Tag;
 use rust_explorer_utils::AppError;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
 
 /// ウィンドウ状態
@@ -39,7 +42,7 @@ impl Default for WindowState {
 }
 
 /// タブ状態
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TabState {
     /// タブID
     pub id: String,
@@ -51,6 +54,33 @@ pub struct TabState {
     pub active: bool,
     /// タブの作成時刻
     pub created_at: chrono::DateTime<chrono::Utc>,
+    /// ナビゲーション履歴（戻る用）
+    #[serde(default)]
+    pub history_back: Vec<PathBuf>,
+    /// ナビゲーション履歴（進む用）
+    #[serde(default)]
+    pub history_forward: Vec<PathBuf>,
+    /// ソート設定
+    #[serde(default)]
+    pub sort: SortConfig,
+    /// フィルタ条件
+    #[serde(default)]
+    pub filter: FilterCriteria,
+    /// 選択中の項目
+    #[serde(default)]
+    pub selected_paths: Vec<PathBuf>,
+    /// ファイル一覧の縦方向のスクロール位置
+    #[serde(default)]
+    pub scroll_offset: f64,
+    /// 所属するペインのID（ペインを使わない場合は None）
+    #[serde(default)]
+    pub pane_id: Option<String>,
+    /// スマートフォルダを開いている場合はその定義（`current_path` は検索範囲）
+    #[serde(default)]
+    pub smart_folder: Option<SmartFolder>,
+    /// タグの付いた項目を一覧している場合はそのタグ名
+    #[serde(default)]
+    pub tag: Option<String>,
 }
 
 impl TabState {
@@ -61,8 +91,34 @@ impl TabState {
             current_path,
             active: false,
             created_at: chrono::Utc::now(),
+            history_back: Vec::new(),
+            history_forward: Vec::new(),
+            sort: SortConfig::default(),
+            filter: FilterCriteria::default(),
+            selected_paths: Vec::new(),
+            scroll_offset: 0.0,
+            pane_id: None,
+            smart_folder: None,
+            tag: None,
+        }
+    }
+
+    /// パス・履歴・ソート・選択などを引き継いだ複製を作成
+    pub fn duplicate(&self, id: String) -> Self {
+        Self {
+            id,
+            active: false,
+            created_at: chrono::Utc::now(),
+            ..self.clone()
         }
     }
+
+    /// パスからタブ名を決める（ルートなど名前がない場合はパス全体）
+    pub fn name_for_path(path: &Path) -> String {
+        path.file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string())
+    }
 }
 
 /// ペイン状態
@@ -78,10 +134,72 @@ pub struct PaneState {
     pub size: PaneSize,
     /// 表示状態
     pub visible: bool,
+    /// 子ペインのID（分割ペインのみ、並び順どおり）
+    #[serde(default)]
+    pub children: Vec<String>,
+    /// ペイン内のアクティブタブID（ファイルリストペインのみ）
+    #[serde(default)]
+    pub active_tab_id: Option<String>,
+}
+
+impl PaneState {
+    /// 分割ペインか
+    pub fn is_split(&self) -> bool {
+        matches!(self.pane_type, PaneType::Split(_))
+    }
+
+    /// 同じ分割内での大きさの比率
+    pub fn flex(&self) -> f64 {
+        self.size.flex.unwrap_or(1.0)
+    }
+}
+
+/// ペインの分割方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SplitDirection {
+    /// 左右に並べる
+    Horizontal,
+    /// 上下に並べる
+    Vertical,
+}
+
+/// ペインの分割ツリー（`AppState::panes` から組み立てる）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PaneTree {
+    /// タブを表示するペイン
+    Leaf(String),
+    /// 子ペインを並べる分割
+    Split {
+        id: String,
+        direction: SplitDirection,
+        children: Vec<PaneTree>,
+    },
+}
+
+impl PaneTree {
+    /// ペインID
+    pub fn id(&self) -> &str {
+        match self {
+            PaneTree::Leaf(id) | PaneTree::Split { id, .. } => id,
+        }
+    }
+
+    /// ツリー順のファイルリストペインID
+    pub fn leaf_ids(&self) -> Vec<String> {
+        match self {
+            PaneTree::Leaf(id) => vec![id.clone()],
+            PaneTree::Split { children, .. } => {
+                children.iter().flat_map(PaneTree::leaf_ids).collect()
+            }
+        }
+    }
 }
 
+/// ペインの大きさの比率の下限（ドラッグでペインが消えないようにする）
+pub const MIN_PANE_FLEX: f64 = 0.1;
+
 /// ペインタイプ
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PaneType {
     /// ファイルリスト
     FileList,
@@ -91,10 +209,12 @@ pub enum PaneType {
     Properties,
     /// ログ
     Log,
+    /// 子ペインを並べる分割
+    Split(SplitDirection),
 }
 
 /// ペイン位置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum PanePosition {
     Left,
     Right,
@@ -104,7 +224,7 @@ pub enum PanePosition {
 }
 
 /// ペインサイズ
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PaneSize {
     pub width: Option<f64>,
     pub height: Option<f64>,
@@ -138,6 +258,24 @@ impl Default for UiState {
     }
 }
 
+/// 2つの状態のタブと開いているパスの違い
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StateDiff {
+    /// 比較元にないタブのパス
+    pub added_tabs: Vec<PathBuf>,
+    /// 比較元にだけあるタブのパス
+    pub removed_tabs: Vec<PathBuf>,
+    /// 開いているパスが変わったタブ（比較元のパス、このパス）
+    pub changed_paths: Vec<(PathBuf, PathBuf)>,
+}
+
+impl StateDiff {
+    /// 違いがないか
+    pub fn is_empty(&self) -> bool {
+        self.added_tabs.is_empty() && self.removed_tabs.is_empty() && self.changed_paths.is_empty()
+    }
+}
+
 /// アプリケーション状態
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppState {
@@ -153,8 +291,23 @@ pub struct AppState {
     pub active_tab_id: Option<String>,
     /// 最後に保存された時刻
     pub last_saved: chrono::DateTime<chrono::Utc>,
+    /// 閉じたタブ（最後に閉じたものが末尾、再び開く用）
+    #[serde(default)]
+    pub closed_tabs: Vec<TabState>,
+    /// 分割ツリーのルートペインID
+    #[serde(default)]
+    pub root_pane_id: Option<String>,
+    /// フォーカスのあるペインID
+    #[serde(default)]
+    pub focused_pane_id: Option<String>,
+    /// 開いているワークスペース名（名前を付けて保存していなければ None）
+    #[serde(default)]
+    pub workspace_name: Option<String>,
 }
 
+/// 再び開けるように保持する閉じたタブの最大数
+pub const MAX_CLOSED_TABS: usize = 20;
+
 impl Default for AppState {
     fn default() -> Self {
         Self {
@@ -164,6 +317,10 @@ impl Default for AppState {
             ui: UiState::default(),
             active_tab_id: None,
             last_saved: chrono::Utc::now(),
+            closed_tabs: Vec::new(),
+            root_pane_id: None,
+            focused_pane_id: None,
+            workspace_name: None,
         }
     }
 }
@@ -171,43 +328,474 @@ impl Default for AppState {
 impl AppState {
     /// 新しいタブを追加
     pub fn add_tab(&mut self, tab: TabState) {
-        // 他のタブを非アクティブにする
+        self.insert_tab(self.tabs.len(), tab);
+    }
+
+    /// 指定位置にタブを挿入してアクティブにする（位置は末尾までに丸める）
+    ///
+    /// ペインを使っている場合、所属ペインがないタブはフォーカスのあるペインに入る。
+    pub fn insert_tab(&mut self, index: usize, tab: TabState) {
+        let mut new_tab = tab;
+        if self.root_pane_id.is_some() && !self.is_leaf_pane(new_tab.pane_id.as_deref()) {
+            new_tab.pane_id = self.focused_pane_id.clone();
+        }
+
+        // 同じペインの他のタブを非アクティブにする
         for existing_tab in &mut self.tabs {
-            existing_tab.active = false;
+            if existing_tab.pane_id == new_tab.pane_id {
+                existing_tab.active = false;
+            }
         }
 
-        let tab_id = tab.id.clone();
-        let mut new_tab = tab;
+        let tab_id = new_tab.id.clone();
+        let pane_id = new_tab.pane_id.clone();
         new_tab.active = true;
 
-        self.tabs.push(new_tab);
-        self.active_tab_id = Some(tab_id);
+        self.tabs.insert(index.min(self.tabs.len()), new_tab);
+        self.active_tab_id = Some(tab_id.clone());
+        if let Some(pane) = pane_id.as_deref().and_then(|id| self.pane_mut(id)) {
+            pane.active_tab_id = Some(tab_id);
+            self.focused_pane_id = pane_id;
+        }
         self.last_saved = chrono::Utc::now();
     }
 
-    /// タブを削除
-    pub fn remove_tab(&mut self, tab_id: &str) -> Result<(), AppError> {
-        let tab_index = self
-            .tabs
+    /// タブを複製し、元のタブの右隣に挿入する
+    pub fn duplicate_tab(&mut self, tab_id: &str, new_id: String) -> Result<TabState, AppError> {
+        let index = self.tab_index(tab_id)?;
+        let duplicate = self.tabs[index].duplicate(new_id);
+        self.insert_tab(index + 1, duplicate.clone());
+        Ok(duplicate)
+    }
+
+    /// タブを指定位置へ移動（位置は末尾までに丸める）
+    pub fn move_tab(&mut self, tab_id: &str, index: usize) -> Result<(), AppError> {
+        let from = self.tab_index(tab_id)?;
+        let tab = self.tabs.remove(from);
+        self.tabs.insert(index.min(self.tabs.len()), tab);
+        self.last_saved = chrono::Utc::now();
+        Ok(())
+    }
+
+    /// 最後に閉じたタブを末尾に開き直す
+    pub fn reopen_closed_tab(&mut self) -> Option<TabState> {
+        let tab = self.closed_tabs.pop()?;
+        self.add_tab(tab.clone());
+        Some(tab)
+    }
+
+    /// タブを更新し、更新後のタブを返す
+    pub fn update_tab<F>(&mut self, tab_id: &str, updater: F) -> Result<TabState, AppError>
+    where
+        F: FnOnce(&mut TabState),
+    {
+        let index = self.tab_index(tab_id)?;
+        let tab = &mut self.tabs[index];
+        updater(tab);
+        let updated = tab.clone();
+        self.last_saved = chrono::Utc::now();
+        Ok(updated)
+    }
+
+    /// タブの位置を取得
+    fn tab_index(&self, tab_id: &str) -> Result<usize, AppError> {
+        self.tabs
             .iter()
             .position(|t| t.id == tab_id)
-            .ok_or_else(|| AppError::Internal(format!("Tab not found: {}", tab_id)))?;
-
-        let _removed_tab = self.tabs.remove(tab_index);
-
-        // アクティブタブが削除された場合、新しいアクティブタブを設定
-        if self.active_tab_id.as_deref() == Some(tab_id) {
-            if !self.tabs.is_empty() {
-                // 削除されたタブの前のタブをアクティブにする、なければ次のタブ
-                let new_active_index = if tab_index > 0 { tab_index - 1 } else { 0 };
-                if let Some(new_active_tab) = self.tabs.get_mut(new_active_index) {
-                    new_active_tab.active = true;
-                    self.active_tab_id = Some(new_active_tab.id.clone());
+            .ok_or_else(|| AppError::Internal(format!("Tab not found: {}", tab_id)))
+    }
+
+    /// 保存したセッションを開けるよう、なくなったパスを置き換える
+    ///
+    /// 表示中のディレクトリがなくなったタブは、残っている最も近い親ディレクトリ
+    /// （なければ `fallback`）を開く。履歴と選択からはなくなったパスを除く。
+    pub fn restore_missing_paths(&mut self, fallback: &Path) {
+        for tab in &mut self.tabs {
+            let path = existing_directory(&tab.current_path, fallback);
+            if path != tab.current_path {
+                tab.name = TabState::name_for_path(&path);
+                tab.current_path = path;
+                tab.selected_paths.clear();
+                tab.scroll_offset = 0.0;
+            }
+            tab.history_back.retain(|path| path.is_dir());
+            tab.history_forward.retain(|path| path.is_dir());
+            tab.selected_paths.retain(|path| path.exists());
+        }
+        self.ensure_pane_layout();
+    }
+
+    /// `base` と比べたタブと開いているパスの違い（タブはIDで対応付ける）
+    pub fn diff(&self, base: &AppState) -> StateDiff {
+        let mut diff = StateDiff::default();
+        for tab in &self.tabs {
+            match base.tabs.iter().find(|t| t.id == tab.id) {
+                Some(old) if old.current_path != tab.current_path => diff
+                    .changed_paths
+                    .push((old.current_path.clone(), tab.current_path.clone())),
+                Some(_) => {}
+                None => diff.added_tabs.push(tab.current_path.clone()),
+            }
+        }
+        diff.removed_tabs = base
+            .tabs
+            .iter()
+            .filter(|old| !self.tabs.iter().any(|t| t.id == old.id))
+            .map(|old| old.current_path.clone())
+            .collect();
+        diff
+    }
+
+    /// 閉じたタブとして保持（古いものから捨てる）
+    fn push_closed_tab(&mut self, mut tab: TabState) {
+        tab.active = false;
+        self.closed_tabs.push(tab);
+        if self.closed_tabs.len() > MAX_CLOSED_TABS {
+            self.closed_tabs.remove(0);
+        }
+    }
+
+    /// 同じペインから外れたアクティブタブの代わりをアクティブにする
+    ///
+    /// 外れた位置の前のタブを選び、なければ次のタブを選ぶ。
+    fn activate_replacement(&mut self, removed: &TabState, removed_index: usize) {
+        let same_pane = |t: &&TabState| t.pane_id == removed.pane_id;
+        let replacement = self.tabs[..removed_index.min(self.tabs.len())]
+            .iter()
+            .rev()
+            .find(same_pane)
+            .or_else(|| {
+                self.tabs[removed_index.min(self.tabs.len())..]
+                    .iter()
+                    .find(same_pane)
+            })
+            .map(|t| t.id.clone());
+
+        if let Some(id) = &replacement
+            && let Some(tab) = self.tabs.iter_mut().find(|t| t.id == *id)
+        {
+            tab.active = true;
+        }
+        if let Some(pane) = removed.pane_id.as_deref().and_then(|id| self.pane_mut(id)) {
+            pane.active_tab_id = replacement.clone();
+        }
+        if self.active_tab_id.as_deref() == Some(removed.id.as_str()) {
+            self.active_tab_id = replacement;
+        }
+    }
+
+    /// ペインを取得
+    pub fn pane(&self, pane_id: &str) -> Option<&PaneState> {
+        self.panes.iter().find(|p| p.id == pane_id)
+    }
+
+    /// ペインを可変参照で取得
+    fn pane_mut(&mut self, pane_id: &str) -> Option<&mut PaneState> {
+        self.panes.iter_mut().find(|p| p.id == pane_id)
+    }
+
+    /// ペインを分割するペインのID
+    fn parent_pane_id(&self, pane_id: &str) -> Option<String> {
+        self.panes
+            .iter()
+            .find(|p| p.children.iter().any(|c| c == pane_id))
+            .map(|p| p.id.clone())
+    }
+
+    /// 分割ツリーに含まれるファイルリストペインか
+    fn is_leaf_pane(&self, pane_id: Option<&str>) -> bool {
+        pane_id.is_some_and(|id| self.leaf_pane_ids().iter().any(|leaf| leaf == id))
+    }
+
+    /// ペインの分割ツリーを組み立てる（ルートがない、または壊れている場合は None）
+    pub fn pane_tree(&self) -> Option<PaneTree> {
+        fn build(state: &AppState, id: &str, depth: usize) -> Option<PaneTree> {
+            // 循環した参照で無限に再帰しないよう、ペイン数より深くはたどらない
+            if depth > state.panes.len() {
+                return None;
+            }
+            let pane = state.pane(id)?;
+            match pane.pane_type {
+                PaneType::Split(direction) => {
+                    let children = pane
+                        .children
+                        .iter()
+                        .map(|child| build(state, child, depth + 1))
+                        .collect::<Option<Vec<_>>>()?;
+                    (!children.is_empty()).then(|| PaneTree::Split {
+                        id: pane.id.clone(),
+                        direction,
+                        children,
+                    })
+                }
+                _ => Some(PaneTree::Leaf(pane.id.clone())),
+            }
+        }
+        build(self, self.root_pane_id.as_deref()?, 0)
+    }
+
+    /// ツリー順のファイルリストペインID
+    pub fn leaf_pane_ids(&self) -> Vec<String> {
+        self.pane_tree()
+            .map(|tree| tree.leaf_ids())
+            .unwrap_or_default()
+    }
+
+    /// ペインに属するタブ（表示順）
+    pub fn pane_tabs(&self, pane_id: &str) -> Vec<&TabState> {
+        self.tabs
+            .iter()
+            .filter(|t| t.pane_id.as_deref() == Some(pane_id))
+            .collect()
+    }
+
+    /// ツリー順で次のファイルリストペイン（コピー・移動先）
+    pub fn other_pane_id(&self, pane_id: &str) -> Option<String> {
+        let leaves = self.leaf_pane_ids();
+        let index = leaves.iter().position(|id| id == pane_id)?;
+        (leaves.len() > 1).then(|| leaves[(index + 1) % leaves.len()].clone())
+    }
+
+    /// ペインのレイアウトを整える
+    ///
+    /// ツリーがない・壊れている場合はすべてのタブを持つペインを1つ作る。
+    /// ツリーにないペインは捨て、所属ペインがないタブはフォーカスのあるペインへ移す。
+    pub fn ensure_pane_layout(&mut self) {
+        if self.pane_tree().is_none() {
+            let mut pane =
+                state_utils::create_default_pane(PaneType::FileList, PanePosition::Center);
+            pane.active_tab_id = self.active_tab_id.clone();
+            self.panes = vec![pane.clone()];
+            self.root_pane_id = Some(pane.id.clone());
+            self.focused_pane_id = Some(pane.id);
+        }
+
+        let leaves = self.leaf_pane_ids();
+        let in_tree: Vec<String> = {
+            fn collect(tree: &PaneTree, ids: &mut Vec<String>) {
+                ids.push(tree.id().to_string());
+                if let PaneTree::Split { children, .. } = tree {
+                    children.iter().for_each(|child| collect(child, ids));
+                }
+            }
+            let mut ids = Vec::new();
+            if let Some(tree) = self.pane_tree() {
+                collect(&tree, &mut ids);
+            }
+            ids
+        };
+        self.panes.retain(|p| in_tree.contains(&p.id));
+        if !self
+            .focused_pane_id
+            .as_ref()
+            .is_some_and(|id| leaves.contains(id))
+        {
+            self.focused_pane_id = leaves.first().cloned();
+        }
+
+        for tab in &mut self.tabs {
+            if !tab.pane_id.as_ref().is_some_and(|id| leaves.contains(id)) {
+                tab.pane_id = self.focused_pane_id.clone();
+            }
+        }
+
+        // ペインごとのアクティブタブとタブのフラグを揃える
+        for leaf in &leaves {
+            let tab_ids: Vec<String> = self.pane_tabs(leaf).iter().map(|t| t.id.clone()).collect();
+            let Some(pane) = self.pane_mut(leaf) else {
+                continue;
+            };
+            if !pane
+                .active_tab_id
+                .as_ref()
+                .is_some_and(|id| tab_ids.contains(id))
+            {
+                pane.active_tab_id = tab_ids.first().cloned();
+            }
+            let active = pane.active_tab_id.clone();
+            for tab in &mut self.tabs {
+                if tab.pane_id.as_deref() == Some(leaf.as_str()) {
+                    tab.active = active.as_deref() == Some(tab.id.as_str());
+                }
+            }
+        }
+        self.active_tab_id = self
+            .focused_pane_id
+            .as_deref()
+            .and_then(|id| self.pane(id))
+            .and_then(|pane| pane.active_tab_id.clone());
+    }
+
+    /// ペインにフォーカス
+    pub fn focus_pane(&mut self, pane_id: &str) -> Result<(), AppError> {
+        if !self.is_leaf_pane(Some(pane_id)) {
+            return Err(AppError::Internal(format!("Pane not found: {}", pane_id)));
+        }
+        self.focused_pane_id = Some(pane_id.to_string());
+        self.active_tab_id = self.pane(pane_id).and_then(|p| p.active_tab_id.clone());
+        self.last_saved = chrono::Utc::now();
+        Ok(())
+    }
+
+    /// ペインを分割し、`tab` を開いた新しいペインのIDを返す
+    ///
+    /// 同じ方向の分割の中にあるペインを分割した場合は、分割を入れ子にせず隣に並べる。
+    pub fn split_pane(
+        &mut self,
+        pane_id: &str,
+        direction: SplitDirection,
+        tab: TabState,
+    ) -> Result<String, AppError> {
+        if !self.is_leaf_pane(Some(pane_id)) {
+            return Err(AppError::Internal(format!("Pane not found: {}", pane_id)));
+        }
+        let mut new_pane =
+            state_utils::create_default_pane(PaneType::FileList, PanePosition::Center);
+        let new_pane_id = new_pane.id.clone();
+        let parent_id = self.parent_pane_id(pane_id);
+        let same_direction = parent_id
+            .as_deref()
+            .and_then(|id| self.pane(id))
+            .is_some_and(|parent| parent.pane_type == PaneType::Split(direction));
+
+        if let (true, Some(parent_id)) = (same_direction, parent_id.as_deref()) {
+            // 分割したペインの大きさを2つで分け合う
+            let pane = self.pane_mut(pane_id).expect("leaf pane exists");
+            let half = pane.flex() / 2.0;
+            pane.size.flex = Some(half);
+            new_pane.size.flex = Some(half);
+            let parent = self.pane_mut(parent_id).expect("parent pane exists");
+            let index = parent
+                .children
+                .iter()
+                .position(|c| c == pane_id)
+                .unwrap_or(0);
+            parent.children.insert(index + 1, new_pane_id.clone());
+        } else {
+            let pane = self.pane_mut(pane_id).expect("leaf pane exists");
+            let mut split =
+                state_utils::create_default_pane(PaneType::Split(direction), pane.position.clone());
+            split.size = std::mem::replace(
+                &mut pane.size,
+                PaneSize {
+                    width: None,
+                    height: None,
+                    flex: Some(1.0),
+                },
+            );
+            split.children = vec![pane_id.to_string(), new_pane_id.clone()];
+            self.replace_in_parent(pane_id, &split.id, parent_id.as_deref());
+            self.panes.push(split);
+        }
+        self.panes.push(new_pane);
+
+        let mut tab = tab;
+        tab.pane_id = Some(new_pane_id.clone());
+        self.focused_pane_id = Some(new_pane_id.clone());
+        self.add_tab(tab);
+        Ok(new_pane_id)
+    }
+
+    /// ペインを閉じる（ペインのタブは閉じたタブになる）
+    ///
+    /// 子が1つだけになった分割は、残った子で置き換える。
+    pub fn close_pane(&mut self, pane_id: &str) -> Result<(), AppError> {
+        if !self.is_leaf_pane(Some(pane_id)) {
+            return Err(AppError::Internal(format!("Pane not found: {}", pane_id)));
+        }
+        let parent_id = self
+            .parent_pane_id(pane_id)
+            .ok_or_else(|| AppError::Internal("Cannot close the last pane".to_string()))?;
+
+        let (closed, kept): (Vec<TabState>, Vec<TabState>) = std::mem::take(&mut self.tabs)
+            .into_iter()
+            .partition(|t| t.pane_id.as_deref() == Some(pane_id));
+        self.tabs = kept;
+        for tab in closed {
+            self.push_closed_tab(tab);
+        }
+        self.panes.retain(|p| p.id != pane_id);
+
+        let parent = self.pane_mut(&parent_id).expect("parent pane exists");
+        parent.children.retain(|c| c != pane_id);
+        if parent.children.len() == 1 {
+            let child_id = parent.children[0].clone();
+            let size = parent.size.clone();
+            let grandparent_id = self.parent_pane_id(&parent_id);
+            self.replace_in_parent(&parent_id, &child_id, grandparent_id.as_deref());
+            self.panes.retain(|p| p.id != parent_id);
+            if let Some(child) = self.pane_mut(&child_id) {
+                child.size = size;
+            }
+        }
+
+        if self.focused_pane_id.as_deref() == Some(pane_id) {
+            self.focused_pane_id = self.leaf_pane_ids().first().cloned();
+        }
+        self.active_tab_id = self
+            .focused_pane_id
+            .as_deref()
+            .and_then(|id| self.pane(id))
+            .and_then(|pane| pane.active_tab_id.clone());
+        self.last_saved = chrono::Utc::now();
+        Ok(())
+    }
+
+    /// 分割の中でペインを置き換える（親がなければルートを置き換える）
+    fn replace_in_parent(&mut self, old_id: &str, new_id: &str, parent_id: Option<&str>) {
+        match parent_id.and_then(|id| self.pane_mut(id)) {
+            Some(parent) => {
+                for child in &mut parent.children {
+                    if child == old_id {
+                        *child = new_id.to_string();
+                    }
                 }
-            } else {
-                self.active_tab_id = None;
             }
+            None => self.root_pane_id = Some(new_id.to_string()),
         }
+    }
+
+    /// ペインの大きさ（同じ分割内での比率）を変更
+    pub fn resize_pane(&mut self, pane_id: &str, flex: f64) -> Result<(), AppError> {
+        let pane = self
+            .pane_mut(pane_id)
+            .ok_or_else(|| AppError::Internal(format!("Pane not found: {}", pane_id)))?;
+        pane.size.flex = Some(flex.max(MIN_PANE_FLEX));
+        self.last_saved = chrono::Utc::now();
+        Ok(())
+    }
+
+    /// タブを別のペインの末尾へ移動し、移動先でアクティブにする
+    pub fn move_tab_to_pane(&mut self, tab_id: &str, pane_id: &str) -> Result<TabState, AppError> {
+        if !self.is_leaf_pane(Some(pane_id)) {
+            return Err(AppError::Internal(format!("Pane not found: {}", pane_id)));
+        }
+        let index = self.tab_index(tab_id)?;
+        if self.tabs[index].pane_id.as_deref() != Some(pane_id) {
+            let mut tab = self.tabs.remove(index);
+            if tab.active {
+                self.activate_replacement(&tab, index);
+            }
+            tab.pane_id = Some(pane_id.to_string());
+            self.tabs.push(tab);
+        }
+        self.set_active_tab(tab_id)?;
+        self.tabs
+            .iter()
+            .find(|t| t.id == tab_id)
+            .cloned()
+            .ok_or_else(|| AppError::Internal(format!("Tab not found: {}", tab_id)))
+    }
+
+    /// タブを削除
+    pub fn remove_tab(&mut self, tab_id: &str) -> Result<(), AppError> {
+        let tab_index = self.tab_index(tab_id)?;
+
+        let removed_tab = self.tabs.remove(tab_index);
+        if removed_tab.active || self.active_tab_id.as_deref() == Some(tab_id) {
+            self.activate_replacement(&removed_tab, tab_index);
+        }
+        self.push_closed_tab(removed_tab);
 
         self.last_saved = chrono::Utc::now();
         Ok(())
@@ -215,20 +803,21 @@ impl AppState {
 
     /// アクティブタブを変更
     pub fn set_active_tab(&mut self, tab_id: &str) -> Result<(), AppError> {
-        // すべてのタブを非アクティブにする
+        let index = self.tab_index(tab_id)?;
+        let pane_id = self.tabs[index].pane_id.clone();
+
+        // 同じペインのタブを非アクティブにし、指定されたタブをアクティブにする
         for tab in &mut self.tabs {
-            tab.active = false;
+            if tab.pane_id == pane_id {
+                tab.active = tab.id == tab_id;
+            }
         }
 
-        // 指定されたタブをアクティブにする
-        let tab = self
-            .tabs
-            .iter_mut()
-            .find(|t| t.id == tab_id)
-            .ok_or_else(|| AppError::Internal(format!("Tab not found: {}", tab_id)))?;
-
-        tab.active = true;
         self.active_tab_id = Some(tab_id.to_string());
+        if let Some(pane) = pane_id.as_deref().and_then(|id| self.pane_mut(id)) {
+            pane.active_tab_id = Some(tab_id.to_string());
+            self.focused_pane_id = pane_id;
+        }
         self.last_saved = chrono::Utc::now();
         Ok(())
     }
@@ -267,6 +856,14 @@ impl AppState {
     }
 }
 
+/// `path` またはその最も近い既存の親ディレクトリ（どちらもなければ `fallback`）
+fn existing_directory(path: &Path, fallback: &Path) -> PathBuf {
+    path.ancestors()
+        .find(|ancestor| !ancestor.as_os_str().is_empty() && ancestor.is_dir())
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| fallback.to_path_buf())
+}
+
 /// 状態変更イベント
 #[derive(Debug, Clone)]
 pub enum StateChangeEvent {
@@ -278,10 +875,22 @@ pub enum StateChangeEvent {
     TabRemoved(String),
     /// アクティブタブ変更
     ActiveTabChanged(String),
+    /// タブの並び替え
+    TabMoved { tab_id: String, index: usize },
+    /// タブの内容（パス・履歴・選択など）の更新
+    TabUpdated(TabState),
     /// ペイン追加
     PaneAdded(PaneState),
     /// ペイン削除
     PaneRemoved(String),
+    /// ペインの大きさの変更
+    PaneResized { pane_id: String, flex: f64 },
+    /// フォーカスのあるペインの変更
+    PaneFocused(String),
+    /// ペインのレイアウトの整理（読み込んだ状態の修復など）
+    PaneLayoutChanged,
+    /// 状態全体の置き換え（ワークスペースの切り替えなど）
+    StateReplaced,
     /// UI状態変更
     UiStateChanged(UiState),
 }
@@ -327,13 +936,16 @@ impl StateManager {
     where
         F: FnOnce(&mut AppState) -> Result<Option<StateChangeEvent>, AppError>,
     {
-        let mut state = self
-            .state
-            .write()
-            .map_err(|e| AppError::Internal(format!("Failed to write state: {}", e)))?;
+        let event = {
+            let mut state = self
+                .state
+                .write()
+                .map_err(|e| AppError::Internal(format!("Failed to write state: {}", e)))?;
+            updater(&mut state)?
+        };
 
-        if let Some(event) = updater(&mut state)? {
-            // イベントをトリガー
+        // コールバックから状態を読めるよう、ロックを解放してからイベントをトリガー
+        if let Some(event) = event {
             self.trigger_event(event);
         }
 
@@ -376,6 +988,115 @@ impl StateManager {
         })
     }
 
+    /// タブを複製し、複製したタブを返す
+    pub fn duplicate_tab(&self, tab_id: &str) -> Result<TabState, AppError> {
+        let mut duplicate = None;
+        self.update_state(|state| {
+            let tab = state.duplicate_tab(tab_id, state_utils::generate_tab_id())?;
+            duplicate = Some(tab.clone());
+            Ok(Some(StateChangeEvent::TabAdded(tab)))
+        })?;
+        duplicate.ok_or_else(|| AppError::Internal("Tab was not duplicated".to_string()))
+    }
+
+    /// タブを指定位置へ移動
+    pub fn move_tab(&self, tab_id: &str, index: usize) -> Result<(), AppError> {
+        let tab_id = tab_id.to_string();
+        self.update_state(|state| {
+            state.move_tab(&tab_id, index)?;
+            Ok(Some(StateChangeEvent::TabMoved { tab_id, index }))
+        })
+    }
+
+    /// 最後に閉じたタブを開き直す（閉じたタブがなければ None）
+    pub fn reopen_closed_tab(&self) -> Result<Option<TabState>, AppError> {
+        let mut reopened = None;
+        self.update_state(|state| {
+            reopened = state.reopen_closed_tab();
+            Ok(reopened.clone().map(StateChangeEvent::TabAdded))
+        })?;
+        Ok(reopened)
+    }
+
+    /// タブを更新
+    pub fn update_tab<F>(&self, tab_id: &str, updater: F) -> Result<(), AppError>
+    where
+        F: FnOnce(&mut TabState),
+    {
+        self.update_state(|state| {
+            let tab = state.update_tab(tab_id, updater)?;
+            Ok(Some(StateChangeEvent::TabUpdated(tab)))
+        })
+    }
+
+    /// ペインのレイアウトを整える
+    pub fn ensure_pane_layout(&self) -> Result<(), AppError> {
+        self.update_state(|state| {
+            state.ensure_pane_layout();
+            Ok(Some(StateChangeEvent::PaneLayoutChanged))
+        })
+    }
+
+    /// ペインを分割し、新しいペインのIDを返す
+    pub fn split_pane(
+        &self,
+        pane_id: &str,
+        direction: SplitDirection,
+        tab: TabState,
+    ) -> Result<String, AppError> {
+        let mut new_pane = None;
+        self.update_state(|state| {
+            let id = state.split_pane(pane_id, direction, tab)?;
+            let pane = state.pane(&id).cloned();
+            new_pane = Some(id);
+            Ok(pane.map(StateChangeEvent::PaneAdded))
+        })?;
+        new_pane.ok_or_else(|| AppError::Internal("Pane was not split".to_string()))
+    }
+
+    /// ペインを閉じる
+    pub fn close_pane(&self, pane_id: &str) -> Result<(), AppError> {
+        let pane_id = pane_id.to_string();
+        self.update_state(|state| {
+            state.close_pane(&pane_id)?;
+            Ok(Some(StateChangeEvent::PaneRemoved(pane_id)))
+        })
+    }
+
+    /// ペインの大きさを変更
+    pub fn resize_pane(&self, pane_id: &str, flex: f64) -> Result<(), AppError> {
+        let pane_id = pane_id.to_string();
+        self.update_state(|state| {
+            state.resize_pane(&pane_id, flex)?;
+            Ok(Some(StateChangeEvent::PaneResized { pane_id, flex }))
+        })
+    }
+
+    /// ペインにフォーカス
+    pub fn focus_pane(&self, pane_id: &str) -> Result<(), AppError> {
+        let pane_id = pane_id.to_string();
+        self.update_state(|state| {
+            state.focus_pane(&pane_id)?;
+            Ok(Some(StateChangeEvent::PaneFocused(pane_id)))
+        })
+    }
+
+    /// タブを別のペインへ移動
+    pub fn move_tab_to_pane(&self, tab_id: &str, pane_id: &str) -> Result<(), AppError> {
+        self.update_state(|state| {
+            let tab = state.move_tab_to_pane(tab_id, pane_id)?;
+            Ok(Some(StateChangeEvent::TabUpdated(tab)))
+        })
+    }
+
+    /// 状態全体を置き換える
+    pub fn replace_state(&self, new_state: AppState) -> Result<(), AppError> {
+        self.update_state(|state| {
+            *state = new_state;
+            Ok(Some(StateChangeEvent::StateReplaced))
+        })
+    }
+
     /// UI状態を更新
     pub fn update_ui_state(&self, ui_state: UiState) -> Result<(), AppError> {
         self.update_state(|state| {
@@ -433,34 +1154,62 @@ impl Clone for StateManager {
 /// 状態管理ユーティリティ
 pub mod state_utils {
     use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
 
-    /// タブIDを生成
-    pub fn generate_tab_id() -> String {
+    /// 時刻の分解能が粗い環境でも続けて生成したIDが重ならないようにする連番
+    static ID_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+    fn generate_id(prefix: &str) -> String {
         format!(
-            "tab_{}",
-            chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0)
+            "{}_{}_{}",
+            prefix,
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0),
+            ID_SEQUENCE.fetch_add(1, Ordering::Relaxed)
         )
     }
 
+    /// タブIDを生成
+    pub fn generate_tab_id() -> String {
+        generate_id("tab")
+    }
+
     /// ペインIDを生成
     pub fn generate_pane_id() -> String {
-        format!(
-            "pane_{}",
-            chrono::Utc::now().timestamp_nanos_opt().unwrap_or(0)
-        )
+        generate_id("pane")
+    }
+
+    /// スマートフォルダIDを生成
+    pub fn generate_smart_folder_id() -> String {
+        generate_id("smart")
+    }
+
+    /// ブックマーク（フォルダ）IDを生成
+    pub fn generate_bookmark_id() -> String {
+        generate_id("bookmark")
     }
 
     /// デフォルトタブを作成
     pub fn create_default_tab(path: PathBuf) -> TabState {
         let id = generate_tab_id();
-        let name = path
-            .file_name()
-            .map(|n| n.to_string_lossy().to_string())
-            .unwrap_or_else(|| "New Tab".to_string());
+        let name = TabState::name_for_path(&path);
 
         TabState::new(id, name, path)
     }
 
+    /// スマートフォルダを開くタブを作成
+    pub fn create_smart_folder_tab(folder: &SmartFolder) -> TabState {
+        let mut tab = TabState::new(generate_tab_id(), folder.name.clone(), folder.scope.clone());
+        tab.smart_folder = Some(folder.clone());
+        tab
+    }
+
+    /// タグの付いた項目を一覧するタブを作成（`current_path` は使わないためホーム）
+    pub fn create_tag_tab(tag: &Tag, home: PathBuf) -> TabState {
+        let mut tab = TabState::new(generate_tab_id(), tag.name.clone(), home);
+        tab.tag = Some(tag.name.clone());
+        tab
+    }
+
     /// デフォルトペインを作成
     pub fn create_default_pane(pane_type: PaneType, position: PanePosition) -> PaneState {
         PaneState {
@@ -473,6 +1222,8 @@ pub mod state_utils {
                 flex: Some(1.0),
             },
             visible: true,
+            children: Vec::new(),
+            active_tab_id: None,
         }
     }
 }
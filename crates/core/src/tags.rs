@@ -0,0 +1,440 @@
+//! タグ（色ラベル）
+//!
+//! ファイルやフォルダにユーザー定義のタグと色を付けます。タグはパスごとに記録し、
+//! アプリ内での名前変更・移動は `TagStore::relocate` で付け替えます。必要なら
+//! 拡張属性 `user.xdg.tags` にもタグ名を書き出し、他のアプリと共有します。
+
+use rust_explorer_utils::AppError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// タグを書き出す拡張属性（freedesktop.org の共通の属性名、値はカンマ区切り）
+pub const XDG_TAGS_ATTRIBUTE: &str = "user.xdg.tags";
+
+/// タグ名の最大文字数
+pub const MAX_TAG_NAME_LEN: usize = 64;
+
+/// タグの色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagColor {
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Blue,
+    Purple,
+    Gray,
+}
+
+impl TagColor {
+    /// 選べる色（表示順）
+    pub const ALL: [TagColor; 7] = [
+        TagColor::Red,
+        TagColor::Orange,
+        TagColor::Yellow,
+        TagColor::Green,
+        TagColor::Blue,
+        TagColor::Purple,
+        TagColor::Gray,
+    ];
+
+    /// 表示名
+    pub fn label(&self) -> &'static str {
+        match self {
+            TagColor::Red => "赤",
+            TagColor::Orange => "オレンジ",
+            TagColor::Yellow => "黄",
+            TagColor::Green => "緑",
+            TagColor::Blue => "青",
+            TagColor::Purple => "紫",
+            TagColor::Gray => "グレー",
+        }
+    }
+
+    /// 表示に使う RGB
+    pub fn rgb(&self) -> (u8, u8, u8) {
+        match self {
+            TagColor::Red => (239, 68, 68),
+            TagColor::Orange => (249, 115, 22),
+            TagColor::Yellow => (234, 179, 8),
+            TagColor::Green => (34, 197, 94),
+            TagColor::Blue => (59, 130, 246),
+            TagColor::Purple => (168, 85, 247),
+            TagColor::Gray => (107, 114, 128),
+        }
+    }
+}
+
+/// タグの定義
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tag {
+    pub name: String,
+    pub color: TagColor,
+}
+
+/// タグの定義とパスへの割り当て
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TagStore {
+    tags: Vec<Tag>,
+    /// パスごとのタグ名（タグの定義順）
+    assignments: BTreeMap<PathBuf, Vec<String>>,
+}
+
+impl TagStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 定義済みのタグ（表示順）
+    pub fn tags(&self) -> &[Tag] {
+        &self.tags
+    }
+
+    /// 名前でタグを探す（大文字小文字は区別しない）
+    pub fn tag(&self, name: &str) -> Option<&Tag> {
+        self.position(name).map(|index| &self.tags[index])
+    }
+
+    /// タグを定義する
+    pub fn define(&mut self, name: &str, color: TagColor) -> Result<&Tag, AppError> {
+        let name = validate_tag_name(name)?;
+        if self.position(&name).is_some() {
+            return Err(AppError::InvalidInput(format!(
+                "Tag already exists: {}",
+                name
+            )));
+        }
+        self.tags.push(Tag { name, color });
+        Ok(self.tags.last().expect("tag was just added"))
+    }
+
+    /// タグの色を変える
+    pub fn recolor(&mut self, name: &str, color: TagColor) -> Result<(), AppError> {
+        let index = self.require(name)?;
+        self.tags[index].color = color;
+        Ok(())
+    }
+
+    /// タグの名前を変え、割り当てたパスを返す
+    pub fn rename_tag(&mut self, name: &str, new_name: &str) -> Result<Vec<PathBuf>, AppError> {
+        let index = self.require(name)?;
+        let new_name = validate_tag_name(new_name)?;
+        if self.position(&new_name).is_some_and(|other| other != index) {
+            return Err(AppError::InvalidInput(format!(
+                "Tag already exists: {}",
+                new_name
+            )));
+        }
+        let old_name = std::mem::replace(&mut self.tags[index].name, new_name.clone());
+        let mut affected = Vec::new();
+        for (path, names) in &mut self.assignments {
+            if let Some(assigned) = names.iter_mut().find(|n| **n == old_name) {
+                *assigned = new_name.clone();
+                affected.push(path.clone());
+            }
+        }
+        Ok(affected)
+    }
+
+    /// タグを削除し、割り当てていたパスを返す
+    pub fn remove_tag(&mut self, name: &str) -> Result<Vec<PathBuf>, AppError> {
+        let index = self.require(name)?;
+        let removed = self.tags.remove(index);
+        let mut affected = Vec::new();
+        self.assignments.retain(|path, names| {
+            if names.contains(&removed.name) {
+                names.retain(|n| *n != removed.name);
+                affected.push(path.clone());
+            }
+            !names.is_empty()
+        });
+        Ok(affected)
+    }
+
+    /// タグの表示順を変える（`index` が範囲外なら末尾へ）
+    pub fn move_tag(&mut self, name: &str, index: usize) -> Result<(), AppError> {
+        let from = self.require(name)?;
+        let tag = self.tags.remove(from);
+        let index = index.min(self.tags.len());
+        self.tags.insert(index, tag);
+        for names in self.assignments.values_mut() {
+            sort_by_definition(names, &self.tags);
+        }
+        Ok(())
+    }
+
+    /// パスにタグを付ける
+    pub fn assign(&mut self, path: &Path, name: &str) -> Result<(), AppError> {
+        if !path.is_absolute() {
+            return Err(AppError::InvalidPath(path.to_path_buf()));
+        }
+        let tag_name = self.tags[self.require(name)?].name.clone();
+        let names = self.assignments.entry(path.to_path_buf()).or_default();
+        if !names.contains(&tag_name) {
+            names.push(tag_name);
+            sort_by_definition(names, &self.tags);
+        }
+        Ok(())
+    }
+
+    /// パスからタグを外す（付いていなければ false）
+    pub fn unassign(&mut self, path: &Path, name: &str) -> bool {
+        let Some(tag_name) = self.tag(name).map(|tag| tag.name.clone()) else {
+            return false;
+        };
+        let Some(names) = self.assignments.get_mut(path) else {
+            return false;
+        };
+        let before = names.len();
+        names.retain(|n| *n != tag_name);
+        let removed = names.len() != before;
+        if names.is_empty() {
+            self.assignments.remove(path);
+        }
+        removed
+    }
+
+    /// タグを付け外しし、付いた状態になったかを返す
+    pub fn toggle(&mut self, path: &Path, name: &str) -> Result<bool, AppError> {
+        if self.unassign(path, name) {
+            return Ok(false);
+        }
+        self.assign(path, name)?;
+        Ok(true)
+    }
+
+    /// パスに付いたタグ（定義順）
+    pub fn tags_for(&self, path: &Path) -> Vec<&Tag> {
+        self.names_for(path)
+            .iter()
+            .filter_map(|name| self.tag(name))
+            .collect()
+    }
+
+    /// パスに付いたタグ名（定義順）
+    pub fn names_for(&self, path: &Path) -> &[String] {
+        self.assignments
+            .get(path)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// タグを付けたパス（パス順）
+    pub fn paths_with(&self, name: &str) -> Vec<PathBuf> {
+        let Some(tag) = self.tag(name) else {
+            return Vec::new();
+        };
+        self.assignments
+            .iter()
+            .filter(|(_, names)| names.contains(&tag.name))
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+
+    /// 名前変更・移動に合わせて `from` とその中の項目のタグを `to` へ付け替える
+    ///
+    /// 付け替えた項目があれば true を返す。
+    pub fn relocate(&mut self, from: &Path, to: &Path) -> bool {
+        if from == to {
+            return false;
+        }
+        let moved: Vec<PathBuf> = self
+            .assignments
+            .keys()
+            .filter(|path| path.starts_with(from))
+            .cloned()
+            .collect();
+        for path in &moved {
+            if let Some(names) = self.assignments.remove(path) {
+                let suffix = path.strip_prefix(from).unwrap_or(Path::new(""));
+                let target = if suffix.as_os_str().is_empty() {
+                    to.to_path_buf()
+                } else {
+                    to.join(suffix)
+                };
+                self.assignments.insert(target, names);
+            }
+        }
+        !moved.is_empty()
+    }
+
+    /// 削除した `path` とその中の項目のタグを外す
+    pub fn forget(&mut self, path: &Path) -> bool {
+        let before = self.assignments.len();
+        self.assignments
+            .retain(|assigned, _| !assigned.starts_with(path));
+        self.assignments.len() != before
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        let key = name.trim().to_lowercase();
+        self.tags
+            .iter()
+            .position(|tag| tag.name.to_lowercase() == key)
+    }
+
+    fn require(&self, name: &str) -> Result<usize, AppError> {
+        self.position(name)
+            .ok_or_else(|| AppError::InvalidInput(format!("Unknown tag: {}", name)))
+    }
+}
+
+/// 前後の空白を除いたタグ名（空・長すぎる名前・カンマを含む名前はエラー）
+fn validate_tag_name(name: &str) -> Result<String, AppError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::InvalidInput(
+            "Tag name must not be empty".to_string(),
+        ));
+    }
+    if name.chars().count() > MAX_TAG_NAME_LEN {
+        return Err(AppError::InvalidInput(format!(
+            "Tag name must be at most {} characters",
+            MAX_TAG_NAME_LEN
+        )));
+    }
+    // 拡張属性ではカンマで区切って保存する
+    if name.contains(',') {
+        return Err(AppError::InvalidInput(format!(
+            "Tag name must not contain a comma: {}",
+            name
+        )));
+    }
+    Ok(name.to_string())
+}
+
+fn sort_by_definition(names: &mut [String], tags: &[Tag]) {
+    names.sort_by_key(|name| tags.iter().position(|tag| tag.name == *name));
+}
+
+/// パスのタグ名を拡張属性 `user.xdg.tags` に書き出す（空なら属性を削除する）
+pub fn write_xattr_tags(path: &Path, names: &[String]) -> Result<(), AppError> {
+    if names.is_empty() {
+        xattr::remove(path, XDG_TAGS_ATTRIBUTE)
+    } else {
+        xattr::set(path, XDG_TAGS_ATTRIBUTE, names.join(",").as_bytes())
+    }
+}
+
+/// 拡張属性 `user.xdg.tags` のタグ名を読む（属性がなければ空）
+pub fn read_xattr_tags(path: &Path) -> Result<Vec<String>, AppError> {
+    let value = xattr::get(path, XDG_TAGS_ATTRIBUTE)?.unwrap_or_default();
+    Ok(String::from_utf8_lossy(&value)
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+#[cfg(target_os = "linux")]
+mod xattr {
+    use rust_explorer_utils::AppError;
+    use std::ffi::CString;
+    use std::io;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    fn c_string(bytes: &[u8]) -> Result<CString, AppError> {
+        CString::new(bytes).map_err(|_| AppError::InvalidInput("Path contains a NUL byte".into()))
+    }
+
+    pub fn set(path: &Path, name: &str, value: &[u8]) -> Result<(), AppError> {
+        let c_path = c_string(path.as_os_str().as_bytes())?;
+        let c_name = c_string(name.as_bytes())?;
+        // SAFETY: パスと属性名は NUL 終端され、値は長さ付きで渡す
+        let result = unsafe {
+            libc::setxattr(
+                c_path.as_ptr(),
+                c_name.as_ptr(),
+                value.as_ptr().cast(),
+                value.len(),
+                0,
+            )
+        };
+        if result == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error().into())
+        }
+    }
+
+    pub fn get(path: &Path, name: &str) -> Result<Option<Vec<u8>>, AppError> {
+        let c_path = c_string(path.as_os_str().as_bytes())?;
+        let c_name = c_string(name.as_bytes())?;
+        loop {
+            // SAFETY: 大きさを問い合わせるだけで、バッファには書き込まれない
+            let size = unsafe {
+                libc::getxattr(c_path.as_ptr(), c_name.as_ptr(), std::ptr::null_mut(), 0)
+            };
+            if size < 0 {
+                return missing_or_error();
+            }
+            let mut buffer = vec![0_u8; size as usize];
+            // SAFETY: バッファの長さを渡しているため、範囲外には書き込まれない
+            let read = unsafe {
+                libc::getxattr(
+                    c_path.as_ptr(),
+                    c_name.as_ptr(),
+                    buffer.as_mut_ptr().cast(),
+                    buffer.len(),
+                )
+            };
+            if read >= 0 {
+                buffer.truncate(read as usize);
+                return Ok(Some(buffer));
+            }
+            // 問い合わせてから読むまでに値が大きくなった場合は読み直す
+            if io::Error::last_os_error().raw_os_error() != Some(libc::ERANGE) {
+                return missing_or_error();
+            }
+        }
+    }
+
+    pub fn remove(path: &Path, name: &str) -> Result<(), AppError> {
+        let c_path = c_string(path.as_os_str().as_bytes())?;
+        let c_name = c_string(name.as_bytes())?;
+        // SAFETY: パスと属性名は NUL 終端されている
+        if unsafe { libc::removexattr(c_path.as_ptr(), c_name.as_ptr()) } == 0 {
+            return Ok(());
+        }
+        missing_or_error().map(|_| ())
+    }
+
+    /// 属性がなければ None、それ以外はエラー
+    fn missing_or_error() -> Result<Option<Vec<u8>>, AppError> {
+        let error = io::Error::last_os_error();
+        if error.raw_os_error() == Some(libc::ENODATA) {
+            Ok(None)
+        } else {
+            Err(error.into())
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod xattr {
+    use rust_explorer_utils::AppError;
+    use std::path::Path;
+
+    fn unsupported() -> AppError {
+        AppError::InvalidOperation(
+            "Extended attributes are not supported on this platform".to_string(),
+        )
+    }
+
+    pub fn set(_path: &Path, _name: &str, _value: &[u8]) -> Result<(), AppError> {
+        Err(unsupported())
+    }
+
+    pub fn get(_path: &Path, _name: &str) -> Result<Option<Vec<u8>>, AppError> {
+        Err(unsupported())
+    }
+
+    pub fn remove(_path: &Path, _name: &str) -> Result<(), AppError> {
+        Err(unsupported())
+    }
+}
@@ -0,0 +1,155 @@
+//! ブックマークのテスト
+
+use crate::bookmarks::{BookmarkEntry, Bookmarks};
+use crate::quick_open::{QuickOpen, QuickOpenSource};
+use rust_explorer_utils::AppError;
+use std::path::{Path, PathBuf};
+
+fn names(bookmarks: &Bookmarks) -> Vec<&str> {
+    bookmarks
+        .bookmarks()
+        .into_iter()
+        .map(|bookmark| bookmark.name.as_str())
+        .collect()
+}
+
+#[test]
+fn test_bookmark_add_rename_remove() {
+    let mut bookmarks = Bookmarks::new();
+    let docs = bookmarks
+        .add(Path::new("/home/u/docs"), None)
+        .unwrap()
+        .clone();
+    assert_eq!(docs.name, "docs");
+    assert_eq!(
+        bookmarks.add(Path::new("/"), None).unwrap().name,
+        "/".to_string()
+    );
+
+    assert!(matches!(
+        bookmarks.add(Path::new("/home/u/docs"), None),
+        Err(AppError::InvalidInput(_))
+    ));
+    assert!(bookmarks.add(Path::new("relative"), None).is_err());
+    assert!(bookmarks.add(Path::new("/x"), Some("missing")).is_err());
+
+    bookmarks.rename(&docs.id, "  書類 ").unwrap();
+    assert_eq!(bookmarks.get(&docs.id).unwrap().name, "書類");
+    assert!(bookmarks.rename(&docs.id, " ").is_err());
+    assert!(bookmarks.rename("missing", "x").is_err());
+    assert_eq!(
+        bookmarks
+            .find(Path::new("/home/u/docs"))
+            .map(|b| b.id.clone()),
+        Some(docs.id.clone())
+    );
+
+    assert!(bookmarks.remove(&docs.id));
+    assert!(!bookmarks.remove(&docs.id));
+    assert_eq!(names(&bookmarks), vec!["/"]);
+}
+
+#[test]
+fn test_bookmark_folders_and_order() {
+    let mut bookmarks = Bookmarks::new();
+    let a = bookmarks.add(Path::new("/a"), None).unwrap().id.clone();
+    let work = bookmarks.add_folder("Work").unwrap().id.clone();
+    let b = bookmarks
+        .add(Path::new("/b"), Some(&work))
+        .unwrap()
+        .id
+        .clone();
+    let c = bookmarks.add(Path::new("/c"), None).unwrap().id.clone();
+    // フォルダの中は最上位の並びの位置に展開される
+    assert_eq!(names(&bookmarks), vec!["a", "b", "c"]);
+
+    bookmarks.move_by(&c, -1).unwrap();
+    bookmarks.move_by(&c, -5).unwrap();
+    assert_eq!(names(&bookmarks), vec!["c", "a", "b"]);
+
+    bookmarks.move_to(&a, Some(&work), 0).unwrap();
+    assert_eq!(names(&bookmarks), vec!["c", "a", "b"]);
+    assert_eq!(bookmarks.folder(&work).unwrap().bookmarks.len(), 2);
+    bookmarks.move_by(&a, 1).unwrap();
+    assert_eq!(names(&bookmarks), vec!["c", "b", "a"]);
+
+    // フォルダは入れ子にできない
+    let other = bookmarks.add_folder("Other").unwrap().id.clone();
+    assert!(bookmarks.move_to(&other, Some(&work), 0).is_err());
+    bookmarks.move_to(&other, None, 0).unwrap();
+    assert!(matches!(bookmarks.entries()[0], BookmarkEntry::Folder(_)));
+
+    bookmarks.move_to(&b, None, usize::MAX).unwrap();
+    assert_eq!(names(&bookmarks), vec!["c", "a", "b"]);
+
+    bookmarks.rename(&work, "仕事").unwrap();
+    assert_eq!(bookmarks.folder(&work).unwrap().name, "仕事");
+    // フォルダを削除すると中身も消える
+    assert!(bookmarks.remove(&work));
+    assert_eq!(names(&bookmarks), vec!["c", "b"]);
+
+    let json = serde_json::to_string(&bookmarks).unwrap();
+    let restored: Bookmarks = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, bookmarks);
+    let empty: Bookmarks = serde_json::from_str("{}").unwrap();
+    assert!(empty.entries().is_empty());
+}
+
+#[test]
+fn test_bookmark_shortcuts() {
+    let mut bookmarks = Bookmarks::new();
+    let folder = bookmarks.add_folder("F").unwrap().id.clone();
+    for i in 0..11 {
+        let target = (i % 2 == 0).then_some(folder.as_str());
+        bookmarks
+            .add(&PathBuf::from(format!("/p{i}")), target)
+            .unwrap();
+    }
+    let order = bookmarks.bookmarks();
+    assert_eq!(bookmarks.shortcut(1).unwrap().id, order[0].id);
+    assert_eq!(bookmarks.shortcut(9).unwrap().id, order[8].id);
+    assert!(bookmarks.shortcut(0).is_none());
+    assert!(bookmarks.shortcut(10).is_none());
+    assert_eq!(bookmarks.shortcut_number(&order[2].id), Some(3));
+    assert_eq!(bookmarks.shortcut_number(&order[9].id), None);
+}
+
+#[test]
+fn test_bookmarks_follow_moves() {
+    let mut bookmarks = Bookmarks::new();
+    let project = bookmarks
+        .add(Path::new("/a/project"), None)
+        .unwrap()
+        .id
+        .clone();
+    let src = bookmarks
+        .add(Path::new("/a/project/src"), None)
+        .unwrap()
+        .id
+        .clone();
+    bookmarks.rename(&src, "Sources").unwrap();
+
+    assert!(bookmarks.relocate(Path::new("/a/project"), Path::new("/b/renamed")));
+    let project = bookmarks.get(&project).unwrap();
+    assert_eq!(project.path, PathBuf::from("/b/renamed"));
+    // パスの名前のままだった表示名は追従し、付けた名前は残す
+    assert_eq!(project.name, "renamed");
+    let src = bookmarks.get(&src).unwrap();
+    assert_eq!(src.path, PathBuf::from("/b/renamed/src"));
+    assert_eq!(src.name, "Sources");
+
+    assert!(!bookmarks.relocate(Path::new("/missing"), Path::new("/x")));
+}
+
+#[test]
+fn test_quick_open_includes_bookmarks() {
+    let mut bookmarks = Bookmarks::new();
+    bookmarks.add(Path::new("/srv/projects"), None).unwrap();
+
+    let mut quick_open = QuickOpen::new();
+    quick_open.add_bookmarks(&bookmarks);
+    let found = quick_open.search("proj", None, 10);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].source, QuickOpenSource::Bookmark);
+    assert!(found[0].is_dir);
+}
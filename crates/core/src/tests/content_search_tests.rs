@@ -0,0 +1,196 @@
+//! ファイルの内容の検索のテスト
+
+use crate::content_search::{ContentSearch, ContentSearchEvent, ContentSearchOptions};
+use crate::job::{JobKind, JobManager, JobStatus};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use tempfile::TempDir;
+
+/// 検索を実行するジョブマネージャー
+static JOBS: LazyLock<JobManager> = LazyLock::new(|| JobManager::new().unwrap());
+
+/// 検索用のディレクトリを作成
+fn search_tree() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::create_dir_all(root.join("target")).unwrap();
+    fs::write(root.join(".gitignore"), "target/\n").unwrap();
+    fs::write(
+        root.join("src/main.rs"),
+        "fn main() {\n    let todo = 1;\n    // TODO: remove\n}\n",
+    )
+    .unwrap();
+    fs::write(root.join("src/notes.txt"), "todo list\n").unwrap();
+    fs::write(root.join("target/out.rs"), "// TODO: generated\n").unwrap();
+    fs::write(root.join("image.bin"), b"TODO\0\x01\x02").unwrap();
+    temp_dir
+}
+
+/// 見つかった行の（相対パス, 行番号）
+fn search(root: &Path, options: ContentSearchOptions) -> Vec<(PathBuf, usize)> {
+    let (matches, _) = ContentSearch::start(&JOBS, root, options)
+        .unwrap()
+        .collect()
+        .unwrap();
+    matches
+        .into_iter()
+        .map(|m| {
+            let path = m.entry.path.strip_prefix(root).unwrap().to_path_buf();
+            (path, m.line_number)
+        })
+        .collect()
+}
+
+#[test]
+fn test_content_search_respects_gitignore_and_skips_binary() {
+    let temp_dir = search_tree();
+    let root = temp_dir.path();
+
+    let (matches, progress) = ContentSearch::start(&JOBS, root, ContentSearchOptions::new("todo"))
+        .unwrap()
+        .collect()
+        .unwrap();
+    let found: Vec<_> = matches
+        .iter()
+        .map(|m| (m.entry.path.strip_prefix(root).unwrap(), m.line_number))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (Path::new("src/main.rs"), 2),
+            (Path::new("src/main.rs"), 3),
+            (Path::new("src/notes.txt"), 1),
+        ]
+    );
+    assert_eq!(progress.matched_files, 2);
+    assert_eq!(progress.skipped_files, 1);
+
+    // 前後の行と一致した範囲
+    let hit = &matches[1];
+    assert_eq!(hit.line, "    // TODO: remove");
+    assert_eq!(hit.ranges, vec![7..11]);
+    assert_eq!(hit.before, vec!["fn main() {", "    let todo = 1;"]);
+    assert_eq!(hit.after, vec!["}"]);
+
+    let all = search(
+        root,
+        ContentSearchOptions {
+            respect_gitignore: false,
+            ..ContentSearchOptions::new("TODO:")
+        },
+    );
+    assert_eq!(
+        all,
+        vec![
+            (PathBuf::from("src/main.rs"), 3),
+            (PathBuf::from("target/out.rs"), 1),
+        ]
+    );
+}
+
+#[test]
+fn test_content_search_globs_and_limits() {
+    let temp_dir = search_tree();
+    let root = temp_dir.path();
+
+    let included = search(
+        root,
+        ContentSearchOptions {
+            include: vec!["*.txt".to_string()],
+            ..ContentSearchOptions::new("todo")
+        },
+    );
+    assert_eq!(included, vec![(PathBuf::from("src/notes.txt"), 1)]);
+
+    let excluded = search(
+        root,
+        ContentSearchOptions {
+            exclude: vec!["*.txt".to_string()],
+            ..ContentSearchOptions::new("todo")
+        },
+    );
+    assert_eq!(excluded.len(), 2);
+
+    let (matches, progress) = ContentSearch::start(
+        &JOBS,
+        root,
+        ContentSearchOptions {
+            max_matches: 1,
+            ..ContentSearchOptions::new("todo")
+        },
+    )
+    .unwrap()
+    .collect()
+    .unwrap();
+    assert_eq!(matches.len(), 1);
+    assert!(progress.truncated);
+
+    assert!(
+        ContentSearch::start(
+            &JOBS,
+            root,
+            ContentSearchOptions {
+                include: vec!["[".to_string()],
+                ..ContentSearchOptions::new("todo")
+            },
+        )
+        .is_err()
+    );
+    assert!(
+        ContentSearch::start(&JOBS, &root.join("missing"), ContentSearchOptions::new("x")).is_err()
+    );
+}
+
+#[test]
+fn test_content_search_cancel() {
+    let temp_dir = search_tree();
+    let search =
+        ContentSearch::start(&JOBS, temp_dir.path(), ContentSearchOptions::new("todo")).unwrap();
+    search.cancel();
+    assert!(search.canceller().is_cancelled());
+    // 中断後は Finished が届かずに終わる場合がある
+    while search.recv().is_some() {}
+}
+
+#[test]
+fn test_content_search_runs_as_job() {
+    let temp_dir = TempDir::new().unwrap();
+    // 受信しない間に検索が送信待ちで止まるよう、チャネルの容量より多く見つける
+    for i in 0..200 {
+        fs::write(temp_dir.path().join(format!("{i:03}.txt")), "todo\n").unwrap();
+    }
+    let jobs = JobManager::new().unwrap();
+    let search =
+        ContentSearch::start(&jobs, temp_dir.path(), ContentSearchOptions::new("todo")).unwrap();
+    let id = search.job_id();
+    assert_eq!(
+        jobs.jobs()[0].kind,
+        JobKind::ContentSearch {
+            root: temp_dir.path().to_path_buf(),
+            pattern: "todo".to_string(),
+        }
+    );
+
+    // ジョブの一覧からの中止は検索側に届く
+    jobs.cancel(id).unwrap();
+    let mut cancelled = false;
+    while let Some(event) = search.recv() {
+        if let ContentSearchEvent::Cancelled(progress) = event {
+            assert!(progress.searched_files < 200);
+            cancelled = true;
+        }
+    }
+    assert!(cancelled);
+    assert_eq!(jobs.block_on(jobs.wait(id)), Some(JobStatus::Cancelled));
+
+    // 最後まで検索したジョブは完了になる
+    let search =
+        ContentSearch::start(&jobs, temp_dir.path(), ContentSearchOptions::new("todo")).unwrap();
+    let id = search.job_id();
+    let (matches, _) = search.collect().unwrap();
+    assert_eq!(matches.len(), 200);
+    assert_eq!(jobs.block_on(jobs.wait(id)), Some(JobStatus::Completed));
+    assert_eq!(jobs.progress(id).unwrap().processed_files, 200);
+}
@@ -1,8 +1,13 @@
 //! ファイルシステムAPIのテスト
 
-use crate::filesystem::{FileSystemApi, FileSystemManager, FileType};
+use crate::filesystem::{
+    CacheConfig, CachedFileSystemManager, FileSystemApi, FileSystemManager, FileType,
+};
+use crate::listing::{ListingEvent, ListingOptions};
+use rust_explorer_utils::AppError;
 use std::fs;
 use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
 use tempfile::TempDir;
 use tokio;
 
@@ -167,3 +172,384 @@ async fn test_nested_directory_access() {
         .unwrap();
     assert_eq!(nested_file.file_type, FileType::File);
 }
+
+#[tokio::test]
+async fn test_copy_file() {
+    let temp_dir = create_test_structure().await.unwrap();
+    let manager = FileSystemManager::new();
+    let from = temp_dir.path().join("test_file.txt");
+    let to = temp_dir.path().join("copied.txt");
+
+    manager.copy(&from, &to).await.unwrap();
+
+    assert!(from.exists());
+    assert_eq!(fs::read_to_string(&to).unwrap(), "test content");
+}
+
+#[tokio::test]
+async fn test_copy_directory_recursive() {
+    let temp_dir = create_test_structure().await.unwrap();
+    let manager = FileSystemManager::new();
+    let from = temp_dir.path().join("test_dir");
+    let to = temp_dir.path().join("copied_dir");
+
+    manager.copy(&from, &to).await.unwrap();
+
+    assert_eq!(
+        fs::read_to_string(to.join("nested_file.txt")).unwrap(),
+        "nested content"
+    );
+    assert!(from.join("nested_file.txt").exists());
+}
+
+#[tokio::test]
+async fn test_copy_does_not_overwrite() {
+    let temp_dir = create_test_structure().await.unwrap();
+    let manager = FileSystemManager::new();
+    let from = temp_dir.path().join("test_file.txt");
+    let to = temp_dir.path().join(".hidden_file");
+
+    let result = manager.copy(&from, &to).await;
+    assert!(matches!(result, Err(AppError::AlreadyExists(_))));
+    assert_eq!(fs::read_to_string(&to).unwrap(), "hidden content");
+}
+
+#[tokio::test]
+async fn test_copy_directory_into_itself() {
+    let temp_dir = create_test_structure().await.unwrap();
+    let manager = FileSystemManager::new();
+    let from = temp_dir.path().join("test_dir");
+    let to = from.join("inner");
+
+    let result = manager.copy(&from, &to).await;
+    assert!(matches!(result, Err(AppError::InvalidOperation(_))));
+}
+
+#[tokio::test]
+async fn test_copy_missing_source() {
+    let temp_dir = TempDir::new().unwrap();
+    let manager = FileSystemManager::new();
+
+    let result = manager
+        .copy(
+            &temp_dir.path().join("missing.txt"),
+            &temp_dir.path().join("out.txt"),
+        )
+        .await;
+    assert!(matches!(result, Err(AppError::NotFound(_))));
+}
+
+#[tokio::test]
+async fn test_move_item() {
+    let temp_dir = create_test_structure().await.unwrap();
+    let manager = FileSystemManager::new();
+    let from = temp_dir.path().join("test_dir");
+    let to = temp_dir.path().join("moved_dir");
+
+    manager.move_item(&from, &to).await.unwrap();
+
+    assert!(!from.exists());
+    assert!(to.join("nested_file.txt").exists());
+}
+
+#[tokio::test]
+async fn test_delete_file_and_directory() {
+    let temp_dir = create_test_structure().await.unwrap();
+    let manager = FileSystemManager::new();
+
+    let file_path = temp_dir.path().join("test_file.txt");
+    manager.delete(&file_path, false).await.unwrap();
+    assert!(!file_path.exists());
+
+    // 空でないディレクトリは非再帰では削除できない
+    let dir_path = temp_dir.path().join("test_dir");
+    let result = manager.delete(&dir_path, false).await;
+    assert!(matches!(result, Err(AppError::DirectoryNotEmpty(_))));
+    assert!(dir_path.exists());
+
+    manager.delete(&dir_path, true).await.unwrap();
+    assert!(!dir_path.exists());
+}
+
+#[tokio::test]
+async fn test_rename() {
+    let temp_dir = create_test_structure().await.unwrap();
+    let manager = FileSystemManager::new();
+    let path = temp_dir.path().join("test_file.txt");
+
+    let new_path = manager.rename(&path, "renamed.txt").await.unwrap();
+
+    assert_eq!(new_path, temp_dir.path().join("renamed.txt"));
+    assert!(new_path.exists());
+    assert!(!path.exists());
+}
+
+#[tokio::test]
+async fn test_rename_invalid_or_existing_name() {
+    let temp_dir = create_test_structure().await.unwrap();
+    let manager = FileSystemManager::new();
+    let path = temp_dir.path().join("test_file.txt");
+
+    let result = manager.rename(&path, "a/b.txt").await;
+    assert!(matches!(result, Err(AppError::InvalidInput(_))));
+
+    let result = manager.rename(&path, "").await;
+    assert!(matches!(result, Err(AppError::InvalidInput(_))));
+
+    let result = manager.rename(&path, "test_dir").await;
+    assert!(matches!(result, Err(AppError::AlreadyExists(_))));
+    assert!(path.exists());
+}
+
+#[tokio::test]
+async fn test_create_directory() {
+    let temp_dir = TempDir::new().unwrap();
+    let manager = FileSystemManager::new();
+    let path = temp_dir.path().join("new_dir");
+
+    manager.create_directory(&path).await.unwrap();
+    assert!(path.is_dir());
+
+    let result = manager.create_directory(&path).await;
+    assert!(matches!(result, Err(AppError::AlreadyExists(_))));
+
+    let result = manager
+        .create_directory(&temp_dir.path().join("missing").join("child"))
+        .await;
+    assert!(matches!(result, Err(AppError::NotFound(_))));
+}
+
+#[tokio::test]
+async fn test_cached_listing_hits_and_misses() {
+    let temp_dir = create_test_structure().await.unwrap();
+    let manager = CachedFileSystemManager::new();
+
+    let first = manager.list_directory(temp_dir.path()).await.unwrap();
+    let second = manager.list_directory(temp_dir.path()).await.unwrap();
+    assert_eq!(first.len(), second.len());
+
+    let stats = manager.stats();
+    assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+    assert_eq!(stats.hit_rate(), 0.5);
+}
+
+#[tokio::test]
+async fn test_cached_listing_revalidates_on_mtime_change() {
+    let temp_dir = create_test_structure().await.unwrap();
+    let manager = CachedFileSystemManager::new();
+    manager.list_directory(temp_dir.path()).await.unwrap();
+
+    // API を経由しない変更はディレクトリの更新日時で検出する
+    fs::write(temp_dir.path().join("external.txt"), "x").unwrap();
+    fs::File::open(temp_dir.path())
+        .unwrap()
+        .set_modified(SystemTime::now() + Duration::from_secs(10))
+        .unwrap();
+
+    let entries = manager.list_directory(temp_dir.path()).await.unwrap();
+    assert!(entries.iter().any(|e| e.name == "external.txt"));
+    assert_eq!(manager.stats().stale, 1);
+    assert_eq!(manager.stats().hits, 0);
+}
+
+#[tokio::test]
+async fn test_cached_listing_expires_after_ttl() {
+    let temp_dir = create_test_structure().await.unwrap();
+    let manager = CachedFileSystemManager::with_config(CacheConfig {
+        ttl: Duration::ZERO,
+        ..CacheConfig::default()
+    });
+
+    manager.list_directory(temp_dir.path()).await.unwrap();
+    manager.list_directory(temp_dir.path()).await.unwrap();
+    assert_eq!(manager.stats().hits, 0);
+    assert_eq!(manager.stats().misses, 2);
+}
+
+#[tokio::test]
+async fn test_cached_listing_evicts_least_recently_used() {
+    let temp_dir = TempDir::new().unwrap();
+    let dirs: Vec<PathBuf> = ["a", "b", "c"]
+        .iter()
+        .map(|name| temp_dir.path().join(name))
+        .collect();
+    for dir in &dirs {
+        fs::create_dir(dir).unwrap();
+    }
+    let manager = CachedFileSystemManager::with_config(CacheConfig {
+        max_entries: 2,
+        ..CacheConfig::default()
+    });
+
+    manager.list_directory(&dirs[0]).await.unwrap();
+    manager.list_directory(&dirs[1]).await.unwrap();
+    manager.list_directory(&dirs[0]).await.unwrap();
+    manager.list_directory(&dirs[2]).await.unwrap();
+    assert_eq!(manager.stats().evictions, 1);
+
+    manager.reset_stats();
+    manager.list_directory(&dirs[0]).await.unwrap();
+    manager.list_directory(&dirs[1]).await.unwrap();
+    let stats = manager.stats();
+    assert_eq!((stats.hits, stats.misses), (1, 1));
+}
+
+#[tokio::test]
+async fn test_cache_invalidation() {
+    let temp_dir = create_test_structure().await.unwrap();
+    let nested = temp_dir.path().join("test_dir");
+    let manager = CachedFileSystemManager::new();
+
+    manager.list_directory(temp_dir.path()).await.unwrap();
+    manager.list_directory(&nested).await.unwrap();
+
+    manager.invalidate(&nested);
+    assert_eq!(manager.stats().entries, 1);
+
+    manager.list_directory(&nested).await.unwrap();
+    manager.invalidate_subtree(temp_dir.path());
+    assert_eq!(manager.stats().entries, 0);
+}
+
+#[tokio::test]
+async fn test_cache_invalidated_by_mutations() {
+    let temp_dir = create_test_structure().await.unwrap();
+    let manager = CachedFileSystemManager::new();
+    let root = temp_dir.path();
+    manager.list_directory(root).await.unwrap();
+
+    manager
+        .create_directory(&root.join("created"))
+        .await
+        .unwrap();
+    let entries = manager.list_directory(root).await.unwrap();
+    assert!(entries.iter().any(|e| e.name == "created"));
+
+    manager
+        .rename(&root.join("test_file.txt"), "renamed.txt")
+        .await
+        .unwrap();
+    let entries = manager.list_directory(root).await.unwrap();
+    assert!(entries.iter().any(|e| e.name == "renamed.txt"));
+    assert!(!entries.iter().any(|e| e.name == "test_file.txt"));
+
+    manager
+        .list_directory(&root.join("test_dir"))
+        .await
+        .unwrap();
+    manager.delete(&root.join("test_dir"), true).await.unwrap();
+    let entries = manager.list_directory(root).await.unwrap();
+    assert!(!entries.iter().any(|e| e.name == "test_dir"));
+    assert!(
+        manager
+            .list_directory(&root.join("test_dir"))
+            .await
+            .is_err()
+    );
+
+    assert_eq!(manager.stats().hits, 0);
+}
+
+/// 指定数のファイルを持つディレクトリを作成
+fn create_large_directory(count: usize) -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    for i in 0..count {
+        fs::write(temp_dir.path().join(format!("file_{i:04}.txt")), "data").unwrap();
+    }
+    temp_dir
+}
+
+#[test]
+fn test_streaming_listing_batches() {
+    let temp_dir = create_large_directory(100);
+    let manager = FileSystemManager::new();
+    let stream = manager
+        .list_directory_streaming(
+            temp_dir.path(),
+            ListingOptions {
+                initial_batch_size: 10,
+                max_batch_size: 40,
+                load_metadata: true,
+            },
+        )
+        .unwrap();
+
+    let mut batch_sizes = Vec::new();
+    let mut metadata_count = 0;
+    let mut finished = None;
+    while let Some(event) = stream.recv() {
+        match event {
+            ListingEvent::Entries(batch) => {
+                // 名前の一覧の段階ではメタデータを読み込まない
+                assert!(batch.iter().all(|e| e.size == 0 && e.modified.is_none()));
+                assert!(batch.iter().all(|e| e.file_type == FileType::File));
+                batch_sizes.push(batch.len());
+            }
+            ListingEvent::Metadata(batch) => {
+                assert!(batch.iter().all(|e| e.size == 4 && e.modified.is_some()));
+                metadata_count += batch.len();
+            }
+            ListingEvent::Failed(error) => panic!("listing failed: {error}"),
+            ListingEvent::Finished { total } => finished = Some(total),
+        }
+    }
+
+    assert_eq!(batch_sizes, vec![10, 20, 40, 30]);
+    assert_eq!(metadata_count, 100);
+    assert_eq!(finished, Some(100));
+}
+
+#[tokio::test]
+async fn test_streaming_listing_collect() {
+    let temp_dir = create_test_structure().await.unwrap();
+    let manager = FileSystemManager::new();
+
+    let mut entries = manager
+        .list_directory_streaming(temp_dir.path(), ListingOptions::default())
+        .unwrap()
+        .collect()
+        .unwrap();
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, vec![".hidden_file", "test_dir", "test_file.txt"]);
+    assert_eq!(entries[2].size, 12);
+}
+
+#[test]
+fn test_streaming_listing_cancel() {
+    let temp_dir = create_large_directory(300);
+    let manager = FileSystemManager::new();
+    let stream = manager
+        .list_directory_streaming(
+            temp_dir.path(),
+            ListingOptions {
+                initial_batch_size: 1,
+                max_batch_size: 1,
+                load_metadata: true,
+            },
+        )
+        .unwrap();
+
+    assert!(matches!(stream.recv(), Some(ListingEvent::Entries(_))));
+    stream.cancel();
+
+    // 中断後はバッファ済みの分だけが届き、完了は通知されない
+    let remaining: Vec<ListingEvent> = std::iter::from_fn(|| stream.recv()).collect();
+    assert!(remaining.len() < 300);
+    assert!(
+        !remaining
+            .iter()
+            .any(|e| matches!(e, ListingEvent::Finished { .. }))
+    );
+}
+
+#[test]
+fn test_streaming_listing_invalid_path() {
+    let manager = FileSystemManager::new();
+    let result = manager.list_directory_streaming(
+        &PathBuf::from("/nonexistent/path"),
+        ListingOptions::default(),
+    );
+    assert!(matches!(result, Err(AppError::InvalidPath(_))));
+}
@@ -0,0 +1,168 @@
+//! 訪れた場所の履歴のテスト
+
+use crate::history::{HistoryExclusions, LocationHistory, MAX_HISTORY_ENTRIES, VisitKind};
+use crate::quick_open::{QuickOpen, QuickOpenSource};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use std::path::{Path, PathBuf};
+
+fn now() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap()
+}
+
+fn paths(entries: &[&crate::history::HistoryEntry]) -> Vec<PathBuf> {
+    entries.iter().map(|entry| entry.path.clone()).collect()
+}
+
+#[test]
+fn test_history_record_and_frecency() {
+    let none = HistoryExclusions::default();
+    let mut history = LocationHistory::new();
+    let old = now() - Duration::days(60);
+
+    // 以前によく訪れた場所
+    for _ in 0..5 {
+        assert!(history.record(Path::new("/old"), VisitKind::Directory, old, &none));
+    }
+    // 最近1度だけ訪れた場所
+    history.record(Path::new("/new"), VisitKind::Directory, now(), &none);
+    history.record(Path::new("/file.txt"), VisitKind::File, now(), &none);
+    assert!(!history.record(Path::new("relative"), VisitKind::Directory, now(), &none));
+
+    let old_entry = history.get(Path::new("/old")).unwrap();
+    assert_eq!(old_entry.visit_count, 5);
+    assert_eq!(old_entry.last_visited, old);
+    assert!((old_entry.frecency(now()) - 1.5).abs() < 1e-9);
+    assert!((history.get(Path::new("/new")).unwrap().frecency(now()) - 1.0).abs() < 1e-9);
+
+    assert_eq!(
+        paths(&history.top(10, None, now())),
+        vec![
+            PathBuf::from("/old"),
+            PathBuf::from("/new"),
+            PathBuf::from("/file.txt"),
+        ]
+    );
+    assert_eq!(
+        paths(&history.top(10, Some(VisitKind::File), now())),
+        vec![PathBuf::from("/file.txt")]
+    );
+    assert_eq!(history.top(1, None, now()).len(), 1);
+
+    // 時間が経つと新しい場所が上位になる
+    let later = now() + Duration::days(200);
+    for _ in 0..3 {
+        history.record(Path::new("/new"), VisitKind::Directory, later, &none);
+    }
+    assert_eq!(history.top(1, None, later)[0].path, PathBuf::from("/new"));
+}
+
+#[test]
+fn test_history_sampled_visits_are_bounded() {
+    let none = HistoryExclusions::default();
+    let mut history = LocationHistory::new();
+    for day in 0..30 {
+        let at = now() - Duration::days(day);
+        history.record(Path::new("/a"), VisitKind::Directory, at, &none);
+    }
+    let entry = history.get(Path::new("/a")).unwrap();
+    assert_eq!(entry.visit_count, 30);
+    assert_eq!(entry.recent_visits.len(), 10);
+    assert_eq!(entry.last_visited, now());
+    assert_eq!(
+        *entry.recent_visits.first().unwrap(),
+        now() - Duration::days(9)
+    );
+}
+
+#[test]
+fn test_history_exclusions() {
+    let exclusions = HistoryExclusions::new(&["/tmp", "**/node_modules", " ", "/home/*/.cache"]);
+    assert!(exclusions.is_excluded(Path::new("/tmp")));
+    assert!(exclusions.is_excluded(Path::new("/tmp/build/out")));
+    assert!(!exclusions.is_excluded(Path::new("/tmpfiles")));
+    assert!(exclusions.is_excluded(Path::new("/src/app/node_modules")));
+    assert!(exclusions.is_excluded(Path::new("/src/app/node_modules/react/index.js")));
+    assert!(exclusions.is_excluded(Path::new("/home/u/.cache/x")));
+    assert!(!exclusions.is_excluded(Path::new("/home/u/docs")));
+
+    let mut history = LocationHistory::new();
+    let none = HistoryExclusions::default();
+    assert!(!history.record(Path::new("/tmp/x"), VisitKind::File, now(), &exclusions));
+    history.record(Path::new("/tmp/y"), VisitKind::File, now(), &none);
+    history.record(
+        Path::new("/home/u/docs"),
+        VisitKind::Directory,
+        now(),
+        &none,
+    );
+    assert!(history.forget_excluded(&exclusions));
+    assert!(!history.forget_excluded(&exclusions));
+    assert_eq!(history.entries().len(), 1);
+}
+
+#[test]
+fn test_history_remove_clear_relocate() {
+    let none = HistoryExclusions::default();
+    let mut history = LocationHistory::new();
+    history.record(Path::new("/a/project"), VisitKind::Directory, now(), &none);
+    history.record(
+        Path::new("/a/project/src"),
+        VisitKind::Directory,
+        now(),
+        &none,
+    );
+    history.record(Path::new("/b/renamed"), VisitKind::Directory, now(), &none);
+    history.record(Path::new("/c"), VisitKind::Directory, now(), &none);
+
+    assert!(history.relocate(Path::new("/a/project"), Path::new("/b/renamed")));
+    // 付け替え先の記録と訪問を合わせる
+    assert_eq!(history.get(Path::new("/b/renamed")).unwrap().visit_count, 2);
+    assert!(history.get(Path::new("/b/renamed/src")).is_some());
+    assert!(history.get(Path::new("/a/project")).is_none());
+    assert!(!history.relocate(Path::new("/missing"), Path::new("/x")));
+
+    assert!(history.remove(Path::new("/c")));
+    assert!(!history.remove(Path::new("/c")));
+    assert_eq!(history.entries().len(), 2);
+
+    let json = serde_json::to_string(&history).unwrap();
+    let restored: LocationHistory = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, history);
+    let empty: LocationHistory = serde_json::from_str("{}").unwrap();
+    assert!(empty.entries().is_empty());
+
+    history.clear();
+    assert!(history.entries().is_empty());
+}
+
+#[test]
+fn test_history_forgets_least_used_beyond_limit() {
+    let none = HistoryExclusions::default();
+    let mut history = LocationHistory::new();
+    let old = now() - Duration::days(365);
+    history.record(Path::new("/stale"), VisitKind::Directory, old, &none);
+    for i in 0..MAX_HISTORY_ENTRIES {
+        history.record(
+            &PathBuf::from(format!("/p{i}")),
+            VisitKind::Directory,
+            now(),
+            &none,
+        );
+    }
+    assert_eq!(history.entries().len(), MAX_HISTORY_ENTRIES);
+    assert!(history.get(Path::new("/stale")).is_none());
+}
+
+#[test]
+fn test_quick_open_includes_history() {
+    let none = HistoryExclusions::default();
+    let mut history = LocationHistory::new();
+    history.record(Path::new("/srv/report.pdf"), VisitKind::File, now(), &none);
+
+    let mut quick_open = QuickOpen::new();
+    quick_open.add_history(&history, now());
+    let found = quick_open.search("report", None, 10);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].source, QuickOpenSource::Recent);
+    assert!(!found[0].is_dir);
+}
@@ -0,0 +1,412 @@
+//! ファイル操作ジョブのテスト
+
+use crate::conflict::{ConflictDecision, ConflictPolicy, ConflictResolution};
+use crate::job::{JobEvent, JobKind, JobManager, JobStatus};
+use crate::trash::TrashManager;
+use std::fs;
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+use tokio::runtime::Handle;
+
+/// コピー元とコピー先ディレクトリを作成
+fn create_job_structure() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let src = temp_dir.path().join("src");
+    fs::create_dir_all(src.join("nested")).unwrap();
+    fs::write(src.join("a.txt"), "aaaa").unwrap();
+    fs::write(src.join("nested").join("b.txt"), "bbbbbbbb").unwrap();
+    fs::create_dir(temp_dir.path().join("dest")).unwrap();
+    temp_dir
+}
+
+#[tokio::test]
+async fn test_copy_job_completes_with_progress() {
+    let temp_dir = create_job_structure();
+    let manager = JobManager::with_handle(Handle::current(), 1);
+
+    let id = manager.submit(JobKind::Copy {
+        sources: vec![temp_dir.path().join("src")],
+        destination: temp_dir.path().join("dest"),
+    });
+
+    assert_eq!(manager.wait(id).await, Some(JobStatus::Completed));
+
+    let copied = temp_dir.path().join("dest").join("src");
+    assert_eq!(fs::read_to_string(copied.join("a.txt")).unwrap(), "aaaa");
+    assert!(copied.join("nested").join("b.txt").exists());
+    assert!(temp_dir.path().join("src").exists());
+
+    let progress = manager.progress(id).unwrap();
+    assert_eq!(progress.total_bytes, 12);
+    assert_eq!(progress.processed_bytes, 12);
+    // src, nested, a.txt, b.txt
+    assert_eq!(progress.total_files, 4);
+    assert_eq!(progress.processed_files, 4);
+    assert_eq!(progress.fraction(), 1.0);
+}
+
+#[tokio::test]
+async fn test_move_job() {
+    let temp_dir = create_job_structure();
+    let manager = JobManager::with_handle(Handle::current(), 1);
+    let moved = Arc::new(Mutex::new(Vec::new()));
+    let moved_clone = Arc::clone(&moved);
+    manager
+        .on_job_event(move |event| {
+            if let JobEvent::Moved { from, to, .. } = event {
+                moved_clone.lock().unwrap().push((from.clone(), to.clone()));
+            }
+        })
+        .unwrap();
+
+    let id = manager.submit(JobKind::Move {
+        sources: vec![temp_dir.path().join("src").join("a.txt")],
+        destination: temp_dir.path().join("dest"),
+    });
+
+    assert_eq!(manager.wait(id).await, Some(JobStatus::Completed));
+    assert!(!temp_dir.path().join("src").join("a.txt").exists());
+    assert!(temp_dir.path().join("dest").join("a.txt").exists());
+    // 移動した項目ごとに移動先を通知する
+    assert_eq!(
+        *moved.lock().unwrap(),
+        vec![(
+            temp_dir.path().join("src").join("a.txt"),
+            temp_dir.path().join("dest").join("a.txt")
+        )]
+    );
+}
+
+#[tokio::test]
+async fn test_delete_job() {
+    let temp_dir = create_job_structure();
+    let manager = JobManager::with_handle(Handle::current(), 1);
+
+    let id = manager.submit(JobKind::Delete {
+        paths: vec![temp_dir.path().join("src")],
+    });
+
+    assert_eq!(manager.wait(id).await, Some(JobStatus::Completed));
+    assert!(!temp_dir.path().join("src").exists());
+    assert_eq!(manager.progress(id).unwrap().processed_files, 4);
+}
+
+#[tokio::test]
+async fn test_trash_job() {
+    let temp_dir = create_job_structure();
+    let trash = TrashManager::with_data_home(temp_dir.path().join("data"));
+    let manager = JobManager::with_handle(Handle::current(), 1).with_trash_manager(trash);
+
+    let id = manager.submit(JobKind::Trash {
+        paths: vec![temp_dir.path().join("src")],
+    });
+
+    assert_eq!(manager.wait(id).await, Some(JobStatus::Completed));
+    assert!(!temp_dir.path().join("src").exists());
+    assert!(
+        manager
+            .trash_manager()
+            .home_trash()
+            .join("files/src/a.txt")
+            .exists()
+    );
+    assert_eq!(manager.progress(id).unwrap().processed_files, 1);
+}
+
+#[tokio::test]
+async fn test_job_fails_when_copying_into_itself() {
+    let temp_dir = create_job_structure();
+    let manager = JobManager::with_handle(Handle::current(), 1);
+
+    let id = manager.submit(JobKind::Copy {
+        sources: vec![temp_dir.path().join("src")],
+        destination: temp_dir.path().join("src").join("nested"),
+    });
+
+    assert!(matches!(manager.wait(id).await, Some(JobStatus::Failed(_))));
+}
+
+#[tokio::test]
+async fn test_conflict_policies() {
+    let temp_dir = create_job_structure();
+    let existing = temp_dir.path().join("dest").join("a.txt");
+    fs::write(&existing, "existing").unwrap();
+    let manager = JobManager::with_handle(Handle::current(), 1);
+    let kind = JobKind::Copy {
+        sources: vec![temp_dir.path().join("src").join("a.txt")],
+        destination: temp_dir.path().join("dest"),
+    };
+
+    let id = manager.submit_with_policy(
+        kind.clone(),
+        ConflictPolicy::Always(ConflictResolution::Skip),
+    );
+    assert_eq!(manager.wait(id).await, Some(JobStatus::Completed));
+    assert_eq!(fs::read_to_string(&existing).unwrap(), "existing");
+
+    let id = manager.submit_with_policy(
+        kind.clone(),
+        ConflictPolicy::Always(ConflictResolution::Rename),
+    );
+    assert_eq!(manager.wait(id).await, Some(JobStatus::Completed));
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("dest").join("a (2).txt")).unwrap(),
+        "aaaa"
+    );
+
+    // 既存ファイル（8バイト）の方が大きいので残る
+    let id = manager.submit_with_policy(
+        kind.clone(),
+        ConflictPolicy::Always(ConflictResolution::KeepLarger),
+    );
+    assert_eq!(manager.wait(id).await, Some(JobStatus::Completed));
+    assert_eq!(fs::read_to_string(&existing).unwrap(), "existing");
+
+    let id =
+        manager.submit_with_policy(kind, ConflictPolicy::Always(ConflictResolution::Overwrite));
+    assert_eq!(manager.wait(id).await, Some(JobStatus::Completed));
+    assert_eq!(fs::read_to_string(&existing).unwrap(), "aaaa");
+}
+
+#[tokio::test]
+async fn test_conflict_asks_and_applies_to_all() {
+    let temp_dir = create_job_structure();
+    let dest = temp_dir.path().join("dest");
+    fs::write(temp_dir.path().join("src").join("c.txt"), "cccc").unwrap();
+    fs::write(dest.join("a.txt"), "old a").unwrap();
+    fs::write(dest.join("c.txt"), "old c").unwrap();
+
+    let manager = JobManager::with_handle(Handle::current(), 1);
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    manager
+        .on_job_event(move |event| {
+            if let JobEvent::ConflictDetected { conflict, .. } = event {
+                let _ = sender.send(conflict.clone());
+            }
+        })
+        .unwrap();
+
+    let id = manager.submit(JobKind::Copy {
+        sources: vec![
+            temp_dir.path().join("src").join("a.txt"),
+            temp_dir.path().join("src").join("c.txt"),
+        ],
+        destination: dest.clone(),
+    });
+
+    let conflict = receiver.recv().await.unwrap();
+    assert_eq!(conflict.destination, dest.join("a.txt"));
+    assert_eq!(manager.status(id), Some(JobStatus::WaitingForDecision));
+
+    manager
+        .resolve_conflict(
+            id,
+            ConflictDecision {
+                resolution: ConflictResolution::Overwrite,
+                apply_to_all: true,
+            },
+        )
+        .unwrap();
+
+    // 「すべてに適用」なので2件目の競合では確認されない
+    assert_eq!(manager.wait(id).await, Some(JobStatus::Completed));
+    assert!(receiver.try_recv().is_err());
+    assert_eq!(fs::read_to_string(dest.join("a.txt")).unwrap(), "aaaa");
+    assert_eq!(fs::read_to_string(dest.join("c.txt")).unwrap(), "cccc");
+}
+
+#[tokio::test]
+async fn test_overwrite_merges_directories() {
+    let temp_dir = create_job_structure();
+    let existing = temp_dir.path().join("dest").join("src");
+    fs::create_dir(&existing).unwrap();
+    fs::write(existing.join("a.txt"), "old a").unwrap();
+    fs::write(existing.join("keep.txt"), "keep").unwrap();
+    // ファイルをディレクトリで置き換える
+    fs::write(existing.join("nested"), "file").unwrap();
+
+    let manager = JobManager::with_handle(Handle::current(), 1);
+    let id = manager.submit_with_policy(
+        JobKind::Move {
+            sources: vec![temp_dir.path().join("src")],
+            destination: temp_dir.path().join("dest"),
+        },
+        ConflictPolicy::Always(ConflictResolution::Overwrite),
+    );
+    assert_eq!(manager.wait(id).await, Some(JobStatus::Completed));
+
+    assert_eq!(fs::read_to_string(existing.join("a.txt")).unwrap(), "aaaa");
+    assert_eq!(
+        fs::read_to_string(existing.join("keep.txt")).unwrap(),
+        "keep"
+    );
+    assert_eq!(
+        fs::read_to_string(existing.join("nested").join("b.txt")).unwrap(),
+        "bbbbbbbb"
+    );
+    assert!(!temp_dir.path().join("src").exists());
+    // 一時的な名前のファイルは残らない
+    let names: Vec<_> = fs::read_dir(&existing)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert_eq!(names.len(), 3, "{:?}", names);
+}
+
+#[tokio::test]
+async fn test_cancelled_overwrite_keeps_destination() {
+    let temp_dir = create_job_structure();
+    let dest = temp_dir.path().join("dest");
+    fs::write(dest.join("a.txt"), "old a").unwrap();
+
+    let manager = JobManager::with_handle(Handle::current(), 1);
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    manager
+        .on_job_event(move |event| {
+            if let JobEvent::ConflictDetected { id, .. } = event {
+                let _ = sender.send(*id);
+            }
+        })
+        .unwrap();
+
+    let id = manager.submit(JobKind::Copy {
+        sources: vec![temp_dir.path().join("src").join("a.txt")],
+        destination: dest.clone(),
+    });
+    assert_eq!(receiver.recv().await, Some(id));
+    // 上書きを選んだ後、転送前に止めてキャンセルする
+    manager.pause(id).unwrap();
+    manager
+        .resolve_conflict(
+            id,
+            ConflictDecision {
+                resolution: ConflictResolution::Overwrite,
+                apply_to_all: false,
+            },
+        )
+        .unwrap();
+    manager.cancel(id).unwrap();
+    assert_eq!(manager.wait(id).await, Some(JobStatus::Cancelled));
+
+    assert_eq!(fs::read_to_string(dest.join("a.txt")).unwrap(), "old a");
+    assert_eq!(fs::read_dir(&dest).unwrap().count(), 1);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_failed_copy_leaves_no_partial_destination() {
+    let temp_dir = create_job_structure();
+    let dest = temp_dir.path().join("dest");
+    // ソケットは開けないため、フォルダの途中でコピーが失敗する
+    let _socket = std::os::unix::net::UnixListener::bind(
+        temp_dir.path().join("src").join("nested").join("socket"),
+    )
+    .unwrap();
+
+    let manager = JobManager::with_handle(Handle::current(), 1);
+    let id = manager.submit(JobKind::Copy {
+        sources: vec![temp_dir.path().join("src")],
+        destination: dest.clone(),
+    });
+    assert!(matches!(manager.wait(id).await, Some(JobStatus::Failed(_))));
+    assert_eq!(fs::read_dir(&dest).unwrap().count(), 0);
+}
+
+#[tokio::test]
+async fn test_cancel_while_waiting_for_decision() {
+    let temp_dir = create_job_structure();
+    let dest = temp_dir.path().join("dest");
+    fs::write(dest.join("a.txt"), "old a").unwrap();
+
+    let manager = JobManager::with_handle(Handle::current(), 1);
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+    manager
+        .on_job_event(move |event| {
+            if let JobEvent::ConflictDetected { id, .. } = event {
+                let _ = sender.send(*id);
+            }
+        })
+        .unwrap();
+
+    let id = manager.submit(JobKind::Move {
+        sources: vec![temp_dir.path().join("src").join("a.txt")],
+        destination: dest.clone(),
+    });
+
+    assert_eq!(receiver.recv().await, Some(id));
+    manager.cancel(id).unwrap();
+    assert_eq!(manager.wait(id).await, Some(JobStatus::Cancelled));
+    assert_eq!(fs::read_to_string(dest.join("a.txt")).unwrap(), "old a");
+    assert!(temp_dir.path().join("src").join("a.txt").exists());
+}
+
+#[tokio::test]
+async fn test_pause_resume_and_cancel() {
+    let temp_dir = create_job_structure();
+    let manager = JobManager::with_handle(Handle::current(), 1);
+    let kind = JobKind::Copy {
+        sources: vec![temp_dir.path().join("src")],
+        destination: temp_dir.path().join("dest"),
+    };
+
+    // 一時停止したままキャンセルすると何もコピーされない
+    let cancelled = manager.submit(kind.clone());
+    manager.pause(cancelled).unwrap();
+    manager.cancel(cancelled).unwrap();
+    assert_eq!(manager.wait(cancelled).await, Some(JobStatus::Cancelled));
+    assert!(!temp_dir.path().join("dest").join("src").exists());
+
+    // 一時停止後に再開すると完了する
+    let resumed = manager.submit(kind);
+    manager.pause(resumed).unwrap();
+    manager.resume(resumed).unwrap();
+    assert_eq!(manager.wait(resumed).await, Some(JobStatus::Completed));
+    assert!(temp_dir.path().join("dest").join("src").exists());
+
+    assert!(manager.pause(9999).is_err());
+}
+
+#[tokio::test]
+async fn test_job_events() {
+    let temp_dir = create_job_structure();
+    let manager = JobManager::with_handle(Handle::current(), 1);
+    let events = Arc::new(Mutex::new(Vec::new()));
+
+    let events_clone = Arc::clone(&events);
+    manager
+        .on_job_event(move |event| events_clone.lock().unwrap().push(event.clone()))
+        .unwrap();
+
+    let id = manager.submit(JobKind::Copy {
+        sources: vec![temp_dir.path().join("src")],
+        destination: temp_dir.path().join("dest"),
+    });
+    manager.wait(id).await;
+
+    let events = events.lock().unwrap();
+    assert!(matches!(events.first(), Some(JobEvent::Queued { .. })));
+    assert!(matches!(events.get(1), Some(JobEvent::Started(_))));
+    assert!(
+        events
+            .iter()
+            .any(|e| matches!(e, JobEvent::Progress { .. }))
+    );
+    assert!(matches!(events.last(), Some(JobEvent::Completed(_))));
+    assert!(!events.iter().any(|e| matches!(e, JobEvent::Moved { .. })));
+    assert!(events.iter().all(|e| e.job_id() == id));
+}
+
+#[tokio::test]
+async fn test_jobs_listing_and_clear() {
+    let temp_dir = create_job_structure();
+    let manager = JobManager::with_handle(Handle::current(), 1);
+
+    let id = manager.submit(JobKind::Delete {
+        paths: vec![temp_dir.path().join("src").join("a.txt")],
+    });
+    assert_eq!(manager.jobs().len(), 1);
+
+    manager.wait(id).await;
+    manager.clear_finished();
+    assert!(manager.jobs().is_empty());
+}
@@ -1,2 +1,14 @@
+mod bookmark_tests;
+mod content_search_tests;
 mod filesystem_tests;
+mod history_tests;
+mod job_tests;
+mod query_tests;
+mod quick_open_tests;
+mod search_tests;
+mod smart_folder_tests;
 mod state_tests;
+mod tag_tests;
+mod trash_tests;
+mod undo_tests;
+mod watcher_tests;
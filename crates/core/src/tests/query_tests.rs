@@ -0,0 +1,187 @@
+//! クエリのテスト
+
+use crate::file_sorting::{FileSortFilterManager, FilterCriteria};
+use crate::filesystem::{FileEntry, FileType};
+use crate::query::FileQuery;
+use crate::search::SearchIndex;
+use rust_explorer_utils::AppError;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+fn now() -> SystemTime {
+    SystemTime::UNIX_EPOCH + DAY * 20_000
+}
+
+fn entry(path: &str, file_type: FileType, size: u64, age_days: u32) -> FileEntry {
+    let path = PathBuf::from(path);
+    FileEntry {
+        name: path.file_name().unwrap().to_string_lossy().to_string(),
+        path,
+        file_type,
+        size,
+        modified: Some(now() - DAY * age_days),
+    }
+}
+
+fn entries() -> Vec<FileEntry> {
+    vec![
+        entry("/p/src/main.rs", FileType::File, 2_000, 1),
+        entry("/p/Cargo.toml", FileType::File, 500, 30),
+        entry("/p/target/debug/app.rs", FileType::File, 20 << 20, 2),
+        entry("/p/src", FileType::Directory, 4096, 1),
+        entry("/p/notes/meeting notes.md", FileType::File, 100, 400),
+    ]
+}
+
+/// クエリに一致するパス
+fn matching(query: &str) -> Vec<String> {
+    let query = FileQuery::parse_at(query, now()).unwrap();
+    entries()
+        .into_iter()
+        .filter(|entry| query.matches(entry))
+        .map(|entry| entry.path.to_string_lossy().to_string())
+        .collect()
+}
+
+/// 解析エラーのメッセージ
+fn parse_error(query: &str) -> String {
+    match FileQuery::parse_at(query, now()) {
+        Err(AppError::InvalidInput(message)) => message,
+        other => panic!("expected a parse error for {:?}, got {:?}", query, other),
+    }
+}
+
+#[test]
+fn test_query_fields() {
+    assert_eq!(
+        matching("ext:rs,TOML"),
+        vec!["/p/src/main.rs", "/p/Cargo.toml", "/p/target/debug/app.rs"]
+    );
+    assert_eq!(matching("size:>10MB"), vec!["/p/target/debug/app.rs"]);
+    assert_eq!(
+        matching("size:1KB.."),
+        vec!["/p/src/main.rs", "/p/target/debug/app.rs"]
+    );
+    assert_eq!(matching("size:..1KB"), matching("size:<=1KB"));
+    assert_eq!(
+        matching("size:<=500"),
+        vec!["/p/Cargo.toml", "/p/notes/meeting notes.md"]
+    );
+    assert_eq!(
+        matching("modified:<7d"),
+        vec!["/p/src/main.rs", "/p/target/debug/app.rs", "/p/src"]
+    );
+    assert_eq!(matching("modified:>1y"), vec!["/p/notes/meeting notes.md"]);
+    assert_eq!(matching("type:dir"), vec!["/p/src"]);
+    assert_eq!(
+        matching("name:/^(main|app)\\.rs$/"),
+        vec!["/p/src/main.rs", "/p/target/debug/app.rs"]
+    );
+    assert_eq!(
+        matching("\"meeting notes\""),
+        vec!["/p/notes/meeting notes.md"]
+    );
+}
+
+#[test]
+fn test_query_operators() {
+    assert_eq!(matching("ext:rs -path:target"), vec!["/p/src/main.rs"]);
+    assert_eq!(matching("ext:rs NOT path:target"), vec!["/p/src/main.rs"]);
+    assert_eq!(
+        matching("type:dir OR ext:toml"),
+        vec!["/p/Cargo.toml", "/p/src"]
+    );
+    assert_eq!(
+        matching("(ext:md | ext:toml) size:<1KB"),
+        vec!["/p/Cargo.toml", "/p/notes/meeting notes.md"]
+    );
+    // AND は OR より優先
+    assert_eq!(
+        matching("ext:md OR ext:rs AND modified:<7d"),
+        vec![
+            "/p/src/main.rs",
+            "/p/target/debug/app.rs",
+            "/p/notes/meeting notes.md"
+        ]
+    );
+    assert_eq!(matching("").len(), entries().len());
+}
+
+#[test]
+fn test_query_errors_point_at_token() {
+    assert_eq!(
+        parse_error("ext:rs color:red"),
+        "Unknown field at column 8: 'color:red'"
+    );
+    assert_eq!(
+        parse_error("size:>10XB"),
+        "Invalid size at column 1: 'size:>10XB'"
+    );
+    assert_eq!(
+        parse_error("(ext:rs OR"),
+        "Unexpected end of query at column 11: ''"
+    );
+    assert_eq!(
+        parse_error("name:\"open"),
+        "Unterminated quoted phrase at column 1: 'name:\"open'"
+    );
+    assert_eq!(parse_error("a ) b"), "Unexpected token at column 3: ')'");
+    assert_eq!(
+        parse_error("name:/(/"),
+        "Invalid regular expression at column 1: 'name:/(/'"
+    );
+}
+
+#[test]
+fn test_query_slashes_in_paths_are_literal() {
+    // path: の値は / で始まっても正規表現にしない
+    assert_eq!(matching("path:/p/notes"), vec!["/p/notes/meeting notes.md"]);
+    assert_eq!(
+        matching("path:/target/ ext:rs"),
+        vec!["/p/target/debug/app.rs"]
+    );
+    // 閉じる / が語の終わりになければ正規表現ではない
+    assert!(matching("/src/main").is_empty());
+    assert_eq!(matching("(name:/^main/)"), vec!["/p/src/main.rs"]);
+}
+
+#[test]
+fn test_query_in_filter_manager_and_index() {
+    let mut manager = FileSortFilterManager::new();
+    assert!(manager.set_query("ext:").is_err());
+    assert!(!manager.has_active_filters());
+
+    manager.set_query("type:file size:<1KB").unwrap();
+    assert!(manager.has_active_filters());
+    assert_eq!(
+        manager.filter_criteria().query.as_deref(),
+        Some("type:file size:<1KB")
+    );
+    let mut listed = entries();
+    manager.process_entries(&mut listed);
+    assert_eq!(listed.len(), 2);
+
+    // 保存していた条件からも復元する
+    let mut restored = FileSortFilterManager::new();
+    restored.update_filter_criteria(manager.filter_criteria().clone());
+    assert!(restored.has_active_filters());
+    restored.update_filter_criteria(FilterCriteria {
+        query: Some("size:>>".to_string()),
+        ..Default::default()
+    });
+    assert!(!restored.has_active_filters());
+
+    let mut index = SearchIndex::new();
+    for entry in entries() {
+        index.upsert(entry);
+    }
+    let query = FileQuery::parse_at("ext:rs -path:target", now()).unwrap();
+    let found: Vec<_> = index
+        .query(&query, 10)
+        .into_iter()
+        .map(|e| e.path)
+        .collect();
+    assert_eq!(found, vec![PathBuf::from("/p/src/main.rs")]);
+}
@@ -0,0 +1,181 @@
+//! クイックオープンのテスト
+
+use crate::filesystem::{FileEntry, FileType};
+use crate::quick_open::{
+    MAX_INDEXED_CANDIDATES, QuickOpen, QuickOpenCandidate, QuickOpenSource, fuzzy_match,
+};
+use crate::search::SearchIndex;
+use crate::state::{AppState, TabState};
+use std::path::{Path, PathBuf};
+
+fn entry(path: &str, file_type: FileType) -> FileEntry {
+    let path = PathBuf::from(path);
+    FileEntry {
+        name: path.file_name().unwrap().to_string_lossy().to_string(),
+        path,
+        file_type,
+        size: 0,
+        modified: None,
+    }
+}
+
+fn candidate(path: &str, source: QuickOpenSource, frecency: f64) -> QuickOpenCandidate {
+    QuickOpenCandidate {
+        path: PathBuf::from(path),
+        source,
+        is_dir: true,
+        frecency,
+    }
+}
+
+fn found(quick_open: &QuickOpen, pattern: &str, index: Option<&SearchIndex>) -> Vec<String> {
+    quick_open
+        .search(pattern, index, 10)
+        .into_iter()
+        .map(|m| m.path.to_string_lossy().to_string())
+        .collect()
+}
+
+#[test]
+fn test_fuzzy_match_scoring() {
+    let found = fuzzy_match("fb", "foo_bar").unwrap();
+    assert_eq!(found.positions, vec![0, 4]);
+    assert!(fuzzy_match("xyz", "foo_bar").is_none());
+    assert!(fuzzy_match("BAR", "foo_bar").is_some());
+
+    // 単語の先頭・大文字の始まり・連続した一致ほど高い
+    let boundary = fuzzy_match("mr", "main.rs").unwrap().score;
+    let inside = fuzzy_match("mr", "summary").unwrap().score;
+    assert!(boundary > inside);
+    let camel = fuzzy_match("fl", "FileList").unwrap().score;
+    let plain = fuzzy_match("fl", "filelist").unwrap().score;
+    assert!(camel > plain);
+    let consecutive = fuzzy_match("doc", "documents").unwrap().score;
+    let scattered = fuzzy_match("doc", "dxoxc").unwrap().score;
+    assert!(consecutive > scattered);
+
+    // 空白で区切った断片はそれぞれ照合する
+    let found = fuzzy_match("rs main", "src/main.rs").unwrap();
+    assert_eq!(found.positions, vec![4, 5, 6, 7, 9, 10]);
+    assert!(fuzzy_match("rs toml", "src/main.rs").is_none());
+}
+
+#[test]
+fn test_quick_open_prefers_basename_and_frecency() {
+    let mut quick_open = QuickOpen::new();
+    quick_open.add(candidate("/main/src/lib", QuickOpenSource::Recent, 0.0));
+    quick_open.add(candidate("/work/lib/main", QuickOpenSource::Recent, 0.0));
+    assert_eq!(
+        found(&quick_open, "main", None),
+        vec!["/work/lib/main", "/main/src/lib"]
+    );
+
+    // 名前と完全に一致するディレクトリを優先する
+    let mut quick_open = QuickOpen::new();
+    quick_open.add(candidate("/p/src/sources", QuickOpenSource::Recent, 0.0));
+    quick_open.add(candidate("/p/src", QuickOpenSource::Recent, 0.0));
+    assert_eq!(found(&quick_open, "src", None)[0], "/p/src");
+
+    // よく使う場所ほど上位
+    let mut quick_open = QuickOpen::new();
+    quick_open.add(candidate("/a/project", QuickOpenSource::Recent, 0.1));
+    quick_open.add(candidate("/b/project", QuickOpenSource::Recent, 2.0));
+    assert_eq!(
+        found(&quick_open, "proj", None),
+        vec!["/b/project", "/a/project"]
+    );
+
+    // 同じパスはブックマークとして扱う
+    quick_open.add(candidate("/a/project", QuickOpenSource::Bookmark, 0.0));
+    assert_eq!(quick_open.candidates().len(), 2);
+    assert_eq!(quick_open.candidates()[0].source, QuickOpenSource::Bookmark);
+    assert_eq!(quick_open.candidates()[0].frecency, 0.1);
+}
+
+#[test]
+fn test_quick_open_recent_locations_from_state() {
+    let mut state = AppState::default();
+    let mut docs = TabState::new(
+        "tab1".to_string(),
+        "docs".to_string(),
+        PathBuf::from("/home/u/docs"),
+    );
+    docs.history_back = vec![PathBuf::from("/home/u"), PathBuf::from("/home/u/music")];
+    let mut code = TabState::new(
+        "tab2".to_string(),
+        "code".to_string(),
+        PathBuf::from("/home/u/code"),
+    );
+    code.history_back = vec![PathBuf::from("/home/u")];
+    state.add_tab(docs);
+    state.add_tab(code);
+    state.set_active_tab("tab1").unwrap();
+
+    let quick_open = QuickOpen::from_state(&state);
+    // アクティブなタブの現在のパスが最も新しく、何度も開いた場所は重みを足し合わせる
+    assert_eq!(
+        found(&quick_open, "", None),
+        vec!["/home/u", "/home/u/docs", "/home/u/music", "/home/u/code"]
+    );
+    assert!(
+        quick_open
+            .candidates()
+            .iter()
+            .all(|c| c.source == QuickOpenSource::Recent && c.is_dir)
+    );
+}
+
+#[test]
+fn test_quick_open_searches_index() {
+    let mut index = SearchIndex::new();
+    index.upsert(entry("/p/src", FileType::Directory));
+    index.upsert(entry("/p/src/main.rs", FileType::File));
+    index.upsert(entry("/p/README.md", FileType::File));
+
+    let mut quick_open = QuickOpen::new();
+    quick_open.add(candidate("/p/src", QuickOpenSource::Recent, 1.0));
+
+    let matches = quick_open.search("main", Some(&index), 10);
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].path, PathBuf::from("/p/src/main.rs"));
+    assert_eq!(matches[0].source, QuickOpenSource::Indexed);
+    assert!(!matches[0].is_dir);
+    assert_eq!(matches[0].positions, vec![7, 8, 9, 10]);
+
+    // 最近開いた場所と同じパスは索引から重ねて出さない
+    let matches = quick_open.search("src", Some(&index), 10);
+    assert_eq!(matches[0].path, PathBuf::from("/p/src"));
+    assert_eq!(matches[0].source, QuickOpenSource::Recent);
+    assert_eq!(
+        matches
+            .iter()
+            .filter(|m| m.path == Path::new("/p/src"))
+            .count(),
+        1
+    );
+
+    // 入力が空なら索引は使わない
+    assert_eq!(found(&quick_open, " ", Some(&index)), vec!["/p/src"]);
+    assert_eq!(quick_open.search("src", Some(&index), 1).len(), 1);
+}
+
+#[test]
+fn test_quick_open_limits_indexed_candidates() {
+    let mut index = SearchIndex::new();
+    for i in 0..MAX_INDEXED_CANDIDATES + 200 {
+        index.upsert(entry(&format!("/data/report{i}.txt"), FileType::File));
+    }
+    index.upsert(entry("/reports/summary.md", FileType::File));
+
+    let quick_open = QuickOpen::new();
+    assert_eq!(
+        quick_open.search("report", Some(&index), usize::MAX).len(),
+        MAX_INDEXED_CANDIDATES
+    );
+    // 最後の断片が名前に含まれない索引のパスは照合しない
+    assert_eq!(
+        found(&quick_open, "reports sum", Some(&index)),
+        vec!["/reports/summary.md"]
+    );
+    assert!(found(&quick_open, "summary reports", Some(&index)).is_empty());
+}
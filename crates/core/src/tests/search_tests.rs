@@ -0,0 +1,288 @@
+//! 検索索引のテスト
+
+use crate::filesystem::{FileEntry, FileType};
+use crate::query::FileQuery;
+use crate::search::{IndexSnapshot, SearchIndex, SearchIndexer, SearchQuery, glob_match};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+fn entry(path: &str) -> FileEntry {
+    let path = PathBuf::from(path);
+    FileEntry {
+        name: path.file_name().unwrap().to_string_lossy().to_string(),
+        path,
+        file_type: FileType::File,
+        size: 0,
+        modified: None,
+    }
+}
+
+fn index_of(paths: &[&str]) -> SearchIndex {
+    let mut index = SearchIndex::new();
+    for path in paths {
+        index.upsert(entry(path));
+    }
+    index
+}
+
+fn found(index: &SearchIndex, query: SearchQuery) -> Vec<PathBuf> {
+    index
+        .search(&query, 10)
+        .into_iter()
+        .map(|entry| entry.path)
+        .collect()
+}
+
+/// 条件を満たすまで待つ
+fn wait_until(condition: impl Fn() -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !condition() {
+        assert!(Instant::now() < deadline, "timed out waiting for the index");
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+#[test]
+fn test_search_queries() {
+    let index = index_of(&[
+        "/p/src/main.rs",
+        "/p/src/Mainframe.txt",
+        "/p/docs/domain.md",
+        "/p/main",
+    ]);
+
+    assert_eq!(
+        found(&index, SearchQuery::Prefix("MAIN".to_string())),
+        vec![
+            PathBuf::from("/p/main"),
+            PathBuf::from("/p/src/main.rs"),
+            PathBuf::from("/p/src/Mainframe.txt"),
+        ]
+    );
+    // 完全一致、前方一致、名前の短いものの順
+    assert_eq!(
+        found(&index, SearchQuery::Substring("main".to_string())),
+        vec![
+            PathBuf::from("/p/main"),
+            PathBuf::from("/p/src/main.rs"),
+            PathBuf::from("/p/src/Mainframe.txt"),
+            PathBuf::from("/p/docs/domain.md"),
+        ]
+    );
+    assert_eq!(
+        found(&index, SearchQuery::parse("*.rs")),
+        vec![PathBuf::from("/p/src/main.rs")]
+    );
+    assert_eq!(
+        found(&index, SearchQuery::parse("/p/src/*")),
+        vec![
+            PathBuf::from("/p/src/main.rs"),
+            PathBuf::from("/p/src/Mainframe.txt"),
+        ]
+    );
+    assert_eq!(index.search(&SearchQuery::parse("main"), 2).len(), 2);
+}
+
+#[test]
+fn test_glob_match() {
+    let glob = |pattern: &str, text: &str| glob_match(&pattern.chars().collect::<Vec<_>>(), text);
+    assert!(glob("*.rs", "main.rs"));
+    assert!(!glob("*.rs", "src/main.rs"));
+    assert!(glob("**/*.rs", "/p/src/main.rs"));
+    assert!(glob("/p/**/x", "/p/a/b/x"));
+    assert!(!glob("/p/*/x", "/p/a/b/x"));
+    assert!(glob("/p/*/x", "/p/a/x"));
+    assert!(glob("*a/*", "aba/c"));
+    assert!(glob("a*b*c", "axbybzc"));
+    assert!(glob("[a-c]?[!x]", "b1y"));
+    assert!(!glob("[a-c]?[!x]", "b1x"));
+    assert!(!glob("?", "/"));
+    assert!(glob("[ab", "[ab"));
+    assert!(glob("**", ""));
+    assert!(!glob("*", "a/b"));
+    // `*` が `/` に当たったら前の `**` からやり直す
+    assert!(glob("**/src/*.rs", "/x/src/y/src/z.rs"));
+    // 一致しない星の多いパターンでも照合の手間は増えすぎない
+    let text = "a".repeat(200);
+    assert!(!glob(&format!("{}b", "a*".repeat(20)), &text));
+}
+
+#[test]
+fn test_search_uses_trigrams() {
+    let mut index = index_of(&[
+        "/p/src/main.rs",
+        "/p/src/Mainframe.txt",
+        "/p/docs/domain.md",
+        "/p/ab",
+    ]);
+    // 2文字以下は全件から探す
+    assert_eq!(found(&index, SearchQuery::parse("ab")).len(), 1);
+    assert_eq!(found(&index, SearchQuery::parse("ain")).len(), 3);
+    assert!(found(&index, SearchQuery::parse("xyz")).is_empty());
+    assert_eq!(
+        found(&index, SearchQuery::parse("/P/**/MAIN*")),
+        vec![
+            PathBuf::from("/p/src/main.rs"),
+            PathBuf::from("/p/src/Mainframe.txt"),
+        ]
+    );
+    assert_eq!(
+        found(&index, SearchQuery::parse("do*in.md")),
+        vec![PathBuf::from("/p/docs/domain.md")]
+    );
+    // 名前の外の文字はパスのグロブの絞り込みに使わない
+    assert_eq!(found(&index, SearchQuery::parse("/p/docs/*")).len(), 1);
+    assert_eq!(found(&index, SearchQuery::parse("**cs/dom*")).len(), 1);
+
+    // 名前を変えると古い名前では見つからない
+    index.remove_tree(Path::new("/p/src/main.rs"));
+    index.upsert(entry("/p/src/lib.rs"));
+    assert_eq!(
+        found(&index, SearchQuery::parse("main")),
+        vec![
+            PathBuf::from("/p/src/Mainframe.txt"),
+            PathBuf::from("/p/docs/domain.md"),
+        ]
+    );
+    assert_eq!(found(&index, SearchQuery::parse("lib")).len(), 1);
+}
+
+#[test]
+fn test_search_large_index_checks_only_candidates() {
+    let mut index = SearchIndex::new();
+    for i in 0..100_000 {
+        index.upsert(entry(&format!("/data/d{}/report_{i:06}.txt", i % 100)));
+    }
+
+    // 3文字組で絞り込み、全件ではなく索引の2%以下の候補だけを照合する
+    for i in 0..200 {
+        let text = SearchQuery::Substring(format!("{:06}", i * 397));
+        assert_eq!(found(&index, text.clone()).len(), 1);
+        assert!(
+            index.candidate_count(&text) <= index.len() / 50,
+            "{:?}",
+            text
+        );
+
+        let glob = SearchQuery::parse(&format!("/data/**/*_{:06}.txt", i * 401));
+        assert_eq!(found(&index, glob.clone()).len(), 1);
+        assert!(
+            index.candidate_count(&glob) <= index.len() / 50,
+            "{:?}",
+            glob
+        );
+    }
+
+    // 絞り込めない短い条件だけが全件を照合する
+    assert_eq!(
+        index.candidate_count(&SearchQuery::Substring("_1".to_string())),
+        100_000
+    );
+    assert_eq!(
+        index.candidate_count(&SearchQuery::Substring("zzz".to_string())),
+        0
+    );
+    assert_eq!(
+        index.candidate_count(&SearchQuery::Prefix("report_00000".to_string())),
+        10
+    );
+}
+
+#[test]
+fn test_upsert_and_remove_tree() {
+    let mut index = index_of(&["/p/a", "/p/a/b.txt", "/p/a/c/d.txt", "/p/ab.txt"]);
+    index.upsert(entry("/p/a/b.txt"));
+    assert_eq!(index.len(), 4);
+
+    // 配下だけを削除し、名前が前方一致するだけの兄弟は残す
+    assert_eq!(index.remove_tree(Path::new("/p/a")), 3);
+    assert_eq!(index.len(), 1);
+    assert!(index.contains(Path::new("/p/ab.txt")));
+    assert_eq!(
+        found(&index, SearchQuery::parse("b.txt")),
+        vec![PathBuf::from("/p/ab.txt")]
+    );
+
+    // 削除した場所は再利用する
+    index.upsert(entry("/p/new.txt"));
+    assert_eq!(
+        found(&index, SearchQuery::Prefix("new".to_string())),
+        vec![PathBuf::from("/p/new.txt")]
+    );
+}
+
+#[test]
+fn test_query_under_scope() {
+    let index = index_of(&[
+        "/p/a",
+        "/p/a/x.rs",
+        "/p/a/sub/y.rs",
+        "/p/ab/z.rs",
+        "/q/w.rs",
+    ]);
+    let query = FileQuery::parse("ext:rs").unwrap();
+    let mut paths: Vec<PathBuf> = index
+        .query_under(Path::new("/p/a"), &query, 10)
+        .into_iter()
+        .map(|entry| entry.path)
+        .collect();
+    paths.sort();
+    // 名前が前方一致するだけの兄弟と範囲外は含めない
+    assert_eq!(
+        paths,
+        vec![PathBuf::from("/p/a/sub/y.rs"), PathBuf::from("/p/a/x.rs")]
+    );
+
+    // 検索範囲のディレクトリ自身は含めない
+    let all = FileQuery::parse("a").unwrap();
+    assert!(index.query_under(Path::new("/p/a"), &all, 10).is_empty());
+    assert_eq!(index.query_under(Path::new("/p"), &query, 1).len(), 1);
+}
+
+#[test]
+fn test_snapshot_round_trip() {
+    let index = index_of(&["/p/a.txt", "/p/b.txt"]);
+    let json = serde_json::to_string(&index.snapshot()).unwrap();
+    let restored = SearchIndex::from_snapshot(serde_json::from_str(&json).unwrap());
+    assert_eq!(restored.len(), 2);
+    assert!(restored.contains(Path::new("/p/b.txt")));
+
+    // 形式の違う索引は読み捨てる
+    let outdated = IndexSnapshot {
+        version: 0,
+        ..index.snapshot()
+    };
+    assert!(SearchIndex::from_snapshot(outdated).is_empty());
+}
+
+#[test]
+fn test_indexer_crawls_and_follows_changes() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().to_path_buf();
+    fs::create_dir_all(root.join("src/nested")).unwrap();
+    fs::write(root.join("src/nested/lib.rs"), "").unwrap();
+
+    // 保存していた索引にある消えたパスは巡回で取り除く
+    let mut stale = SearchIndex::new();
+    stale.upsert(entry(root.join("gone.rs").to_str().unwrap()));
+    let indexer = SearchIndexer::start(vec![root.clone()], move || stale).unwrap();
+
+    let search = |text: &str| indexer.search(&SearchQuery::parse(text), 10).unwrap();
+    wait_until(|| !indexer.status().crawling);
+    assert_eq!(search("lib.rs")[0].path, root.join("src/nested/lib.rs"));
+    assert!(search("gone").is_empty());
+    assert_eq!(indexer.status().indexed, 4);
+
+    fs::write(root.join("src/added.rs"), "").unwrap();
+    wait_until(|| !search("added").is_empty());
+
+    fs::rename(root.join("src"), root.join("moved")).unwrap();
+    wait_until(|| {
+        search("lib.rs")
+            .first()
+            .is_some_and(|entry| entry.path == root.join("moved/nested/lib.rs"))
+    });
+    assert_eq!(search("*.rs").len(), 2);
+}
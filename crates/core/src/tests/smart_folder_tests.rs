@@ -0,0 +1,188 @@
+//! スマートフォルダのテスト
+
+use crate::search::{SearchIndex, SearchIndexer};
+use crate::smart_folder::{SmartFolder, SmartFolderResults, SmartFolderUpdate, SmartFolders};
+use crate::state::{TabState, state_utils};
+use rust_explorer_utils::AppError;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tempfile::TempDir;
+
+fn names(folders: &SmartFolders) -> Vec<&str> {
+    folders
+        .folders()
+        .iter()
+        .map(|folder| folder.name.as_str())
+        .collect()
+}
+
+/// 条件を満たす結果が届くまで待つ
+fn wait_for(results: &SmartFolderResults, condition: impl Fn(&SmartFolderUpdate) -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let update = results
+            .receiver()
+            .recv_timeout(remaining)
+            .expect("timed out waiting for smart folder results");
+        if condition(&update) {
+            return;
+        }
+    }
+}
+
+fn paths(update: &SmartFolderUpdate) -> Vec<PathBuf> {
+    let mut paths: Vec<_> = update.entries.iter().map(|e| e.path.clone()).collect();
+    paths.sort();
+    paths
+}
+
+#[test]
+fn test_smart_folder_validation() {
+    let scope = PathBuf::from("/projects");
+    let folder = SmartFolder::new("  Rust  ", " ext:rs ", scope.clone()).unwrap();
+    assert_eq!(folder.name, "Rust");
+    assert_eq!(folder.query, "ext:rs");
+    assert!(folder.compile().is_ok());
+
+    assert!(matches!(
+        SmartFolder::new(" ", "ext:rs", scope.clone()),
+        Err(AppError::InvalidInput(_))
+    ));
+    assert!(matches!(
+        SmartFolder::new("Empty", "  ", scope.clone()),
+        Err(AppError::InvalidInput(_))
+    ));
+    match SmartFolder::new("Bad", "size:>>", scope) {
+        Err(AppError::InvalidInput(message)) => assert!(message.contains("column")),
+        other => panic!("expected a parse error, got {:?}", other),
+    }
+    assert!(matches!(
+        SmartFolder::new("Relative", "ext:rs", PathBuf::from("relative")),
+        Err(AppError::InvalidPath(_))
+    ));
+}
+
+#[test]
+fn test_smart_folders_edit_rename_and_reorder() {
+    let scope = PathBuf::from("/projects");
+    let mut folders = SmartFolders::new();
+    let rust = folders
+        .add("Rust", "ext:rs", scope.clone())
+        .unwrap()
+        .id
+        .clone();
+    let large = folders
+        .add("Large", "size:>100MB", scope.clone())
+        .unwrap()
+        .id
+        .clone();
+    let recent = folders
+        .add("Recent", "modified:<7d", scope.clone())
+        .unwrap()
+        .id
+        .clone();
+    assert!(folders.add("rust", "ext:toml", scope.clone()).is_err());
+    assert_eq!(names(&folders), vec!["Rust", "Large", "Recent"]);
+
+    folders.rename(&rust, "Rust sources").unwrap();
+    assert!(folders.rename(&large, "RECENT").is_err());
+    // 自分自身と同じ名前（大文字小文字違い）には変更できる
+    folders.rename(&recent, "recent").unwrap();
+
+    folders
+        .update(&large, "size:>1GB", PathBuf::from("/data"))
+        .unwrap();
+    assert!(folders.update(&large, "size:", scope.clone()).is_err());
+    let updated = folders.get(&large).unwrap();
+    assert_eq!(updated.query, "size:>1GB");
+    assert_eq!(updated.scope, PathBuf::from("/data"));
+
+    folders.move_to(&recent, 0).unwrap();
+    assert_eq!(names(&folders), vec!["recent", "Rust sources", "Large"]);
+    folders.move_to(&recent, 10).unwrap();
+    assert_eq!(names(&folders), vec!["Rust sources", "Large", "recent"]);
+    assert!(folders.move_to("missing", 0).is_err());
+
+    assert_eq!(folders.remove(&rust).unwrap().name, "Rust sources");
+    assert!(folders.remove(&rust).is_none());
+    assert_eq!(names(&folders), vec!["Large", "recent"]);
+
+    let json = serde_json::to_string(&folders).unwrap();
+    let restored: SmartFolders = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, folders);
+    let empty: SmartFolders = serde_json::from_str("{}").unwrap();
+    assert!(empty.folders().is_empty());
+}
+
+#[test]
+fn test_smart_folder_tab_state() {
+    let folder = SmartFolder::new("Rust", "ext:rs", PathBuf::from("/projects")).unwrap();
+    let tab = state_utils::create_smart_folder_tab(&folder);
+    assert_eq!(tab.name, "Rust");
+    assert_eq!(tab.current_path, PathBuf::from("/projects"));
+    assert_eq!(tab.smart_folder.as_ref(), Some(&folder));
+
+    // 以前の形式で保存したタブは通常のタブとして復元する
+    let json = serde_json::to_value(&tab).unwrap();
+    let mut object = json.as_object().unwrap().clone();
+    object.remove("smart_folder");
+    let restored: TabState = serde_json::from_value(object.into()).unwrap();
+    assert!(restored.smart_folder.is_none());
+}
+
+#[test]
+fn test_smart_folder_results_follow_changes() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().to_path_buf();
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::write(root.join("main.rs"), "").unwrap();
+    fs::write(root.join("notes.txt"), "").unwrap();
+    fs::write(root.join("src/lib.rs"), "").unwrap();
+
+    let folder = SmartFolder::new("Rust", "ext:rs", root.clone()).unwrap();
+    let results = SmartFolderResults::start(&folder, None, 100).unwrap();
+    wait_for(&results, |update| {
+        !update.indexing && paths(update) == vec![root.join("main.rs"), root.join("src/lib.rs")]
+    });
+
+    fs::write(root.join("src/added.rs"), "").unwrap();
+    wait_for(&results, |update| {
+        paths(update).contains(&root.join("src/added.rs"))
+    });
+
+    // 検索範囲のディレクトリ自身は結果に含めない
+    let dirs = SmartFolder::new("Dirs", "type:dir", root.clone()).unwrap();
+    let results = SmartFolderResults::start(&dirs, None, 100).unwrap();
+    wait_for(&results, |update| {
+        !update.indexing && paths(update) == vec![root.join("src")]
+    });
+
+    let missing = SmartFolder::new("Missing", "ext:rs", root.join("missing")).unwrap();
+    assert!(matches!(
+        SmartFolderResults::start(&missing, None, 100),
+        Err(AppError::InvalidPath(_))
+    ));
+}
+
+#[test]
+fn test_smart_folder_results_use_shared_index() {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path().to_path_buf();
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::write(root.join("build.rs"), "").unwrap();
+    fs::write(root.join("src/lib.rs"), "").unwrap();
+
+    let shared = Arc::new(SearchIndexer::start(vec![root.clone()], SearchIndex::new).unwrap());
+    assert!(shared.covers(&root.join("src")));
+    assert!(!shared.covers(temp_dir.path().parent().unwrap()));
+
+    // 共有の索引から検索範囲の配下だけを結果にする
+    let folder = SmartFolder::new("Src", "ext:rs", root.join("src")).unwrap();
+    let results = SmartFolderResults::start(&folder, Some(&shared), 100).unwrap();
+    wait_for(&results, |update| {
+        !update.indexing && paths(update) == vec![root.join("src/lib.rs")]
+    });
+}
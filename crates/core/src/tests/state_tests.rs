@@ -1,7 +1,29 @@
 //! 状態管理システムのテスト
 
-use crate::state::{AppState, StateManager, TabState, UiState, WindowState, state_utils};
+use crate::file_sorting::SortCriteria;
+use crate::state::{
+    AppState, MAX_CLOSED_TABS, MIN_PANE_FLEX, PaneTree, SplitDirection, StateChangeEvent,
+    StateManager, TabState, UiState, WindowState, state_utils,
+};
 use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// `tab1`〜`tabN` を順に追加した状態を作成
+fn state_with_tabs(count: usize) -> AppState {
+    let mut state = AppState::default();
+    for i in 1..=count {
+        state.add_tab(TabState::new(
+            format!("tab{}", i),
+            format!("Tab {}", i),
+            PathBuf::from(format!("/test{}", i)),
+        ));
+    }
+    state
+}
+
+fn tab_ids(state: &AppState) -> Vec<&str> {
+    state.tabs.iter().map(|t| t.id.as_str()).collect()
+}
 
 #[test]
 fn test_app_state_default() {
@@ -371,3 +393,423 @@ fn test_state_serialization() {
     assert_eq!(restored_state.tabs[0].id, "tab1");
     assert_eq!(restored_state.active_tab_id, Some("tab1".to_string()));
 }
+
+#[test]
+fn test_app_state_duplicate_tab() {
+    let mut state = state_with_tabs(2);
+    state
+        .update_tab("tab1", |tab| {
+            tab.history_back.push(PathBuf::from("/prev"));
+            tab.sort.criteria = SortCriteria::Size;
+            tab.scroll_offset = 120.0;
+        })
+        .unwrap();
+
+    let duplicate = state.duplicate_tab("tab1", "tab3".to_string()).unwrap();
+
+    // 元のタブの右隣に挿入され、アクティブになる
+    assert_eq!(tab_ids(&state), vec!["tab1", "tab3", "tab2"]);
+    assert_eq!(state.active_tab_id, Some("tab3".to_string()));
+    assert_eq!(duplicate.current_path, PathBuf::from("/test1"));
+    assert_eq!(duplicate.history_back, vec![PathBuf::from("/prev")]);
+    assert_eq!(duplicate.sort.criteria, SortCriteria::Size);
+    assert_eq!(duplicate.scroll_offset, 120.0);
+    assert!(state.duplicate_tab("missing", "tab4".to_string()).is_err());
+}
+
+#[test]
+fn test_app_state_move_tab() {
+    let mut state = state_with_tabs(3);
+
+    state.move_tab("tab3", 0).unwrap();
+    assert_eq!(tab_ids(&state), vec!["tab3", "tab1", "tab2"]);
+
+    // 範囲外の位置は末尾に丸める
+    state.move_tab("tab3", 10).unwrap();
+    assert_eq!(tab_ids(&state), vec!["tab1", "tab2", "tab3"]);
+    assert_eq!(state.active_tab_id, Some("tab3".to_string()));
+    assert!(state.move_tab("missing", 0).is_err());
+}
+
+#[test]
+fn test_app_state_reopen_closed_tab() {
+    let mut state = state_with_tabs(2);
+    state
+        .update_tab("tab1", |tab| tab.current_path = PathBuf::from("/moved"))
+        .unwrap();
+
+    state.remove_tab("tab1").unwrap();
+    state.remove_tab("tab2").unwrap();
+    assert!(state.tabs.is_empty());
+    assert_eq!(state.closed_tabs.len(), 2);
+
+    // 最後に閉じたタブから開き直す
+    let reopened = state.reopen_closed_tab().unwrap();
+    assert_eq!(reopened.id, "tab2");
+    let reopened = state.reopen_closed_tab().unwrap();
+    assert_eq!(reopened.current_path, PathBuf::from("/moved"));
+    assert_eq!(state.active_tab_id, Some("tab1".to_string()));
+    assert!(state.reopen_closed_tab().is_none());
+}
+
+#[test]
+fn test_app_state_closed_tabs_are_limited() {
+    let mut state = state_with_tabs(MAX_CLOSED_TABS + 5);
+    for i in 1..=MAX_CLOSED_TABS + 5 {
+        state.remove_tab(&format!("tab{}", i)).unwrap();
+    }
+
+    assert_eq!(state.closed_tabs.len(), MAX_CLOSED_TABS);
+    assert_eq!(state.closed_tabs[0].id, "tab6");
+}
+
+#[test]
+fn test_state_manager_tab_events() {
+    let manager = StateManager::with_state(state_with_tabs(2));
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let observer = manager.clone();
+    let recorded = events.clone();
+    manager
+        .on_state_change(move |event| {
+            // コールバック中でも状態を読める
+            let tabs = observer.get_state().unwrap().tabs.len();
+            recorded
+                .lock()
+                .unwrap()
+                .push((format!("{:?}", event), tabs));
+        })
+        .unwrap();
+
+    let duplicate = manager.duplicate_tab("tab1").unwrap();
+    manager.move_tab(&duplicate.id, 0).unwrap();
+    manager
+        .update_tab(&duplicate.id, |tab| {
+            tab.selected_paths = vec![PathBuf::from("/a")]
+        })
+        .unwrap();
+    manager.remove_tab(&duplicate.id).unwrap();
+    let reopened = manager.reopen_closed_tab().unwrap().unwrap();
+    assert_eq!(reopened.selected_paths, vec![PathBuf::from("/a")]);
+
+    let events = events.lock().unwrap();
+    assert_eq!(events.len(), 5);
+    assert!(events[1].0.starts_with("TabMoved"));
+    assert!(events[2].0.starts_with("TabUpdated"));
+    assert_eq!(events[3].1, 2);
+    assert_eq!(events[4].1, 3);
+}
+
+#[test]
+fn test_state_manager_update_missing_tab() {
+    let manager = StateManager::new();
+    let events = Arc::new(Mutex::new(0));
+    let counter = events.clone();
+    manager
+        .on_state_change(move |event| {
+            if matches!(event, StateChangeEvent::TabUpdated(_)) {
+                *counter.lock().unwrap() += 1;
+            }
+        })
+        .unwrap();
+
+    assert!(manager.update_tab("missing", |_| {}).is_err());
+    assert_eq!(*events.lock().unwrap(), 0);
+}
+
+#[test]
+fn test_tab_state_deserialize_without_per_tab_fields() {
+    // タブごとの履歴などを持たない古い状態ファイルも読める
+    let json = r#"{
+        "id": "tab1",
+        "name": "Tab 1",
+        "current_path": "/test",
+        "active": true,
+        "created_at": "2024-01-01T00:00:00Z"
+    }"#;
+
+    let tab: TabState = serde_json::from_str(json).unwrap();
+    assert!(tab.history_back.is_empty());
+    assert!(tab.selected_paths.is_empty());
+    assert_eq!(tab.scroll_offset, 0.0);
+}
+
+/// 分割したペインに開くタブを作成
+fn split_tab(id: &str) -> TabState {
+    TabState::new(id.to_string(), id.to_string(), PathBuf::from("/split"))
+}
+
+#[test]
+fn test_ensure_pane_layout_adopts_existing_tabs() {
+    let mut state = state_with_tabs(2);
+    state.ensure_pane_layout();
+
+    let root = state.root_pane_id.clone().unwrap();
+    assert_eq!(state.pane_tree(), Some(PaneTree::Leaf(root.clone())));
+    assert_eq!(state.focused_pane_id, Some(root.clone()));
+    assert!(state.tabs.iter().all(|t| t.pane_id == Some(root.clone())));
+    assert_eq!(
+        state.pane(&root).unwrap().active_tab_id,
+        Some("tab2".to_string())
+    );
+}
+
+#[test]
+fn test_split_pane_builds_tree() {
+    let mut state = state_with_tabs(1);
+    state.ensure_pane_layout();
+    let left = state.root_pane_id.clone().unwrap();
+
+    let right = state
+        .split_pane(&left, SplitDirection::Horizontal, split_tab("tab2"))
+        .unwrap();
+    let bottom = state
+        .split_pane(&right, SplitDirection::Vertical, split_tab("tab3"))
+        .unwrap();
+    // 同じ方向の分割は入れ子にせず隣に並べる
+    let third = state
+        .split_pane(&left, SplitDirection::Horizontal, split_tab("tab4"))
+        .unwrap();
+
+    let Some(PaneTree::Split {
+        direction,
+        children,
+        ..
+    }) = state.pane_tree()
+    else {
+        panic!("root must be a split");
+    };
+    assert_eq!(direction, SplitDirection::Horizontal);
+    assert_eq!(children.len(), 3);
+    assert_eq!(children[1], PaneTree::Leaf(third.clone()));
+    assert!(matches!(
+        &children[2],
+        PaneTree::Split {
+            direction: SplitDirection::Vertical,
+            ..
+        }
+    ));
+    assert_eq!(
+        state.leaf_pane_ids(),
+        vec![left.clone(), third.clone(), right, bottom]
+    );
+    assert_eq!(state.pane(&left).unwrap().flex(), 0.5);
+    assert_eq!(state.pane(&third).unwrap().flex(), 0.5);
+
+    // 新しいペインにフォーカスし、ペインごとにアクティブなタブを持つ
+    assert_eq!(state.focused_pane_id, Some(third.clone()));
+    assert_eq!(state.active_tab_id, Some("tab4".to_string()));
+    assert!(state.tabs.iter().find(|t| t.id == "tab1").unwrap().active);
+    assert_eq!(state.pane_tabs(&third).len(), 1);
+}
+
+#[test]
+fn test_close_pane_collapses_split() {
+    let mut state = state_with_tabs(1);
+    state.ensure_pane_layout();
+    let left = state.root_pane_id.clone().unwrap();
+    state.resize_pane(&left, 2.0).unwrap();
+    let right = state
+        .split_pane(&left, SplitDirection::Horizontal, split_tab("tab2"))
+        .unwrap();
+
+    state.close_pane(&right).unwrap();
+
+    // 残ったペインがルートになり、分割前の大きさを引き継ぐ
+    assert_eq!(state.pane_tree(), Some(PaneTree::Leaf(left.clone())));
+    assert_eq!(state.pane(&left).unwrap().flex(), 2.0);
+    assert_eq!(state.panes.len(), 1);
+    assert_eq!(state.focused_pane_id, Some(left.clone()));
+    assert_eq!(state.active_tab_id, Some("tab1".to_string()));
+    assert_eq!(state.closed_tabs.last().unwrap().id, "tab2");
+    assert!(state.close_pane(&left).is_err());
+}
+
+#[test]
+fn test_pane_tabs_are_independent() {
+    let mut state = state_with_tabs(2);
+    state.ensure_pane_layout();
+    let left = state.root_pane_id.clone().unwrap();
+    let right = state
+        .split_pane(&left, SplitDirection::Horizontal, split_tab("tab3"))
+        .unwrap();
+
+    // 別のペインのタブを閉じても、他のペインのアクティブタブは変わらない
+    state.remove_tab("tab2").unwrap();
+    assert_eq!(state.active_tab_id, Some("tab3".to_string()));
+    assert_eq!(
+        state.pane(&left).unwrap().active_tab_id,
+        Some("tab1".to_string())
+    );
+
+    state.move_tab_to_pane("tab1", &right).unwrap();
+    assert_eq!(state.pane_tabs(&right).len(), 2);
+    assert!(state.pane_tabs(&left).is_empty());
+    assert_eq!(state.pane(&left).unwrap().active_tab_id, None);
+    assert_eq!(state.other_pane_id(&right), Some(left.clone()));
+
+    state.focus_pane(&left).unwrap();
+    assert_eq!(state.active_tab_id, None);
+    assert!(state.focus_pane("missing").is_err());
+    state.resize_pane(&left, 0.0).unwrap();
+    assert_eq!(state.pane(&left).unwrap().flex(), MIN_PANE_FLEX);
+}
+
+#[test]
+fn test_pane_layout_round_trip() {
+    let mut state = state_with_tabs(1);
+    state.ensure_pane_layout();
+    let root = state.root_pane_id.clone().unwrap();
+    let right = state
+        .split_pane(&root, SplitDirection::Horizontal, split_tab("tab2"))
+        .unwrap();
+    state
+        .split_pane(&right, SplitDirection::Vertical, split_tab("tab3"))
+        .unwrap();
+    state.resize_pane(&root, 1.5).unwrap();
+
+    let json = serde_json::to_string(&state).unwrap();
+    let mut restored: AppState = serde_json::from_str(&json).unwrap();
+    restored.ensure_pane_layout();
+
+    assert_eq!(restored.pane_tree(), state.pane_tree());
+    assert_eq!(restored.focused_pane_id, state.focused_pane_id);
+    assert_eq!(restored.pane(&root).unwrap().flex(), 1.5);
+}
+
+#[test]
+fn test_ensure_pane_layout_repairs_broken_tree() {
+    let mut state = state_with_tabs(1);
+    state.ensure_pane_layout();
+    let root = state.root_pane_id.clone().unwrap();
+    let right = state
+        .split_pane(&root, SplitDirection::Horizontal, split_tab("tab2"))
+        .unwrap();
+
+    // 子ペインが失われた状態ファイルは1つのペインに作り直す
+    state.panes.retain(|p| p.id != right);
+    state.ensure_pane_layout();
+
+    assert!(matches!(state.pane_tree(), Some(PaneTree::Leaf(_))));
+    assert_eq!(state.panes.len(), 1);
+    let leaf = state.leaf_pane_ids()[0].clone();
+    assert_eq!(state.pane_tabs(&leaf).len(), 2);
+}
+
+#[test]
+fn test_state_manager_pane_events() {
+    let manager = StateManager::with_state(state_with_tabs(1));
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let recorded = events.clone();
+    manager
+        .on_state_change(move |event| recorded.lock().unwrap().push(format!("{:?}", event)))
+        .unwrap();
+
+    manager.ensure_pane_layout().unwrap();
+    let root = manager.get_state().unwrap().root_pane_id.unwrap();
+    let right = manager
+        .split_pane(&root, SplitDirection::Vertical, split_tab("tab2"))
+        .unwrap();
+    manager.resize_pane(&right, 0.5).unwrap();
+    manager.focus_pane(&root).unwrap();
+    manager.move_tab_to_pane("tab2", &root).unwrap();
+    manager.close_pane(&right).unwrap();
+
+    let events = events.lock().unwrap();
+    let kinds: Vec<&str> = events
+        .iter()
+        .map(|e| e.split(['(', ' ']).next().unwrap())
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            "PaneLayoutChanged",
+            "PaneAdded",
+            "PaneResized",
+            "PaneFocused",
+            "TabUpdated",
+            "PaneRemoved"
+        ]
+    );
+}
+
+#[test]
+fn test_restore_missing_paths() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let existing = temp_dir.path().join("docs");
+    std::fs::create_dir(&existing).unwrap();
+    let fallback = PathBuf::from("/fallback");
+
+    let mut state = AppState::default();
+    let mut moved = TabState::new(
+        "moved".to_string(),
+        "gone".to_string(),
+        existing.join("gone/deeper"),
+    );
+    moved.history_back = vec![existing.clone(), temp_dir.path().join("removed")];
+    moved.selected_paths = vec![existing.join("gone/deeper/file")];
+    state.add_tab(moved);
+    state.add_tab(TabState::new(
+        "kept".to_string(),
+        "docs".to_string(),
+        existing.clone(),
+    ));
+    state.add_tab(TabState::new(
+        "relative".to_string(),
+        "missing".to_string(),
+        PathBuf::from("missing-relative-dir"),
+    ));
+
+    state.restore_missing_paths(&fallback);
+
+    // なくなったディレクトリは最も近い既存の親に置き換える
+    let moved = &state.tabs[0];
+    assert_eq!(moved.current_path, existing);
+    assert_eq!(moved.name, "docs");
+    assert_eq!(moved.history_back, vec![existing.clone()]);
+    assert!(moved.selected_paths.is_empty());
+    assert_eq!(state.tabs[1].current_path, existing);
+    assert_eq!(state.tabs[2].current_path, fallback);
+    assert!(state.pane_tree().is_some());
+}
+
+#[test]
+fn test_state_diff() {
+    let base = state_with_tabs(3);
+    let mut state = base.clone();
+    assert!(state.diff(&base).is_empty());
+
+    state.tabs[0].current_path = PathBuf::from("/moved");
+    let removed = state.tabs.remove(2);
+    state.add_tab(TabState::new(
+        "new".to_string(),
+        "new".to_string(),
+        PathBuf::from("/new"),
+    ));
+
+    let diff = state.diff(&base);
+    assert_eq!(
+        diff.changed_paths,
+        vec![(base.tabs[0].current_path.clone(), PathBuf::from("/moved"))]
+    );
+    assert_eq!(diff.added_tabs, vec![PathBuf::from("/new")]);
+    assert_eq!(diff.removed_tabs, vec![removed.current_path]);
+}
+
+#[test]
+fn test_state_manager_replace_state() {
+    let manager = StateManager::with_state(state_with_tabs(1));
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let recorded = events.clone();
+    manager
+        .on_state_change(move |event| recorded.lock().unwrap().push(format!("{:?}", event)))
+        .unwrap();
+
+    let mut workspace = state_with_tabs(3);
+    workspace.workspace_name = Some("review".to_string());
+    manager.replace_state(workspace).unwrap();
+
+    let state = manager.get_state().unwrap();
+    assert_eq!(state.tabs.len(), 3);
+    assert_eq!(state.workspace_name, Some("review".to_string()));
+    assert_eq!(*events.lock().unwrap(), vec!["StateReplaced".to_string()]);
+}
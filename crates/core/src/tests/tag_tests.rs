@@ -0,0 +1,143 @@
+//! タグのテスト
+
+use crate::state::{TabState, state_utils};
+use crate::tags::{TagColor, TagStore, read_xattr_tags, write_xattr_tags};
+use rust_explorer_utils::AppError;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+fn names(store: &TagStore, path: &str) -> Vec<String> {
+    store
+        .tags_for(Path::new(path))
+        .into_iter()
+        .map(|tag| tag.name.clone())
+        .collect()
+}
+
+#[test]
+fn test_tag_definitions() {
+    let mut store = TagStore::new();
+    assert_eq!(
+        store.define("  Work ", TagColor::Blue).unwrap().name,
+        "Work"
+    );
+    store.define("Urgent", TagColor::Red).unwrap();
+    assert!(matches!(
+        store.define("work", TagColor::Green),
+        Err(AppError::InvalidInput(_))
+    ));
+    assert!(store.define(" ", TagColor::Green).is_err());
+    assert!(store.define("a,b", TagColor::Green).is_err());
+
+    store.recolor("WORK", TagColor::Purple).unwrap();
+    assert_eq!(store.tag("work").unwrap().color, TagColor::Purple);
+    assert!(store.recolor("missing", TagColor::Red).is_err());
+
+    store.move_tag("Urgent", 0).unwrap();
+    let order: Vec<&str> = store.tags().iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(order, vec!["Urgent", "Work"]);
+}
+
+#[test]
+fn test_tag_assignments() {
+    let mut store = TagStore::new();
+    store.define("Work", TagColor::Blue).unwrap();
+    store.define("Urgent", TagColor::Red).unwrap();
+
+    let report = Path::new("/docs/report.pdf");
+    store.assign(report, "urgent").unwrap();
+    store.assign(report, "Work").unwrap();
+    store.assign(report, "Work").unwrap();
+    // 定義順に並ぶ
+    assert_eq!(names(&store, "/docs/report.pdf"), vec!["Work", "Urgent"]);
+    assert!(store.assign(Path::new("relative"), "Work").is_err());
+    assert!(store.assign(report, "missing").is_err());
+
+    assert!(!store.toggle(report, "Urgent").unwrap());
+    assert!(store.toggle(Path::new("/docs"), "Urgent").unwrap());
+    assert_eq!(store.paths_with("urgent"), vec![PathBuf::from("/docs")]);
+
+    // 名前を変えると割り当ても追従し、影響したパスを返す
+    let affected = store.rename_tag("Work", "Office").unwrap();
+    assert_eq!(affected, vec![PathBuf::from("/docs/report.pdf")]);
+    assert_eq!(names(&store, "/docs/report.pdf"), vec!["Office"]);
+    assert!(store.rename_tag("Office", "urgent").is_err());
+
+    let affected = store.remove_tag("Office").unwrap();
+    assert_eq!(affected, vec![PathBuf::from("/docs/report.pdf")]);
+    assert!(store.tags_for(report).is_empty());
+
+    let json = serde_json::to_string(&store).unwrap();
+    let restored: TagStore = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, store);
+    let empty: TagStore = serde_json::from_str("{}").unwrap();
+    assert!(empty.tags().is_empty());
+}
+
+#[test]
+fn test_tags_follow_renames_and_moves() {
+    let mut store = TagStore::new();
+    store.define("Keep", TagColor::Green).unwrap();
+    store.assign(Path::new("/a/project"), "Keep").unwrap();
+    store
+        .assign(Path::new("/a/project/src/main.rs"), "Keep")
+        .unwrap();
+    store.assign(Path::new("/a/project-old"), "Keep").unwrap();
+
+    assert!(store.relocate(Path::new("/a/project"), Path::new("/b/renamed")));
+    assert_eq!(
+        store.paths_with("Keep"),
+        vec![
+            PathBuf::from("/a/project-old"),
+            PathBuf::from("/b/renamed"),
+            PathBuf::from("/b/renamed/src/main.rs"),
+        ]
+    );
+    assert!(!store.relocate(Path::new("/missing"), Path::new("/elsewhere")));
+
+    assert!(store.forget(Path::new("/b/renamed")));
+    assert_eq!(
+        store.paths_with("Keep"),
+        vec![PathBuf::from("/a/project-old")]
+    );
+}
+
+#[test]
+fn test_tag_tab_state() {
+    let mut store = TagStore::new();
+    let tag = store.define("Work", TagColor::Blue).unwrap().clone();
+    let tab = state_utils::create_tag_tab(&tag, PathBuf::from("/home/u"));
+    assert_eq!(tab.name, "Work");
+    assert_eq!(tab.tag.as_deref(), Some("Work"));
+
+    let json = serde_json::to_value(&tab).unwrap();
+    let mut object = json.as_object().unwrap().clone();
+    object.remove("tag");
+    let restored: TabState = serde_json::from_value(object.into()).unwrap();
+    assert!(restored.tag.is_none());
+}
+
+#[test]
+fn test_xattr_tags_round_trip() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("tagged.txt");
+    fs::write(&file, "").unwrap();
+
+    let tags = vec!["Work".to_string(), "Urgent".to_string()];
+    // 拡張属性に対応しないファイルシステムでは確かめられない
+    if write_xattr_tags(&file, &tags).is_err() {
+        return;
+    }
+    assert_eq!(read_xattr_tags(&file).unwrap(), tags);
+
+    // 名前を変えても属性はファイルと一緒に残る
+    let renamed = temp_dir.path().join("renamed.txt");
+    fs::rename(&file, &renamed).unwrap();
+    assert_eq!(read_xattr_tags(&renamed).unwrap(), tags);
+
+    write_xattr_tags(&renamed, &[]).unwrap();
+    assert!(read_xattr_tags(&renamed).unwrap().is_empty());
+    // 属性がなくても削除は成功する
+    write_xattr_tags(&renamed, &[]).unwrap();
+}
@@ -0,0 +1,140 @@
+//! ゴミ箱のテスト
+
+use crate::trash::{TrashItem, TrashManager};
+use rust_explorer_utils::AppError;
+use std::fs;
+use tempfile::TempDir;
+
+/// 削除対象とデータディレクトリを同じ一時ディレクトリに作成
+fn create_trash_env() -> (TempDir, TrashManager) {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("file.txt"), "content").unwrap();
+    fs::create_dir_all(temp_dir.path().join("folder").join("nested")).unwrap();
+    fs::write(temp_dir.path().join("folder").join("a.txt"), "a").unwrap();
+    let trash = TrashManager::with_data_home(temp_dir.path().join("data"));
+    (temp_dir, trash)
+}
+
+/// ホームのゴミ箱内の項目だけを取得
+async fn home_items(trash: &TrashManager) -> Vec<TrashItem> {
+    trash
+        .list()
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|item| item.trashed_path.starts_with(trash.home_trash()))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_trash_writes_info_file() {
+    let (temp_dir, trash) = create_trash_env();
+    let path = temp_dir.path().join("file.txt");
+
+    let item = trash.trash(&path).await.unwrap();
+    assert!(!path.exists());
+    assert_eq!(item.trashed_path, trash.home_trash().join("files/file.txt"));
+    assert_eq!(fs::read_to_string(&item.trashed_path).unwrap(), "content");
+
+    let info = fs::read_to_string(trash.home_trash().join("info/file.txt.trashinfo")).unwrap();
+    let mut lines = info.lines();
+    assert_eq!(lines.next(), Some("[Trash Info]"));
+    assert_eq!(
+        lines.next(),
+        Some(format!("Path={}", path.display()).as_str())
+    );
+    assert!(lines.next().unwrap().starts_with("DeletionDate="));
+}
+
+#[tokio::test]
+async fn test_trash_same_name_twice() {
+    let (temp_dir, trash) = create_trash_env();
+    let path = temp_dir.path().join("file.txt");
+
+    let first = trash.trash(&path).await.unwrap();
+    fs::write(&path, "second").unwrap();
+    let second = trash.trash(&path).await.unwrap();
+
+    assert_eq!(first.name, "file.txt");
+    assert_eq!(second.name, "file (2).txt");
+    assert_eq!(home_items(&trash).await.len(), 2);
+}
+
+#[tokio::test]
+async fn test_list_and_restore() {
+    let (temp_dir, trash) = create_trash_env();
+    let folder = temp_dir.path().join("folder");
+    trash.trash(&folder).await.unwrap();
+
+    let items = home_items(&trash).await;
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].original_path, folder);
+    assert!(items[0].is_dir);
+    assert!(items[0].deletion_date.is_some());
+
+    let restored = trash.restore(&items[0]).await.unwrap();
+    assert_eq!(restored, folder);
+    assert_eq!(fs::read_to_string(folder.join("a.txt")).unwrap(), "a");
+    assert!(home_items(&trash).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_restore_refuses_to_overwrite() {
+    let (temp_dir, trash) = create_trash_env();
+    let path = temp_dir.path().join("file.txt");
+    let item = trash.trash(&path).await.unwrap();
+    fs::write(&path, "replacement").unwrap();
+
+    let result = trash.restore(&item).await;
+    assert!(matches!(result, Err(AppError::AlreadyExists(_))));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "replacement");
+    assert!(item.trashed_path.exists());
+}
+
+#[tokio::test]
+async fn test_empty_trash() {
+    let (temp_dir, trash) = create_trash_env();
+    trash
+        .trash(&temp_dir.path().join("file.txt"))
+        .await
+        .unwrap();
+    trash.trash(&temp_dir.path().join("folder")).await.unwrap();
+    assert_eq!(home_items(&trash).await.len(), 2);
+
+    trash.empty().await.unwrap();
+    assert!(home_items(&trash).await.is_empty());
+    assert_eq!(
+        fs::read_dir(trash.home_trash().join("info"))
+            .unwrap()
+            .count(),
+        0
+    );
+}
+
+#[tokio::test]
+async fn test_trash_ignores_orphaned_info() {
+    let (_temp_dir, trash) = create_trash_env();
+    let info = trash.home_trash().join("info");
+    fs::create_dir_all(&info).unwrap();
+    fs::write(
+        info.join("missing.txt.trashinfo"),
+        "[Trash Info]\nPath=/tmp/missing.txt\nDeletionDate=2024-01-01T00:00:00\n",
+    )
+    .unwrap();
+
+    assert!(home_items(&trash).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_cannot_trash_the_trash() {
+    let (temp_dir, trash) = create_trash_env();
+    trash
+        .trash(&temp_dir.path().join("file.txt"))
+        .await
+        .unwrap();
+
+    let result = trash.trash(trash.home_trash()).await;
+    assert!(matches!(result, Err(AppError::InvalidOperation(_))));
+    let result = trash.trash(&temp_dir.path().join("missing")).await;
+    assert!(matches!(result, Err(AppError::NotFound(_))));
+}
@@ -0,0 +1,237 @@
+//! 元に戻す・やり直しジャーナルのテスト
+
+use crate::filesystem::FileSystemManager;
+use crate::trash::TrashManager;
+use crate::undo::{JournalOperation, UndoJournal};
+use rust_explorer_utils::AppError;
+use std::fs;
+use tempfile::TempDir;
+
+fn create_test_dir() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("file.txt"), "content").unwrap();
+    fs::create_dir(temp_dir.path().join("target")).unwrap();
+    temp_dir
+}
+
+#[tokio::test]
+async fn test_undo_redo_rename() {
+    let temp_dir = create_test_dir();
+    let manager = FileSystemManager::new();
+    let mut journal = UndoJournal::new();
+    let original = temp_dir.path().join("file.txt");
+
+    let renamed = journal
+        .rename(&manager, &original, "renamed.txt")
+        .await
+        .unwrap();
+    assert!(journal.can_undo());
+    assert!(!journal.can_redo());
+
+    journal.undo(&manager).await.unwrap();
+    assert!(original.exists());
+    assert!(!renamed.exists());
+    assert!(journal.can_redo());
+
+    journal.redo(&manager).await.unwrap();
+    assert!(!original.exists());
+    assert_eq!(fs::read_to_string(&renamed).unwrap(), "content");
+}
+
+#[tokio::test]
+async fn test_undo_move() {
+    let temp_dir = create_test_dir();
+    let manager = FileSystemManager::new();
+    let mut journal = UndoJournal::new();
+    let from = temp_dir.path().join("file.txt");
+    let to = temp_dir.path().join("target").join("file.txt");
+
+    journal.move_item(&manager, &from, &to).await.unwrap();
+    assert!(to.exists());
+
+    let entry = journal.undo(&manager).await.unwrap();
+    assert!(matches!(entry.operation, JournalOperation::Move { .. }));
+    assert!(from.exists());
+    assert!(!to.exists());
+}
+
+#[tokio::test]
+async fn test_undo_recorded_move() {
+    let temp_dir = create_test_dir();
+    let manager = FileSystemManager::new();
+    let mut journal = UndoJournal::new();
+    let from = temp_dir.path().join("file.txt");
+    let to = temp_dir.path().join("target").join("file.txt");
+
+    // 移動ジョブが移動し終えた項目を記録する
+    fs::rename(&from, &to).unwrap();
+    journal.record_moved(&from, &to);
+    // 既にない項目は記録しない
+    journal.record_moved(&to, &temp_dir.path().join("missing.txt"));
+    assert_eq!(journal.undo_entries().len(), 1);
+
+    journal.undo(&manager).await.unwrap();
+    assert!(from.exists());
+    assert!(!to.exists());
+    journal.redo(&manager).await.unwrap();
+    assert!(to.exists());
+}
+
+#[tokio::test]
+async fn test_undo_create_directory() {
+    let temp_dir = create_test_dir();
+    let manager = FileSystemManager::new();
+    let mut journal = UndoJournal::new();
+    let path = temp_dir.path().join("new_folder");
+
+    journal.create_directory(&manager, &path).await.unwrap();
+    journal.undo(&manager).await.unwrap();
+    assert!(!path.exists());
+
+    journal.redo(&manager).await.unwrap();
+    assert!(path.is_dir());
+
+    // 中身が追加されたフォルダは削除しない
+    fs::write(path.join("added.txt"), "data").unwrap();
+    let result = journal.undo(&manager).await;
+    assert!(matches!(result, Err(AppError::UndoRefused(_))));
+    assert!(path.join("added.txt").exists());
+}
+
+#[tokio::test]
+async fn test_undo_redo_trash() {
+    let temp_dir = create_test_dir();
+    let manager = FileSystemManager::new();
+    let trash = TrashManager::with_data_home(temp_dir.path().join("data"));
+    let mut journal = UndoJournal::new();
+    let original = temp_dir.path().join("file.txt");
+
+    let item = journal.trash(&trash, &original).await.unwrap();
+    assert!(!original.exists());
+
+    journal.undo(&manager).await.unwrap();
+    assert_eq!(fs::read_to_string(&original).unwrap(), "content");
+    assert!(!item.info_path.exists());
+
+    journal.redo(&manager).await.unwrap();
+    assert!(!original.exists());
+    assert!(item.trashed_path.exists());
+    assert!(item.info_path.exists());
+}
+
+#[tokio::test]
+async fn test_undo_refuses_when_modified() {
+    let temp_dir = create_test_dir();
+    let manager = FileSystemManager::new();
+    let mut journal = UndoJournal::new();
+    let original = temp_dir.path().join("file.txt");
+
+    let renamed = journal
+        .rename(&manager, &original, "renamed.txt")
+        .await
+        .unwrap();
+    fs::write(&renamed, "changed after rename").unwrap();
+
+    let result = journal.undo(&manager).await;
+    assert!(matches!(result, Err(AppError::UndoRefused(_))));
+    assert!(!original.exists());
+    assert!(!journal.can_undo());
+}
+
+#[tokio::test]
+async fn test_undo_refuses_to_clobber() {
+    let temp_dir = create_test_dir();
+    let manager = FileSystemManager::new();
+    let mut journal = UndoJournal::new();
+    let original = temp_dir.path().join("file.txt");
+
+    journal
+        .rename(&manager, &original, "renamed.txt")
+        .await
+        .unwrap();
+    fs::write(&original, "new file with the old name").unwrap();
+
+    let result = journal.undo(&manager).await;
+    assert!(matches!(result, Err(AppError::UndoRefused(_))));
+    assert_eq!(
+        fs::read_to_string(&original).unwrap(),
+        "new file with the old name"
+    );
+}
+
+#[tokio::test]
+async fn test_journal_limits_and_clears_redo() {
+    let temp_dir = create_test_dir();
+    let manager = FileSystemManager::new();
+    let mut journal = UndoJournal::with_max_entries(2);
+
+    for name in ["a", "b", "c"] {
+        journal
+            .create_directory(&manager, &temp_dir.path().join(name))
+            .await
+            .unwrap();
+    }
+    assert_eq!(journal.undo_entries().len(), 2);
+
+    journal.undo(&manager).await.unwrap();
+    assert!(journal.can_redo());
+
+    // 新しい操作を記録するとやり直し履歴は消える
+    journal
+        .create_directory(&manager, &temp_dir.path().join("d"))
+        .await
+        .unwrap();
+    assert!(!journal.can_redo());
+}
+
+#[tokio::test]
+async fn test_undo_in_steps_releases_journal() {
+    let temp_dir = create_test_dir();
+    let manager = FileSystemManager::new();
+    let mut journal = UndoJournal::new();
+    let original = temp_dir.path().join("file.txt");
+
+    assert!(matches!(
+        journal.take_undo(),
+        Err(AppError::InvalidOperation(_))
+    ));
+
+    let renamed = journal
+        .rename(&manager, &original, "renamed.txt")
+        .await
+        .unwrap();
+
+    // 取り出してから戻すまでの間も、別の移動を記録できる
+    let entry = journal.take_undo().unwrap();
+    let moved_to = temp_dir.path().join("moved.txt");
+    fs::write(&moved_to, "moved").unwrap();
+    journal.record_moved(&temp_dir.path().join("other.txt"), &moved_to);
+    let result = entry.revert(&manager).await;
+    journal.finish_undo(entry, result).unwrap();
+
+    assert!(original.exists());
+    assert!(!renamed.exists());
+    assert_eq!(journal.undo_entries().len(), 1);
+    assert!(journal.can_redo());
+}
+
+#[tokio::test]
+async fn test_journal_serialization_round_trip() {
+    let temp_dir = create_test_dir();
+    let manager = FileSystemManager::new();
+    let mut journal = UndoJournal::new();
+    let original = temp_dir.path().join("file.txt");
+
+    journal
+        .rename(&manager, &original, "renamed.txt")
+        .await
+        .unwrap();
+
+    // 再起動後を想定して復元したジャーナルで元に戻す
+    let json = serde_json::to_string(&journal).unwrap();
+    let mut restored: UndoJournal = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, journal);
+
+    restored.undo(&manager).await.unwrap();
+    assert!(original.exists());
+}
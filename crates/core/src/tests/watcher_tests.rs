@@ -0,0 +1,111 @@
+//! ディレクトリ監視のテスト
+
+use crate::event::{Event, EventManager};
+use crate::watcher::{DirectoryChange, DirectoryDelta, DirectoryWatcher};
+use std::fs;
+use std::sync::mpsc;
+use std::time::Duration;
+use tempfile::TempDir;
+
+/// 監視サービスと差分の受信側を作成
+fn create_watcher() -> (DirectoryWatcher, mpsc::Receiver<DirectoryDelta>) {
+    let event_manager = EventManager::new();
+    let (sender, receiver) = mpsc::channel();
+    event_manager
+        .on_event(move |event| {
+            if let Event::DirectoryUpdated(delta) = event {
+                let _ = sender.send(delta.clone());
+            }
+        })
+        .unwrap();
+
+    let watcher =
+        DirectoryWatcher::with_debounce(event_manager, Duration::from_millis(50)).unwrap();
+    (watcher, receiver)
+}
+
+/// 期待する変更が届くまで差分を受信する
+fn wait_for_change(
+    receiver: &mpsc::Receiver<DirectoryDelta>,
+    expected: &DirectoryChange,
+) -> DirectoryDelta {
+    loop {
+        let delta = receiver
+            .recv_timeout(Duration::from_secs(5))
+            .expect("no directory update received");
+        if delta.changes.contains(expected) {
+            return delta;
+        }
+    }
+}
+
+#[test]
+fn test_watcher_reports_changes() {
+    let temp_dir = TempDir::new().unwrap();
+    let (watcher, receiver) = create_watcher();
+    watcher.watch(temp_dir.path()).unwrap();
+
+    let file = temp_dir.path().join("new.txt");
+    fs::write(&file, "content").unwrap();
+    let delta = wait_for_change(&receiver, &DirectoryChange::Added(file.clone()));
+    assert_eq!(delta.directory, temp_dir.path());
+
+    let renamed = temp_dir.path().join("renamed.txt");
+    fs::rename(&file, &renamed).unwrap();
+    wait_for_change(
+        &receiver,
+        &DirectoryChange::Renamed {
+            from: file,
+            to: renamed.clone(),
+        },
+    );
+
+    fs::remove_file(&renamed).unwrap();
+    wait_for_change(&receiver, &DirectoryChange::Removed(renamed));
+}
+
+#[test]
+fn test_watch_is_reference_counted() {
+    let temp_dir = TempDir::new().unwrap();
+    let (watcher, receiver) = create_watcher();
+
+    watcher.watch(temp_dir.path()).unwrap();
+    watcher.watch(temp_dir.path()).unwrap();
+    watcher.unwatch(temp_dir.path()).unwrap();
+    assert_eq!(watcher.watched(), vec![temp_dir.path().to_path_buf()]);
+
+    watcher.unwatch(temp_dir.path()).unwrap();
+    assert!(watcher.watched().is_empty());
+
+    fs::write(temp_dir.path().join("ignored.txt"), "x").unwrap();
+    assert!(receiver.recv_timeout(Duration::from_millis(300)).is_err());
+
+    assert!(watcher.watch(&temp_dir.path().join("missing")).is_err());
+}
+
+#[test]
+fn test_apply_delta() {
+    let temp_dir = TempDir::new().unwrap();
+    let keep = temp_dir.path().join("keep.txt");
+    let added = temp_dir.path().join("added.txt");
+    fs::write(&keep, "keep").unwrap();
+    fs::write(&added, "added").unwrap();
+
+    let mut entries = vec![crate::FileEntry::from_path(&keep).unwrap()];
+    let mut removed = entries[0].clone();
+    removed.path = temp_dir.path().join("removed.txt");
+    entries.push(removed.clone());
+
+    let delta = DirectoryDelta {
+        directory: temp_dir.path().to_path_buf(),
+        changes: vec![
+            DirectoryChange::Added(added.clone()),
+            DirectoryChange::Removed(removed.path),
+        ],
+    };
+    delta.apply_to(&mut entries);
+
+    let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, vec!["keep.txt", "added.txt"]);
+    assert_eq!(entries[1].size, 5);
+}
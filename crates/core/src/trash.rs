@@ -0,0 +1,594 @@
+//! ゴミ箱（freedesktop.org Trash 仕様）
+//!
+//! 削除した項目を `$XDG_DATA_HOME/Trash` またはマウントポイントごとの
+//! `.Trash-$uid` へ移動し、一覧・復元・空にする操作を提供します。
+
+use crate::conflict::numbered_name;
+use crate::filesystem::remove_tree;
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC, percent_decode_str, percent_encode};
+use rust_explorer_utils::AppError;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tokio::io::AsyncWriteExt;
+
+/// `.trashinfo` のパスでエスケープしない文字（RFC 2396 の unreserved と区切りの `/`）
+const PATH_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'/')
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~')
+    .remove(b'!')
+    .remove(b'*')
+    .remove(b'\'')
+    .remove(b'(')
+    .remove(b')');
+
+/// `.trashinfo` の削除日時の形式
+const DELETION_DATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// `.trashinfo` の拡張子
+const INFO_EXTENSION: &str = ".trashinfo";
+
+/// ゴミ箱内の項目
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrashItem {
+    /// `files/` 内での名前
+    pub name: String,
+    /// 削除前のパス
+    pub original_path: PathBuf,
+    /// 削除日時（ローカル時刻）
+    pub deletion_date: Option<chrono::NaiveDateTime>,
+    /// `files/` 内の実体のパス
+    pub trashed_path: PathBuf,
+    /// `info/` 内の `.trashinfo` のパス
+    pub info_path: PathBuf,
+    pub is_dir: bool,
+    pub size: u64,
+}
+
+/// 1つのゴミ箱ディレクトリ
+#[derive(Debug, Clone, PartialEq)]
+struct TrashDirectory {
+    root: PathBuf,
+    /// マウントポイントのゴミ箱の場合はそのトップディレクトリ（相対パスの基準）
+    topdir: Option<PathBuf>,
+}
+
+impl TrashDirectory {
+    fn files(&self) -> PathBuf {
+        self.root.join("files")
+    }
+
+    fn info(&self) -> PathBuf {
+        self.root.join("info")
+    }
+}
+
+/// ゴミ箱管理
+#[derive(Debug, Clone)]
+pub struct TrashManager {
+    home_trash: PathBuf,
+}
+
+impl Default for TrashManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TrashManager {
+    /// `$XDG_DATA_HOME/Trash`（未設定なら `~/.local/share/Trash`）を使うゴミ箱を作成
+    pub fn new() -> Self {
+        Self::with_data_home(default_data_home())
+    }
+
+    /// データディレクトリを指定してゴミ箱を作成
+    pub fn with_data_home(data_home: impl Into<PathBuf>) -> Self {
+        Self {
+            home_trash: data_home.into().join("Trash"),
+        }
+    }
+
+    /// ホームのゴミ箱のパス
+    pub fn home_trash(&self) -> &Path {
+        &self.home_trash
+    }
+
+    /// 項目をゴミ箱へ移動
+    pub async fn trash(&self, path: &Path) -> Result<TrashItem, AppError> {
+        let path = std::path::absolute(path).map_err(|_| AppError::InvalidPath(path.into()))?;
+        let metadata = fs::symlink_metadata(&path)
+            .await
+            .map_err(|_| AppError::NotFound(path.clone()))?;
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .ok_or_else(|| AppError::InvalidPath(path.clone()))?;
+
+        let directory = self.directory_for(&path).await?;
+        if directory.root.starts_with(&path) || path.starts_with(&directory.root) {
+            return Err(AppError::InvalidOperation(format!(
+                "Cannot move {} to the trash",
+                path.display()
+            )));
+        }
+
+        let trash_error = |source| AppError::TrashFailed {
+            path: path.clone(),
+            source,
+        };
+        fs::create_dir_all(directory.files())
+            .await
+            .map_err(trash_error)?;
+        fs::create_dir_all(directory.info())
+            .await
+            .map_err(trash_error)?;
+
+        let (name, info_path) = reserve_name(&directory, &file_name, &path)
+            .await
+            .map_err(trash_error)?;
+        let trashed_path = directory.files().join(&name);
+
+        if let Err(source) = fs::rename(&path, &trashed_path).await {
+            let _ = fs::remove_file(&info_path).await;
+            return Err(trash_error(source));
+        }
+
+        Ok(TrashItem {
+            name,
+            original_path: path.clone(),
+            deletion_date: Some(chrono::Local::now().naive_local()),
+            trashed_path,
+            info_path,
+            is_dir: metadata.is_dir(),
+            size: metadata.len(),
+        })
+    }
+
+    /// ゴミ箱内の項目を新しい順に一覧
+    pub async fn list(&self) -> Result<Vec<TrashItem>, AppError> {
+        let mut items = Vec::new();
+        for directory in self.directories().await {
+            items.extend(list_directory(&directory).await?);
+        }
+        items.sort_by_key(|item| std::cmp::Reverse(item.deletion_date));
+        Ok(items)
+    }
+
+    /// ゴミ箱内の項目数
+    pub async fn count(&self) -> usize {
+        self.list().await.map(|items| items.len()).unwrap_or(0)
+    }
+
+    /// 項目を元の場所へ復元し、復元先のパスを返す
+    pub async fn restore(&self, item: &TrashItem) -> Result<PathBuf, AppError> {
+        let original = &item.original_path;
+        if fs::symlink_metadata(original).await.is_ok() {
+            return Err(AppError::AlreadyExists(original.clone()));
+        }
+        let move_error = |source| AppError::MoveFailed {
+            from: item.trashed_path.clone(),
+            to: original.clone(),
+            source,
+        };
+
+        if let Some(parent) = original.parent() {
+            fs::create_dir_all(parent).await.map_err(move_error)?;
+        }
+        fs::rename(&item.trashed_path, original)
+            .await
+            .map_err(move_error)?;
+        let _ = fs::remove_file(&item.info_path).await;
+
+        Ok(original.clone())
+    }
+
+    /// 項目をゴミ箱から完全に削除
+    pub async fn delete_permanently(&self, item: &TrashItem) -> Result<(), AppError> {
+        remove_tree(&item.trashed_path).await?;
+        let _ = fs::remove_file(&item.info_path).await;
+        Ok(())
+    }
+
+    /// すべてのゴミ箱を空にし、削除した項目数を返す
+    pub async fn empty(&self) -> Result<usize, AppError> {
+        let mut removed = 0;
+        for directory in self.directories().await {
+            for (dir, count) in [(directory.files(), true), (directory.info(), false)] {
+                let Ok(mut entries) = fs::read_dir(&dir).await else {
+                    continue;
+                };
+                while let Some(entry) = entries.next_entry().await.map_err(AppError::FileSystem)? {
+                    remove_tree(&entry.path()).await?;
+                    if count {
+                        removed += 1;
+                    }
+                }
+            }
+        }
+        Ok(removed)
+    }
+
+    /// 一覧対象の既存ゴミ箱ディレクトリ
+    async fn directories(&self) -> Vec<TrashDirectory> {
+        let mut directories = vec![TrashDirectory {
+            root: self.home_trash.clone(),
+            topdir: None,
+        }];
+
+        for topdir in mount_points().await {
+            for directory in mount_trash_candidates(&topdir) {
+                if fs::metadata(&directory.root)
+                    .await
+                    .is_ok_and(|m| m.is_dir())
+                    && !directories.iter().any(|d| d.root == directory.root)
+                {
+                    directories.push(directory);
+                }
+            }
+        }
+        directories
+    }
+
+    /// 項目の移動先となるゴミ箱ディレクトリを決定する
+    #[cfg(unix)]
+    async fn directory_for(&self, path: &Path) -> Result<TrashDirectory, AppError> {
+        use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+        let home = TrashDirectory {
+            root: self.home_trash.clone(),
+            topdir: None,
+        };
+        let device = fs::symlink_metadata(path)
+            .await
+            .map_err(|_| AppError::NotFound(path.to_path_buf()))?
+            .dev();
+
+        // ホームのゴミ箱は未作成の場合があるため、存在する最も近い祖先で比較する
+        for ancestor in self.home_trash.ancestors() {
+            if let Ok(metadata) = fs::metadata(ancestor).await {
+                if metadata.dev() == device {
+                    return Ok(home);
+                }
+                break;
+            }
+        }
+
+        let topdir = mount_topdir(path, device).await;
+        let [shared, personal] = mount_trash_candidates(&topdir);
+
+        // 管理者が用意した $topdir/.Trash はスティッキービット付きで
+        // シンボリックリンクでない場合のみ使う
+        let shared_parent = topdir.join(".Trash");
+        if let Ok(metadata) = fs::symlink_metadata(&shared_parent).await
+            && metadata.is_dir()
+            && metadata.permissions().mode() & 0o1000 != 0
+            && create_private_dir(&shared.root).await.is_ok()
+        {
+            return Ok(shared);
+        }
+
+        create_private_dir(&personal.root)
+            .await
+            .map_err(|source| AppError::TrashFailed {
+                path: path.to_path_buf(),
+                source,
+            })?;
+        Ok(personal)
+    }
+
+    /// 項目の移動先となるゴミ箱ディレクトリを決定する
+    #[cfg(not(unix))]
+    async fn directory_for(&self, _path: &Path) -> Result<TrashDirectory, AppError> {
+        Ok(TrashDirectory {
+            root: self.home_trash.clone(),
+            topdir: None,
+        })
+    }
+}
+
+/// `$XDG_DATA_HOME`（未設定または相対パスなら `~/.local/share`）
+fn default_data_home() -> PathBuf {
+    std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .unwrap_or_else(|| {
+            std::env::var_os("HOME")
+                .map(PathBuf::from)
+                .unwrap_or_default()
+                .join(".local")
+                .join("share")
+        })
+}
+
+/// 現在のユーザーID
+#[cfg(unix)]
+fn current_uid() -> u32 {
+    // SAFETY: getuid は常に成功し、副作用もない
+    unsafe { libc::getuid() }
+}
+
+#[cfg(not(unix))]
+fn current_uid() -> u32 {
+    0
+}
+
+/// マウントポイントのゴミ箱の候補（`$topdir/.Trash/$uid` と `$topdir/.Trash-$uid`）
+fn mount_trash_candidates(topdir: &Path) -> [TrashDirectory; 2] {
+    let uid = current_uid();
+    [
+        TrashDirectory {
+            root: topdir.join(".Trash").join(uid.to_string()),
+            topdir: Some(topdir.to_path_buf()),
+        },
+        TrashDirectory {
+            root: topdir.join(format!(".Trash-{uid}")),
+            topdir: Some(topdir.to_path_buf()),
+        },
+    ]
+}
+
+/// パスが属するマウントポイントのトップディレクトリ
+#[cfg(unix)]
+async fn mount_topdir(path: &Path, device: u64) -> PathBuf {
+    use std::os::unix::fs::MetadataExt;
+
+    let mut topdir = path.parent().unwrap_or(path).to_path_buf();
+    while let Some(parent) = topdir.parent() {
+        match fs::metadata(parent).await {
+            Ok(metadata) if metadata.dev() == device => topdir = parent.to_path_buf(),
+            _ => break,
+        }
+    }
+    topdir
+}
+
+/// 自分だけが読み書きできるディレクトリを作成
+#[cfg(unix)]
+async fn create_private_dir(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    if fs::metadata(path).await.is_ok_and(|m| m.is_dir()) {
+        return Ok(());
+    }
+    fs::create_dir_all(path).await?;
+    fs::set_permissions(path, std::fs::Permissions::from_mode(0o700)).await
+}
+
+/// マウントされているファイルシステムのトップディレクトリ一覧
+#[cfg(target_os = "linux")]
+async fn mount_points() -> Vec<PathBuf> {
+    let Ok(mounts) = fs::read_to_string("/proc/self/mounts").await else {
+        return Vec::new();
+    };
+    mounts
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(1))
+        .map(|field| PathBuf::from(unescape_mount_field(field)))
+        .collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+async fn mount_points() -> Vec<PathBuf> {
+    Vec::new()
+}
+
+/// `/proc/self/mounts` の8進エスケープ（`\040` など）を戻す
+#[cfg(target_os = "linux")]
+fn unescape_mount_field(field: &str) -> String {
+    let mut result = String::with_capacity(field.len());
+    let mut chars = field.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            let digits: String = chars.clone().take(3).collect();
+            if let Ok(code) = u8::from_str_radix(&digits, 8) {
+                result.push(code as char);
+                chars.nth(2);
+                continue;
+            }
+        }
+        result.push(c);
+    }
+    result
+}
+
+/// 重ならない名前を選び、`.trashinfo` を作成して予約する
+async fn reserve_name(
+    directory: &TrashDirectory,
+    file_name: &str,
+    original: &Path,
+) -> std::io::Result<(String, PathBuf)> {
+    let mut n = 1;
+    loop {
+        let name = if n == 1 {
+            file_name.to_string()
+        } else {
+            numbered_name(file_name, n)
+        };
+        n += 1;
+
+        if fs::symlink_metadata(directory.files().join(&name))
+            .await
+            .is_ok()
+        {
+            continue;
+        }
+        let info_path = directory.info().join(format!("{name}{INFO_EXTENSION}"));
+        match write_info_file(&info_path, original).await {
+            Ok(()) => return Ok((name, info_path)),
+            Err(error) if error.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+/// `.trashinfo` を新規作成（既存なら AlreadyExists）
+async fn write_info_file(info_path: &Path, original: &Path) -> std::io::Result<()> {
+    let content = format!(
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        encode_path(original),
+        chrono::Local::now().format(DELETION_DATE_FORMAT)
+    );
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(info_path)
+        .await?;
+    file.write_all(content.as_bytes()).await?;
+    file.flush().await
+}
+
+/// ゴミ箱内の実体のパスに対応する `.trashinfo` のパス
+pub(crate) fn info_path_for(trashed: &Path) -> Option<PathBuf> {
+    let name = trashed.file_name()?.to_string_lossy();
+    let root = trashed.parent()?.parent()?;
+    Some(root.join("info").join(format!("{name}{INFO_EXTENSION}")))
+}
+
+/// 元に戻した項目を再びゴミ箱の同じ場所へ入れるため `.trashinfo` を書き直す
+pub(crate) async fn write_info(trashed: &Path, original: &Path) -> Result<(), AppError> {
+    let info_path = info_path_for(trashed).ok_or_else(|| AppError::InvalidPath(trashed.into()))?;
+    write_info_file(&info_path, original)
+        .await
+        .map_err(|source| AppError::TrashFailed {
+            path: original.to_path_buf(),
+            source,
+        })
+}
+
+/// ゴミ箱から取り出した項目の `.trashinfo` を削除
+pub(crate) async fn remove_info(trashed: &Path) {
+    if let Some(info_path) = info_path_for(trashed) {
+        let _ = fs::remove_file(info_path).await;
+    }
+}
+
+/// 1つのゴミ箱ディレクトリの項目を列挙（実体のない `.trashinfo` は無視する）
+async fn list_directory(directory: &TrashDirectory) -> Result<Vec<TrashItem>, AppError> {
+    let Ok(mut entries) = fs::read_dir(directory.info()).await else {
+        return Ok(Vec::new());
+    };
+
+    let mut items = Vec::new();
+    while let Some(entry) = entries.next_entry().await.map_err(AppError::FileSystem)? {
+        let info_path = entry.path();
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let Some(name) = file_name.strip_suffix(INFO_EXTENSION) else {
+            continue;
+        };
+        let Ok(content) = fs::read_to_string(&info_path).await else {
+            continue;
+        };
+        let Some((original, deletion_date)) = parse_info(&content) else {
+            continue;
+        };
+
+        let trashed_path = directory.files().join(name);
+        let Ok(metadata) = fs::symlink_metadata(&trashed_path).await else {
+            continue;
+        };
+        let original_path = match &directory.topdir {
+            Some(topdir) if original.is_relative() => topdir.join(original),
+            _ => original,
+        };
+
+        items.push(TrashItem {
+            name: name.to_string(),
+            original_path,
+            deletion_date,
+            trashed_path,
+            info_path,
+            is_dir: metadata.is_dir(),
+            size: metadata.len(),
+        });
+    }
+    Ok(items)
+}
+
+/// `.trashinfo` の内容から元のパスと削除日時を取り出す
+fn parse_info(content: &str) -> Option<(PathBuf, Option<chrono::NaiveDateTime>)> {
+    let mut lines = content.lines().map(str::trim);
+    if lines.next()? != "[Trash Info]" {
+        return None;
+    }
+
+    let mut path = None;
+    let mut deletion_date = None;
+    for line in lines {
+        if let Some(value) = line.strip_prefix("Path=") {
+            path = Some(decode_path(value));
+        } else if let Some(value) = line.strip_prefix("DeletionDate=") {
+            deletion_date = chrono::NaiveDateTime::parse_from_str(value, DELETION_DATE_FORMAT).ok();
+        }
+    }
+    Some((path?, deletion_date))
+}
+
+/// パスを `.trashinfo` 用にパーセントエンコード
+fn encode_path(path: &Path) -> String {
+    #[cfg(unix)]
+    let bytes = std::os::unix::ffi::OsStrExt::as_bytes(path.as_os_str()).to_vec();
+    #[cfg(not(unix))]
+    let bytes = path.to_string_lossy().into_owned().into_bytes();
+
+    percent_encode(&bytes, PATH_ENCODE_SET).to_string()
+}
+
+/// パーセントエンコードされたパスを戻す
+fn decode_path(value: &str) -> PathBuf {
+    let bytes: Vec<u8> = percent_decode_str(value).collect();
+
+    #[cfg(unix)]
+    let os_string: OsString = std::os::unix::ffi::OsStringExt::from_vec(bytes);
+    #[cfg(not(unix))]
+    let os_string = OsString::from(String::from_utf8_lossy(&bytes).into_owned());
+
+    PathBuf::from(os_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_encoding_round_trip() {
+        let path = Path::new("/home/user/日本語 ファイル%.txt");
+        let encoded = encode_path(path);
+        assert_eq!(
+            encoded,
+            "/home/user/%E6%97%A5%E6%9C%AC%E8%AA%9E%20%E3%83%95%E3%82%A1%E3%82%A4%E3%83%AB%25.txt"
+        );
+        assert_eq!(decode_path(&encoded), path);
+    }
+
+    #[test]
+    fn test_parse_info() {
+        let (path, date) =
+            parse_info("[Trash Info]\nPath=foo/bar%20baz\nDeletionDate=2024-05-01T12:30:00\n")
+                .unwrap();
+        assert_eq!(path, PathBuf::from("foo/bar baz"));
+        assert_eq!(
+            date.unwrap().format(DELETION_DATE_FORMAT).to_string(),
+            "2024-05-01T12:30:00"
+        );
+
+        assert!(parse_info("Path=/tmp/a\n").is_none());
+        assert!(parse_info("[Trash Info]\nDeletionDate=2024-05-01T12:30:00\n").is_none());
+    }
+
+    #[test]
+    fn test_info_path_for() {
+        assert_eq!(
+            info_path_for(Path::new("/data/Trash/files/a.txt")),
+            Some(PathBuf::from("/data/Trash/info/a.txt.trashinfo"))
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_unescape_mount_field() {
+        assert_eq!(unescape_mount_field("/media/my\\040disk"), "/media/my disk");
+    }
+}
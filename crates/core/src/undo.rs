@@ -0,0 +1,512 @@
+//! ファイル操作の元に戻す・やり直し
+//!
+//! 名前の変更・移動・フォルダ作成・ゴミ箱への移動を記録するジャーナルを提供します。
+//! 移動ジョブで移動した項目も `record_moved` で記録できます。
+//! ジャーナルはシリアライズ可能で、再起動後も元に戻せるよう永続化できます。
+
+use crate::filesystem::FileSystemApi;
+use crate::trash::{self, TrashItem, TrashManager};
+use rust_explorer_utils::AppError;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// デフォルトで保持する操作数
+const DEFAULT_MAX_ENTRIES: usize = 100;
+
+/// 操作後の項目の状態（元に戻す前に変更されていないか確認するため）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Fingerprint {
+    pub is_dir: bool,
+    pub size: u64,
+    pub modified: Option<SystemTime>,
+}
+
+impl Fingerprint {
+    /// パスの現在の状態を取得（存在しなければ None）
+    pub fn capture(path: &Path) -> Option<Self> {
+        let metadata = std::fs::symlink_metadata(path).ok()?;
+        Some(Self {
+            is_dir: metadata.is_dir(),
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+
+    /// 同じ項目とみなせるか（ディレクトリは中身の変化で更新日時が変わるため種類のみ比較）
+    pub fn matches(&self, other: &Fingerprint) -> bool {
+        if self.is_dir || other.is_dir {
+            return self.is_dir == other.is_dir;
+        }
+        self.size == other.size && self.modified == other.modified
+    }
+}
+
+/// 記録される操作
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum JournalOperation {
+    /// 名前の変更
+    Rename {
+        from: PathBuf,
+        to: PathBuf,
+        fingerprint: Fingerprint,
+    },
+    /// 移動
+    Move {
+        from: PathBuf,
+        to: PathBuf,
+        fingerprint: Fingerprint,
+    },
+    /// フォルダ作成
+    CreateDirectory { path: PathBuf },
+    /// ゴミ箱への移動
+    Trash {
+        original: PathBuf,
+        trashed: PathBuf,
+        fingerprint: Fingerprint,
+    },
+}
+
+impl JournalOperation {
+    /// 表示用の説明
+    pub fn description(&self) -> String {
+        let name = |path: &Path| {
+            path.file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| path.display().to_string())
+        };
+        match self {
+            JournalOperation::Rename { from, to, .. } => {
+                format!("「{}」を「{}」に名前変更", name(from), name(to))
+            }
+            JournalOperation::Move { from, to, .. } => {
+                format!("「{}」を {} へ移動", name(from), to.display())
+            }
+            JournalOperation::CreateDirectory { path } => {
+                format!("フォルダ「{}」を作成", name(path))
+            }
+            JournalOperation::Trash { original, .. } => {
+                format!("「{}」をゴミ箱へ移動", name(original))
+            }
+        }
+    }
+}
+
+/// ジャーナルの1件
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub id: u64,
+    pub operation: JournalOperation,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+}
+
+/// 元に戻す・やり直しのジャーナル
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UndoJournal {
+    undo_stack: Vec<JournalEntry>,
+    redo_stack: Vec<JournalEntry>,
+    next_id: u64,
+    max_entries: usize,
+}
+
+impl UndoJournal {
+    /// 新しいジャーナルを作成
+    pub fn new() -> Self {
+        Self::with_max_entries(DEFAULT_MAX_ENTRIES)
+    }
+
+    /// 保持する操作数を指定してジャーナルを作成
+    pub fn with_max_entries(max_entries: usize) -> Self {
+        Self {
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            next_id: 1,
+            max_entries: max_entries.max(1),
+        }
+    }
+
+    /// 操作を記録（やり直し履歴は破棄される）
+    pub fn record(&mut self, operation: JournalOperation) {
+        self.undo_stack.push(JournalEntry {
+            id: self.next_id,
+            operation,
+            timestamp: chrono::Utc::now(),
+        });
+        self.next_id += 1;
+        self.redo_stack.clear();
+
+        if self.undo_stack.len() > self.max_entries {
+            let excess = self.undo_stack.len() - self.max_entries;
+            self.undo_stack.drain(..excess);
+        }
+    }
+
+    /// 元に戻せる操作があるか
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    /// やり直せる操作があるか
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// 次に元に戻す操作
+    pub fn peek_undo(&self) -> Option<&JournalEntry> {
+        self.undo_stack.last()
+    }
+
+    /// 次にやり直す操作
+    pub fn peek_redo(&self) -> Option<&JournalEntry> {
+        self.redo_stack.last()
+    }
+
+    /// 元に戻せる操作の一覧（古い順）
+    pub fn undo_entries(&self) -> &[JournalEntry] {
+        &self.undo_stack
+    }
+
+    /// 履歴をすべて消去
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+
+    /// 名前を変更して記録
+    pub async fn rename<F: FileSystemApi + Sync>(
+        &mut self,
+        fs: &F,
+        path: &Path,
+        new_name: &str,
+    ) -> Result<PathBuf, AppError> {
+        let to = fs.rename(path, new_name).await?;
+        if let Some(fingerprint) = Fingerprint::capture(&to) {
+            self.record(JournalOperation::Rename {
+                from: path.to_path_buf(),
+                to: to.clone(),
+                fingerprint,
+            });
+        }
+        Ok(to)
+    }
+
+    /// 移動して記録
+    pub async fn move_item<F: FileSystemApi + Sync>(
+        &mut self,
+        fs: &F,
+        from: &Path,
+        to: &Path,
+    ) -> Result<(), AppError> {
+        fs.move_item(from, to).await?;
+        self.record_moved(from, to);
+        Ok(())
+    }
+
+    /// 移動ジョブなどほかの方法で移動し終えた項目を記録
+    pub fn record_moved(&mut self, from: &Path, to: &Path) {
+        if let Some(fingerprint) = Fingerprint::capture(to) {
+            self.record(JournalOperation::Move {
+                from: from.to_path_buf(),
+                to: to.to_path_buf(),
+                fingerprint,
+            });
+        }
+    }
+
+    /// フォルダを作成して記録
+    pub async fn create_directory<F: FileSystemApi + Sync>(
+        &mut self,
+        fs: &F,
+        path: &Path,
+    ) -> Result<(), AppError> {
+        fs.create_directory(path).await?;
+        self.record(JournalOperation::CreateDirectory {
+            path: path.to_path_buf(),
+        });
+        Ok(())
+    }
+
+    /// ゴミ箱へ移動して記録
+    pub async fn trash(
+        &mut self,
+        trash: &TrashManager,
+        path: &Path,
+    ) -> Result<TrashItem, AppError> {
+        let item = trash.trash(path).await?;
+        if let Some(fingerprint) = Fingerprint::capture(&item.trashed_path) {
+            self.record(JournalOperation::Trash {
+                original: item.original_path.clone(),
+                trashed: item.trashed_path.clone(),
+                fingerprint,
+            });
+        }
+        Ok(item)
+    }
+
+    /// 直前の操作を元に戻し、戻した操作を返す
+    ///
+    /// 操作後にファイルが変更・削除されていた場合は `AppError::UndoRefused` を返し、
+    /// その操作は履歴から取り除かれる。
+    pub async fn undo<F: FileSystemApi + Sync>(
+        &mut self,
+        fs: &F,
+    ) -> Result<JournalEntry, AppError> {
+        let entry = self.take_undo()?;
+        let result = entry.revert(fs).await;
+        self.finish_undo(entry, result)
+    }
+
+    /// 元に戻した操作をやり直し、やり直した操作を返す
+    pub async fn redo<F: FileSystemApi + Sync>(
+        &mut self,
+        fs: &F,
+    ) -> Result<JournalEntry, AppError> {
+        let entry = self.take_redo()?;
+        let result = entry.reapply(fs).await;
+        self.finish_redo(entry, result)
+    }
+
+    /// 次に元に戻す操作を履歴から取り出す
+    ///
+    /// ジャーナルを共有している場合に、ロックを保持したままファイルを操作しないための
+    /// 分割版。取り出した操作は `JournalEntry::revert` の結果とともに `finish_undo` に渡す。
+    pub fn take_undo(&mut self) -> Result<JournalEntry, AppError> {
+        self.undo_stack
+            .pop()
+            .ok_or_else(|| AppError::InvalidOperation("Nothing to undo".to_string()))
+    }
+
+    /// 次にやり直す操作を履歴から取り出す（`take_undo` のやり直し版）
+    pub fn take_redo(&mut self) -> Result<JournalEntry, AppError> {
+        self.redo_stack
+            .pop()
+            .ok_or_else(|| AppError::InvalidOperation("Nothing to redo".to_string()))
+    }
+
+    /// 元に戻した結果を履歴に反映し、戻した操作を返す
+    pub fn finish_undo(
+        &mut self,
+        entry: JournalEntry,
+        result: Result<JournalOperation, AppError>,
+    ) -> Result<JournalEntry, AppError> {
+        match result {
+            Ok(operation) => {
+                let entry = JournalEntry { operation, ..entry };
+                self.redo_stack.push(entry.clone());
+                Ok(entry)
+            }
+            Err(error @ AppError::UndoRefused(_)) => Err(error),
+            Err(error) => {
+                // 一時的な失敗の可能性があるため履歴に戻す
+                self.undo_stack.push(entry);
+                Err(error)
+            }
+        }
+    }
+
+    /// やり直した結果を履歴に反映し、やり直した操作を返す
+    pub fn finish_redo(
+        &mut self,
+        entry: JournalEntry,
+        result: Result<JournalOperation, AppError>,
+    ) -> Result<JournalEntry, AppError> {
+        match result {
+            Ok(operation) => {
+                let entry = JournalEntry { operation, ..entry };
+                self.undo_stack.push(entry.clone());
+                Ok(entry)
+            }
+            Err(error @ AppError::UndoRefused(_)) => Err(error),
+            Err(error) => {
+                self.redo_stack.push(entry);
+                Err(error)
+            }
+        }
+    }
+}
+
+impl JournalEntry {
+    /// 操作を取り消し、やり直し用に状態を更新した操作を返す
+    pub async fn revert<F: FileSystemApi + Sync>(
+        &self,
+        fs: &F,
+    ) -> Result<JournalOperation, AppError> {
+        revert(fs, &self.operation).await
+    }
+
+    /// 取り消した操作をもう一度適用し、元に戻す用に状態を更新した操作を返す
+    pub async fn reapply<F: FileSystemApi + Sync>(
+        &self,
+        fs: &F,
+    ) -> Result<JournalOperation, AppError> {
+        reapply(fs, &self.operation).await
+    }
+}
+
+impl Default for UndoJournal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// `path` に記録時と同じ項目があることを確認
+fn ensure_unchanged(path: &Path, fingerprint: &Fingerprint) -> Result<(), AppError> {
+    match Fingerprint::capture(path) {
+        Some(current) if current.matches(fingerprint) => Ok(()),
+        Some(_) => Err(AppError::UndoRefused(format!(
+            "{} has been modified since the operation",
+            path.display()
+        ))),
+        None => Err(AppError::UndoRefused(format!(
+            "{} no longer exists",
+            path.display()
+        ))),
+    }
+}
+
+/// `path` が空いていることを確認（上書きしないため）
+fn ensure_vacant(path: &Path) -> Result<(), AppError> {
+    if std::fs::symlink_metadata(path).is_ok() {
+        Err(AppError::UndoRefused(format!(
+            "{} is already occupied",
+            path.display()
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// ファイル名部分を取得
+fn file_name(path: &Path) -> Result<String, AppError> {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| AppError::InvalidPath(path.to_path_buf()))
+}
+
+/// 操作を取り消し、やり直し用に状態を更新した操作を返す
+async fn revert<F: FileSystemApi + Sync>(
+    fs: &F,
+    operation: &JournalOperation,
+) -> Result<JournalOperation, AppError> {
+    match operation {
+        JournalOperation::Rename {
+            from,
+            to,
+            fingerprint,
+        } => {
+            ensure_unchanged(to, fingerprint)?;
+            ensure_vacant(from)?;
+            fs.rename(to, &file_name(from)?).await?;
+            Ok(JournalOperation::Rename {
+                from: from.clone(),
+                to: to.clone(),
+                fingerprint: Fingerprint::capture(from).unwrap_or(fingerprint.clone()),
+            })
+        }
+        JournalOperation::Move {
+            from,
+            to,
+            fingerprint,
+        } => {
+            ensure_unchanged(to, fingerprint)?;
+            ensure_vacant(from)?;
+            fs.move_item(to, from).await?;
+            Ok(JournalOperation::Move {
+                from: from.clone(),
+                to: to.clone(),
+                fingerprint: Fingerprint::capture(from).unwrap_or(fingerprint.clone()),
+            })
+        }
+        JournalOperation::CreateDirectory { path } => {
+            if !path.is_dir() {
+                return Err(AppError::UndoRefused(format!(
+                    "{} no longer exists",
+                    path.display()
+                )));
+            }
+            // 中身が追加されたフォルダは削除しない
+            fs.delete(path, false).await.map_err(|error| match error {
+                AppError::DirectoryNotEmpty(path) => {
+                    AppError::UndoRefused(format!("{} is no longer empty", path.display()))
+                }
+                other => other,
+            })?;
+            Ok(operation.clone())
+        }
+        JournalOperation::Trash {
+            original,
+            trashed,
+            fingerprint,
+        } => {
+            ensure_unchanged(trashed, fingerprint)?;
+            ensure_vacant(original)?;
+            fs.move_item(trashed, original).await?;
+            trash::remove_info(trashed).await;
+            Ok(JournalOperation::Trash {
+                original: original.clone(),
+                trashed: trashed.clone(),
+                fingerprint: Fingerprint::capture(original).unwrap_or(fingerprint.clone()),
+            })
+        }
+    }
+}
+
+/// 取り消した操作をもう一度適用し、元に戻す用に状態を更新した操作を返す
+async fn reapply<F: FileSystemApi + Sync>(
+    fs: &F,
+    operation: &JournalOperation,
+) -> Result<JournalOperation, AppError> {
+    match operation {
+        JournalOperation::Rename {
+            from,
+            to,
+            fingerprint,
+        } => {
+            ensure_unchanged(from, fingerprint)?;
+            ensure_vacant(to)?;
+            fs.rename(from, &file_name(to)?).await?;
+            Ok(JournalOperation::Rename {
+                from: from.clone(),
+                to: to.clone(),
+                fingerprint: Fingerprint::capture(to).unwrap_or(fingerprint.clone()),
+            })
+        }
+        JournalOperation::Move {
+            from,
+            to,
+            fingerprint,
+        } => {
+            ensure_unchanged(from, fingerprint)?;
+            ensure_vacant(to)?;
+            fs.move_item(from, to).await?;
+            Ok(JournalOperation::Move {
+                from: from.clone(),
+                to: to.clone(),
+                fingerprint: Fingerprint::capture(to).unwrap_or(fingerprint.clone()),
+            })
+        }
+        JournalOperation::CreateDirectory { path } => {
+            ensure_vacant(path)?;
+            fs.create_directory(path).await?;
+            Ok(operation.clone())
+        }
+        JournalOperation::Trash {
+            original,
+            trashed,
+            fingerprint,
+        } => {
+            ensure_unchanged(original, fingerprint)?;
+            ensure_vacant(trashed)?;
+            trash::write_info(trashed, original).await?;
+            if let Err(error) = fs.move_item(original, trashed).await {
+                trash::remove_info(trashed).await;
+                return Err(error);
+            }
+            Ok(JournalOperation::Trash {
+                original: original.clone(),
+                trashed: trashed.clone(),
+                fingerprint: Fingerprint::capture(trashed).unwrap_or(fingerprint.clone()),
+            })
+        }
+    }
+}
@@ -0,0 +1,475 @@
+//! ディレクトリ変更の監視
+//!
+//! 開いているディレクトリを OS の通知機能（Linux では inotify）で監視し、
+//! 短時間に集中した変更をまとめて追加・削除・変更・名前変更の差分として
+//! `EventManager` へ通知します。
+
+use crate::event::{Event, EventManager};
+use crate::filesystem::FileEntry;
+use notify::event::{ModifyKind, RenameMode};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use rust_explorer_utils::AppError;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// デフォルトのまとめる間隔
+const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// 変更が続いても通知を遅らせる最大時間（デバウンス間隔に対する倍率）
+const MAX_LATENCY_FACTOR: u32 = 5;
+
+/// ディレクトリ内の1件の変更
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DirectoryChange {
+    Added(PathBuf),
+    Removed(PathBuf),
+    Modified(PathBuf),
+    Renamed { from: PathBuf, to: PathBuf },
+}
+
+/// 1つのディレクトリに対する変更の差分
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectoryDelta {
+    pub directory: PathBuf,
+    pub changes: Vec<DirectoryChange>,
+}
+
+impl DirectoryDelta {
+    /// 差分を一覧に適用する（並び順は呼び出し側で整える）
+    pub fn apply_to(&self, entries: &mut Vec<FileEntry>) {
+        for change in &self.changes {
+            match change {
+                DirectoryChange::Added(path) | DirectoryChange::Modified(path) => {
+                    entries.retain(|e| e.path != *path);
+                    entries.extend(FileEntry::from_path(path));
+                }
+                DirectoryChange::Removed(path) => entries.retain(|e| e.path != *path),
+                DirectoryChange::Renamed { from, to } => {
+                    entries.retain(|e| e.path != *from && e.path != *to);
+                    entries.extend(FileEntry::from_path(to));
+                }
+            }
+        }
+    }
+}
+
+/// 監視イベントを正規化したもの
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum RawChange {
+    Created(PathBuf),
+    Removed(PathBuf),
+    Modified(PathBuf),
+    Renamed(PathBuf, PathBuf),
+}
+
+impl RawChange {
+    /// notify のイベントを変換
+    pub(crate) fn from_notify(event: notify::Event) -> Vec<RawChange> {
+        let mut paths = event.paths.into_iter();
+        match event.kind {
+            EventKind::Access(_) => Vec::new(),
+            EventKind::Create(_) => paths.map(RawChange::Created).collect(),
+            EventKind::Remove(_) => paths.map(RawChange::Removed).collect(),
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) => {
+                match (paths.next(), paths.next()) {
+                    (Some(from), Some(to)) => vec![RawChange::Renamed(from, to)],
+                    (Some(path), None) => vec![RawChange::Modified(path)],
+                    _ => Vec::new(),
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+                paths.map(RawChange::Removed).collect()
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+                paths.map(RawChange::Created).collect()
+            }
+            _ => paths.map(RawChange::Modified).collect(),
+        }
+    }
+}
+
+/// 保留中の変更
+#[derive(Debug, Clone, PartialEq)]
+enum Pending {
+    /// 最初の通知の時点で存在していたか
+    Path {
+        existed_before: bool,
+    },
+    Renamed {
+        from: PathBuf,
+    },
+}
+
+/// 集中した変更をまとめる
+#[derive(Debug, Default)]
+pub(crate) struct ChangeCoalescer {
+    /// 通知順を保つため、キーの順番を別に持つ
+    order: Vec<PathBuf>,
+    pending: HashMap<PathBuf, Pending>,
+}
+
+impl ChangeCoalescer {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub(crate) fn push(&mut self, change: RawChange) {
+        match change {
+            RawChange::Created(path) => self.touch(path, false),
+            RawChange::Removed(path) | RawChange::Modified(path) => self.touch(path, true),
+            RawChange::Renamed(from, to) => self.rename(from, to),
+        }
+    }
+
+    /// 保留中の変更を、現在の状態と突き合わせてディレクトリごとの差分にする
+    pub(crate) fn finish(&mut self, exists: impl Fn(&Path) -> bool) -> Vec<DirectoryDelta> {
+        let mut deltas: Vec<DirectoryDelta> = Vec::new();
+        let mut push = |change: DirectoryChange| {
+            let path = match &change {
+                DirectoryChange::Added(path)
+                | DirectoryChange::Removed(path)
+                | DirectoryChange::Modified(path)
+                | DirectoryChange::Renamed { to: path, .. } => path,
+            };
+            let directory = path.parent().unwrap_or(path).to_path_buf();
+            match deltas.iter_mut().find(|d| d.directory == directory) {
+                Some(delta) => delta.changes.push(change),
+                None => deltas.push(DirectoryDelta {
+                    directory,
+                    changes: vec![change],
+                }),
+            }
+        };
+
+        for path in std::mem::take(&mut self.order) {
+            let Some(pending) = self.pending.remove(&path) else {
+                continue;
+            };
+            let now_exists = exists(&path);
+            match pending {
+                Pending::Path {
+                    existed_before: true,
+                } if now_exists => push(DirectoryChange::Modified(path)),
+                Pending::Path {
+                    existed_before: true,
+                } => push(DirectoryChange::Removed(path)),
+                Pending::Path {
+                    existed_before: false,
+                } if now_exists => push(DirectoryChange::Added(path)),
+                Pending::Path {
+                    existed_before: false,
+                } => {}
+                Pending::Renamed { from } if !now_exists => push(DirectoryChange::Removed(from)),
+                // 別のディレクトリへの名前変更は削除と追加として扱う
+                Pending::Renamed { from } if from.parent() != path.parent() => {
+                    push(DirectoryChange::Removed(from));
+                    push(DirectoryChange::Added(path));
+                }
+                Pending::Renamed { from } => {
+                    push(DirectoryChange::Renamed { from, to: path });
+                }
+            }
+        }
+        deltas
+    }
+
+    fn touch(&mut self, path: PathBuf, existed_before: bool) {
+        if !self.pending.contains_key(&path) {
+            self.order.push(path.clone());
+            self.pending.insert(path, Pending::Path { existed_before });
+        }
+    }
+
+    fn rename(&mut self, from: PathBuf, to: PathBuf) {
+        let previous = self.pending.remove(&from);
+        self.order.retain(|p| *p != from);
+        // 名前変更先に保留中の変更があれば上書きされる
+        if self.pending.remove(&to).is_some() {
+            self.order.retain(|p| *p != to);
+        }
+
+        let pending = match previous {
+            // このまとまりの中で作られた項目は、新しい名前で作られたものとして扱う
+            Some(Pending::Path {
+                existed_before: false,
+            }) => Pending::Path {
+                existed_before: false,
+            },
+            // 連続した名前変更は最初の名前からの変更にまとめる
+            Some(Pending::Renamed { from: original }) => Pending::Renamed { from: original },
+            _ => Pending::Renamed { from },
+        };
+
+        // 元の名前に戻った場合は変更として扱う
+        let pending = match pending {
+            Pending::Renamed { from } if from == to => Pending::Path {
+                existed_before: true,
+            },
+            other => other,
+        };
+        self.order.push(to.clone());
+        self.pending.insert(to, pending);
+    }
+}
+
+/// 監視中のディレクトリと参照数
+type WatchedDirectories = Arc<Mutex<HashMap<PathBuf, usize>>>;
+
+/// ディレクトリ監視サービス
+pub struct DirectoryWatcher {
+    watcher: Mutex<RecommendedWatcher>,
+    watched: WatchedDirectories,
+    event_manager: EventManager,
+}
+
+impl DirectoryWatcher {
+    /// デフォルトの間隔で変更をまとめる監視サービスを作成
+    pub fn new(event_manager: EventManager) -> Result<Self, AppError> {
+        Self::with_debounce(event_manager, DEFAULT_DEBOUNCE)
+    }
+
+    /// 変更をまとめる間隔を指定して監視サービスを作成
+    pub fn with_debounce(
+        event_manager: EventManager,
+        debounce: Duration,
+    ) -> Result<Self, AppError> {
+        let (sender, receiver) = mpsc::channel();
+        let watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+            if let Ok(event) = result {
+                let _ = sender.send(event);
+            }
+        })
+        .map_err(|e| AppError::Internal(format!("Failed to start directory watcher: {}", e)))?;
+
+        let watched: WatchedDirectories = Arc::new(Mutex::new(HashMap::new()));
+        let thread_watched = Arc::clone(&watched);
+        let thread_events = event_manager.clone();
+        std::thread::Builder::new()
+            .name("rust-explorer-watcher".to_string())
+            .spawn(move || debounce_loop(receiver, debounce, thread_watched, thread_events))
+            .map_err(AppError::FileSystem)?;
+
+        Ok(Self {
+            watcher: Mutex::new(watcher),
+            watched,
+            event_manager,
+        })
+    }
+
+    /// 差分の通知先
+    pub fn event_manager(&self) -> &EventManager {
+        &self.event_manager
+    }
+
+    /// ディレクトリの監視を開始（同じディレクトリを複数回指定した場合は参照数を増やす）
+    pub fn watch(&self, directory: &Path) -> Result<(), AppError> {
+        let mut watched = self
+            .watched
+            .lock()
+            .map_err(|e| AppError::Internal(format!("Failed to lock watcher: {}", e)))?;
+        if let Some(count) = watched.get_mut(directory) {
+            *count += 1;
+            return Ok(());
+        }
+
+        self.watcher
+            .lock()
+            .map_err(|e| AppError::Internal(format!("Failed to lock watcher: {}", e)))?
+            .watch(directory, RecursiveMode::NonRecursive)
+            .map_err(|e| match e.kind {
+                notify::ErrorKind::PathNotFound => AppError::NotFound(directory.to_path_buf()),
+                _ => AppError::Internal(format!("Failed to watch {}: {}", directory.display(), e)),
+            })?;
+        watched.insert(directory.to_path_buf(), 1);
+        Ok(())
+    }
+
+    /// ディレクトリの監視を終了（参照数が 0 になったら実際に解除する）
+    pub fn unwatch(&self, directory: &Path) -> Result<(), AppError> {
+        let mut watched = self
+            .watched
+            .lock()
+            .map_err(|e| AppError::Internal(format!("Failed to lock watcher: {}", e)))?;
+        let Some(count) = watched.get_mut(directory) else {
+            return Ok(());
+        };
+        *count -= 1;
+        if *count > 0 {
+            return Ok(());
+        }
+
+        watched.remove(directory);
+        // 監視中のディレクトリ自体が削除された場合は既に解除されている
+        let _ = self
+            .watcher
+            .lock()
+            .map_err(|e| AppError::Internal(format!("Failed to lock watcher: {}", e)))?
+            .unwatch(directory);
+        Ok(())
+    }
+
+    /// 監視中のディレクトリ一覧
+    pub fn watched(&self) -> Vec<PathBuf> {
+        self.watched
+            .lock()
+            .map(|watched| watched.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// 変更通知を受け取り、一定時間静かになったらまとめて通知する
+fn debounce_loop(
+    receiver: mpsc::Receiver<notify::Event>,
+    debounce: Duration,
+    watched: WatchedDirectories,
+    event_manager: EventManager,
+) {
+    let mut coalescer = ChangeCoalescer::default();
+    let mut first_change: Option<Instant> = None;
+    let mut last_change = Instant::now();
+    let max_latency = debounce * MAX_LATENCY_FACTOR;
+
+    loop {
+        let received = match first_change {
+            None => receiver.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            Some(first) => {
+                let quiet = debounce.saturating_sub(last_change.elapsed());
+                let deadline = max_latency.saturating_sub(first.elapsed());
+                receiver.recv_timeout(quiet.min(deadline))
+            }
+        };
+
+        match received {
+            Ok(event) => {
+                for change in RawChange::from_notify(event) {
+                    coalescer.push(change);
+                }
+                last_change = Instant::now();
+                if !coalescer.is_empty() {
+                    first_change.get_or_insert(last_change);
+                }
+                continue;
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        first_change = None;
+        for delta in coalescer.finish(|path| std::fs::symlink_metadata(path).is_ok()) {
+            let is_watched = watched
+                .lock()
+                .is_ok_and(|watched| watched.contains_key(&delta.directory));
+            if is_watched {
+                let _ = event_manager.handle_event(Event::DirectoryUpdated(delta));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(name: &str) -> PathBuf {
+        PathBuf::from("/watched").join(name)
+    }
+
+    fn coalesce(changes: Vec<RawChange>, existing: &[&str]) -> Vec<DirectoryChange> {
+        let mut coalescer = ChangeCoalescer::default();
+        for change in changes {
+            coalescer.push(change);
+        }
+        let existing: Vec<PathBuf> = existing.iter().map(|name| path(name)).collect();
+        coalescer
+            .finish(|p| existing.iter().any(|e| e == p))
+            .into_iter()
+            .flat_map(|delta| {
+                assert_eq!(delta.directory, PathBuf::from("/watched"));
+                delta.changes
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_coalesce_burst_of_writes() {
+        let changes = vec![
+            RawChange::Created(path("a.txt")),
+            RawChange::Modified(path("a.txt")),
+            RawChange::Modified(path("a.txt")),
+            RawChange::Modified(path("b.txt")),
+        ];
+        assert_eq!(
+            coalesce(changes, &["a.txt", "b.txt"]),
+            vec![
+                DirectoryChange::Added(path("a.txt")),
+                DirectoryChange::Modified(path("b.txt")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_coalesce_temporary_file() {
+        let changes = vec![
+            RawChange::Created(path("tmp")),
+            RawChange::Modified(path("tmp")),
+            RawChange::Removed(path("tmp")),
+            RawChange::Removed(path("old.txt")),
+        ];
+        assert_eq!(
+            coalesce(changes, &[]),
+            vec![DirectoryChange::Removed(path("old.txt"))]
+        );
+    }
+
+    #[test]
+    fn test_coalesce_renames() {
+        // 連続した名前変更は1件にまとめる
+        let changes = vec![
+            RawChange::Renamed(path("a"), path("b")),
+            RawChange::Renamed(path("b"), path("c")),
+        ];
+        assert_eq!(
+            coalesce(changes, &["c"]),
+            vec![DirectoryChange::Renamed {
+                from: path("a"),
+                to: path("c"),
+            }]
+        );
+
+        // 作成直後の名前変更（保存時の一時ファイルなど）は追加になる
+        let changes = vec![
+            RawChange::Created(path("file.tmp")),
+            RawChange::Renamed(path("file.tmp"), path("file.txt")),
+        ];
+        assert_eq!(
+            coalesce(changes, &["file.txt"]),
+            vec![DirectoryChange::Added(path("file.txt"))]
+        );
+    }
+
+    #[test]
+    fn test_rename_across_directories() {
+        let mut coalescer = ChangeCoalescer::default();
+        coalescer.push(RawChange::Renamed(
+            PathBuf::from("/one/a"),
+            PathBuf::from("/two/a"),
+        ));
+        let deltas = coalescer.finish(|_| true);
+        assert_eq!(
+            deltas,
+            vec![
+                DirectoryDelta {
+                    directory: PathBuf::from("/one"),
+                    changes: vec![DirectoryChange::Removed(PathBuf::from("/one/a"))],
+                },
+                DirectoryDelta {
+                    directory: PathBuf::from("/two"),
+                    changes: vec![DirectoryChange::Added(PathBuf::from("/two/a"))],
+                },
+            ]
+        );
+    }
+}
@@ -1,28 +1,58 @@
 //! アプリケーションのメインエントリポイント
 
-use crate::window::MainWindow;
-use rust_explorer_config::Settings;
-use rust_explorer_core::{Event, EventManager, FileSystemManager};
-use rust_explorer_utils::AppError;
+use crate::components::main_content::default_directory;
+use crate::components::{
+    RecoveryReason, SessionRecovery, UndoCommand, UndoController, flush_user_data,
+    global_event_manager,
+};
+use crate::window::{MainWindow, session_window_state};
+use rust_explorer_config::{AutoSaveScheduler, Settings, StatePersistenceConfig, state_helpers};
+use rust_explorer_core::{
+    AppState, Event, EventManager, FileSystemManager, IndexSnapshot, JournalEntry, SearchIndex,
+    SearchIndexer, StateManager, UndoJournal,
+};
+use rust_explorer_utils::{AppError, has_panic_occurred};
+use std::cell::{Ref, RefCell, RefMut};
+use std::rc::Rc;
+use std::sync::{Arc, MutexGuard};
 
 /// アプリケーションのメインクラス
 pub struct App {
-    settings: Settings,
+    /// ウィンドウと共有する設定（再読み込みした内容も終了時に保存する）
+    settings: Rc<RefCell<Settings>>,
     filesystem: FileSystemManager,
     event_manager: EventManager,
+    /// ウィンドウと共有する元に戻す履歴（移動ジョブで移動した項目も記録する）
+    undo: UndoController,
+    /// ウィンドウ・タブ・ペインのセッション状態
+    state_manager: StateManager,
+    /// セッション状態の自動保存（初期化後に開始する）
+    auto_save: Option<AutoSaveScheduler>,
+    /// 起動時に選んでもらうセッションの復元候補
+    recovery: Option<SessionRecovery>,
+    /// 高速検索の索引（索引を作るディレクトリが設定されていれば初期化時に開始する）
+    search_indexer: Option<Arc<SearchIndexer>>,
 }
 
 impl App {
     /// 新しいアプリケーションインスタンスを作成
     pub fn new() -> Result<Self, AppError> {
-        let settings = Settings::load()?;
+        let settings = Rc::new(RefCell::new(Settings::load()?));
         let filesystem = FileSystemManager::new();
-        let event_manager = EventManager::new();
+        // ディレクトリ監視と同じ購読者を共有する
+        let event_manager = global_event_manager().clone();
+        // 履歴が読めない場合は空の履歴で起動する
+        let undo = UndoController::load();
 
         Ok(App {
             settings,
             filesystem,
             event_manager,
+            undo,
+            state_manager: StateManager::new(),
+            auto_save: None,
+            recovery: None,
+            search_indexer: None,
         })
     }
 
@@ -33,16 +63,127 @@ impl App {
             self.filesystem.current_path().to_path_buf(),
         ))?;
 
+        // 前回終了時のウィンドウ・タブ・ペインを復元
+        let interrupted = state_helpers::session_was_interrupted().unwrap_or(false);
+        let session = state_helpers::session_state_exists()
+            .unwrap_or(false)
+            .then(state_helpers::load_session_state);
+        self.recovery = Self::session_recovery(session.as_ref(), interrupted);
+        let state = Self::restore_session(&mut self.settings.borrow_mut(), session);
+        self.state_manager.replace_state(state)?;
+
+        // 移動ジョブで移動した項目も元に戻せるようにする
+        self.undo.record_job_moves()?;
+
+        // 異常終了したら次回の起動時に分かるよう、正常に終了するまで印を残す
+        state_helpers::mark_session_running()?;
+
+        // 以降の変更はまとめて定期的に保存する
+        let state_manager = self.state_manager.clone();
+        let auto_save = AutoSaveScheduler::new(&StatePersistenceConfig::default(), move || {
+            state_helpers::save_session_state(&state_manager.get_state()?)
+        });
+        let handle = auto_save.handle();
+        self.state_manager
+            .on_state_change(move |_| handle.mark_dirty())?;
+        self.auto_save = Some(auto_save);
+
+        // 検索の索引をバックグラウンドで読み込み、最新の状態に巡回し直す
+        let search_roots = self.settings.borrow().search_roots.clone();
+        if !search_roots.is_empty() {
+            self.search_indexer = Some(Arc::new(SearchIndexer::start(search_roots, || {
+                state_helpers::load_search_index::<IndexSnapshot>()
+                    .map(SearchIndex::from_snapshot)
+                    .unwrap_or_default()
+            })?));
+        }
+
         Ok(())
     }
 
+    /// 保存されたセッションから起動時の状態を作る
+    ///
+    /// なくなったパスは既存の親ディレクトリなどに置き換え、ウィンドウの大きさと
+    /// 位置は設定へ反映する。読めないセッションは捨てて新しい状態で起動する。
+    fn restore_session(
+        settings: &mut Settings,
+        session: Option<Result<AppState, AppError>>,
+    ) -> AppState {
+        let mut state = match session {
+            Some(Ok(state)) => state,
+            Some(Err(e)) => {
+                eprintln!("セッションの復元エラー: {}", e);
+                return Self::new_session(settings);
+            }
+            None => return Self::new_session(settings),
+        };
+
+        state.restore_missing_paths(&default_directory(settings));
+        let window = &state.window;
+        settings.update_window_state(
+            window.width as u32,
+            window.height as u32,
+            window.x.map(|x| x as i32),
+            window.y.map(|y| y as i32),
+            window.maximized,
+        );
+        state
+    }
+
+    /// セッションが読めなかった場合や前回が異常終了した場合の復元候補
+    ///
+    /// 読めないセッションファイルは次の保存で上書きしないよう隔離する。
+    /// 使えるバックアップがなければ復元画面は出さない。
+    fn session_recovery(
+        session: Option<&Result<AppState, AppError>>,
+        interrupted: bool,
+    ) -> Option<SessionRecovery> {
+        let (reason, current) = match session {
+            Some(Err(e)) => {
+                let quarantined = state_helpers::quarantine_session_state().unwrap_or_else(|e| {
+                    eprintln!("セッションファイルの隔離エラー: {}", e);
+                    None
+                });
+                let reason = RecoveryReason::CorruptState {
+                    error: e.to_string(),
+                    quarantined,
+                };
+                (reason, None)
+            }
+            Some(Ok(state)) if interrupted => (RecoveryReason::Interrupted, Some(state)),
+            _ => return None,
+        };
+
+        let backups = state_helpers::session_backups().unwrap_or_default();
+        SessionRecovery::new(
+            reason,
+            current,
+            backups.into_iter().map(|backup| {
+                let state = state_helpers::load_session_backup(&backup.path);
+                (backup, state)
+            }),
+        )
+    }
+
+    /// 設定のウィンドウの大きさと位置で新しいセッションを作る
+    fn new_session(settings: &Settings) -> AppState {
+        AppState {
+            window: session_window_state(settings),
+            ..AppState::default()
+        }
+    }
+
     /// アプリケーションを起動
     pub fn run(mut self) -> Result<(), AppError> {
         // 初期化処理
         self.initialize()?;
 
         // メインウィンドウを作成して起動
-        let main_window = MainWindow::new(&self.settings)?;
+        let main_window = MainWindow::new(self.settings.clone())?
+            .with_state_manager(self.state_manager.clone())
+            .with_recovery(self.recovery.take())
+            .with_search_indexer(self.search_indexer.clone())
+            .with_undo(self.undo.clone());
         main_window.launch()?;
 
         // アプリケーション終了時の処理
@@ -52,24 +193,65 @@ impl App {
     }
 
     /// アプリケーション終了処理
+    ///
+    /// 1つの手順が失敗しても残りの保存は行い、失敗はまとめて返す。
     pub fn shutdown(&mut self) -> Result<(), AppError> {
+        let mut errors = Vec::new();
+
+        // 自動保存を止めて未保存のセッションを保存
+        if let Some(auto_save) = self.auto_save.take() {
+            errors.extend(auto_save.shutdown().err());
+        }
+
+        // ウィンドウの大きさと位置は設定にも残す
+        match self.state_manager.get_state() {
+            Ok(state) => {
+                let window = state.window;
+                self.settings.borrow_mut().update_window_state(
+                    window.width as u32,
+                    window.height as u32,
+                    window.x.map(|x| x as i32),
+                    window.y.map(|y| y as i32),
+                    window.maximized,
+                );
+            }
+            Err(e) => errors.push(e),
+        }
+
         // 設定を保存
-        self.settings.save()?;
+        errors.extend(self.settings.borrow().save().err());
+
+        // 元に戻す履歴と、保存を待っているタグ・ブックマークなどを保存
+        errors.extend(self.save_undo_journal().err());
+        errors.extend(flush_user_data().err());
+
+        // 次回の起動ですぐ検索できるよう索引を保存
+        if let Some(indexer) = self.search_indexer.take() {
+            let saved = indexer
+                .snapshot()
+                .and_then(|snapshot| state_helpers::save_search_index(&snapshot));
+            errors.extend(saved.err());
+        }
+
+        // パニック後の終了では印を残し、次回の起動時に復元画面を出す
+        if !has_panic_occurred() {
+            errors.extend(state_helpers::mark_session_closed().err());
+        }
 
         // その他のクリーンアップ処理
         println!("アプリケーションを終了します");
 
-        Ok(())
+        combine_errors(errors)
     }
 
     /// 設定への参照を取得
-    pub fn settings(&self) -> &Settings {
-        &self.settings
+    pub fn settings(&self) -> Ref<'_, Settings> {
+        self.settings.borrow()
     }
 
     /// 設定への可変参照を取得
-    pub fn settings_mut(&mut self) -> &mut Settings {
-        &mut self.settings
+    pub fn settings_mut(&mut self) -> RefMut<'_, Settings> {
+        self.settings.borrow_mut()
     }
 
     /// ファイルシステムマネージャーへの参照を取得
@@ -86,15 +268,77 @@ impl App {
     pub fn event_manager(&self) -> &EventManager {
         &self.event_manager
     }
+
+    /// セッション状態の管理マネージャーを取得
+    pub fn state_manager(&self) -> &StateManager {
+        &self.state_manager
+    }
+
+    /// 高速検索の索引を取得（索引を作るディレクトリが設定されていなければ None）
+    pub fn search_indexer(&self) -> Option<&SearchIndexer> {
+        self.search_indexer.as_deref()
+    }
+
+    /// 元に戻す履歴への参照を取得
+    pub fn undo_journal(&self) -> MutexGuard<'_, UndoJournal> {
+        self.undo.journal()
+    }
+
+    /// 元に戻す履歴への可変参照を取得（変更は次回保存時に永続化される）
+    pub fn undo_journal_mut(&mut self) -> MutexGuard<'_, UndoJournal> {
+        self.undo.journal_mut()
+    }
+
+    /// 直前のファイル操作を元に戻す
+    pub fn undo(&mut self) -> Result<JournalEntry, AppError> {
+        self.undo.run(UndoCommand::Undo)
+    }
+
+    /// 元に戻したファイル操作をやり直す
+    pub fn redo(&mut self) -> Result<JournalEntry, AppError> {
+        self.undo.run(UndoCommand::Redo)
+    }
+
+    /// 元に戻す履歴に変更があれば保存
+    pub fn save_undo_journal(&mut self) -> Result<(), AppError> {
+        self.undo.save()
+    }
+}
+
+/// 終了処理のエラーをまとめる（1つならそのまま返す）
+fn combine_errors(mut errors: Vec<AppError>) -> Result<(), AppError> {
+    match errors.len() {
+        0 => Ok(()),
+        1 => Err(errors.remove(0)),
+        count => {
+            let messages: Vec<String> = errors.iter().map(ToString::to_string).collect();
+            Err(AppError::Internal(format!(
+                "{} shutdown steps failed: {}",
+                count,
+                messages.join("; ")
+            )))
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rust_explorer_config::{AppPaths, init_app_paths};
+    use std::sync::OnceLock;
+
+    /// 保存先を一時ディレクトリにしてアプリケーションを作成（実際の設定ディレクトリを汚さない）
+    fn new_test_app() -> Result<App, AppError> {
+        static TEMP_ROOT: OnceLock<tempfile::TempDir> = OnceLock::new();
+        let root = TEMP_ROOT.get_or_init(|| tempfile::TempDir::new().unwrap());
+        let paths = init_app_paths(AppPaths::portable(root.path()));
+        assert_eq!(paths.config_dir(), root.path());
+        App::new()
+    }
 
     #[test]
     fn test_app_creation() {
-        let result = App::new();
+        let result = new_test_app();
         assert!(result.is_ok());
 
         let app = result.unwrap();
@@ -104,29 +348,46 @@ mod tests {
 
     #[test]
     fn test_app_initialization() {
-        let mut app = App::new().unwrap();
+        let mut app = new_test_app().unwrap();
         let result = app.initialize();
         assert!(result.is_ok());
     }
 
     #[test]
     fn test_app_shutdown() {
-        let mut app = App::new().unwrap();
+        let mut app = new_test_app().unwrap();
         let result = app.shutdown();
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_combine_errors() {
+        assert!(combine_errors(Vec::new()).is_ok());
+        assert!(matches!(
+            combine_errors(vec![AppError::Config("settings".to_string())]),
+            Err(AppError::Config(_))
+        ));
+
+        let error = combine_errors(vec![
+            AppError::Config("settings".to_string()),
+            AppError::InvalidPath("/index".into()),
+        ])
+        .unwrap_err()
+        .to_string();
+        assert!(error.contains("2 shutdown steps failed"));
+        assert!(error.contains("settings") && error.contains("/index"));
+    }
+
     #[test]
     fn test_app_accessors() {
-        let mut app = App::new().unwrap();
+        let mut app = new_test_app().unwrap();
 
         // 設定への参照テスト
-        let settings = app.settings();
-        assert_eq!(settings.window_width(), 1200);
+        assert_eq!(app.settings().window_width(), 1200);
 
         // 設定への可変参照テスト
-        let settings_mut = app.settings_mut();
-        settings_mut.update_window_state(1024, 768, None, None, false);
+        app.settings_mut()
+            .update_window_state(1024, 768, None, None, false);
         assert_eq!(app.settings().window_width(), 1024);
 
         // ファイルシステムマネージャーテスト
@@ -136,4 +397,41 @@ mod tests {
         // イベントマネージャーテスト
         let _event_manager = app.event_manager();
     }
+
+    #[test]
+    fn test_restore_session() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let mut saved = AppState::default();
+        saved.window.width = 900.0;
+        saved.window.x = Some(40.0);
+        saved.add_tab(rust_explorer_core::TabState::new(
+            "tab".to_string(),
+            "gone".to_string(),
+            temp_dir.path().join("gone"),
+        ));
+
+        let mut settings = Settings::default();
+        let state = App::restore_session(&mut settings, Some(Ok(saved)));
+
+        // なくなったディレクトリは既存の親で開き、ウィンドウの位置と大きさを設定へ戻す
+        assert_eq!(state.tabs[0].current_path, temp_dir.path());
+        assert!(state.pane_tree().is_some());
+        assert_eq!(settings.window_width(), 900);
+        assert_eq!(settings.window_position(), (Some(40), None));
+
+        // 読めないセッションは設定のウィンドウで新しく始める
+        let state = App::restore_session(
+            &mut settings,
+            Some(Err(AppError::Config("broken".to_string()))),
+        );
+        assert!(state.tabs.is_empty());
+        assert_eq!(state.window.width, 900.0);
+    }
+
+    #[test]
+    fn test_session_recovery_not_needed() {
+        // 読めたセッションで正常に終了していれば、セッションがなければ復元画面は出さない
+        assert!(App::session_recovery(Some(&Ok(AppState::default())), false).is_none());
+        assert!(App::session_recovery(None, true).is_none());
+    }
 }
@@ -0,0 +1,284 @@
+//! ブックマーク（お気に入り）
+//!
+//! サイドバーのお気に入りに登録した場所の追加・整理と、Ctrl+1〜9 の
+//! ショートカットでの移動を提供します。ブックマークは設定ディレクトリの JSON に
+//! 保存スレッドで保存し、ファイル一覧からのドラッグでも登録できます。
+
+use super::error_dialog::display_error_globally;
+use super::user_data::{BOOKMARKS_SAVE, follow_job_moves};
+use floem::keyboard::{Key, Modifiers};
+use floem::reactive::{RwSignal, SignalGet, SignalUpdate, SignalWith};
+use rust_explorer_config::state_helpers;
+use rust_explorer_core::{Bookmark, Bookmarks, MAX_BOOKMARK_SHORTCUTS};
+use rust_explorer_utils::AppError;
+use std::path::{Path, PathBuf};
+
+/// ブックマークを開くキーならショートカットの番号（Ctrl+1〜9）
+pub fn bookmark_shortcut_for_key(key: &Key, modifiers: Modifiers) -> Option<usize> {
+    if !modifiers.control() || modifiers.alt() || modifiers.shift() {
+        return None;
+    }
+    let Key::Character(c) = key else {
+        return None;
+    };
+    c.parse::<usize>()
+        .ok()
+        .filter(|number| (1..=MAX_BOOKMARK_SHORTCUTS).contains(number))
+}
+
+/// ショートカットの表示（10件目以降は空）
+pub fn bookmark_shortcut_label(number: Option<usize>) -> String {
+    number
+        .map(|number| format!("Ctrl+{}", number))
+        .unwrap_or_default()
+}
+
+/// ブックマークの追加・整理と、開く場所の受け渡し
+///
+/// サイドバーやショートカットで開くブックマークは `requested` に置き、
+/// アクティブなタブを持つメインコンテンツが開く。
+#[derive(Clone)]
+pub struct BookmarksController {
+    bookmarks: RwSignal<Bookmarks>,
+    dragged: RwSignal<Vec<PathBuf>>,
+    requested: RwSignal<Option<Bookmark>>,
+    persist: bool,
+}
+
+impl BookmarksController {
+    /// 保存されたブックマークを読み込んで作成し、アプリ内での移動に追随させる
+    pub fn load() -> Self {
+        let bookmarks = match state_helpers::bookmarks_exist() {
+            Ok(true) => state_helpers::load_bookmarks().unwrap_or_else(|e| {
+                display_error_globally(&e);
+                Bookmarks::new()
+            }),
+            Ok(false) => Bookmarks::new(),
+            Err(e) => {
+                display_error_globally(&e);
+                Bookmarks::new()
+            }
+        };
+        let controller = Self::with_bookmarks(bookmarks, true);
+        // 移動ジョブで移動した場所のブックマークを付け替える
+        let moved = controller.clone();
+        follow_job_moves(move |from, to| moved.relocate(from, to));
+        controller
+    }
+
+    /// 保存しないブックマークで作成
+    pub fn in_memory(bookmarks: Bookmarks) -> Self {
+        Self::with_bookmarks(bookmarks, false)
+    }
+
+    fn with_bookmarks(bookmarks: Bookmarks, persist: bool) -> Self {
+        Self {
+            bookmarks: RwSignal::new(bookmarks),
+            dragged: RwSignal::new(Vec::new()),
+            requested: RwSignal::new(None),
+            persist,
+        }
+    }
+
+    /// ブックマークの一覧
+    pub fn bookmarks(&self) -> RwSignal<Bookmarks> {
+        self.bookmarks
+    }
+
+    /// ファイル一覧からドラッグ中の項目
+    pub fn dragged(&self) -> RwSignal<Vec<PathBuf>> {
+        self.dragged
+    }
+
+    /// 開くよう求められたブックマーク
+    pub fn requested(&self) -> RwSignal<Option<Bookmark>> {
+        self.requested
+    }
+
+    /// パスを登録する（登録済みのパスは飛ばす）
+    pub fn add(&self, paths: &[PathBuf], folder: Option<&str>) -> Result<(), AppError> {
+        let mut bookmarks = self.bookmarks.get_untracked();
+        let mut added = false;
+        for path in paths {
+            if bookmarks.find(path).is_some() {
+                continue;
+            }
+            bookmarks.add(path, folder)?;
+            added = true;
+        }
+        if added {
+            self.commit(bookmarks);
+        }
+        Ok(())
+    }
+
+    /// ドラッグ中の項目を登録する
+    pub fn add_dragged(&self, folder: Option<&str>) {
+        let paths = self.dragged.get_untracked();
+        self.dragged.set(Vec::new());
+        if let Err(e) = self.add(&paths, folder) {
+            display_error_globally(&e);
+        }
+    }
+
+    /// フォルダを作成
+    pub fn add_folder(&self, name: &str) -> Result<(), AppError> {
+        let mut bookmarks = self.bookmarks.get_untracked();
+        bookmarks.add_folder(name)?;
+        self.commit(bookmarks);
+        Ok(())
+    }
+
+    /// ブックマークまたはフォルダの名前を変更
+    pub fn rename(&self, id: &str, name: &str) -> Result<(), AppError> {
+        let mut bookmarks = self.bookmarks.get_untracked();
+        bookmarks.rename(id, name)?;
+        self.commit(bookmarks);
+        Ok(())
+    }
+
+    /// ブックマークまたはフォルダを削除
+    pub fn remove(&self, id: &str) {
+        let mut bookmarks = self.bookmarks.get_untracked();
+        if bookmarks.remove(id) {
+            self.commit(bookmarks);
+        }
+    }
+
+    /// 同じフォルダの中で前後へ移動
+    pub fn move_by(&self, id: &str, offset: isize) {
+        let mut bookmarks = self.bookmarks.get_untracked();
+        match bookmarks.move_by(id, offset) {
+            Ok(()) => self.commit(bookmarks),
+            Err(e) => display_error_globally(&e),
+        }
+    }
+
+    /// フォルダの末尾（None なら最上位の末尾）へ移動
+    pub fn move_to_folder(&self, id: &str, folder: Option<&str>) {
+        let mut bookmarks = self.bookmarks.get_untracked();
+        match bookmarks.move_to(id, folder, usize::MAX) {
+            Ok(()) => self.commit(bookmarks),
+            Err(e) => display_error_globally(&e),
+        }
+    }
+
+    /// 名前変更・移動した場所のブックマークを付け替える
+    pub fn relocate(&self, from: &Path, to: &Path) {
+        let mut bookmarks = self.bookmarks.get_untracked();
+        if bookmarks.relocate(from, to) {
+            self.commit(bookmarks);
+        }
+    }
+
+    /// ブックマークを開くよう求める
+    pub fn open(&self, id: &str) {
+        let bookmark = self.bookmarks.with_untracked(|b| b.get(id).cloned());
+        if bookmark.is_some() {
+            self.requested.set(bookmark);
+        }
+    }
+
+    /// ショートカットの番号のブックマークを開くよう求める
+    pub fn open_shortcut(&self, number: usize) {
+        let bookmark = self
+            .bookmarks
+            .with_untracked(|b| b.shortcut(number).cloned());
+        if bookmark.is_some() {
+            self.requested.set(bookmark);
+        }
+    }
+
+    /// 反映し、保存を予約する
+    fn commit(&self, bookmarks: Bookmarks) {
+        if self.persist {
+            BOOKMARKS_SAVE.save(bookmarks.clone());
+        }
+        self.bookmarks.set(bookmarks);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bookmark_shortcut_for_key() {
+        let ctrl = Modifiers::CONTROL;
+        assert_eq!(
+            bookmark_shortcut_for_key(&Key::Character("1".into()), ctrl),
+            Some(1)
+        );
+        assert_eq!(
+            bookmark_shortcut_for_key(&Key::Character("9".into()), ctrl),
+            Some(9)
+        );
+        assert_eq!(
+            bookmark_shortcut_for_key(&Key::Character("0".into()), ctrl),
+            None
+        );
+        assert_eq!(
+            bookmark_shortcut_for_key(&Key::Character("1".into()), Modifiers::empty()),
+            None
+        );
+        assert_eq!(
+            bookmark_shortcut_for_key(&Key::Character("1".into()), ctrl | Modifiers::SHIFT),
+            None
+        );
+        assert_eq!(bookmark_shortcut_label(Some(3)), "Ctrl+3");
+        assert_eq!(bookmark_shortcut_label(None), "");
+    }
+
+    #[test]
+    fn test_bookmarks_controller() {
+        let controller = BookmarksController::in_memory(Bookmarks::new());
+        controller.add_folder("Work").unwrap();
+        let folder = controller
+            .bookmarks()
+            .with_untracked(|b| b.entries()[0].id().to_string());
+
+        let a = PathBuf::from("/a");
+        let b = PathBuf::from("/b");
+        controller.dragged().set(vec![a.clone(), b.clone()]);
+        controller.add_dragged(Some(&folder));
+        assert!(controller.dragged().get_untracked().is_empty());
+        // 登録済みのパスは飛ばす
+        controller
+            .add(&[a.clone(), PathBuf::from("/c")], None)
+            .unwrap();
+        let paths: Vec<PathBuf> = controller.bookmarks().with_untracked(|b| {
+            b.bookmarks()
+                .into_iter()
+                .map(|bookmark| bookmark.path.clone())
+                .collect()
+        });
+        assert_eq!(paths, vec![a.clone(), b.clone(), PathBuf::from("/c")]);
+
+        controller.open_shortcut(2);
+        assert_eq!(
+            controller.requested().get_untracked().map(|b| b.path),
+            Some(b.clone())
+        );
+
+        let id = controller
+            .bookmarks()
+            .with_untracked(|bookmarks| bookmarks.find(&b).unwrap().id.clone());
+        controller.move_to_folder(&id, None);
+        controller.rename(&id, "B").unwrap();
+        controller.relocate(Path::new("/b"), Path::new("/moved"));
+        let moved = controller
+            .bookmarks()
+            .with_untracked(|bookmarks| bookmarks.get(&id).cloned())
+            .unwrap();
+        assert_eq!(moved.path, PathBuf::from("/moved"));
+        assert_eq!(moved.name, "B");
+
+        controller.remove(&folder);
+        assert_eq!(
+            controller
+                .bookmarks()
+                .with_untracked(|bookmarks| bookmarks.bookmarks().len()),
+            2
+        );
+    }
+}
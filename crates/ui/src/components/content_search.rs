@@ -0,0 +1,412 @@
+//! ファイルの内容の検索パネル
+//!
+//! 表示中のディレクトリ以下のファイルの内容を検索し、見つかった行を届いた順に表示します。
+//! 結果からファイルを開いたり、ファイルのあるディレクトリを新しいタブで表示したりできます。
+
+use super::error_dialog::display_error_globally;
+use super::job_panel::global_job_manager;
+use floem::ext_event::create_signal_from_channel;
+use floem::prelude::*;
+use floem::reactive::{RwSignal, create_effect};
+use floem::views::Checkbox;
+use rust_explorer_core::{
+    CaseSensitivity, ContentMatch, ContentSearch, ContentSearchEvent, ContentSearchOptions,
+    ContentSearchProgress,
+};
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// パネルに表示する見つかった行の上限
+const MAX_DISPLAYED_MATCHES: usize = 2_000;
+
+/// 検索の入力と結果
+#[derive(Clone, Copy)]
+pub struct ContentSearchState {
+    /// 検索する文字列
+    pub pattern: RwSignal<String>,
+    /// 正規表現として扱うか
+    pub regex: RwSignal<bool>,
+    /// 大文字と小文字を区別するか
+    pub case_sensitive: RwSignal<bool>,
+    /// 単語単位で検索するか
+    pub whole_word: RwSignal<bool>,
+    /// `.gitignore` に従うか
+    pub respect_gitignore: RwSignal<bool>,
+    /// 対象にするファイルのグロブ（カンマ区切り）
+    pub include: RwSignal<String>,
+    /// 除外するファイルのグロブ（カンマ区切り）
+    pub exclude: RwSignal<String>,
+    /// 見つかった行（届いた順）
+    pub results: RwSignal<Vec<ContentMatch>>,
+    /// 最後に届いた進み具合
+    pub progress: RwSignal<Option<ContentSearchProgress>>,
+    /// 検索中か
+    pub running: RwSignal<bool>,
+}
+
+impl ContentSearchState {
+    /// 空の入力で作成
+    pub fn new() -> Self {
+        Self {
+            pattern: RwSignal::new(String::new()),
+            regex: RwSignal::new(false),
+            case_sensitive: RwSignal::new(false),
+            whole_word: RwSignal::new(false),
+            respect_gitignore: RwSignal::new(true),
+            include: RwSignal::new(String::new()),
+            exclude: RwSignal::new(String::new()),
+            results: RwSignal::new(Vec::new()),
+            progress: RwSignal::new(None),
+            running: RwSignal::new(false),
+        }
+    }
+
+    /// 入力から検索の条件を作成
+    pub fn options(&self) -> ContentSearchOptions {
+        ContentSearchOptions {
+            regex: self.regex.get_untracked(),
+            case: if self.case_sensitive.get_untracked() {
+                CaseSensitivity::Sensitive
+            } else {
+                CaseSensitivity::Insensitive
+            },
+            whole_word: self.whole_word.get_untracked(),
+            respect_gitignore: self.respect_gitignore.get_untracked(),
+            include: split_globs(&self.include.get_untracked()),
+            exclude: split_globs(&self.exclude.get_untracked()),
+            max_matches: MAX_DISPLAYED_MATCHES,
+            ..ContentSearchOptions::new(self.pattern.get_untracked())
+        }
+    }
+}
+
+impl Default for ContentSearchState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// カンマ区切りのグロブを分ける
+pub fn split_globs(text: &str) -> Vec<String> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|glob| !glob.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// 結果の位置の表示（`root` からの相対パスと行番号）
+pub fn format_match_location(found: &ContentMatch, root: &Path) -> String {
+    let path = found
+        .entry
+        .path
+        .strip_prefix(root)
+        .unwrap_or(&found.entry.path);
+    format!("{}:{}", path.display(), found.line_number)
+}
+
+/// 進み具合の表示
+pub fn format_search_progress(progress: &ContentSearchProgress, running: bool) -> String {
+    let mut line = format!(
+        "{} 件（{} ファイル）— {} ファイルを検索",
+        progress.matches, progress.matched_files, progress.searched_files
+    );
+    if progress.skipped_files > 0 {
+        line.push_str(&format!("、{} ファイルをスキップ", progress.skipped_files));
+    }
+    if progress.truncated {
+        line.push_str("（上限に達したため打ち切り）");
+    } else if running {
+        line.push_str(" …");
+    }
+    line
+}
+
+/// `root` 以下の検索をジョブとして開始し、届いた結果を `state` に反映する
+///
+/// 返された検索を破棄すると検索は中断される。ジョブパネルから中止した場合も検索中の
+/// 状態を終える。
+pub fn start_content_search(root: &Path, state: ContentSearchState) -> Option<ContentSearch> {
+    state.results.set(Vec::new());
+    state.progress.set(None);
+    let search = match ContentSearch::start(global_job_manager(), root, state.options()) {
+        Ok(search) => search,
+        Err(e) => {
+            display_error_globally(&e);
+            state.running.set(false);
+            return None;
+        }
+    };
+    state.running.set(true);
+
+    let canceller = search.canceller();
+    let events = create_signal_from_channel(search.receiver().clone());
+    create_effect(move |_| {
+        events.with(|event| {
+            if canceller.is_cancelled() {
+                return;
+            }
+            match event {
+                Some(ContentSearchEvent::Matches(batch)) => {
+                    state
+                        .results
+                        .update(|results| results.extend(batch.iter().cloned()));
+                }
+                Some(ContentSearchEvent::Progress(progress)) => state.progress.set(Some(*progress)),
+                Some(ContentSearchEvent::Failed(e)) => {
+                    display_error_globally(e);
+                    state.running.set(false);
+                }
+                Some(ContentSearchEvent::Cancelled(progress))
+                | Some(ContentSearchEvent::Finished(progress)) => {
+                    state.progress.set(Some(*progress));
+                    state.running.set(false);
+                }
+                None => {}
+            }
+        });
+    });
+
+    Some(search)
+}
+
+/// 内容の検索パネルを作成
+///
+/// `root` 以下を検索し、結果の「開く」で `on_open`、「タブで表示」で `on_reveal` を呼ぶ。
+pub fn content_search_panel(
+    root: RwSignal<PathBuf>,
+    on_open: impl Fn(&ContentMatch) + 'static,
+    on_reveal: impl Fn(&ContentMatch) + 'static,
+) -> impl IntoView {
+    let state = ContentSearchState::new();
+    // 検索の開始要求（0 は未検索）
+    let request = RwSignal::new(0_u64);
+    let search = Rc::new(RefCell::new(None::<ContentSearch>));
+
+    // 要求ごとに前の検索を中断して新しい検索を始める
+    let effect_search = search.clone();
+    create_effect(move |_| {
+        if request.get() == 0 {
+            return;
+        }
+        let path = root.get_untracked();
+        *effect_search.borrow_mut() = start_content_search(&path, state);
+    });
+    let cancel_search = search.clone();
+    let cancel = move || {
+        if let Some(search) = cancel_search.borrow_mut().take() {
+            search.cancel();
+        }
+        state.running.set(false);
+    };
+    let start = move || request.update(|request| *request += 1);
+
+    let on_open = Rc::new(on_open);
+    let on_reveal = Rc::new(on_reveal);
+
+    v_stack((
+        // 検索する文字列と開始・中止
+        h_stack((
+            text_input(state.pattern)
+                .placeholder("ファイルの内容を検索")
+                .on_key_down(
+                    floem::keyboard::Key::Named(floem::keyboard::NamedKey::Enter),
+                    |_| true,
+                    move |_| start(),
+                )
+                .style(|s| {
+                    s.flex_grow(1.0)
+                        .padding(6.0)
+                        .border(1.0)
+                        .border_color(Color::rgb8(209, 213, 219))
+                        .border_radius(4.0)
+                }),
+            button(label(move || {
+                if state.running.get() {
+                    "中止"
+                } else {
+                    "検索"
+                }
+            }))
+            .action(move || {
+                if state.running.get_untracked() {
+                    cancel();
+                } else {
+                    start();
+                }
+            })
+            .style(|s| s.padding_horiz(12.0).padding_vert(6.0)),
+        ))
+        .style(|s| s.gap(8.0).items_center().width_full()),
+        // 検索の条件
+        h_stack((
+            Checkbox::labeled_rw(state.regex, || "正規表現"),
+            Checkbox::labeled_rw(state.case_sensitive, || "大文字と小文字を区別"),
+            Checkbox::labeled_rw(state.whole_word, || "単語単位"),
+            Checkbox::labeled_rw(state.respect_gitignore, || ".gitignore に従う"),
+        ))
+        .style(|s| s.gap(12.0).font_size(12.0).items_center()),
+        h_stack((
+            text_input(state.include)
+                .placeholder("対象 (例: *.rs, *.toml)")
+                .style(glob_input_style),
+            text_input(state.exclude)
+                .placeholder("除外 (例: target/**)")
+                .style(glob_input_style),
+        ))
+        .style(|s| s.gap(8.0).width_full()),
+        // 進み具合
+        label(move || {
+            state
+                .progress
+                .get()
+                .map(|progress| format_search_progress(&progress, state.running.get()))
+                .unwrap_or_else(|| {
+                    if state.running.get() {
+                        "検索中…".to_string()
+                    } else {
+                        String::new()
+                    }
+                })
+        })
+        .style(|s| s.font_size(11.0).color(Color::rgb8(107, 114, 128))),
+        // 見つかった行
+        scroll(
+            dyn_stack(
+                move || {
+                    state
+                        .results
+                        .get()
+                        .into_iter()
+                        .enumerate()
+                        .collect::<Vec<_>>()
+                },
+                |(index, _)| *index,
+                move |(_, found)| match_row(found, root, on_open.clone(), on_reveal.clone()),
+            )
+            .style(|s| s.flex_col().width_full()),
+        )
+        .style(|s| s.max_height(240.0).width_full()),
+    ))
+    .on_cleanup(move || {
+        search.borrow_mut().take();
+    })
+    .style(|s| {
+        s.gap(6.0)
+            .padding(8.0)
+            .width_full()
+            .background(Color::rgb8(248, 249, 250))
+            .border(1.0)
+            .border_color(Color::rgb8(229, 231, 235))
+            .border_radius(6.0)
+    })
+}
+
+fn glob_input_style(s: floem::style::Style) -> floem::style::Style {
+    s.flex_grow(1.0)
+        .padding(4.0)
+        .font_size(12.0)
+        .border(1.0)
+        .border_color(Color::rgb8(209, 213, 219))
+        .border_radius(4.0)
+}
+
+/// 見つかった1行の表示
+fn match_row(
+    found: ContentMatch,
+    root: RwSignal<PathBuf>,
+    on_open: Rc<dyn Fn(&ContentMatch)>,
+    on_reveal: Rc<dyn Fn(&ContentMatch)>,
+) -> impl IntoView {
+    let location = format_match_location(&found, &root.get_untracked());
+    let context = |lines: &[String]| lines.join("\n");
+    let before = context(&found.before);
+    let after = context(&found.after);
+    let line = found.line.clone();
+    let open_found = found.clone();
+    let double_click_found = found.clone();
+    let double_click_open = on_open.clone();
+
+    v_stack((
+        h_stack((
+            label(move || location.clone())
+                .style(|s| s.font_size(12.0).font_weight(floem::text::Weight::BOLD)),
+            container("").style(|s| s.flex_grow(1.0)),
+            button(label(|| "開く"))
+                .on_click_stop(move |_| on_open(&open_found))
+                .style(|s| s.font_size(11.0)),
+            button(label(|| "タブで表示"))
+                .on_click_stop(move |_| on_reveal(&found))
+                .style(|s| s.font_size(11.0)),
+        ))
+        .style(|s| s.gap(6.0).items_center()),
+        label(move || before.clone()).style(context_style),
+        label(move || line.clone()).style(|s| {
+            s.font_size(12.0)
+                .font_family("monospace".to_string())
+                .background(Color::rgb8(254, 249, 195))
+        }),
+        label(move || after.clone()).style(context_style),
+    ))
+    .on_double_click_stop(move |_| double_click_open(&double_click_found))
+    .style(|s| {
+        s.width_full()
+            .padding(4.0)
+            .border_bottom(1.0)
+            .border_color(Color::rgb8(229, 231, 235))
+    })
+}
+
+fn context_style(s: floem::style::Style) -> floem::style::Style {
+    s.font_size(11.0)
+        .font_family("monospace".to_string())
+        .color(Color::rgb8(107, 114, 128))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_explorer_core::{FileEntry, FileType};
+
+    #[test]
+    fn test_split_globs() {
+        assert_eq!(split_globs(" *.rs, ,*.toml "), vec!["*.rs", "*.toml"]);
+        assert!(split_globs("").is_empty());
+    }
+
+    #[test]
+    fn test_format_match_and_progress() {
+        let found = ContentMatch {
+            entry: FileEntry {
+                name: "main.rs".to_string(),
+                path: PathBuf::from("/p/src/main.rs"),
+                file_type: FileType::File,
+                size: 10,
+                modified: None,
+            },
+            line_number: 3,
+            line: "todo".to_string(),
+            ranges: Vec::new(),
+            before: Vec::new(),
+            after: Vec::new(),
+            encoding: "UTF-8",
+        };
+        assert_eq!(
+            format_match_location(&found, Path::new("/p")),
+            "src/main.rs:3"
+        );
+
+        let progress = ContentSearchProgress {
+            searched_files: 10,
+            matched_files: 2,
+            matches: 5,
+            skipped_files: 1,
+            truncated: false,
+        };
+        assert_eq!(
+            format_search_progress(&progress, true),
+            "5 件（2 ファイル）— 10 ファイルを検索、1 ファイルをスキップ …"
+        );
+    }
+}
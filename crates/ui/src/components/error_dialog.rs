@@ -6,6 +6,7 @@ use floem::prelude::*;
 use floem::reactive::RwSignal;
 use floem::style::Position;
 use floem::text::Weight;
+use rust_explorer_core::{Conflict, ConflictDecision, ConflictResolution};
 use rust_explorer_utils::{AppError, ErrorCategory, ErrorSeverity};
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
@@ -62,10 +63,31 @@ pub enum ErrorActionType {
     OpenSettings,
     /// サポートに連絡
     ContactSupport,
+    /// 転送先の競合を解決
+    ResolveConflict {
+        resolution: ConflictResolution,
+        apply_to_all: bool,
+    },
     /// カスタムアクション
     Custom(String),
 }
 
+impl ErrorActionType {
+    /// 競合解決アクションであれば選択内容を返す
+    pub fn conflict_decision(&self) -> Option<ConflictDecision> {
+        match self {
+            ErrorActionType::ResolveConflict {
+                resolution,
+                apply_to_all,
+            } => Some(ConflictDecision {
+                resolution: *resolution,
+                apply_to_all: *apply_to_all,
+            }),
+            _ => None,
+        }
+    }
+}
+
 impl ErrorDisplayInfo {
     /// AppErrorから表示情報を作成
     pub fn from_app_error(error: &AppError) -> Self {
@@ -97,6 +119,68 @@ impl ErrorDisplayInfo {
         }
     }
 
+    /// 転送先の競合から表示情報を作成（解決方法を選ぶボタン付き）
+    pub fn for_conflict(conflict: &Conflict, apply_to_all: bool) -> Self {
+        let name = conflict
+            .destination
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let folder = conflict
+            .destination
+            .parent()
+            .map(|p| p.display().to_string())
+            .unwrap_or_default();
+
+        let format_time = |time: Option<std::time::SystemTime>| {
+            time.map(|t| {
+                chrono::DateTime::<chrono::Local>::from(t)
+                    .format("%Y/%m/%d %H:%M:%S")
+                    .to_string()
+            })
+            .unwrap_or_else(|| "不明".to_string())
+        };
+        let details = format!(
+            "転送元: {}\n  {} バイト, 更新日時 {}\n転送先: {}\n  {} バイト, 更新日時 {}",
+            conflict.source.display(),
+            conflict.source_size,
+            format_time(conflict.source_modified),
+            conflict.destination.display(),
+            conflict.destination_size,
+            format_time(conflict.destination_modified),
+        );
+
+        let mut actions: Vec<ErrorAction> = ConflictResolution::ALL
+            .iter()
+            .map(|resolution| ErrorAction {
+                id: format!("conflict_{:?}", resolution).to_lowercase(),
+                label: resolution.label().to_string(),
+                action_type: ErrorActionType::ResolveConflict {
+                    resolution: *resolution,
+                    apply_to_all,
+                },
+            })
+            .collect();
+        actions.push(ErrorAction {
+            id: "dismiss".to_string(),
+            label: "キャンセル".to_string(),
+            action_type: ErrorActionType::Dismiss,
+        });
+
+        Self {
+            id: format!("conflict_{}", conflict.destination.display()),
+            title: "同じ名前の項目があります".to_string(),
+            message: format!("「{}」は既に {} に存在します。", name, folder),
+            details: Some(details),
+            severity: ErrorSeverity::Warning,
+            category: ErrorCategory::FileSystem,
+            timestamp: chrono::Utc::now(),
+            auto_dismiss: false,
+            auto_dismiss_seconds: 0,
+            actions,
+        }
+    }
+
     /// エラーIDを生成
     fn generate_error_id(error: &AppError) -> String {
         use std::collections::hash_map::DefaultHasher;
@@ -503,6 +587,35 @@ mod tests {
         assert_eq!(icon, "⛔");
     }
 
+    #[test]
+    fn test_conflict_display_info() {
+        let conflict = Conflict {
+            source: std::path::PathBuf::from("/src/report.txt"),
+            destination: std::path::PathBuf::from("/dest/report.txt"),
+            source_size: 10,
+            destination_size: 20,
+            source_modified: None,
+            destination_modified: None,
+            source_is_dir: false,
+            destination_is_dir: false,
+        };
+        let display_info = ErrorDisplayInfo::for_conflict(&conflict, true);
+
+        assert!(!display_info.auto_dismiss);
+        assert!(display_info.message.contains("report.txt"));
+
+        let decisions: Vec<ConflictDecision> = display_info
+            .actions
+            .iter()
+            .filter_map(|action| action.action_type.conflict_decision())
+            .collect();
+        assert_eq!(decisions.len(), ConflictResolution::ALL.len());
+        assert!(decisions.iter().all(|d| d.apply_to_all));
+        assert_eq!(decisions[0].resolution, ConflictResolution::Skip);
+
+        assert_eq!(ErrorActionType::Retry.conflict_decision(), None);
+    }
+
     #[test]
     fn test_error_action_creation() {
         let config_error = AppError::Config("Config error".to_string());
@@ -71,7 +71,7 @@ fn file_icon_view(entry: FileEntry) -> impl View {
 }
 
 /// ファイルサイズをフォーマット
-fn format_file_size(size: u64) -> String {
+pub(crate) fn format_file_size(size: u64) -> String {
     const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
 
     if size == 0 {
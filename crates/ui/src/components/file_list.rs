@@ -2,13 +2,19 @@
 
 use floem::{
     View,
-    reactive::{RwSignal, SignalGet, SignalUpdate},
-    views::{Decorators, dyn_stack, scroll},
+    ext_event::create_signal_from_channel,
+    reactive::{ReadSignal, RwSignal, SignalGet, SignalUpdate, SignalWith, create_effect},
 };
-use rust_explorer_core::FileEntry;
-use std::path::PathBuf;
+use rust_explorer_core::{
+    DirectoryDelta, DirectoryStream, DirectoryWatcher, Event, EventManager, FileEntry,
+    FileSystemManager, ListingEvent, ListingOptions, merge_metadata,
+};
+use std::cell::{OnceCell, RefCell};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 
-use super::file_item::file_item_component;
+use super::modern_file_item::{FileItemDisplayMode, ModernFileItemConfig};
+use super::virtual_file_list::{reconcile_selection, virtual_file_list};
 
 /// ファイルリストの表示設定
 #[derive(Debug, Clone)]
@@ -19,6 +25,8 @@ pub struct FileListConfig {
     pub max_selection: usize,
     /// リストの高さ（None の場合は自動）
     pub height: Option<f64>,
+    /// 項目の表示モード
+    pub display_mode: FileItemDisplayMode,
 }
 
 impl Default for FileListConfig {
@@ -27,6 +35,7 @@ impl Default for FileListConfig {
             show_hidden: false,
             max_selection: 100,
             height: None,
+            display_mode: FileItemDisplayMode::List,
         }
     }
 }
@@ -38,8 +47,8 @@ pub struct FileListState {
     pub current_path: PathBuf,
     /// ファイル一覧
     pub entries: Vec<FileEntry>,
-    /// 選択されたアイテムのインデックス
-    pub selected_indices: Vec<usize>,
+    /// 選択されたアイテムのパス
+    pub selected_paths: HashSet<PathBuf>,
     /// 読み込み中フラグ
     pub loading: bool,
     /// エラーメッセージ
@@ -51,40 +60,185 @@ impl Default for FileListState {
         Self {
             current_path: PathBuf::from("."),
             entries: Vec::new(),
-            selected_indices: Vec::new(),
+            selected_paths: HashSet::new(),
             loading: false,
             error: None,
         }
     }
 }
 
-/// ファイルリストビューを作成
+/// アプリケーション全体で共有するイベントマネージャー
+static GLOBAL_EVENT_MANAGER: std::sync::LazyLock<EventManager> =
+    std::sync::LazyLock::new(EventManager::new);
+
+/// グローバルイベントマネージャーにアクセス
+pub fn global_event_manager() -> &'static EventManager {
+    &GLOBAL_EVENT_MANAGER
+}
+
+/// グローバルディレクトリ監視サービス（inotify の上限などで開始できなければ None）
+static GLOBAL_DIRECTORY_WATCHER: std::sync::LazyLock<Option<DirectoryWatcher>> =
+    std::sync::LazyLock::new(|| DirectoryWatcher::new(global_event_manager().clone()).ok());
+
+/// グローバルディレクトリ監視サービスにアクセス
+pub fn global_directory_watcher() -> Option<&'static DirectoryWatcher> {
+    GLOBAL_DIRECTORY_WATCHER.as_ref()
+}
+
+thread_local! {
+    /// UIスレッドで共有するディレクトリの変更差分のシグナル
+    static DIRECTORY_UPDATES: OnceCell<Option<ReadSignal<Option<DirectoryDelta>>>> =
+        const { OnceCell::new() };
+}
+
+/// ディレクトリの変更差分をUIスレッドで受け取るシグナル（監視できなければ None）
+///
+/// イベントの購読は最初の呼び出しで1度だけ行い、以降は同じシグナルを返す。
+/// 受け取る側のエフェクトはビューと共に破棄されるため、タブを作り直しても
+/// 購読やスレッドは増えない。
+pub fn directory_updates_signal() -> Option<ReadSignal<Option<DirectoryDelta>>> {
+    DIRECTORY_UPDATES.with(|updates| {
+        *updates.get_or_init(|| {
+            let watcher = global_directory_watcher()?;
+            let (sender, receiver) = crossbeam_channel::unbounded();
+            watcher
+                .event_manager()
+                .on_event(move |event| {
+                    if let Event::DirectoryUpdated(delta) = event {
+                        let _ = sender.send(delta.clone());
+                    }
+                })
+                .ok()?;
+            Some(create_signal_from_channel(receiver))
+        })
+    })
+}
+
+/// ディレクトリを段階的に読み込み、届いた分から一覧に反映する
+///
+/// 最初のバッチ（1画面分）は呼び出し中に反映し、残りとメタデータはUIスレッドで
+/// 順次反映する。返されたストリームを破棄すると読み込みは中断される。
+pub fn stream_directory(
+    path: &Path,
+    entries: RwSignal<Vec<FileEntry>>,
+    arrange: impl Fn(&mut Vec<FileEntry>) + 'static,
+) -> Option<DirectoryStream> {
+    let stream =
+        match FileSystemManager::new().list_directory_streaming(path, ListingOptions::default()) {
+            Ok(stream) => stream,
+            Err(e) => {
+                eprintln!("ディレクトリの読み込みに失敗しました: {}", e);
+                entries.set(Vec::new());
+                return None;
+            }
+        };
+
+    let mut initial = match stream.recv() {
+        Some(ListingEvent::Entries(batch)) => batch,
+        Some(ListingEvent::Failed(e)) => {
+            eprintln!("ディレクトリの読み込みに失敗しました: {}", e);
+            Vec::new()
+        }
+        _ => Vec::new(),
+    };
+    arrange(&mut initial);
+    entries.set(initial);
+
+    let canceller = stream.canceller();
+    let events = create_signal_from_channel(stream.receiver().clone());
+    create_effect(move |_| {
+        events.with(|event| {
+            if canceller.is_cancelled() {
+                return;
+            }
+            match event {
+                Some(ListingEvent::Entries(batch)) => entries.update(|entries| {
+                    // 監視による差分で既に追加された項目は重複させない
+                    let added: Vec<FileEntry> = {
+                        let known: HashSet<&Path> =
+                            entries.iter().map(|e| e.path.as_path()).collect();
+                        batch
+                            .iter()
+                            .filter(|e| !known.contains(e.path.as_path()))
+                            .cloned()
+                            .collect()
+                    };
+                    entries.extend(added);
+                    arrange(entries);
+                }),
+                Some(ListingEvent::Metadata(batch)) => entries.update(|entries| {
+                    merge_metadata(entries, batch.clone());
+                    arrange(entries);
+                }),
+                Some(ListingEvent::Failed(e)) => {
+                    eprintln!("ディレクトリの読み込みに失敗しました: {}", e);
+                }
+                _ => {}
+            }
+        });
+    });
+
+    Some(stream)
+}
+
+/// 表示中のディレクトリの監視（破棄時に監視を解除する）
+pub struct DirectoryWatch {
+    watcher: &'static DirectoryWatcher,
+    path: Option<PathBuf>,
+}
+
+impl DirectoryWatch {
+    /// 監視対象のないハンドルを作成
+    pub fn new(watcher: &'static DirectoryWatcher) -> Self {
+        Self {
+            watcher,
+            path: None,
+        }
+    }
+
+    /// 監視対象のディレクトリを切り替え
+    pub fn retarget(&mut self, path: &Path) {
+        if self.path.as_deref() == Some(path) {
+            return;
+        }
+        if let Some(old) = self.path.take() {
+            let _ = self.watcher.unwatch(&old);
+        }
+        if self.watcher.watch(path).is_ok() {
+            self.path = Some(path.to_path_buf());
+        }
+    }
+}
+
+impl Drop for DirectoryWatch {
+    fn drop(&mut self) {
+        if let Some(path) = self.path.take() {
+            let _ = self.watcher.unwatch(&path);
+        }
+    }
+}
+
+/// ファイルリストビューを作成（表示範囲の行だけビューを作成する）
 pub fn file_list_view(
     entries: RwSignal<Vec<FileEntry>>,
-    selected_indices: RwSignal<Vec<usize>>,
+    selection: RwSignal<HashSet<PathBuf>>,
+    display_mode: FileItemDisplayMode,
 ) -> impl View {
-    scroll(dyn_stack(
-        move || entries.get(),
-        |entry| entry.name.clone(),
-        move |entry| {
-            let index = entries
-                .get()
-                .iter()
-                .position(|e| e.name == entry.name)
-                .unwrap_or(0);
-            let is_selected = selected_indices.get().contains(&index);
-            file_item_component(entry, is_selected)
-        },
-    ))
-    .style(|s| s.flex_col().gap(1))
+    let config = ModernFileItemConfig {
+        display_mode,
+        ..ModernFileItemConfig::default()
+    };
+    virtual_file_list(entries, selection, config, |_| {})
 }
 
 /// ファイルリストビューコンポーネント
 pub struct FileListView {
     entries: RwSignal<Vec<FileEntry>>,
-    selected_indices: RwSignal<Vec<usize>>,
+    selection: RwSignal<HashSet<PathBuf>>,
     current_path: RwSignal<PathBuf>,
     config: FileListConfig,
+    watch: RefCell<Option<DirectoryWatch>>,
+    stream: RefCell<Option<DirectoryStream>>,
 }
 
 impl FileListView {
@@ -92,13 +246,15 @@ impl FileListView {
     pub fn new(initial_path: PathBuf, config: FileListConfig) -> Self {
         let view = Self {
             entries: RwSignal::new(Vec::new()),
-            selected_indices: RwSignal::new(Vec::new()),
+            selection: RwSignal::new(HashSet::new()),
             current_path: RwSignal::new(initial_path.clone()),
             config,
+            watch: RefCell::new(None),
+            stream: RefCell::new(None),
         };
 
         // 初期ディレクトリを読み込み
-        view.load_directory_sync(initial_path);
+        view.load_directory(initial_path);
 
         view
     }
@@ -108,130 +264,162 @@ impl FileListView {
         Self::new(initial_path, FileListConfig::default())
     }
 
+    /// 表示中のディレクトリの変更を監視し、差分で一覧を更新する
+    pub fn with_watcher(self, watcher: &'static DirectoryWatcher) -> Self {
+        let Some(updates) = directory_updates_signal() else {
+            return self;
+        };
+        let mut watch = DirectoryWatch::new(watcher);
+        watch.retarget(&self.current_path.get_untracked());
+        *self.watch.borrow_mut() = Some(watch);
+
+        let entries = self.entries;
+        let selection = self.selection;
+        let current_path = self.current_path;
+        let config = self.config.clone();
+        create_effect(move |_| {
+            updates.with(|delta| {
+                if let Some(delta) = delta
+                    && current_path.with_untracked(|path| *path == delta.directory)
+                {
+                    apply_delta(entries, selection, &config, delta);
+                }
+            });
+        });
+
+        self
+    }
+
     /// 現在のパスを取得
     pub fn current_path(&self) -> PathBuf {
         self.current_path.get()
     }
 
-    /// 選択されたファイルエントリを取得
+    /// 選択中の項目のパス
+    pub fn selection(&self) -> RwSignal<HashSet<PathBuf>> {
+        self.selection
+    }
+
+    /// 選択されたファイルエントリを表示順で取得
     pub fn selected_entries(&self) -> Vec<FileEntry> {
-        let entries = self.entries.get();
-        let indices = self.selected_indices.get();
-
-        indices
-            .iter()
-            .filter_map(|&index| entries.get(index))
-            .cloned()
-            .collect()
+        self.selection.with(|selection| {
+            self.entries.with(|entries| {
+                entries
+                    .iter()
+                    .filter(|entry| selection.contains(&entry.path))
+                    .cloned()
+                    .collect()
+            })
+        })
     }
 
     /// ディレクトリを変更
     pub fn change_directory(&self, path: PathBuf) {
         self.current_path.set(path.clone());
-        self.selected_indices.set(Vec::new());
-        self.load_directory_sync(path);
+        self.selection.set(HashSet::new());
+        if let Some(watch) = self.watch.borrow_mut().as_mut() {
+            watch.retarget(&path);
+        }
+        self.load_directory(path);
     }
 
     /// 選択をクリア
     pub fn clear_selection(&self) {
-        self.selected_indices.set(Vec::new());
+        self.selection.set(HashSet::new());
     }
 
     /// アイテムを選択
     pub fn select_item(&self, index: usize) {
-        let entries_len = self.entries.get().len();
-        if index < entries_len {
-            self.selected_indices.set(vec![index]);
+        if let Some(path) = self.path_at(index) {
+            self.selection.set(HashSet::from([path]));
         }
     }
 
     /// 複数アイテムを選択に追加
     pub fn add_to_selection(&self, index: usize) {
-        let entries_len = self.entries.get().len();
-        let mut indices = self.selected_indices.get();
+        let Some(path) = self.path_at(index) else {
+            return;
+        };
+        let max_selection = self.config.max_selection;
+        self.selection.update(|selection| {
+            if selection.len() < max_selection {
+                selection.insert(path);
+            }
+        });
+    }
 
-        if index < entries_len
-            && !indices.contains(&index)
-            && indices.len() < self.config.max_selection
+    /// 変更差分を一覧に反映（表示中のディレクトリ以外の差分は無視する）
+    pub fn apply_delta(&self, delta: &DirectoryDelta) {
+        if self
+            .current_path
+            .with_untracked(|path| *path == delta.directory)
         {
-            indices.push(index);
-            self.selected_indices.set(indices);
+            apply_delta(self.entries, self.selection, &self.config, delta);
         }
     }
 
     /// 表示を更新
     pub fn refresh(&self) {
         let current_path = self.current_path.get();
-        self.load_directory_sync(current_path);
+        self.load_directory(current_path);
     }
 
     /// ファイルリストビューを作成
     pub fn view(&self) -> impl View {
-        file_list_view(self.entries, self.selected_indices)
+        file_list_view(self.entries, self.selection, self.config.display_mode)
     }
 
-    /// ディレクトリを同期的に読み込み
-    fn load_directory_sync(&self, path: PathBuf) {
-        use std::fs;
-
-        let entries = match fs::read_dir(&path) {
-            Ok(entries) => {
-                let mut file_entries = Vec::new();
-
-                for entry in entries.flatten() {
-                    let entry_path = entry.path();
-                    let metadata = entry.metadata().ok();
-
-                    let file_type = if entry_path.is_dir() {
-                        rust_explorer_core::FileType::Directory
-                    } else if entry_path.is_symlink() {
-                        rust_explorer_core::FileType::SymLink
-                    } else if entry_path.is_file() {
-                        rust_explorer_core::FileType::File
-                    } else {
-                        rust_explorer_core::FileType::Other
-                    };
-
-                    let size = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
-                    let modified = metadata.and_then(|m| m.modified().ok());
-
-                    let file_entry = FileEntry {
-                        name: entry.file_name().to_string_lossy().to_string(),
-                        path: entry_path,
-                        file_type,
-                        size,
-                        modified,
-                    };
+    /// 表示位置の項目のパス
+    fn path_at(&self, index: usize) -> Option<PathBuf> {
+        self.entries
+            .with_untracked(|entries| entries.get(index).map(|entry| entry.path.clone()))
+    }
 
-                    file_entries.push(file_entry);
-                }
+    /// ディレクトリを段階的に読み込み（前の読み込みは中断される）
+    fn load_directory(&self, path: PathBuf) {
+        let config = self.config.clone();
+        let stream = stream_directory(&path, self.entries, move |entries| {
+            arrange_entries(entries, &config)
+        });
+        *self.stream.borrow_mut() = stream;
+    }
+}
 
-                // 隠しファイルのフィルタリング
-                if !self.config.show_hidden {
-                    file_entries.retain(|entry| !entry.name.starts_with('.'));
-                }
+/// 隠しファイルを除外し、名前順（フォルダを先）に並べる
+fn arrange_entries(entries: &mut Vec<FileEntry>, config: &FileListConfig) {
+    use rust_explorer_core::FileType;
 
-                // 名前順でソート（フォルダを先に）
-                file_entries.sort_by(|a, b| {
-                    use rust_explorer_core::FileType;
-                    match (&a.file_type, &b.file_type) {
-                        (FileType::Directory, FileType::Directory)
-                        | (FileType::File, FileType::File) => a.name.cmp(&b.name),
-                        (FileType::Directory, _) => std::cmp::Ordering::Less,
-                        (_, FileType::Directory) => std::cmp::Ordering::Greater,
-                        _ => a.name.cmp(&b.name),
-                    }
-                });
+    if !config.show_hidden {
+        entries.retain(|entry| !entry.name.starts_with('.'));
+    }
 
-                file_entries
-            }
-            Err(e) => {
-                eprintln!("ディレクトリの読み込みに失敗しました: {}", e);
-                Vec::new()
-            }
-        };
+    entries.sort_by(|a, b| match (&a.file_type, &b.file_type) {
+        (FileType::Directory, FileType::Directory) | (FileType::File, FileType::File) => {
+            a.name.cmp(&b.name)
+        }
+        (FileType::Directory, _) => std::cmp::Ordering::Less,
+        (_, FileType::Directory) => std::cmp::Ordering::Greater,
+        _ => a.name.cmp(&b.name),
+    });
+}
 
-        self.entries.set(entries);
+/// 差分を一覧に反映し、選択中の項目を引き継ぐ
+fn apply_delta(
+    entries: RwSignal<Vec<FileEntry>>,
+    selection: RwSignal<HashSet<PathBuf>>,
+    config: &FileListConfig,
+    delta: &DirectoryDelta,
+) {
+    entries.update(|entries| {
+        delta.apply_to(entries);
+        arrange_entries(entries, config);
+    });
+
+    let needs_update = selection.with_untracked(|selection| !selection.is_empty());
+    if needs_update {
+        entries.with_untracked(|entries| {
+            selection.update(|selection| reconcile_selection(selection, entries, delta));
+        });
     }
 }
 
@@ -265,6 +453,37 @@ mod tests {
         assert!(config.height.is_none());
     }
 
+    #[test]
+    fn test_apply_delta_keeps_selection() {
+        use rust_explorer_core::DirectoryChange;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("b.txt"), "b").unwrap();
+        let view = FileListView::with_default(temp_dir.path().to_path_buf());
+        view.select_item(0);
+
+        std::fs::write(temp_dir.path().join("a.txt"), "a").unwrap();
+        std::fs::write(temp_dir.path().join(".hidden"), "h").unwrap();
+        view.apply_delta(&DirectoryDelta {
+            directory: temp_dir.path().to_path_buf(),
+            changes: vec![
+                DirectoryChange::Added(temp_dir.path().join("a.txt")),
+                DirectoryChange::Added(temp_dir.path().join(".hidden")),
+            ],
+        });
+
+        let names: Vec<String> = view.entries.get().into_iter().map(|e| e.name).collect();
+        assert_eq!(names, vec!["a.txt", "b.txt"]);
+        assert_eq!(view.selected_entries()[0].name, "b.txt");
+
+        // 別のディレクトリの差分は無視する
+        view.apply_delta(&DirectoryDelta {
+            directory: PathBuf::from("/elsewhere"),
+            changes: vec![DirectoryChange::Removed(temp_dir.path().join("a.txt"))],
+        });
+        assert_eq!(view.entries.get().len(), 2);
+    }
+
     #[test]
     fn test_selection_operations() {
         let view = FileListView::new(PathBuf::from("/test"), FileListConfig::default());
@@ -5,7 +5,7 @@ use floem::views::Decorators;
 use floem::{IntoView, View};
 use rust_explorer_core::{FileEntry, FileNavigationManager as CoreFileNavigationManager, FileType};
 use rust_explorer_utils::AppError;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 /// ファイルナビゲーションの設定
@@ -65,6 +65,8 @@ pub struct FileNavigationManager {
     on_path_change: Option<Box<dyn Fn(PathBuf) + Send + Sync>>,
     /// エラー通知コールバック
     on_error: Option<Box<dyn Fn(String) + Send + Sync>>,
+    /// ファイルを開いたときの通知コールバック
+    on_file_open: Option<Box<dyn Fn(PathBuf) + Send + Sync>>,
 }
 
 impl FileNavigationManager {
@@ -80,6 +82,7 @@ impl FileNavigationManager {
             navigation_manager,
             on_path_change: None,
             on_error: None,
+            on_file_open: None,
         }
     }
 
@@ -93,6 +96,15 @@ impl FileNavigationManager {
         )
     }
 
+    /// 保存しておいた戻る・進む履歴を復元
+    pub fn with_history(self, history_back: Vec<PathBuf>, history_forward: Vec<PathBuf>) -> Self {
+        self.state.update(|state| {
+            state.history_back = history_back;
+            state.history_forward = history_forward;
+        });
+        self
+    }
+
     /// パス変更通知コールバックを設定
     pub fn on_path_change<F>(mut self, callback: F) -> Self
     where
@@ -111,6 +123,20 @@ impl FileNavigationManager {
         self
     }
 
+    /// ファイルを開いたときの通知コールバックを設定
+    pub fn on_file_open<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(PathBuf) + Send + Sync + 'static,
+    {
+        self.on_file_open = Some(Box::new(callback));
+        self
+    }
+
+    /// ナビゲーション状態のシグナル（履歴の保存などに使う）
+    pub fn state_signal(&self) -> RwSignal<FileNavigationState> {
+        self.state
+    }
+
     /// 現在のパスを取得
     pub fn current_path(&self) -> PathBuf {
         self.state.get().current_path
@@ -131,14 +157,16 @@ impl FileNavigationManager {
             }
             FileType::File => {
                 // ファイルの場合は開く
-                if let Err(e) = self.navigation_manager.open_item(&entry.path) {
-                    self.handle_error(format!("ファイルオープンエラー: {}", e));
+                match self.navigation_manager.open_item(&entry.path) {
+                    Ok(()) => self.notify_file_open(&entry.path),
+                    Err(e) => self.handle_error(format!("ファイルオープンエラー: {}", e)),
                 }
             }
             _ => {
                 // その他のタイプは既定の処理
-                if let Err(e) = self.navigation_manager.open_item(&entry.path) {
-                    self.handle_error(format!("アイテムオープンエラー: {}", e));
+                match self.navigation_manager.open_item(&entry.path) {
+                    Ok(()) => self.notify_file_open(&entry.path),
+                    Err(e) => self.handle_error(format!("アイテムオープンエラー: {}", e)),
                 }
             }
         }
@@ -279,6 +307,13 @@ impl FileNavigationManager {
             .is_some()
     }
 
+    /// ファイルを開いたことを通知
+    fn notify_file_open(&self, path: &Path) {
+        if let Some(callback) = &self.on_file_open {
+            callback(path.to_path_buf());
+        }
+    }
+
     /// エラー処理
     fn handle_error(&self, error_message: String) {
         self.state.update(|state| {
@@ -0,0 +1,209 @@
+//! 訪れた場所の履歴
+//!
+//! タブをまたいでディレクトリの移動とファイルを開いた記録を残し、よく使う順に
+//! サイドバーの「最近」へ並べます。履歴はデータディレクトリの JSON に保存し、
+//! 設定の `history_exclusions` に一致する場所は記録しません。
+//! 移動のたびにUIスレッドで書き込まないよう、保存はまとめて保存スレッドで行います。
+
+use super::error_dialog::display_error_globally;
+use super::user_data::{HISTORY_SAVE, follow_job_moves};
+use chrono::Utc;
+use floem::reactive::{RwSignal, SignalGet, SignalUpdate, SignalWith};
+use rust_explorer_config::state_helpers;
+use rust_explorer_core::{HistoryEntry, HistoryExclusions, LocationHistory, VisitKind};
+use std::path::Path;
+
+/// サイドバーの「最近」に表示する数
+pub const RECENT_SIDEBAR_LIMIT: usize = 10;
+
+/// 訪れた場所の記録と、開く場所の受け渡し
+///
+/// サイドバーで開く場所は `requested` に置き、アクティブなタブを持つ
+/// メインコンテンツが開く。
+#[derive(Clone, Copy)]
+pub struct HistoryController {
+    history: RwSignal<LocationHistory>,
+    exclusions: RwSignal<HistoryExclusions>,
+    requested: RwSignal<Option<HistoryEntry>>,
+    persist: bool,
+}
+
+impl HistoryController {
+    /// 保存された履歴を読み込んで作成し、アプリ内での移動に追随させる
+    ///
+    /// 除外する場所に一致するようになった記録は読み込み時に忘れる。
+    pub fn load(exclusions: &[String]) -> Self {
+        let history = match state_helpers::history_exist() {
+            Ok(true) => state_helpers::load_history().unwrap_or_else(|e| {
+                display_error_globally(&e);
+                LocationHistory::new()
+            }),
+            Ok(false) => LocationHistory::new(),
+            Err(e) => {
+                display_error_globally(&e);
+                LocationHistory::new()
+            }
+        };
+        let controller = Self::with_history(history, exclusions, true);
+        controller.set_exclusions(exclusions);
+        // 移動ジョブで移動した場所の記録を付け替える
+        follow_job_moves(move |from, to| controller.relocate(from, to));
+        controller
+    }
+
+    /// 保存しない履歴で作成
+    pub fn in_memory(history: LocationHistory, exclusions: &[String]) -> Self {
+        Self::with_history(history, exclusions, false)
+    }
+
+    fn with_history(history: LocationHistory, exclusions: &[String], persist: bool) -> Self {
+        Self {
+            history: RwSignal::new(history),
+            exclusions: RwSignal::new(HistoryExclusions::new(exclusions)),
+            requested: RwSignal::new(None),
+            persist,
+        }
+    }
+
+    /// 訪れた場所の履歴
+    pub fn history(&self) -> RwSignal<LocationHistory> {
+        self.history
+    }
+
+    /// 開くよう求められた場所
+    pub fn requested(&self) -> RwSignal<Option<HistoryEntry>> {
+        self.requested
+    }
+
+    /// 記録しない場所を変更し、一致するようになった記録を忘れる
+    pub fn set_exclusions(&self, patterns: &[String]) {
+        let exclusions = HistoryExclusions::new(patterns);
+        let mut history = self.history.get_untracked();
+        if history.forget_excluded(&exclusions) {
+            self.commit(history);
+        }
+        self.exclusions.set(exclusions);
+    }
+
+    /// よく使う順の場所（サイドバーの表示用）
+    pub fn top(&self, limit: usize) -> Vec<HistoryEntry> {
+        self.history.with(|history| {
+            history
+                .top(limit, None, Utc::now())
+                .into_iter()
+                .cloned()
+                .collect()
+        })
+    }
+
+    /// ディレクトリへの移動を記録
+    pub fn record_directory(&self, path: &Path) {
+        self.record(path, VisitKind::Directory);
+    }
+
+    /// ファイルを開いたことを記録
+    pub fn record_file(&self, path: &Path) {
+        self.record(path, VisitKind::File);
+    }
+
+    fn record(&self, path: &Path, kind: VisitKind) {
+        let mut history = self.history.get_untracked();
+        let recorded = self
+            .exclusions
+            .with_untracked(|exclusions| history.record(path, kind, Utc::now(), exclusions));
+        if recorded {
+            self.commit(history);
+        }
+    }
+
+    /// 場所の記録を削除
+    pub fn remove(&self, path: &Path) {
+        let mut history = self.history.get_untracked();
+        if history.remove(path) {
+            self.commit(history);
+        }
+    }
+
+    /// 履歴をすべて消去
+    pub fn clear(&self) {
+        self.commit(LocationHistory::new());
+    }
+
+    /// 名前変更・移動した場所の記録を付け替える
+    pub fn relocate(&self, from: &Path, to: &Path) {
+        let mut history = self.history.get_untracked();
+        if history.relocate(from, to) {
+            self.commit(history);
+        }
+    }
+
+    /// 記録した場所を開くよう求める
+    pub fn open(&self, path: &Path) {
+        let entry = self.history.with_untracked(|h| h.get(path).cloned());
+        if entry.is_some() {
+            self.requested.set(entry);
+        }
+    }
+
+    /// 反映し、保存を予約する
+    fn commit(&self, history: LocationHistory) {
+        if self.persist {
+            HISTORY_SAVE.save(history.clone());
+        }
+        self.history.set(history);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_history_controller() {
+        let controller =
+            HistoryController::in_memory(LocationHistory::new(), &["/tmp".to_string()]);
+        controller.record_directory(Path::new("/home/u"));
+        controller.record_directory(Path::new("/home/u"));
+        controller.record_file(Path::new("/home/u/notes.txt"));
+        controller.record_directory(Path::new("/tmp/build"));
+
+        let top: Vec<PathBuf> = controller
+            .top(RECENT_SIDEBAR_LIMIT)
+            .into_iter()
+            .map(|entry| entry.path)
+            .collect();
+        assert_eq!(
+            top,
+            vec![PathBuf::from("/home/u"), PathBuf::from("/home/u/notes.txt")]
+        );
+
+        controller.open(Path::new("/home/u/notes.txt"));
+        assert_eq!(
+            controller.requested().get_untracked().map(|e| e.kind),
+            Some(VisitKind::File)
+        );
+        controller.open(Path::new("/missing"));
+        assert!(controller.requested().get_untracked().is_some());
+
+        controller.relocate(Path::new("/home/u"), Path::new("/home/v"));
+        assert!(
+            controller
+                .history()
+                .with_untracked(|h| h.get(Path::new("/home/v/notes.txt")).is_some())
+        );
+        controller.remove(Path::new("/home/v"));
+        assert_eq!(controller.top(RECENT_SIDEBAR_LIMIT).len(), 1);
+        controller.clear();
+        assert!(controller.top(RECENT_SIDEBAR_LIMIT).is_empty());
+
+        // 設定の再読み込みで除外した場所は記録を忘れる
+        controller.record_directory(Path::new("/srv/cache/x"));
+        controller.record_directory(Path::new("/srv/data"));
+        controller.set_exclusions(&["/srv/cache".to_string()]);
+        assert_eq!(controller.top(RECENT_SIDEBAR_LIMIT).len(), 1);
+        controller.record_directory(Path::new("/srv/cache/y"));
+        controller.record_directory(Path::new("/tmp/z"));
+        assert_eq!(controller.top(RECENT_SIDEBAR_LIMIT).len(), 2);
+    }
+}
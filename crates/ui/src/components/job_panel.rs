@@ -0,0 +1,324 @@
+//! ファイル操作ジョブパネルコンポーネント
+//!
+//! バックグラウンドで実行中のコピー・移動・削除の進捗を表示し、
+//! 一時停止・再開・キャンセルを操作できるパネルを提供します。
+
+use super::error_dialog::{ErrorActionType, ErrorDisplayInfo, error_dialog_component};
+use super::file_item::format_file_size;
+use floem::ext_event::create_signal_from_channel;
+use floem::prelude::*;
+use floem::reactive::{ReadSignal, RwSignal, Scope, create_effect};
+use floem::views::Checkbox;
+use rust_explorer_core::{Conflict, JobEvent, JobId, JobInfo, JobManager, JobStatus};
+use std::cell::OnceCell;
+use std::time::Duration;
+
+/// ジョブパネルの設定
+pub struct JobPanelConfig {
+    pub background_color: Color,
+    pub border_color: Color,
+    pub text_color: Color,
+    /// ジョブ一覧部分の最大の高さ
+    pub max_height: f32,
+}
+
+impl Default for JobPanelConfig {
+    fn default() -> Self {
+        Self {
+            background_color: Color::rgb8(245, 245, 245),
+            border_color: Color::rgb8(200, 200, 200),
+            text_color: Color::rgb8(70, 70, 70),
+            max_height: 160.0,
+        }
+    }
+}
+
+/// グローバルジョブマネージャー
+static GLOBAL_JOB_MANAGER: std::sync::LazyLock<JobManager> = std::sync::LazyLock::new(|| {
+    JobManager::new().expect("Failed to start the file operation runtime")
+});
+
+/// グローバルジョブマネージャーにアクセス
+pub fn global_job_manager() -> &'static JobManager {
+    &GLOBAL_JOB_MANAGER
+}
+
+thread_local! {
+    /// UIスレッドで共有するグローバルジョブマネージャーのイベントのシグナル
+    static JOB_EVENTS: OnceCell<ReadSignal<Option<JobEvent>>> = const { OnceCell::new() };
+    /// UIスレッドで共有するジョブ一覧のシグナル
+    static JOB_INFOS: OnceCell<RwSignal<Vec<JobInfo>>> = const { OnceCell::new() };
+}
+
+/// グローバルジョブマネージャーのイベントをUIスレッドで受け取るシグナル
+///
+/// ジョブイベントはワーカースレッドから届くため、チャネル経由でUIスレッドへ渡す。
+/// イベントの購読は最初の呼び出しで1度だけ行い、以降は同じシグナルを返すため、
+/// パネルやステータスバーを作り直しても購読は増えない。
+pub fn job_events_signal() -> ReadSignal<Option<JobEvent>> {
+    JOB_EVENTS.with(|events| {
+        *events.get_or_init(|| {
+            let (sender, receiver) = crossbeam_channel::unbounded();
+            let _ = global_job_manager().on_job_event(move |event| {
+                let _ = sender.send(event.clone());
+            });
+            create_signal_from_channel(receiver)
+        })
+    })
+}
+
+/// グローバルジョブマネージャーのジョブ一覧を保持するシグナル
+///
+/// ジョブパネルとステータスバーで同じシグナルを共有する。更新するエフェクトは
+/// ビューに属さないスコープに作るため、ビューを作り直しても残る。
+pub fn job_infos_signal() -> RwSignal<Vec<JobInfo>> {
+    JOB_INFOS.with(|infos| {
+        *infos.get_or_init(|| {
+            let manager = global_job_manager();
+            let events = job_events_signal();
+            let cx = Scope::new();
+            let jobs = cx.create_rw_signal(manager.jobs());
+            cx.create_effect(move |_| {
+                if events.with(|e| e.is_some()) {
+                    jobs.set(manager.jobs());
+                }
+            });
+            jobs
+        })
+    })
+}
+
+/// グローバルジョブマネージャーのジョブパネルコンポーネントを作成
+pub fn job_panel(config: JobPanelConfig) -> impl IntoView {
+    let manager = global_job_manager();
+    let jobs = job_infos_signal();
+    let conflict = RwSignal::new(None::<(JobId, Conflict)>);
+    let events = job_events_signal();
+    let text_color = config.text_color;
+    let max_height = config.max_height;
+
+    create_effect(move |_| {
+        events.with(|event| match event {
+            Some(JobEvent::ConflictDetected { id, conflict: c }) => {
+                conflict.set(Some((*id, c.clone())));
+            }
+            Some(JobEvent::Cancelled(id)) | Some(JobEvent::Failed { id, .. })
+                if conflict.with_untracked(|c| c.as_ref().is_some_and(|(cid, _)| cid == id)) =>
+            {
+                conflict.set(None);
+            }
+            _ => {}
+        });
+    });
+
+    v_stack((
+        conflict_prompt(manager, conflict),
+        h_stack((
+            label(|| "ファイル操作").style(move |s| s.font_size(12.0).color(text_color)),
+            container("").style(|s| s.flex_grow(1.0)),
+            button(label(|| "完了済みを消去"))
+                .on_click_stop(move |_| {
+                    manager.clear_finished();
+                    jobs.set(manager.jobs());
+                })
+                .style(|s| s.font_size(11.0)),
+        ))
+        .style(|s| s.items_center().margin_bottom(4.0)),
+        scroll(
+            dyn_stack(
+                move || jobs.get(),
+                |info| info.id,
+                move |info| job_row(manager, jobs, info.id, text_color),
+            )
+            .style(|s| s.flex_col().width_full()),
+        )
+        .style(move |s| s.max_height(max_height)),
+    ))
+    .style(move |s| {
+        s.width_full()
+            .background(config.background_color)
+            .border_top(1.0)
+            .border_color(config.border_color)
+            .padding(6.0)
+            .apply_if(jobs.with(|j| j.is_empty()), |s| s.hide())
+    })
+}
+
+/// デフォルト設定でグローバルジョブマネージャーのパネルを作成
+pub fn default_job_panel() -> impl IntoView {
+    job_panel(JobPanelConfig::default())
+}
+
+/// 転送先の競合の解決方法を選ぶダイアログ
+fn conflict_prompt(
+    manager: &'static JobManager,
+    conflict: RwSignal<Option<(JobId, Conflict)>>,
+) -> impl IntoView {
+    let apply_to_all = RwSignal::new(false);
+
+    dyn_container(
+        move || conflict.get(),
+        move |pending| match pending {
+            Some((id, c)) => v_stack((
+                error_dialog_component(
+                    ErrorDisplayInfo::for_conflict(&c, false),
+                    move |action_type| {
+                        if let Some(mut decision) = action_type.conflict_decision() {
+                            decision.apply_to_all |= apply_to_all.get_untracked();
+                            let _ = manager.resolve_conflict(id, decision);
+                        } else if action_type == ErrorActionType::Dismiss {
+                            let _ = manager.cancel(id);
+                        }
+                        apply_to_all.set(false);
+                        conflict.set(None);
+                    },
+                ),
+                Checkbox::labeled_rw(apply_to_all, || "以降の競合すべてに適用")
+                    .style(|s| s.font_size(12.0).margin_bottom(6.0)),
+            ))
+            .into_any(),
+            None => container("").into_any(),
+        },
+    )
+}
+
+/// 1件分のジョブ行を作成
+fn job_row(
+    manager: &'static JobManager,
+    jobs: RwSignal<Vec<JobInfo>>,
+    id: JobId,
+    text_color: Color,
+) -> impl IntoView {
+    let status = move || {
+        jobs.with(|j| j.iter().find(|i| i.id == id).map(|i| i.status.clone()))
+            .unwrap_or(JobStatus::Completed)
+    };
+
+    h_stack((
+        label(move || {
+            jobs.with(|j| {
+                j.iter()
+                    .find(|i| i.id == id)
+                    .map(format_job_line)
+                    .unwrap_or_default()
+            })
+        })
+        .style(move |s| s.font_size(12.0).color(text_color).flex_grow(1.0)),
+        button(label(move || {
+            if status() == JobStatus::Paused {
+                "再開"
+            } else {
+                "一時停止"
+            }
+        }))
+        .on_click_stop(move |_| {
+            let _ = if status() == JobStatus::Paused {
+                manager.resume(id)
+            } else {
+                manager.pause(id)
+            };
+        })
+        .style(move |s| {
+            s.font_size(11.0)
+                .margin_left(6.0)
+                .apply_if(status().is_finished(), |s| s.hide())
+        }),
+        button(label(|| "キャンセル"))
+            .on_click_stop(move |_| {
+                let _ = manager.cancel(id);
+            })
+            .style(move |s| {
+                s.font_size(11.0)
+                    .margin_left(6.0)
+                    .apply_if(status().is_finished(), |s| s.hide())
+            }),
+    ))
+    .style(|s| s.items_center().width_full().padding_vert(2.0))
+}
+
+/// ジョブ1件の表示文字列を作成
+pub fn format_job_line(info: &JobInfo) -> String {
+    let progress = &info.progress;
+    let percent = (progress.fraction() * 100.0).round() as u32;
+    let description = info.kind.description();
+
+    match &info.status {
+        JobStatus::Queued => format!("{description} — 待機中"),
+        JobStatus::Running => {
+            let mut line = format!(
+                "{description} — {percent}% ({}/{} 項目, {}/s",
+                progress.processed_files,
+                progress.total_files,
+                format_file_size(progress.bytes_per_second() as u64)
+            );
+            if let Some(eta) = progress.eta() {
+                line.push_str(&format!(", 残り {}", format_duration(eta)));
+            }
+            line.push(')');
+            line
+        }
+        JobStatus::Paused => format!("{description} — {percent}% 一時停止中"),
+        JobStatus::WaitingForDecision => format!("{description} — {percent}% 競合の確認待ち"),
+        JobStatus::Completed => format!("{description} — 完了"),
+        JobStatus::Cancelled => format!("{description} — キャンセルされました"),
+        JobStatus::Failed(error) => format!("{description} — 失敗: {error}"),
+    }
+}
+
+/// 残り時間を mm:ss 形式でフォーマット
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, (secs % 3600) / 60, secs % 60)
+    } else {
+        format!("{:02}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_explorer_core::{JobKind, JobProgress};
+    use std::path::PathBuf;
+
+    fn job_info(status: JobStatus) -> JobInfo {
+        JobInfo {
+            id: 1,
+            kind: JobKind::Delete {
+                paths: vec![PathBuf::from("/tmp/a"), PathBuf::from("/tmp/b")],
+            },
+            status,
+            progress: JobProgress {
+                total_bytes: 2048,
+                processed_bytes: 1024,
+                total_files: 2,
+                processed_files: 1,
+                current_path: None,
+                elapsed: Duration::from_secs(2),
+            },
+        }
+    }
+
+    #[test]
+    fn test_format_job_line_running() {
+        let line = format_job_line(&job_info(JobStatus::Running));
+        assert!(line.starts_with("2 項目を削除 — 50%"));
+        assert!(line.contains("512 B/s"));
+        assert!(line.contains("残り 00:02"));
+    }
+
+    #[test]
+    fn test_format_job_line_finished() {
+        assert!(format_job_line(&job_info(JobStatus::Completed)).ends_with("完了"));
+        assert!(
+            format_job_line(&job_info(JobStatus::Failed("disk full".to_string())))
+                .ends_with("失敗: disk full")
+        );
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(75)), "01:15");
+        assert_eq!(format_duration(Duration::from_secs(3725)), "1:02:05");
+    }
+}
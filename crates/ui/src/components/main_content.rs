@@ -2,25 +2,57 @@
 //!
 //! アプリケーションのメインコンテンツ部分を提供します。
 
+use floem::AnyView;
+use floem::event::{Event, EventListener};
+use floem::keyboard::{Key, Modifiers};
 use floem::prelude::*;
 use floem::reactive::RwSignal;
 use floem::text::Weight;
 use rust_explorer_config::Settings;
 use std::cell::RefCell;
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::sync::Arc;
 
+use super::bookmarks::{BookmarksController, bookmark_shortcut_for_key};
+use super::content_search::content_search_panel;
+use super::file_list::{
+    DirectoryWatch, directory_updates_signal, global_directory_watcher, stream_directory,
+};
+use super::history::HistoryController;
+use super::pane::{PanesController, pane_layout_view};
+use super::quick_open::{
+    TabNavigator, TabNavigators, is_quick_open_key, quick_open_candidates, quick_open_popup,
+};
+use super::smart_folders::smart_folder_content;
+use super::tabs::TabsController;
+use super::tags::{TagsController, tag_content, tag_menu};
+use super::undo::{UndoController, undo_command_for_key};
+use super::virtual_file_list::{reconcile_selection, virtual_file_list_with_scroll};
 use super::{
-    ModernFileItemConfig, SortFilterUIManager, breadcrumb_view, modern_file_item_with_double_click,
+    ModernFileItemConfig, SortFilterUIManager, breadcrumb_view, display_error_globally,
     navigation_helpers, simple_filter_bar,
 };
+use crate::state_integration::ReactiveStateManager;
+use floem::reactive::create_effect;
+use rust_explorer_core::{SearchIndexer, StateManager, TabState, VisitKind};
 
 /// メインコンテンツコンポーネントの設定
 pub struct MainContentConfig {
     pub background_color: Color,
     pub padding: f32,
     pub content_type: ContentType,
+    /// クイックオープンで使う検索インデックス
+    pub search_indexer: Option<Arc<SearchIndexer>>,
+    /// ファイル一覧に表示し、付け外しするタグ
+    pub tags: Option<TagsController>,
+    /// ショートカットやサイドバーで開き、ファイル一覧から登録するブックマーク
+    pub bookmarks: Option<BookmarksController>,
+    /// 移動と開いたファイルを記録し、サイドバーの「最近」から開く履歴
+    pub history: Option<HistoryController>,
+    /// Ctrl+Z などで元に戻す・やり直すファイル操作の履歴
+    pub undo: Option<UndoController>,
 }
 
 /// コンテンツタイプの定義
@@ -40,18 +72,35 @@ impl Default for MainContentConfig {
             background_color: Color::rgb8(250, 250, 250),
             padding: 20.0,
             content_type: ContentType::FileExplorer,
+            search_indexer: None,
+            tags: None,
+            bookmarks: None,
+            history: None,
+            undo: None,
         }
     }
 }
 
 /// メインコンテンツコンポーネントを作成
+///
+/// ファイルエクスプローラーのタブとペインは `state_manager` の状態から復元する。
 pub fn main_content_component(
     config: MainContentConfig,
     settings: Rc<RefCell<Settings>>,
+    state_manager: StateManager,
 ) -> impl IntoView {
     container(match config.content_type {
         ContentType::Welcome => create_welcome_content().into_any(),
-        ContentType::FileExplorer => create_file_explorer_content(settings).into_any(),
+        ContentType::FileExplorer => create_file_explorer_content(
+            settings,
+            state_manager,
+            config.search_indexer,
+            config.tags,
+            config.bookmarks,
+            config.history,
+            config.undo,
+        )
+        .into_any(),
         ContentType::Error(message) => create_error_content(message).into_any(),
     })
     .style(move |s| {
@@ -62,8 +111,11 @@ pub fn main_content_component(
 }
 
 /// デフォルト設定でメインコンテンツコンポーネントを作成
-pub fn default_main_content(settings: Rc<RefCell<Settings>>) -> impl IntoView {
-    main_content_component(MainContentConfig::default(), settings)
+pub fn default_main_content(
+    settings: Rc<RefCell<Settings>>,
+    state_manager: StateManager,
+) -> impl IntoView {
+    main_content_component(MainContentConfig::default(), settings, state_manager)
 }
 
 /// ウェルカムコンテンツの作成
@@ -150,157 +202,479 @@ fn create_feature_item(title: &'static str, description: &'static str) -> impl I
     .style(|s| s.items_start())
 }
 
+/// 設定のデフォルトディレクトリ、なければ現在のディレクトリ（フォールバックは/home）
+pub(crate) fn default_directory(settings: &Settings) -> PathBuf {
+    settings
+        .default_directory
+        .clone()
+        .filter(|path| path.is_dir())
+        .unwrap_or_else(|| {
+            env::current_dir().unwrap_or_else(|_| {
+                env::var("HOME")
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|_| PathBuf::from("/"))
+            })
+        })
+}
+
 /// ファイルエクスプローラーコンテンツの作成
-fn create_file_explorer_content(_settings: Rc<RefCell<Settings>>) -> impl IntoView {
-    use floem::reactive::RwSignal;
-    use rust_explorer_core::FileNavigationManager;
-    use std::sync::Arc;
-
-    // 現在のディレクトリを取得（フォールバックは/home）
-    let current_dir = env::current_dir().unwrap_or_else(|_| {
-        env::var("HOME")
-            .map(PathBuf::from)
-            .unwrap_or_else(|_| PathBuf::from("/"))
+///
+/// 分割したペインのタブごとにファイル一覧を持ち、新しいタブは設定の
+/// デフォルトディレクトリで開く。Ctrl+P のクイックオープンで選んだ場所と、
+/// Ctrl+1〜9 やサイドバーで選んだブックマーク・最近の場所はアクティブなタブで開く。
+/// ファイル一覧にフォーカスがあるときは、Ctrl+Z で直前のファイル操作を元に戻し、
+/// Ctrl+Y か Ctrl+Shift+Z でやり直す。
+fn create_file_explorer_content(
+    settings: Rc<RefCell<Settings>>,
+    state_manager: StateManager,
+    search_indexer: Option<Arc<SearchIndexer>>,
+    tags: Option<TagsController>,
+    bookmarks: Option<BookmarksController>,
+    history: Option<HistoryController>,
+    undo: Option<UndoController>,
+) -> impl IntoView {
+    let controller = PanesController::new(
+        ReactiveStateManager::from_state_manager(state_manager.clone()),
+        move || default_directory(&settings.borrow()),
+    );
+    let navigators = TabNavigators::new();
+    let quick_open_visible = RwSignal::new(false);
+
+    let content_navigators = navigators.clone();
+    let shortcuts = FileListShortcuts {
+        bookmarks: bookmarks.clone(),
+        undo,
+    };
+    let content_indexer = search_indexer.clone();
+    let panes = pane_layout_view(controller.clone(), move |tabs, tab| {
+        create_tab_content(
+            tabs,
+            tab,
+            content_navigators.clone(),
+            content_indexer.clone(),
+            tags.clone(),
+            shortcuts.clone(),
+            history,
+        )
     });
 
+    let active_state = state_manager.clone();
+    let open_location = Rc::new(move |path: &Path, is_dir: bool| {
+        let active = active_state
+            .get_state()
+            .ok()
+            .and_then(|state| state.active_tab_id);
+        match active.and_then(|tab_id| navigators.get(&tab_id)) {
+            Some(navigator) => {
+                if let Err(e) = navigator.open(path, is_dir) {
+                    display_error_globally(&e);
+                }
+            }
+            // スマートフォルダやタグのタブは移動できないため新しいタブで開く
+            None => {
+                let Some(pane_id) = controller.focused_pane_id() else {
+                    return;
+                };
+                let tabs = controller.tabs(&pane_id);
+                match (is_dir, path.parent()) {
+                    (false, Some(parent)) => {
+                        tabs.open_tab(parent.to_path_buf(), vec![path.to_path_buf()])
+                    }
+                    _ => tabs.open_tab(path.to_path_buf(), Vec::new()),
+                }
+            }
+        }
+    });
+
+    // サイドバーやショートカットで選ばれたブックマークを開く
+    if let Some(bookmarks) = &bookmarks {
+        let requested = bookmarks.requested();
+        let open_location = open_location.clone();
+        create_effect(move |_| {
+            if let Some(bookmark) = requested.get() {
+                requested.set(None);
+                open_location(&bookmark.path, !bookmark.path.is_file());
+            }
+        });
+    }
+
+    // サイドバーの「最近」で選ばれた場所を開く
+    if let Some(history) = &history {
+        let requested = history.requested();
+        let open_location = open_location.clone();
+        create_effect(move |_| {
+            if let Some(entry) = requested.get() {
+                requested.set(None);
+                open_location(&entry.path, entry.kind == VisitKind::Directory);
+            }
+        });
+    }
+
+    let search_bookmarks = bookmarks.as_ref().map(BookmarksController::bookmarks);
+    let search_history = history.as_ref().map(HistoryController::history);
+    let popup = quick_open_popup(
+        quick_open_visible,
+        move || {
+            let bookmarks = search_bookmarks.map(|bookmarks| bookmarks.get_untracked());
+            let history = search_history.map(|history| history.get_untracked());
+            quick_open_candidates(&state_manager, bookmarks.as_ref(), history.as_ref())
+        },
+        search_indexer,
+        move |found| open_location(&found.path, found.is_dir),
+    );
+
+    stack((panes, popup))
+        .on_event_cont(EventListener::KeyDown, move |event| {
+            let Event::KeyDown(key_event) = event else {
+                return;
+            };
+            let key = &key_event.key.logical_key;
+            if is_quick_open_key(key, key_event.modifiers) {
+                quick_open_visible.set(true);
+            }
+        })
+        .style(|s| s.size_full())
+}
+
+/// ファイル一覧にフォーカスがあるときだけ受け付けるショートカット
+///
+/// 入力欄での Ctrl+Z や Ctrl+1〜9 がファイル操作やブックマークに届かないよう、
+/// ウィンドウ全体ではなく一覧のコンテナで受け取る。
+#[derive(Clone, Default)]
+struct FileListShortcuts {
+    /// Ctrl+1〜9 で開くブックマーク
+    bookmarks: Option<BookmarksController>,
+    /// Ctrl+Z などで元に戻す・やり直す履歴
+    undo: Option<UndoController>,
+}
+
+impl FileListShortcuts {
+    /// キー入力に対応する操作を行う
+    fn handle(&self, key: &Key, modifiers: Modifiers) {
+        if let Some(number) = bookmark_shortcut_for_key(key, modifiers)
+            && let Some(bookmarks) = &self.bookmarks
+        {
+            bookmarks.open_shortcut(number);
+        } else if let Some(command) = undo_command_for_key(key, modifiers)
+            && let Some(undo) = &self.undo
+        {
+            undo.run_in_background(command);
+        }
+    }
+}
+
+/// タブの内容の作成
+///
+/// タブに保存されたパス・履歴・ソートとフィルタ・選択・スクロール位置から
+/// 一覧を作り、変更をタブへ書き戻す。移動と開いたファイルは `history` に記録する。
+/// スマートフォルダのタブは検索結果（共有の索引が検索範囲を含めばそれを使う）を、
+/// タグのタブはタグの付いた項目を表示する。
+fn create_tab_content(
+    controller: TabsController,
+    tab: TabState,
+    navigators: TabNavigators,
+    search_indexer: Option<Arc<SearchIndexer>>,
+    tags: Option<TagsController>,
+    shortcuts: FileListShortcuts,
+    history: Option<HistoryController>,
+) -> AnyView {
+    use std::collections::HashSet;
+
+    let bookmarks = shortcuts.bookmarks.clone();
+    if tab.smart_folder.is_some() {
+        return smart_folder_content(controller, tab, search_indexer).into_any();
+    }
+    if tab.tag.is_some() {
+        return match tags {
+            Some(tags) => tag_content(controller, tab, tags).into_any(),
+            None => create_error_content("タグを利用できません".to_string()).into_any(),
+        };
+    }
+
+    let tab_id = tab.id.clone();
+
     // リアクティブな現在のパス
-    let current_path = RwSignal::new(current_dir.clone());
+    let current_path = RwSignal::new(tab.current_path.clone());
     let current_path_for_nav = current_path;
-    let current_path_for_reload = current_path;
 
-    // ファイルナビゲーションマネージャーを作成
-    let _nav_manager = Arc::new(FileNavigationManager::with_default());
-
-    // UI用のナビゲーションマネージャーを作成
+    // UI用のナビゲーションマネージャーを作成（タブの履歴を引き継ぐ）
     let ui_nav_manager = Arc::new(
-        super::FileNavigationManager::with_default(current_dir.clone())
+        super::FileNavigationManager::with_default(tab.current_path.clone())
+            .with_history(tab.history_back.clone(), tab.history_forward.clone())
             .on_path_change(move |new_path| {
+                if let Some(history) = history {
+                    history.record_directory(&new_path);
+                }
                 current_path_for_nav.set(new_path);
             })
+            .on_file_open(move |path| {
+                if let Some(history) = history {
+                    history.record_file(&path);
+                }
+            })
             .on_error(move |error| {
                 eprintln!("ナビゲーションエラー: {}", error);
             }),
     );
 
-    // ソート・フィルタマネージャーを作成
-    let sort_filter_manager = Arc::new(SortFilterUIManager::new().on_filter_change(move || {
-        // フィルタ変更時にファイルリストを再読み込み
-        // 実際の実装では、ここでファイルリストのリロードをトリガーする
-    }));
+    // 移動したらパスと履歴をタブへ書き戻す
+    let nav_state = ui_nav_manager.state_signal();
+    let nav_controller = controller.clone();
+    let nav_tab_id = tab_id.clone();
+    create_effect(move |previous: Option<PathBuf>| {
+        let state = nav_state.get();
+        if previous.is_some_and(|previous| previous != state.current_path) {
+            nav_controller.update_tab(&nav_tab_id, |tab| {
+                tab.name = TabState::name_for_path(&state.current_path);
+                tab.current_path = state.current_path.clone();
+                tab.history_back = state.history_back.clone();
+                tab.history_forward = state.history_forward.clone();
+                tab.selected_paths.clear();
+                tab.scroll_offset = 0.0;
+            });
+        }
+        state.current_path
+    });
+
+    // ソート・フィルタマネージャーを作成（変更はタブへ書き戻す）
+    let sort_filter_manager = Arc::new(SortFilterUIManager::with_config(
+        tab.sort,
+        tab.filter.clone(),
+    ));
+    let sort_filter_for_tab = sort_filter_manager.clone();
+    let sort_controller = controller.clone();
+    let sort_tab_id = tab_id.clone();
+    create_effect(move |previous: Option<()>| {
+        let sort = sort_filter_for_tab.current_sort_config();
+        let filter = sort_filter_for_tab.current_filter_criteria();
+        if previous.is_some() {
+            sort_controller.update_tab(&sort_tab_id, |tab| {
+                tab.sort = sort;
+                tab.filter = filter;
+            });
+        }
+    });
+
+    // 選択はタブへ書き戻す
+    let selection = RwSignal::new(tab.selected_paths.iter().cloned().collect::<HashSet<_>>());
+    let selection_controller = controller.clone();
+    let selection_tab_id = tab_id.clone();
+    create_effect(move |previous: Option<()>| {
+        let selected: Vec<PathBuf> =
+            selection.with(|selection| selection.iter().cloned().collect());
+        if previous.is_some() {
+            selection_controller.update_tab(&selection_tab_id, |tab| {
+                tab.selected_paths = selected;
+            });
+        }
+    });
+
+    // クイックオープンからこのタブを移動できるようにする
+    let navigator = TabNavigator::new(ui_nav_manager.clone(), selection);
+    navigators.register(&tab_id, navigator.clone());
+
+    // スクロール位置は頻繁に変わるため、タブを離れるときにだけ書き戻す
+    let scroll_offset = RwSignal::new(tab.scroll_offset);
+    let scroll_state = controller.state_manager().clone();
 
     let ui_nav_for_list = ui_nav_manager.clone();
     let sort_filter_for_list = sort_filter_manager.clone();
 
+    // 選択した項目へのタグの付け外し（タグを使わない場合は表示しない）
+    let tag_button = match &tags {
+        Some(tags) => {
+            let tags = tags.clone();
+            button(label(|| "タグ ▾"))
+                .popout_menu(move || {
+                    let paths = selection.with_untracked(|s| s.iter().cloned().collect());
+                    tag_menu(&tags, paths)
+                })
+                .style(|s| s.font_size(12.0))
+                .into_any()
+        }
+        None => empty().into_any(),
+    };
+    let tag_store = tags.as_ref().map(TagsController::store);
+
+    // 選択した項目（選択がなければ表示中のディレクトリ）をブックマークに登録
+    let bookmark_button = match &bookmarks {
+        Some(bookmarks) => {
+            let bookmarks = bookmarks.clone();
+            button(label(|| "☆ ブックマーク"))
+                .action(move || {
+                    let mut paths: Vec<PathBuf> =
+                        selection.with_untracked(|s| s.iter().cloned().collect());
+                    if paths.is_empty() {
+                        paths.push(current_path.get_untracked());
+                    }
+                    paths.sort();
+                    if let Err(e) = bookmarks.add(&paths, None) {
+                        display_error_globally(&e);
+                    }
+                })
+                .style(|s| s.font_size(12.0))
+                .into_any()
+        }
+        None => empty().into_any(),
+    };
+    let drag_paths = bookmarks.as_ref().map(BookmarksController::dragged);
+
+    // 内容の検索パネル（結果はファイルを開くか、別のタブで表示する）
+    let show_content_search = RwSignal::new(false);
+    let ui_nav_for_search = ui_nav_manager.clone();
+    let reveal_controller = controller.clone();
+    let content_search = dyn_container(
+        move || show_content_search.get(),
+        move |show| {
+            if !show {
+                return empty().into_any();
+            }
+            let nav_manager = ui_nav_for_search.clone();
+            let controller = reveal_controller.clone();
+            content_search_panel(
+                current_path,
+                move |found| nav_manager.handle_double_click(&found.entry),
+                move |found| {
+                    if let Some(parent) = found.entry.path.parent() {
+                        controller.open_tab(parent.to_path_buf(), vec![found.entry.path.clone()]);
+                    }
+                },
+            )
+            .style(|s| s.margin_bottom(8.0))
+            .into_any()
+        },
+    );
+
     v_stack((
         // ナビゲーションツールバー
         navigation_helpers::navigation_toolbar(ui_nav_manager.clone())
             .style(|s| s.margin_bottom(8.0)),
         // パンくずナビゲーション
         breadcrumb_view(current_path).style(|s| s.margin_bottom(8.0)),
-        // フィルタバー
-        simple_filter_bar(sort_filter_manager.clone()).style(|s| s.margin_bottom(8.0)),
-        // ファイルリストエリア
+        // フィルタバーと内容の検索の切り替え
+        h_stack((
+            simple_filter_bar(sort_filter_manager.clone()),
+            button(label(move || {
+                if show_content_search.get() {
+                    "内容の検索を閉じる"
+                } else {
+                    "内容を検索"
+                }
+            }))
+            .action(move || show_content_search.update(|show| *show = !*show))
+            .style(|s| s.font_size(12.0)),
+            tag_button,
+            bookmark_button,
+        ))
+        .style(|s| s.gap(8.0).items_center().margin_bottom(8.0)),
+        content_search,
+        // ファイルリストエリア（クリックでフォーカスし、ショートカットを受け取る）
         create_file_list_container_with_sort_filter(
-            current_path_for_reload,
+            current_path,
+            selection,
+            scroll_offset,
             ui_nav_for_list,
             sort_filter_for_list,
-        ),
+            tag_store,
+            drag_paths,
+        )
+        .keyboard_navigable()
+        .on_event_cont(EventListener::KeyDown, move |event| {
+            if let Event::KeyDown(key_event) = event {
+                shortcuts.handle(&key_event.key.logical_key, key_event.modifiers);
+            }
+        }),
     ))
+    .on_cleanup(move || {
+        navigators.unregister(&tab_id, &navigator);
+        // 閉じたタブの場合は書き戻し先がないため失敗しても無視する
+        let _ = scroll_state.update_tab(&tab_id, |tab| {
+            tab.scroll_offset = scroll_offset.get_untracked();
+        });
+    })
     .style(|s| s.size_full().gap(5.0))
-}
-
-/// 同期的にディレクトリを読み込み
-fn load_directory_sync(path: &PathBuf) -> Vec<rust_explorer_core::FileEntry> {
-    use rust_explorer_core::{FileEntry, FileType};
-    use std::fs;
-
-    match fs::read_dir(path) {
-        Ok(entries) => {
-            let mut file_entries = Vec::new();
-
-            for entry in entries.flatten() {
-                let entry_path = entry.path();
-                let metadata = entry.metadata().ok();
-
-                let file_type = if entry_path.is_dir() {
-                    FileType::Directory
-                } else if entry_path.is_symlink() {
-                    FileType::SymLink
-                } else if entry_path.is_file() {
-                    FileType::File
-                } else {
-                    FileType::Other
-                };
-
-                let size = metadata.as_ref().map(|m| m.len()).unwrap_or(0);
-                let modified = metadata.and_then(|m| m.modified().ok());
-
-                let file_entry = FileEntry {
-                    name: entry.file_name().to_string_lossy().to_string(),
-                    path: entry_path,
-                    file_type,
-                    size,
-                    modified,
-                };
-
-                file_entries.push(file_entry);
-            }
-
-            // ソートは後でソート・フィルタマネージャーが行うため、ここでは削除
-
-            file_entries
-        }
-        Err(_) => Vec::new(),
-    }
+    .into_any()
 }
 
 /// ソート・フィルタ機能付きファイルリストコンテナの作成
 fn create_file_list_container_with_sort_filter(
     current_path: RwSignal<PathBuf>,
+    selection: RwSignal<std::collections::HashSet<PathBuf>>,
+    scroll_offset: RwSignal<f64>,
     nav_manager: std::sync::Arc<super::FileNavigationManager>,
     sort_filter_manager: std::sync::Arc<SortFilterUIManager>,
+    tags: Option<RwSignal<rust_explorer_core::TagStore>>,
+    drag_paths: Option<RwSignal<Vec<PathBuf>>>,
 ) -> impl IntoView {
     use floem::reactive::{RwSignal, create_effect};
-    use floem::views::{Decorators, dyn_stack, scroll};
     use rust_explorer_core::FileEntry;
+    use std::collections::HashSet;
 
     let entries = RwSignal::new(Vec::<FileEntry>::new());
-    let selected_indices = RwSignal::new(Vec::<usize>::new());
+    let watch = global_directory_watcher()
+        .zip(directory_updates_signal())
+        .map(|(watcher, updates)| {
+            let sort_filter_manager = sort_filter_manager.clone();
+
+            // 表示中のディレクトリの変更は再読み込みせず差分で反映
+            create_effect(move |_| {
+                updates.with(|delta| {
+                    if let Some(delta) = delta
+                        && current_path.with_untracked(|path| *path == delta.directory)
+                    {
+                        entries.update(|entries| {
+                            delta.apply_to(entries);
+                            sort_filter_manager.process_entries(entries);
+                        });
+                        entries.with_untracked(|entries| {
+                            selection
+                                .update(|selection| reconcile_selection(selection, entries, delta));
+                        });
+                    }
+                });
+            });
+
+            Rc::new(RefCell::new(DirectoryWatch::new(watcher)))
+        });
+
+    // パスが変わったら選択をクリア（タブから復元した初回の選択は残す）
+    create_effect(move |previous: Option<PathBuf>| {
+        let path = current_path.get();
+        if previous.is_some_and(|previous| previous != path) {
+            selection.set(HashSet::new());
+        }
+        path
+    });
 
-    // パス変更時にファイルリストを再読み込み
+    // パスやソート・フィルタの変更時にファイルリストを段階的に再読み込みし、監視対象を切り替え
+    let stream = Rc::new(RefCell::new(None));
     create_effect(move |_| {
         let path = current_path.get();
-        let mut file_entries = load_directory_sync(&path);
-        sort_filter_manager.process_entries(&mut file_entries);
-        entries.set(file_entries);
+        sort_filter_manager.current_sort_config();
+        sort_filter_manager.current_filter_criteria();
+        let sort_filter_manager = sort_filter_manager.clone();
+        *stream.borrow_mut() = stream_directory(&path, entries, move |entries| {
+            sort_filter_manager.process_entries(entries)
+        });
+        if let Some(watch) = &watch {
+            watch.borrow_mut().retarget(&path);
+        }
     });
 
-    container(
-        scroll(dyn_stack(
-            move || entries.get(),
-            |entry| entry.name.clone(),
-            move |entry| {
-                let index = entries
-                    .get()
-                    .iter()
-                    .position(|e| e.name == entry.name)
-                    .unwrap_or(0);
-                let is_selected = selected_indices.get().contains(&index);
-
-                // モダンファイルアイテムを作成
-                let nav_manager_clone = nav_manager.clone();
-                modern_file_item_with_double_click(
-                    entry,
-                    is_selected,
-                    ModernFileItemConfig::default(),
-                    move |entry| {
-                        nav_manager_clone.handle_double_click(&entry);
-                    },
-                )
-            },
-        ))
-        .style(|s| s.flex_col().gap(1)),
-    )
+    // 表示範囲の行だけ作成する仮想化リスト
+    container(virtual_file_list_with_scroll(
+        entries,
+        selection,
+        scroll_offset,
+        ModernFileItemConfig {
+            tags,
+            drag_paths,
+            ..ModernFileItemConfig::default()
+        },
+        move |entry| {
+            nav_manager.handle_double_click(&entry);
+        },
+    ))
     .style(|s| {
         s.size_full()
             .border(1.0)
@@ -353,6 +727,11 @@ mod tests {
             background_color: Color::rgb8(255, 255, 255),
             padding: 30.0,
             content_type: ContentType::FileExplorer,
+            search_indexer: None,
+            tags: None,
+            bookmarks: None,
+            history: None,
+            undo: None,
         };
         assert_eq!(config.padding, 30.0);
         matches!(config.content_type, ContentType::FileExplorer);
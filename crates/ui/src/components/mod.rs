@@ -2,42 +2,63 @@
 //!
 //! 再利用可能なUIコンポーネントを含みます。
 
+pub mod bookmarks;
 pub mod breadcrumb;
+pub mod content_search;
 pub mod error_dialog;
 pub mod file_item;
 pub mod file_list;
 pub mod file_navigation;
 pub mod header;
+pub mod history;
+pub mod job_panel;
 pub mod main_content;
 pub mod modern_file_item;
 pub mod modern_header;
 pub mod modern_sidebar;
+pub mod pane;
+pub mod quick_open;
+pub mod recovery;
+pub mod smart_folders;
 pub mod sort_filter;
 pub mod status_bar;
-
-// 将来のコンポーネント用のモジュール宣言
-// pub mod tabs;
-// pub mod pane;
+pub mod tabs;
+pub mod tags;
+pub mod undo;
+pub mod user_data;
+pub mod virtual_file_list;
+pub mod workspace;
 
 // 公開API
+pub use bookmarks::{BookmarksController, bookmark_shortcut_for_key, bookmark_shortcut_label};
 pub use breadcrumb::{
     BreadcrumbConfig, BreadcrumbItem, BreadcrumbNavigation, breadcrumb_navigation, breadcrumb_view,
     default_breadcrumb_navigation,
 };
+pub use content_search::{
+    ContentSearchState, content_search_panel, format_match_location, format_search_progress,
+    split_globs, start_content_search,
+};
 pub use error_dialog::{
     ErrorAction, ErrorActionType, ErrorDisplayInfo, ErrorDisplayManager, display_error_globally,
     error_dialog_component, error_display_area, global_error_manager,
 };
 pub use file_item::{file_item_component, file_item_view, file_item_with_double_click};
 pub use file_list::{
-    FileListConfig, FileListState, FileListView, default_file_list_view, file_list_view,
-    file_list_view_component,
+    DirectoryWatch, FileListConfig, FileListState, FileListView, default_file_list_view,
+    directory_updates_signal, file_list_view, file_list_view_component, global_directory_watcher,
+    global_event_manager, stream_directory,
 };
 pub use file_navigation::{
     FileNavigationConfig, FileNavigationManager, FileNavigationState, navigation_helpers,
     with_double_click_handler,
 };
 pub use header::{HeaderConfig, default_header, header_component};
+pub use history::{HistoryController, RECENT_SIDEBAR_LIMIT};
+pub use job_panel::{
+    JobPanelConfig, default_job_panel, format_job_line, global_job_manager, job_events_signal,
+    job_infos_signal, job_panel,
+};
 pub use main_content::{
     ContentType, MainContentConfig, default_main_content, main_content_component,
 };
@@ -52,11 +73,39 @@ pub use modern_sidebar::{
     ModernSidebar, ModernSidebarConfig, SidebarItem, SidebarItemType, SidebarSection,
     default_modern_sidebar, modern_sidebar_component,
 };
+pub use pane::{
+    PaneCommand, PaneTransfer, PanesController, pane_command_for_key, pane_layout_view,
+    pane_transfer_job,
+};
+pub use quick_open::{
+    TabNavigator, TabNavigators, describe_quick_open_match, is_quick_open_key,
+    quick_open_candidates, quick_open_matches, quick_open_popup, quick_open_source_label,
+};
+pub use recovery::{
+    RecoveryChoice, RecoveryOption, RecoveryReason, SessionRecovery, describe_backup,
+    describe_changes, recovery_screen,
+};
+pub use smart_folders::{
+    SmartFolderDraft, SmartFoldersController, format_smart_folder_status, smart_folder_content,
+    smart_folder_editor,
+};
 pub use sort_filter::{
     SortFilterConfig, SortFilterUIManager, filter_toolbar, simple_filter_bar, sort_filter_toolbar,
     sort_toolbar,
 };
 pub use status_bar::{
     StatusBarConfig, StatusInfo, StatusType, add_status_item, create_status_info,
-    default_status_bar, file_explorer_status_bar, status_bar_component,
+    default_status_bar, file_explorer_status_bar, job_status_message, status_bar_component,
+    status_bar_with_jobs,
+};
+pub use tabs::{
+    TabCommand, TabsController, tab_command_for_key, tab_strip, tabbed_view, wrapped_index,
+};
+pub use tags::{TagsController, tag_chips, tag_color, tag_content, tag_menu};
+pub use undo::{UndoCommand, UndoController, undo_command_for_key};
+pub use user_data::{flush_user_data, follow_job_moves};
+pub use virtual_file_list::{
+    FileCell, FileRow, FileRows, GRID_CELL_SIZE, dragged_paths, grid_columns, reconcile_selection,
+    row_height, virtual_file_list, virtual_file_list_with_scroll,
 };
+pub use workspace::workspace_bar;
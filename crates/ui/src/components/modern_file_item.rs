@@ -2,11 +2,14 @@
 //!
 //! Files CommunityとLapceにインスパイアされたモダンなファイル表示
 
+use super::tags::tag_chips;
 use crate::theme::get_theme;
-use floem::IntoView;
 use floem::peniko::Color;
-use floem::views::{Decorators, container, h_stack, label, svg, text, v_stack};
-use rust_explorer_core::{FileEntry, FileType};
+use floem::reactive::RwSignal;
+use floem::views::{Decorators, container, empty, h_stack, label, svg, text, v_stack};
+use floem::{AnyView, IntoView};
+use rust_explorer_core::{FileEntry, FileType, TagStore};
+use std::path::PathBuf;
 use std::time::SystemTime;
 
 /// ファイルアイテムの表示モード
@@ -33,6 +36,10 @@ pub struct ModernFileItemConfig {
     pub show_details: bool,
     /// ホバーエフェクトを有効にするか
     pub enable_hover: bool,
+    /// 名前の横に色のチップで表示するタグ
+    pub tags: Option<RwSignal<TagStore>>,
+    /// ドラッグした項目を知らせる先（設定すると項目をドラッグできる）
+    pub drag_paths: Option<RwSignal<Vec<PathBuf>>>,
 }
 
 impl Default for ModernFileItemConfig {
@@ -43,6 +50,8 @@ impl Default for ModernFileItemConfig {
             show_selection: true,
             show_details: true,
             enable_hover: true,
+            tags: None,
+            drag_paths: None,
         }
     }
 }
@@ -109,6 +118,8 @@ fn create_list_item(
                         .flex()
                         .min_width(0.0) // Allow text truncation
                 }),
+                // タグ
+                item_tag_chips(&config, &entry_details),
                 // 詳細情報
                 if config.show_details {
                     h_stack((
@@ -215,6 +226,8 @@ fn create_grid_item(
                     .line_height(theme.typography.line_height_tight)
                 // .max_lines(2) // floem 0.2 では利用不可
             }),
+            // タグ
+            item_tag_chips(&config, &entry_details),
             // 詳細情報（オプション）
             if config.show_details && entry_details.file_type == FileType::File {
                 label(move || format_file_size(entry_details.size))
@@ -288,6 +301,8 @@ fn create_compact_item(
                     .flex()
                     .min_width(0.0)
             }),
+            // タグ
+            item_tag_chips(&config, &entry_icon),
         ))
         .style(move |s| {
             let theme_arc = get_theme();
@@ -316,6 +331,14 @@ fn create_compact_item(
     })
 }
 
+/// 項目に付いたタグのチップ（タグを表示しない設定なら空）
+fn item_tag_chips(config: &ModernFileItemConfig, entry: &FileEntry) -> AnyView {
+    match config.tags {
+        Some(tags) => tag_chips(tags, entry.path.clone()).into_any(),
+        None => empty().into_any(),
+    }
+}
+
 /// モダンなファイルアイコンを作成
 fn create_modern_file_icon(entry: &FileEntry, size: f32) -> impl IntoView + use<> {
     let (icon_svg, icon_color) = get_file_icon_and_color(entry);
@@ -507,7 +530,6 @@ pub fn default_modern_file_item(entry: FileEntry, selected: bool) -> impl IntoVi
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::PathBuf;
 
     #[test]
     fn test_modern_file_item_config() {
@@ -517,6 +539,8 @@ mod tests {
         assert!(config.show_selection);
         assert!(config.show_details);
         assert!(config.enable_hover);
+        assert!(config.tags.is_none());
+        assert!(config.drag_paths.is_none());
     }
 
     #[test]
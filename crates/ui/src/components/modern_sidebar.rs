@@ -2,11 +2,27 @@
 //!
 //! Files CommunityとLapceにインスパイアされたモダンなサイドバー
 
+use super::bookmarks::{BookmarksController, bookmark_shortcut_label};
+use super::error_dialog::display_error_globally;
+use super::history::{HistoryController, RECENT_SIDEBAR_LIMIT};
+use super::job_panel::global_job_manager;
+use super::smart_folders::{SmartFolderDraft, SmartFoldersController, smart_folder_editor};
+use super::tags::{TagsController, tag_color};
 use crate::theme::get_theme;
 use floem::IntoView;
+use floem::event::{Event, EventListener};
+use floem::ext_event::create_ext_action;
+use floem::keyboard::{Key, NamedKey};
+use floem::menu::{Menu, MenuItem};
 use floem::peniko::Color;
-use floem::reactive::{RwSignal, SignalGet, SignalUpdate};
-use floem::views::{Decorators, button, container, h_stack, label, scroll, svg, text, v_stack};
+use floem::reactive::{RwSignal, Scope, SignalGet, SignalUpdate, SignalWith};
+use floem::views::{
+    Decorators, button, container, dyn_container, empty, h_stack, h_stack_from_iter, label, scroll,
+    svg, text, text_input, v_stack, v_stack_from_iter,
+};
+use rust_explorer_core::{
+    Bookmark, BookmarkEntry, BookmarkFolder, HistoryEntry, SmartFolder, Tag, TagColor, VisitKind,
+};
 use std::path::PathBuf;
 use std::sync::Arc;
 
@@ -46,6 +62,99 @@ pub enum SidebarItemType {
     Folder,
     /// タグ
     Tag,
+    /// ゴミ箱
+    Trash,
+    /// スマートフォルダ（保存した検索）
+    SmartFolder,
+}
+
+/// スマートフォルダのアイコン
+const SMART_FOLDER_ICON: &str = r#"<svg viewBox="0 0 24 24" fill="currentColor">
+    <path d="M11.5,13A2.5,2.5 0 0,0 14,10.5A2.5,2.5 0 0,0 11.5,8A2.5,2.5 0 0,0 9,10.5A2.5,2.5 0 0,0 11.5,13M11.5,6A4.5,4.5 0 0,1 16,10.5C16,11.38 15.75,12.2 15.31,12.9L18.39,16L17,17.39L13.88,14.32C13.19,14.75 12.37,15 11.5,15A4.5,4.5 0 0,1 7,10.5A4.5,4.5 0 0,1 11.5,6M20,4H4A2,2 0 0,0 2,6V18A2,2 0 0,0 4,20H20A2,2 0 0,0 22,18V6A2,2 0 0,0 20,4Z"/>
+</svg>"#;
+
+impl From<&SmartFolder> for SidebarItem {
+    fn from(folder: &SmartFolder) -> Self {
+        Self {
+            id: folder.id.clone(),
+            label: folder.name.clone(),
+            icon: SMART_FOLDER_ICON.to_string(),
+            path: Some(folder.scope.clone()),
+            item_type: SidebarItemType::SmartFolder,
+            selected: false,
+            badge_count: None,
+        }
+    }
+}
+
+/// ブックマークのアイコン
+const BOOKMARK_ICON: &str = r#"<svg viewBox="0 0 24 24" fill="currentColor">
+    <path d="M12,17.27L18.18,21L16.54,13.97L22,9.24L14.81,8.62L12,2L9.19,8.62L2,9.24L7.45,13.97L5.82,21L12,17.27Z"/>
+</svg>"#;
+
+impl From<&Bookmark> for SidebarItem {
+    fn from(bookmark: &Bookmark) -> Self {
+        Self {
+            id: bookmark.id.clone(),
+            label: bookmark.name.clone(),
+            icon: BOOKMARK_ICON.to_string(),
+            path: Some(bookmark.path.clone()),
+            item_type: SidebarItemType::Favorite,
+            selected: false,
+            badge_count: None,
+        }
+    }
+}
+
+/// 最近の場所のアイコン（ディレクトリ）
+const RECENT_FOLDER_ICON: &str = r#"<svg viewBox="0 0 24 24" fill="currentColor">
+    <path d="M10,4H4C2.89,4 2,4.89 2,6V18A2,2 0 0,0 4,20H20A2,2 0 0,0 22,18V8C22,6.89 21.1,6 20,6H12L10,4Z"/>
+</svg>"#;
+
+/// 最近の場所のアイコン（ファイル）
+const RECENT_FILE_ICON: &str = r#"<svg viewBox="0 0 24 24" fill="currentColor">
+    <path d="M14,2H6A2,2 0 0,0 4,4V20A2,2 0 0,0 6,22H18A2,2 0 0,0 20,20V8L14,2M18,20H6V4H13V9H18V20Z"/>
+</svg>"#;
+
+impl From<&HistoryEntry> for SidebarItem {
+    fn from(entry: &HistoryEntry) -> Self {
+        let icon = match entry.kind {
+            VisitKind::Directory => RECENT_FOLDER_ICON,
+            VisitKind::File => RECENT_FILE_ICON,
+        };
+        Self {
+            id: entry.path.display().to_string(),
+            label: entry
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| entry.path.display().to_string()),
+            icon: icon.to_string(),
+            path: Some(entry.path.clone()),
+            item_type: SidebarItemType::Recent,
+            selected: false,
+            badge_count: None,
+        }
+    }
+}
+
+/// タグのアイコン
+const TAG_ICON: &str = r#"<svg viewBox="0 0 24 24" fill="currentColor">
+    <path d="M5.5,7A1.5,1.5 0 0,1 4,5.5A1.5,1.5 0 0,1 5.5,4A1.5,1.5 0 0,1 7,5.5A1.5,1.5 0 0,1 5.5,7M21.41,11.58L12.41,2.58C12.05,2.22 11.55,2 11,2H4C2.89,2 2,2.89 2,4V11C2,11.55 2.22,12.05 2.59,12.41L11.58,21.41C11.95,21.77 12.45,22 13,22C13.55,22 14.05,21.77 14.41,21.41L21.41,14.41C21.78,14.05 22,13.55 22,13C22,12.44 21.77,11.94 21.41,11.58Z"/>
+</svg>"#;
+
+impl From<&Tag> for SidebarItem {
+    fn from(tag: &Tag) -> Self {
+        Self {
+            id: tag.name.clone(),
+            label: tag.name.clone(),
+            icon: TAG_ICON.to_string(),
+            path: None,
+            item_type: SidebarItemType::Tag,
+            selected: false,
+            badge_count: None,
+        }
+    }
 }
 
 /// モダンサイドバーの設定
@@ -84,11 +193,17 @@ pub struct ModernSidebar {
     sections: RwSignal<Vec<SidebarSection>>,
     visible: RwSignal<bool>,
     width: RwSignal<f32>,
+    smart_folders: Option<SmartFoldersController>,
+    tags: Option<TagsController>,
+    bookmarks: Option<BookmarksController>,
+    history: Option<HistoryController>,
 }
 
 impl ModernSidebar {
     /// 新しいモダンサイドバーを作成
     pub fn new(config: ModernSidebarConfig) -> Self {
+        let trash_manager = global_job_manager().trash_manager();
+
         let sections = vec![
             // クイックアクセスセクション
             SidebarSection {
@@ -160,24 +275,34 @@ impl ModernSidebar {
                         selected: false,
                         badge_count: None,
                     },
+                    SidebarItem {
+                        id: "trash".to_string(),
+                        label: "ゴミ箱".to_string(),
+                        icon: r#"<svg viewBox="0 0 24 24" fill="currentColor">
+                            <path d="M19,4H15.5L14.5,3H9.5L8.5,4H5V6H19M6,19A2,2 0 0,0 8,21H16A2,2 0 0,0 18,19V7H6V19Z"/>
+                        </svg>"#.to_string(),
+                        path: Some(trash_manager.home_trash().join("files")),
+                        item_type: SidebarItemType::Trash,
+                        selected: false,
+                        badge_count: None,
+                    },
                 ],
             },
-
-            // お気に入りセクション
-            SidebarSection {
-                title: "お気に入り".to_string(),
-                collapsible: true,
-                collapsed: false,
-                items: vec![],
-            },
         ];
 
-        Self {
+        let sidebar = Self {
             sections: RwSignal::new(sections),
             visible: RwSignal::new(config.initially_visible),
             width: RwSignal::new(config.width),
+            smart_folders: None,
+            tags: None,
+            bookmarks: None,
+            history: None,
             config,
-        }
+        };
+        // ゴミ箱の中身が多いと数えるのに時間がかかるため、バッジは数え終わってから出す
+        sidebar.refresh_trash_count();
+        sidebar
     }
 
     /// デフォルト設定でモダンサイドバーを作成
@@ -185,6 +310,58 @@ impl ModernSidebar {
         Self::new(ModernSidebarConfig::default())
     }
 
+    /// スマートフォルダのセクションを表示する
+    pub fn with_smart_folders(mut self, controller: SmartFoldersController) -> Self {
+        self.smart_folders = Some(controller);
+        self
+    }
+
+    /// タグのセクションを表示する
+    pub fn with_tags(mut self, controller: TagsController) -> Self {
+        self.tags = Some(controller);
+        self
+    }
+
+    /// お気に入り（ブックマーク）のセクションを表示する
+    pub fn with_bookmarks(mut self, controller: BookmarksController) -> Self {
+        self.bookmarks = Some(controller);
+        self
+    }
+
+    /// 最近の場所のセクションを表示する
+    pub fn with_history(mut self, controller: HistoryController) -> Self {
+        self.history = Some(controller);
+        self
+    }
+
+    /// アイテムを開く（スマートフォルダとタグは新しいタブで、ブックマークと
+    /// 最近の場所はアクティブなタブで開く）
+    pub fn open_item(&self, item: &SidebarItem) {
+        match item.item_type {
+            SidebarItemType::Favorite => {
+                if let Some(controller) = &self.bookmarks {
+                    controller.open(&item.id);
+                }
+            }
+            SidebarItemType::Recent => {
+                if let (Some(controller), Some(path)) = (&self.history, &item.path) {
+                    controller.open(path);
+                }
+            }
+            SidebarItemType::SmartFolder => {
+                if let Some(controller) = &self.smart_folders {
+                    controller.open(&item.id);
+                }
+            }
+            SidebarItemType::Tag => {
+                if let Some(controller) = &self.tags {
+                    controller.open(&item.id);
+                }
+            }
+            _ => {}
+        }
+    }
+
     /// サイドバーの表示/非表示を切り替え
     pub fn toggle_visibility(&self) {
         self.visible.update(|visible| *visible = !*visible);
@@ -207,6 +384,23 @@ impl ModernSidebar {
         });
     }
 
+    /// ゴミ箱の項目数バッジを更新
+    pub fn set_trash_count(&self, count: usize) {
+        set_trash_badge(self.sections, count);
+    }
+
+    /// ゴミ箱の中身を別スレッドで数え直し、数え終わったらバッジを更新
+    pub fn refresh_trash_count(&self) {
+        let sections = self.sections;
+        let show = create_ext_action(Scope::current(), move |count| {
+            set_trash_badge(sections, count);
+        });
+        std::thread::spawn(move || {
+            let manager = global_job_manager();
+            show(manager.block_on(manager.trash_manager().count()));
+        });
+    }
+
     /// セクションの折りたたみ状態を切り替え
     pub fn toggle_section(&self, section_title: String) {
         self.sections.update(|sections| {
@@ -225,6 +419,26 @@ impl ModernSidebar {
         let width = self.width;
         let _sections = self.sections;
         let sidebar_self = Arc::new(self);
+        let smart_folder_section = match sidebar_self.smart_folders.clone() {
+            Some(controller) => {
+                create_smart_folder_section(controller, sidebar_self.clone()).into_any()
+            }
+            None => empty().into_any(),
+        };
+        let bookmark_section = match sidebar_self.bookmarks.clone() {
+            Some(controller) => {
+                create_bookmark_section(controller, sidebar_self.clone()).into_any()
+            }
+            None => empty().into_any(),
+        };
+        let recent_section = match sidebar_self.history {
+            Some(controller) => create_recent_section(controller, sidebar_self.clone()).into_any(),
+            None => empty().into_any(),
+        };
+        let tag_section = match sidebar_self.tags.clone() {
+            Some(controller) => create_tag_section(controller, sidebar_self.clone()).into_any(),
+            None => empty().into_any(),
+        };
 
         container(if visible.get() {
             container(scroll(
@@ -232,15 +446,21 @@ impl ModernSidebar {
                     // サイドバーヘッダー
                     create_sidebar_header(),
                     // セクションリスト（簡略版 - 初期セクションのみ）
-                    v_stack((create_sidebar_section(
-                        SidebarSection {
-                            title: "クイックアクセス".to_string(),
-                            collapsible: false,
-                            collapsed: false,
-                            items: vec![],
-                        },
-                        sidebar_self.clone(),
-                    ),))
+                    v_stack((
+                        create_sidebar_section(
+                            SidebarSection {
+                                title: "クイックアクセス".to_string(),
+                                collapsible: false,
+                                collapsed: false,
+                                items: vec![],
+                            },
+                            sidebar_self.clone(),
+                        ),
+                        bookmark_section,
+                        recent_section,
+                        smart_folder_section,
+                        tag_section,
+                    ))
                     .style(move |s| {
                         let theme_arc = get_theme();
                         let theme = theme_arc.read().unwrap();
@@ -404,11 +624,593 @@ fn create_section_header(
     }
 }
 
+/// お気に入り（ブックマーク）のセクションを作成
+///
+/// ファイル一覧の項目をセクションへドロップすると登録し、フォルダの行へ
+/// ドロップするとそのフォルダに登録する。右クリックで名前の変更とフォルダの移動ができる。
+fn create_bookmark_section(
+    controller: BookmarksController,
+    sidebar: Arc<ModernSidebar>,
+) -> impl IntoView {
+    let bookmarks = controller.bookmarks();
+    let dragged = controller.dragged();
+    let adding_folder = RwSignal::new(false);
+    let folder_name = RwSignal::new(String::new());
+    let renaming = RwSignal::new(None::<String>);
+    let error = RwSignal::new(None::<String>);
+    let folder_controller = controller.clone();
+    let drop_controller = controller.clone();
+
+    let add_folder = move || match folder_controller.add_folder(&folder_name.get_untracked()) {
+        Ok(()) => {
+            folder_name.set(String::new());
+            error.set(None);
+            adding_folder.set(false);
+        }
+        Err(e) => error.set(Some(e.to_string())),
+    };
+
+    v_stack((
+        h_stack((
+            label(|| "お気に入り").style(move |s| {
+                let theme_arc = get_theme();
+                let theme = theme_arc.read().unwrap();
+                s.font_size(theme.typography.label_large)
+                    .font_weight(floem::text::Weight::MEDIUM)
+                    .color(theme.colors.on_surface_variant)
+                    .flex_grow(1.0)
+            }),
+            button(label(|| "＋フォルダ"))
+                .action(move || adding_folder.update(|adding| *adding = !*adding))
+                .style(|s| s.font_size(12.0)),
+        ))
+        .style(move |s| {
+            let theme_arc = get_theme();
+            let theme = theme_arc.read().unwrap();
+            s.width_full().items_center().padding_vert(theme.spacing.sm)
+        }),
+        // フォルダの追加フォーム
+        h_stack((
+            text_input(folder_name)
+                .placeholder("フォルダ名")
+                .style(|s| s.flex_grow(1.0).min_width(0.0).font_size(12.0)),
+            button(label(|| "追加")).action(add_folder),
+        ))
+        .style(move |s| {
+            s.width_full()
+                .gap(4.0)
+                .items_center()
+                .apply_if(!adding_folder.get(), |s| s.hide())
+        }),
+        label(move || error.get().unwrap_or_default()).style(move |s| {
+            s.font_size(11.0)
+                .color(Color::rgb8(220, 53, 69))
+                .apply_if(error.with(Option::is_none), |s| s.hide())
+        }),
+        label(|| "ファイル一覧からドラッグして追加").style(move |s| {
+            s.font_size(11.0)
+                .color(Color::rgb8(107, 114, 128))
+                .apply_if(
+                    bookmarks.with(|b| !b.entries().is_empty()) && dragged.with(Vec::is_empty),
+                    |s| s.hide(),
+                )
+        }),
+        dyn_container(
+            move || bookmarks.get(),
+            move |bookmarks| {
+                let controller = controller.clone();
+                let sidebar = sidebar.clone();
+                let folders: Vec<(String, String)> = bookmarks
+                    .entries()
+                    .iter()
+                    .filter_map(|entry| match entry {
+                        BookmarkEntry::Folder(folder) => {
+                            Some((folder.id.clone(), folder.name.clone()))
+                        }
+                        BookmarkEntry::Bookmark(_) => None,
+                    })
+                    .collect();
+                let rows: Vec<_> = bookmarks
+                    .entries()
+                    .iter()
+                    .map(|entry| match entry {
+                        BookmarkEntry::Bookmark(bookmark) => create_bookmark_row(
+                            bookmark.clone(),
+                            bookmarks.shortcut_number(&bookmark.id),
+                            controller.clone(),
+                            sidebar.clone(),
+                            folders.clone(),
+                            renaming,
+                            false,
+                        )
+                        .into_any(),
+                        BookmarkEntry::Folder(folder) => create_bookmark_folder(
+                            folder.clone(),
+                            &bookmarks,
+                            controller.clone(),
+                            sidebar.clone(),
+                            folders.clone(),
+                            renaming,
+                        )
+                        .into_any(),
+                    })
+                    .collect();
+                v_stack_from_iter(rows).style(|s| s.width_full()).into_any()
+            },
+        ),
+    ))
+    .on_event_stop(EventListener::Drop, move |_| {
+        drop_controller.add_dragged(None)
+    })
+    .style(move |s| {
+        let theme_arc = get_theme();
+        let theme = theme_arc.read().unwrap();
+        s.width_full()
+            .gap(theme.spacing.xs)
+            .border_radius(theme.border_radius.sm)
+            .apply_if(!dragged.with(Vec::is_empty), |s| {
+                s.background(theme.colors.hover)
+            })
+    })
+}
+
+/// ブックマークのフォルダ（見出しと中のブックマーク）
+fn create_bookmark_folder(
+    folder: BookmarkFolder,
+    bookmarks: &rust_explorer_core::Bookmarks,
+    controller: BookmarksController,
+    sidebar: Arc<ModernSidebar>,
+    folders: Vec<(String, String)>,
+    renaming: RwSignal<Option<String>>,
+) -> impl IntoView {
+    let rows: Vec<_> = folder
+        .bookmarks
+        .iter()
+        .map(|bookmark| {
+            create_bookmark_row(
+                bookmark.clone(),
+                bookmarks.shortcut_number(&bookmark.id),
+                controller.clone(),
+                sidebar.clone(),
+                folders.clone(),
+                renaming,
+                true,
+            )
+        })
+        .collect();
+    let (up_id, down_id, remove_id, menu_id, drop_id, editing_id) = (
+        folder.id.clone(),
+        folder.id.clone(),
+        folder.id.clone(),
+        folder.id.clone(),
+        folder.id.clone(),
+        folder.id.clone(),
+    );
+    let (up, down, remove, rename, drop_target) = (
+        controller.clone(),
+        controller.clone(),
+        controller.clone(),
+        controller.clone(),
+        controller,
+    );
+    let name = dyn_container(
+        move || renaming.with(|id| id.as_deref() == Some(editing_id.as_str())),
+        move |editing| {
+            if editing {
+                rename_input(
+                    folder.id.clone(),
+                    folder.name.clone(),
+                    renaming,
+                    rename.clone(),
+                )
+                .into_any()
+            } else {
+                let display = format!("📁 {}", folder.name);
+                label(move || display.clone()).into_any()
+            }
+        },
+    );
+
+    v_stack((
+        h_stack((
+            container(name).style(|s| s.flex_grow(1.0).min_width(0.0).padding_horiz(8.0)),
+            button(label(|| "↑")).action(move || up.move_by(&up_id, -1)),
+            button(label(|| "↓")).action(move || down.move_by(&down_id, 1)),
+            button(label(|| "削除")).action(move || remove.remove(&remove_id)),
+        ))
+        .on_event_stop(EventListener::Drop, move |_| {
+            drop_target.add_dragged(Some(&drop_id));
+        })
+        .context_menu(move || {
+            let rename_id = menu_id.clone();
+            Menu::new("").entry(
+                MenuItem::new("名前を変更").action(move || renaming.set(Some(rename_id.clone()))),
+            )
+        })
+        .style(|s| s.width_full().items_center().gap(2.0).font_size(11.0)),
+        v_stack_from_iter(rows).style(|s| s.width_full().padding_left(12.0)),
+    ))
+    .style(|s| s.width_full())
+}
+
+/// ブックマークの1行（開く・ショートカット・上へ・下へ・削除、右クリックで名前の変更と移動）
+fn create_bookmark_row(
+    bookmark: Bookmark,
+    shortcut: Option<usize>,
+    controller: BookmarksController,
+    sidebar: Arc<ModernSidebar>,
+    folders: Vec<(String, String)>,
+    renaming: RwSignal<Option<String>>,
+    in_folder: bool,
+) -> impl IntoView {
+    let (up_id, down_id, remove_id, menu_id) = (
+        bookmark.id.clone(),
+        bookmark.id.clone(),
+        bookmark.id.clone(),
+        bookmark.id.clone(),
+    );
+    let (up, down, remove, rename, menu) = (
+        controller.clone(),
+        controller.clone(),
+        controller.clone(),
+        controller.clone(),
+        controller,
+    );
+    let item = SidebarItem::from(&bookmark);
+    let row = dyn_container(
+        move || renaming.with(|id| id.as_deref() == Some(bookmark.id.as_str())),
+        move |editing| {
+            if editing {
+                rename_input(
+                    item.id.clone(),
+                    item.label.clone(),
+                    renaming,
+                    rename.clone(),
+                )
+                .into_any()
+            } else {
+                create_sidebar_item(item.clone(), sidebar.clone()).into_any()
+            }
+        },
+    );
+
+    h_stack((
+        container(row).style(|s| s.flex_grow(1.0).min_width(0.0)),
+        label(move || bookmark_shortcut_label(shortcut))
+            .style(|s| s.font_size(10.0).color(Color::rgb8(107, 114, 128))),
+        button(label(|| "↑")).action(move || up.move_by(&up_id, -1)),
+        button(label(|| "↓")).action(move || down.move_by(&down_id, 1)),
+        button(label(|| "削除")).action(move || remove.remove(&remove_id)),
+    ))
+    .context_menu(move || {
+        let rename_id = menu_id.clone();
+        let mut context = Menu::new("").entry(
+            MenuItem::new("名前を変更").action(move || renaming.set(Some(rename_id.clone()))),
+        );
+        if in_folder {
+            let (controller, id) = (menu.clone(), menu_id.clone());
+            context = context.entry(
+                MenuItem::new("フォルダから出す")
+                    .action(move || controller.move_to_folder(&id, None)),
+            );
+        }
+        for (folder_id, name) in &folders {
+            let (controller, id, folder_id) = (menu.clone(), menu_id.clone(), folder_id.clone());
+            context = context.entry(
+                MenuItem::new(format!("「{}」へ移動", name))
+                    .action(move || controller.move_to_folder(&id, Some(&folder_id))),
+            );
+        }
+        context
+    })
+    .style(|s| s.width_full().items_center().gap(2.0).font_size(11.0))
+}
+
+/// 名前の入力欄（Enter で確定し、Escape で取り消す）
+fn rename_input(
+    id: String,
+    name: String,
+    renaming: RwSignal<Option<String>>,
+    controller: BookmarksController,
+) -> impl IntoView {
+    let text = RwSignal::new(name);
+    text_input(text)
+        .request_focus(|| {})
+        .on_event_cont(EventListener::KeyDown, move |event| {
+            let Event::KeyDown(key_event) = event else {
+                return;
+            };
+            match &key_event.key.logical_key {
+                Key::Named(NamedKey::Enter) => {
+                    match controller.rename(&id, &text.get_untracked()) {
+                        Ok(()) => renaming.set(None),
+                        Err(e) => display_error_globally(&e),
+                    }
+                }
+                Key::Named(NamedKey::Escape) => renaming.set(None),
+                _ => {}
+            }
+        })
+        .style(|s| s.width_full().font_size(12.0))
+}
+
+/// 最近の場所のセクションを作成
+///
+/// よく使う順に並べ、クリックするとアクティブなタブで開く。項目ごとに履歴から外せる。
+fn create_recent_section(
+    controller: HistoryController,
+    sidebar: Arc<ModernSidebar>,
+) -> impl IntoView {
+    v_stack((
+        h_stack((
+            label(|| "最近").style(move |s| {
+                let theme_arc = get_theme();
+                let theme = theme_arc.read().unwrap();
+                s.font_size(theme.typography.label_large)
+                    .font_weight(floem::text::Weight::MEDIUM)
+                    .color(theme.colors.on_surface_variant)
+                    .flex_grow(1.0)
+            }),
+            button(label(|| "履歴を消去"))
+                .action(move || controller.clear())
+                .style(|s| s.font_size(11.0)),
+        ))
+        .style(move |s| {
+            let theme_arc = get_theme();
+            let theme = theme_arc.read().unwrap();
+            s.width_full().items_center().padding_vert(theme.spacing.sm)
+        }),
+        dyn_container(
+            move || controller.top(RECENT_SIDEBAR_LIMIT),
+            move |entries| {
+                let sidebar = sidebar.clone();
+                v_stack_from_iter(entries.into_iter().map(move |entry| {
+                    let path = entry.path.clone();
+                    h_stack((
+                        container(create_sidebar_item(
+                            SidebarItem::from(&entry),
+                            sidebar.clone(),
+                        ))
+                        .style(|s| s.flex_grow(1.0).min_width(0.0)),
+                        button(label(|| "×")).action(move || controller.remove(&path)),
+                    ))
+                    .style(|s| s.width_full().items_center().gap(2.0).font_size(11.0))
+                }))
+                .style(|s| s.width_full())
+                .into_any()
+            },
+        ),
+    ))
+    .style(move |s| {
+        let theme_arc = get_theme();
+        let theme = theme_arc.read().unwrap();
+        s.width_full().gap(theme.spacing.xs)
+    })
+}
+
+/// スマートフォルダのセクションを作成
+///
+/// 項目をクリックするとタブで開き、項目ごとのボタンで並べ替え・編集・削除ができる。
+fn create_smart_folder_section(
+    controller: SmartFoldersController,
+    sidebar: Arc<ModernSidebar>,
+) -> impl IntoView {
+    let folders = controller.folders();
+    let editing = RwSignal::new(None::<SmartFolderDraft>);
+    let add_controller = controller.clone();
+    let editor_controller = controller.clone();
+
+    v_stack((
+        h_stack((
+            label(|| "スマートフォルダ").style(move |s| {
+                let theme_arc = get_theme();
+                let theme = theme_arc.read().unwrap();
+                s.font_size(theme.typography.label_large)
+                    .font_weight(floem::text::Weight::MEDIUM)
+                    .color(theme.colors.on_surface_variant)
+                    .flex_grow(1.0)
+            }),
+            button(label(|| "＋"))
+                .action(move || editing.set(Some(add_controller.new_draft())))
+                .style(|s| s.font_size(12.0)),
+        ))
+        .style(move |s| {
+            let theme_arc = get_theme();
+            let theme = theme_arc.read().unwrap();
+            s.width_full().items_center().padding_vert(theme.spacing.sm)
+        }),
+        dyn_container(
+            move || editing.get(),
+            move |draft| match draft {
+                Some(draft) => {
+                    smart_folder_editor(editor_controller.clone(), draft, editing).into_any()
+                }
+                None => empty().into_any(),
+            },
+        ),
+        dyn_container(
+            move || folders.with(|folders| folders.folders().to_vec()),
+            move |folders| {
+                let controller = controller.clone();
+                let sidebar = sidebar.clone();
+                v_stack_from_iter(folders.into_iter().map(move |folder| {
+                    create_smart_folder_row(folder, controller.clone(), sidebar.clone(), editing)
+                }))
+                .style(|s| s.width_full())
+                .into_any()
+            },
+        ),
+    ))
+    .style(move |s| {
+        let theme_arc = get_theme();
+        let theme = theme_arc.read().unwrap();
+        s.width_full().gap(theme.spacing.xs)
+    })
+}
+
+/// スマートフォルダの1行（開く・上へ・下へ・編集・削除）
+fn create_smart_folder_row(
+    folder: SmartFolder,
+    controller: SmartFoldersController,
+    sidebar: Arc<ModernSidebar>,
+    editing: RwSignal<Option<SmartFolderDraft>>,
+) -> impl IntoView {
+    let up_id = folder.id.clone();
+    let down_id = folder.id.clone();
+    let remove_id = folder.id.clone();
+    let draft = SmartFolderDraft::from_folder(&folder);
+    let up_controller = controller.clone();
+    let down_controller = controller.clone();
+
+    h_stack((
+        container(create_sidebar_item(SidebarItem::from(&folder), sidebar))
+            .style(|s| s.flex_grow(1.0).min_width(0.0)),
+        button(label(|| "↑")).action(move || up_controller.move_by(&up_id, -1)),
+        button(label(|| "↓")).action(move || down_controller.move_by(&down_id, 1)),
+        button(label(|| "編集")).action(move || editing.set(Some(draft.clone()))),
+        button(label(|| "削除")).action(move || controller.remove(&remove_id)),
+    ))
+    .style(|s| s.width_full().items_center().gap(2.0).font_size(11.0))
+}
+
+/// タグのセクションを作成
+///
+/// 項目をクリックするとタグの付いた項目をタブで開く。右クリックで色を変えられる。
+fn create_tag_section(controller: TagsController, sidebar: Arc<ModernSidebar>) -> impl IntoView {
+    let store = controller.store();
+    let adding = RwSignal::new(false);
+    let name = RwSignal::new(String::new());
+    let color = RwSignal::new(TagColor::Blue);
+    let error = RwSignal::new(None::<String>);
+    let add_controller = controller.clone();
+
+    let add = move || match add_controller.define(&name.get_untracked(), color.get_untracked()) {
+        Ok(()) => {
+            name.set(String::new());
+            error.set(None);
+            adding.set(false);
+        }
+        Err(e) => error.set(Some(e.to_string())),
+    };
+
+    v_stack((
+        h_stack((
+            label(|| "タグ").style(move |s| {
+                let theme_arc = get_theme();
+                let theme = theme_arc.read().unwrap();
+                s.font_size(theme.typography.label_large)
+                    .font_weight(floem::text::Weight::MEDIUM)
+                    .color(theme.colors.on_surface_variant)
+                    .flex_grow(1.0)
+            }),
+            button(label(|| "＋"))
+                .action(move || adding.update(|adding| *adding = !*adding))
+                .style(|s| s.font_size(12.0)),
+        ))
+        .style(move |s| {
+            let theme_arc = get_theme();
+            let theme = theme_arc.read().unwrap();
+            s.width_full().items_center().padding_vert(theme.spacing.sm)
+        }),
+        // 追加フォーム（名前と色）
+        v_stack((
+            h_stack((
+                text_input(name)
+                    .placeholder("タグ名")
+                    .style(|s| s.flex_grow(1.0).min_width(0.0).font_size(12.0)),
+                button(label(|| "追加")).action(add),
+            ))
+            .style(|s| s.width_full().gap(4.0).items_center()),
+            h_stack_from_iter(TagColor::ALL.into_iter().map(|choice| {
+                empty()
+                    .on_click_stop(move |_| color.set(choice))
+                    .style(move |s| {
+                        s.width(14.0)
+                            .height(14.0)
+                            .border_radius(7.0)
+                            .background(tag_color(choice))
+                            .cursor(floem::style::CursorStyle::Pointer)
+                            .apply_if(color.get() == choice, |s| {
+                                s.border(2.0).border_color(Color::rgb8(31, 41, 55))
+                            })
+                    })
+            }))
+            .style(|s| s.gap(4.0).items_center()),
+            label(move || error.get().unwrap_or_default()).style(move |s| {
+                s.font_size(11.0)
+                    .color(Color::rgb8(220, 53, 69))
+                    .apply_if(error.with(Option::is_none), |s| s.hide())
+            }),
+        ))
+        .style(move |s| {
+            s.width_full()
+                .gap(4.0)
+                .apply_if(!adding.get(), |s| s.hide())
+        }),
+        dyn_container(
+            move || store.with(|store| store.tags().to_vec()),
+            move |tags| {
+                let controller = controller.clone();
+                let sidebar = sidebar.clone();
+                v_stack_from_iter(
+                    tags.into_iter()
+                        .map(move |tag| create_tag_row(tag, controller.clone(), sidebar.clone())),
+                )
+                .style(|s| s.width_full())
+                .into_any()
+            },
+        ),
+    ))
+    .style(move |s| {
+        let theme_arc = get_theme();
+        let theme = theme_arc.read().unwrap();
+        s.width_full().gap(theme.spacing.xs)
+    })
+}
+
+/// タグの1行（色・開く・削除、右クリックで色の変更）
+fn create_tag_row(
+    tag: Tag,
+    controller: TagsController,
+    sidebar: Arc<ModernSidebar>,
+) -> impl IntoView {
+    let color = tag.color;
+    let menu_name = tag.name.clone();
+    let remove_name = tag.name.clone();
+    let menu_controller = controller.clone();
+
+    h_stack((
+        empty().style(move |s| {
+            s.width(10.0)
+                .height(10.0)
+                .border_radius(5.0)
+                .background(tag_color(color))
+        }),
+        container(create_sidebar_item(SidebarItem::from(&tag), sidebar))
+            .style(|s| s.flex_grow(1.0).min_width(0.0)),
+        button(label(|| "削除")).action(move || controller.remove_tag(&remove_name)),
+    ))
+    .context_menu(move || {
+        TagColor::ALL
+            .into_iter()
+            .fold(Menu::new(""), |menu, choice| {
+                let controller = menu_controller.clone();
+                let name = menu_name.clone();
+                let mark = if choice == color { "✓" } else { "　" };
+                menu.entry(
+                    MenuItem::new(format!("{} {}", mark, choice.label()))
+                        .action(move || controller.recolor(&name, choice)),
+                )
+            })
+    })
+    .style(|s| s.width_full().items_center().gap(2.0).font_size(11.0))
+}
+
 /// サイドバーアイテムを作成
-#[allow(dead_code)]
 fn create_sidebar_item(item: SidebarItem, sidebar: Arc<ModernSidebar>) -> impl IntoView {
     let item_id = item.id.clone();
     let selected = item.selected;
+    let open_item = item.clone();
 
     button(
         h_stack((
@@ -466,6 +1268,7 @@ fn create_sidebar_item(item: SidebarItem, sidebar: Arc<ModernSidebar>) -> impl I
     )
     .action(move || {
         sidebar.select_item(item_id.clone());
+        sidebar.open_item(&open_item);
     })
     .style(move |s| {
         let theme_arc = get_theme();
@@ -490,6 +1293,22 @@ fn create_sidebar_item(item: SidebarItem, sidebar: Arc<ModernSidebar>) -> impl I
     })
 }
 
+/// ゴミ箱の項目にバッジを付ける
+fn set_trash_badge(sections: RwSignal<Vec<SidebarSection>>, count: usize) {
+    sections.update(|sections| {
+        for item in sections.iter_mut().flat_map(|s| s.items.iter_mut()) {
+            if item.item_type == SidebarItemType::Trash {
+                item.badge_count = trash_badge(count);
+            }
+        }
+    });
+}
+
+/// ゴミ箱の項目数をバッジ表示用に変換（空ならバッジなし）
+fn trash_badge(count: usize) -> Option<u32> {
+    (count > 0).then(|| count.min(u32::MAX as usize) as u32)
+}
+
 /// プラットフォーム固有のパスを取得
 fn get_desktop_path() -> PathBuf {
     dirs::desktop_dir().unwrap_or_else(|| PathBuf::from("/home"))
@@ -538,6 +1357,51 @@ mod tests {
         assert_ne!(SidebarItemType::Drive, SidebarItemType::Folder);
     }
 
+    #[test]
+    fn test_trash_badge() {
+        let sidebar = ModernSidebar::with_default();
+        let trash_badge = || {
+            sidebar.sections.with(|sections| {
+                sections
+                    .iter()
+                    .flat_map(|s| s.items.iter())
+                    .find(|item| item.item_type == SidebarItemType::Trash)
+                    .map(|item| item.badge_count)
+            })
+        };
+
+        sidebar.set_trash_count(3);
+        assert_eq!(trash_badge(), Some(Some(3)));
+
+        sidebar.set_trash_count(0);
+        assert_eq!(trash_badge(), Some(None));
+    }
+
+    #[test]
+    fn test_bookmark_sidebar_item() {
+        let bookmark = Bookmark {
+            id: "bookmark_1".to_string(),
+            name: "docs".to_string(),
+            path: PathBuf::from("/home/u/docs"),
+        };
+        let item = SidebarItem::from(&bookmark);
+        assert_eq!(item.id, "bookmark_1");
+        assert_eq!(item.item_type, SidebarItemType::Favorite);
+        assert_eq!(item.path, Some(PathBuf::from("/home/u/docs")));
+    }
+
+    #[test]
+    fn test_tag_sidebar_item() {
+        let tag = Tag {
+            name: "Work".to_string(),
+            color: TagColor::Blue,
+        };
+        let item = SidebarItem::from(&tag);
+        assert_eq!(item.id, "Work");
+        assert_eq!(item.item_type, SidebarItemType::Tag);
+        assert!(item.path.is_none());
+    }
+
     #[test]
     fn test_sidebar_creation() {
         let sidebar = ModernSidebar::with_default();
@@ -0,0 +1,680 @@
+//! 分割ペイン
+//!
+//! ファイル一覧を左右・上下に分割して並べます。ペインごとにタブを持ち、
+//! 境界のドラッグで大きさを変えられます。分割の構成・大きさ・フォーカスは
+//! `AppState::panes` に保存し、次回起動時に同じ分割を復元します。
+//! 名前を付けて保存したワークスペースに切り替えることもできます。
+
+use crate::state_integration::ReactiveStateManager;
+use crate::theme::get_theme;
+use floem::event::{Event, EventListener};
+use floem::keyboard::{Key, Modifiers, NamedKey};
+use floem::reactive::{RwSignal, SignalGet, SignalUpdate, SignalWith, create_memo};
+use floem::style::CursorStyle;
+use floem::views::{
+    Decorators, container, dyn_container, empty, h_stack_from_iter, v_stack, v_stack_from_iter,
+};
+use floem::{AnyView, IntoView};
+use rust_explorer_config::state_helpers;
+use rust_explorer_core::{
+    AppState, JobKind, MIN_PANE_FLEX, PaneTree, SplitDirection, TabState, state_utils,
+};
+use rust_explorer_utils::AppError;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use super::error_dialog::display_error_globally;
+use super::job_panel::global_job_manager;
+use super::tabs::{TabsController, tabbed_view, wrapped_index};
+use super::workspace::workspace_bar;
+
+/// ペイン境界の太さ
+const DIVIDER_SIZE: f64 = 4.0;
+
+/// ペイン操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaneCommand {
+    /// フォーカスのあるペインを左右に分割
+    SplitHorizontal,
+    /// フォーカスのあるペインを上下に分割
+    SplitVertical,
+    /// フォーカスのあるペインを閉じる
+    ClosePane,
+    /// 次のペインへフォーカス
+    FocusNext,
+    /// 前のペインへフォーカス
+    FocusPrevious,
+    /// 選択項目を隣のペインへコピー
+    CopyToOther,
+    /// 選択項目を隣のペインへ移動
+    MoveToOther,
+}
+
+/// キー入力に対応するペイン操作
+pub fn pane_command_for_key(key: &Key, modifiers: Modifiers) -> Option<PaneCommand> {
+    if modifiers.alt() {
+        return None;
+    }
+    let (ctrl, shift) = (modifiers.control(), modifiers.shift());
+    match key {
+        Key::Named(NamedKey::F5) if !ctrl && !shift => Some(PaneCommand::CopyToOther),
+        Key::Named(NamedKey::F6) if !ctrl && !shift => Some(PaneCommand::MoveToOther),
+        // Shift 併用時は配列によって "|" で届く
+        Key::Character(c) if ctrl && c == "\\" => Some(if shift {
+            PaneCommand::SplitVertical
+        } else {
+            PaneCommand::SplitHorizontal
+        }),
+        Key::Character(c) if ctrl && c == "|" => Some(PaneCommand::SplitVertical),
+        Key::Character(c) if ctrl && shift && c.eq_ignore_ascii_case("w") => {
+            Some(PaneCommand::ClosePane)
+        }
+        Key::Named(NamedKey::ArrowRight) if ctrl && shift => Some(PaneCommand::FocusNext),
+        Key::Named(NamedKey::ArrowLeft) if ctrl && shift => Some(PaneCommand::FocusPrevious),
+        _ => None,
+    }
+}
+
+/// 隣のペインへの転送方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaneTransfer {
+    Copy,
+    Move,
+}
+
+/// `pane_id` のペインの選択項目を隣のペインのディレクトリへ転送するジョブ
+///
+/// 転送元はペインのアクティブなタブの選択項目、転送先は隣のペインの
+/// アクティブなタブで表示しているディレクトリ。
+pub fn pane_transfer_job(
+    state: &AppState,
+    pane_id: &str,
+    transfer: PaneTransfer,
+) -> Result<JobKind, AppError> {
+    let active_tab = |pane_id: &str| {
+        let tab_id = state.pane(pane_id)?.active_tab_id.clone()?;
+        state.tabs.iter().find(|t| t.id == tab_id)
+    };
+    let source = active_tab(pane_id)
+        .filter(|tab| !tab.selected_paths.is_empty())
+        .ok_or_else(|| {
+            AppError::FileSystemCustom("コピー・移動する項目が選択されていません".to_string())
+        })?;
+    let destination = state
+        .other_pane_id(pane_id)
+        .and_then(|other| active_tab(&other))
+        .ok_or_else(|| {
+            AppError::FileSystemCustom("コピー・移動先のペインがありません".to_string())
+        })?;
+    if destination.current_path == source.current_path {
+        return Err(AppError::FileSystemCustom(
+            "コピー・移動先が同じディレクトリです".to_string(),
+        ));
+    }
+
+    let sources = source.selected_paths.clone();
+    let destination = destination.current_path.clone();
+    Ok(match transfer {
+        PaneTransfer::Copy => JobKind::Copy {
+            sources,
+            destination,
+        },
+        PaneTransfer::Move => JobKind::Move {
+            sources,
+            destination,
+        },
+    })
+}
+
+/// ペインの管理
+///
+/// 分割・閉じる・フォーカス・大きさの変更を `StateManager` へ反映する。
+#[derive(Clone)]
+pub struct PanesController {
+    manager: ReactiveStateManager,
+    /// 新しいタブで開くディレクトリ
+    default_path: Rc<dyn Fn() -> PathBuf>,
+    /// ワークスペースを開くたびに増やし、同じ分割でもペインを作り直す
+    generation: RwSignal<u64>,
+}
+
+impl PanesController {
+    /// ペインの管理を作成
+    ///
+    /// 保存された分割を整え、タブのないペインには `default_path` のタブを開く。
+    pub fn new(
+        manager: ReactiveStateManager,
+        default_path: impl Fn() -> PathBuf + 'static,
+    ) -> Self {
+        let controller = Self {
+            manager,
+            default_path: Rc::new(default_path),
+            generation: RwSignal::new(0),
+        };
+        if let Err(e) = controller.fill_empty_panes() {
+            display_error_globally(&e);
+        }
+        controller.manager.refresh();
+        controller
+    }
+
+    /// 分割を整え、タブのないペインに既定のディレクトリのタブを開く
+    fn fill_empty_panes(&self) -> Result<(), AppError> {
+        self.manager.ensure_pane_layout()?;
+        let state = self.manager.state_manager().get_state()?;
+        for pane_id in state.leaf_pane_ids() {
+            if state.pane_tabs(&pane_id).is_empty() {
+                self.manager.add_tab(self.default_tab(&pane_id))?;
+            }
+        }
+        // タブを開いたペインにフォーカスが移るため元に戻す
+        match state.focused_pane_id {
+            Some(focused) => self.manager.focus_pane(&focused),
+            None => Ok(()),
+        }
+    }
+
+    /// 内部のリアクティブ状態管理マネージャー
+    pub fn manager(&self) -> &ReactiveStateManager {
+        &self.manager
+    }
+
+    /// ペインのタブの管理を作成
+    pub fn tabs(&self, pane_id: &str) -> TabsController {
+        TabsController::new(
+            self.manager.clone(),
+            pane_id.to_string(),
+            self.default_path.clone(),
+        )
+    }
+
+    /// フォーカスのあるペインID
+    pub fn focused_pane_id(&self) -> Option<String> {
+        self.manager
+            .reactive_state()
+            .with_untracked(|state| state.focused_pane_id.clone())
+    }
+
+    /// ペイン操作を実行
+    pub fn run(&self, command: PaneCommand) {
+        let Some(focused) = self.focused_pane_id() else {
+            return;
+        };
+        match command {
+            PaneCommand::SplitHorizontal => self.split(&focused, SplitDirection::Horizontal),
+            PaneCommand::SplitVertical => self.split(&focused, SplitDirection::Vertical),
+            PaneCommand::ClosePane => self.close(&focused),
+            PaneCommand::FocusNext => self.focus_relative(&focused, 1),
+            PaneCommand::FocusPrevious => self.focus_relative(&focused, -1),
+            PaneCommand::CopyToOther => self.transfer(&focused, PaneTransfer::Copy),
+            PaneCommand::MoveToOther => self.transfer(&focused, PaneTransfer::Move),
+        }
+    }
+
+    /// ペインを分割し、分割元のアクティブなタブと同じディレクトリを新しいペインで開く
+    pub fn split(&self, pane_id: &str, direction: SplitDirection) {
+        let path = self.manager.reactive_state().with_untracked(|state| {
+            let tab_id = state.pane(pane_id)?.active_tab_id.clone()?;
+            state
+                .tabs
+                .iter()
+                .find(|t| t.id == tab_id)
+                .map(|t| t.current_path.clone())
+        });
+        let tab = state_utils::create_default_tab(path.unwrap_or_else(|| (self.default_path)()));
+        self.apply(|manager| manager.split_pane(pane_id, direction, tab).map(|_| ()));
+    }
+
+    /// ペインを閉じる（最後のペインは閉じない）
+    pub fn close(&self, pane_id: &str) {
+        let is_last = self
+            .manager
+            .reactive_state()
+            .with_untracked(|state| state.leaf_pane_ids().len() <= 1);
+        if !is_last {
+            self.apply(|manager| manager.close_pane(pane_id));
+        }
+    }
+
+    /// ペインにフォーカス
+    pub fn focus(&self, pane_id: &str) {
+        if self.focused_pane_id().as_deref() != Some(pane_id) {
+            self.apply(|manager| manager.focus_pane(pane_id));
+        }
+    }
+
+    /// 分割内のペインの大きさを確定して保存
+    pub fn resize(&self, sizes: &[(String, f64)]) {
+        self.apply(|manager| {
+            sizes
+                .iter()
+                .try_for_each(|(pane_id, flex)| manager.resize_pane(pane_id, *flex))
+        });
+    }
+
+    /// 選択項目を隣のペインへコピー・移動するジョブを開始
+    pub fn transfer(&self, pane_id: &str, transfer: PaneTransfer) {
+        let job = self
+            .manager
+            .reactive_state()
+            .with_untracked(|state| pane_transfer_job(state, pane_id, transfer));
+        match job {
+            Ok(job) => {
+                global_job_manager().submit(job);
+            }
+            Err(e) => display_error_globally(&e),
+        }
+    }
+
+    /// 開いているワークスペース名
+    pub fn workspace_name(&self) -> Option<String> {
+        self.manager
+            .reactive_state()
+            .with(|state| state.workspace_name.clone())
+    }
+
+    /// 保存されたワークスペース名の一覧
+    pub fn workspace_names(&self) -> Vec<String> {
+        state_helpers::list_workspaces().unwrap_or_else(|e| {
+            display_error_globally(&e);
+            Vec::new()
+        })
+    }
+
+    /// 現在のタブとペインを名前を付けてワークスペースに保存
+    pub fn save_workspace(&self, name: &str) {
+        self.apply(|manager| {
+            let mut state = manager.state_manager().get_state()?;
+            state.workspace_name = Some(name.to_string());
+            state_helpers::save_workspace(name, &state)?;
+            manager.replace_state(state)
+        });
+    }
+
+    /// ワークスペースを開く（ウィンドウの大きさと位置は今のまま）
+    pub fn open_workspace(&self, name: &str) {
+        let default_path = (self.default_path)();
+        self.apply(|manager| {
+            let mut state: AppState = state_helpers::load_workspace(name)?;
+            state.restore_missing_paths(&default_path);
+            state.window = manager.state_manager().get_state()?.window;
+            state.workspace_name = Some(name.to_string());
+            manager.replace_state(state)?;
+            self.fill_empty_panes()
+        });
+        self.generation.update(|generation| *generation += 1);
+    }
+
+    /// ワークスペースを削除（開いているタブとペインはそのまま残す）
+    pub fn delete_workspace(&self, name: &str) {
+        self.apply(|manager| {
+            state_helpers::delete_workspace(name)?;
+            let mut state = manager.state_manager().get_state()?;
+            if state.workspace_name.as_deref() == Some(name) {
+                state.workspace_name = None;
+                manager.replace_state(state)?;
+            }
+            Ok(())
+        });
+    }
+
+    /// 隣のペインへフォーカス
+    fn focus_relative(&self, pane_id: &str, offset: isize) {
+        let next = self.manager.reactive_state().with_untracked(|state| {
+            let leaves = state.leaf_pane_ids();
+            let index = leaves.iter().position(|id| id == pane_id)?;
+            Some(leaves[wrapped_index(leaves.len(), index, offset)].clone())
+        });
+        if let Some(next) = next {
+            self.focus(&next);
+        }
+    }
+
+    /// ペインに開く既定のディレクトリのタブ
+    fn default_tab(&self, pane_id: &str) -> TabState {
+        let mut tab = state_utils::create_default_tab((self.default_path)());
+        tab.pane_id = Some(pane_id.to_string());
+        tab
+    }
+
+    /// 状態管理マネージャーへの操作を実行し、成功したらすぐに反映
+    fn apply(&self, operation: impl FnOnce(&ReactiveStateManager) -> Result<(), AppError>) {
+        match operation(&self.manager) {
+            Ok(()) => self.manager.refresh(),
+            Err(e) => display_error_globally(&e),
+        }
+    }
+}
+
+/// タブの内容を作る関数
+type PaneContent = Rc<dyn Fn(TabsController, TabState) -> AnyView>;
+
+/// 分割ペインのビューを作成
+///
+/// 分割の構成が変わったときだけ作り直し、大きさやタブの変更では作り直さない。
+/// F5・F6 で隣のペインへのコピー・移動、Ctrl+\ で分割などのショートカットを受け付ける。
+pub fn pane_layout_view<V: IntoView + 'static>(
+    controller: PanesController,
+    content: impl Fn(TabsController, TabState) -> V + 'static,
+) -> impl IntoView {
+    let content: PaneContent = Rc::new(move |tabs, tab| content(tabs, tab).into_any());
+    let reactive_state = controller.manager().reactive_state();
+    let generation = controller.generation;
+    let tree = create_memo(move |_| {
+        (
+            generation.get(),
+            reactive_state.with(|state| state.pane_tree()),
+        )
+    });
+    let key_controller = controller.clone();
+    let workspace_controller = controller.clone();
+
+    v_stack((
+        workspace_bar(workspace_controller),
+        container(dyn_container(
+            move || tree.get(),
+            move |(_, tree)| -> AnyView {
+                match tree {
+                    Some(tree) => pane_node(controller.clone(), tree, content.clone()),
+                    None => empty().into_any(),
+                }
+            },
+        ))
+        .style(|s| s.size_full().min_height(0.0)),
+    ))
+    .on_event_cont(EventListener::KeyDown, move |event| {
+        if let Event::KeyDown(key_event) = event
+            && let Some(command) =
+                pane_command_for_key(&key_event.key.logical_key, key_event.modifiers)
+        {
+            key_controller.run(command);
+        }
+    })
+    .style(|s| s.size_full().gap(4.0))
+}
+
+/// 分割ツリーの1つのノード
+fn pane_node(controller: PanesController, node: PaneTree, content: PaneContent) -> AnyView {
+    match node {
+        PaneTree::Leaf(pane_id) => leaf_pane(controller, pane_id, content),
+        PaneTree::Split {
+            direction,
+            children,
+            ..
+        } => split_pane(controller, direction, children, content),
+    }
+}
+
+/// タブを表示するペイン（クリックでフォーカスする）
+fn leaf_pane(controller: PanesController, pane_id: String, content: PaneContent) -> AnyView {
+    let reactive_state = controller.manager().reactive_state();
+    let is_focused = {
+        let pane_id = pane_id.clone();
+        move || {
+            reactive_state.with(|state| {
+                state.leaf_pane_ids().len() > 1
+                    && state.focused_pane_id.as_deref() == Some(pane_id.as_str())
+            })
+        }
+    };
+    let focus = controller.clone();
+    let focus_id = pane_id.clone();
+
+    container(tabbed_view(controller.tabs(&pane_id), move |tabs, tab| {
+        content(tabs, tab)
+    }))
+    .on_event_cont(EventListener::PointerDown, move |_| focus.focus(&focus_id))
+    .style(move |s| {
+        let theme_arc = get_theme();
+        let theme = theme_arc.read().unwrap();
+        let border = if is_focused() {
+            theme.colors.primary
+        } else {
+            theme.colors.border
+        };
+        s.size_full()
+            .min_width(0.0)
+            .min_height(0.0)
+            .padding(theme.spacing.xs)
+            .border(1.0)
+            .border_radius(theme.border_radius.sm)
+            .border_color(border)
+    })
+    .into_any()
+}
+
+/// 子ペインを並べ、境界のドラッグで隣り合うペインの大きさを変える分割
+fn split_pane(
+    controller: PanesController,
+    direction: SplitDirection,
+    children: Vec<PaneTree>,
+    content: PaneContent,
+) -> AnyView {
+    let horizontal = direction == SplitDirection::Horizontal;
+    let ids: Rc<Vec<String>> = Rc::new(children.iter().map(|c| c.id().to_string()).collect());
+    let flexes: Rc<Vec<RwSignal<f64>>> = Rc::new(
+        controller
+            .manager()
+            .reactive_state()
+            .with_untracked(|state| {
+                ids.iter()
+                    .map(|id| RwSignal::new(state.pane(id).map_or(1.0, |p| p.flex())))
+                    .collect()
+            }),
+    );
+    // ドラッグ中の境界（左側の子の位置）と直前のポインタ位置
+    let dragging = RwSignal::new(None::<(usize, Option<f64>)>);
+    let size = RwSignal::new(0.0);
+
+    let count = children.len();
+    let mut items: Vec<AnyView> = Vec::with_capacity(count * 2);
+    for (index, child) in children.into_iter().enumerate() {
+        if index > 0 {
+            items.push(divider(horizontal, index - 1, dragging));
+        }
+        let flex = flexes[index];
+        items.push(
+            container(pane_node(controller.clone(), child, content.clone()))
+                .style(move |s| {
+                    s.flex_grow(flex.get() as f32)
+                        .flex_basis(0.0)
+                        .min_width(0.0)
+                        .min_height(0.0)
+                })
+                .into_any(),
+        );
+    }
+
+    let position = move |event: &Event| {
+        event
+            .point()
+            .map(|point| if horizontal { point.x } else { point.y })
+    };
+    let move_flexes = flexes.clone();
+    let commit_flexes = flexes;
+    let stack = if horizontal {
+        h_stack_from_iter(items).into_any()
+    } else {
+        v_stack_from_iter(items).into_any()
+    };
+    stack
+        .on_resize(move |rect| {
+            size.set(if horizontal {
+                rect.width()
+            } else {
+                rect.height()
+            })
+        })
+        .on_event_cont(EventListener::PointerDown, move |event| {
+            // 境界で押されたときだけ開始位置を記録する
+            if let Some((index, None)) = dragging.get_untracked() {
+                dragging.set(Some((index, position(event))));
+            }
+        })
+        .on_event_cont(EventListener::PointerMove, move |event| {
+            let (Some((index, Some(last))), Some(current)) =
+                (dragging.get_untracked(), position(event))
+            else {
+                return;
+            };
+            let total: f64 = move_flexes.iter().map(|f| f.get_untracked()).sum();
+            let available = size.get_untracked() - DIVIDER_SIZE * (count - 1) as f64;
+            if available > 0.0 {
+                resize_adjacent(
+                    move_flexes[index],
+                    move_flexes[index + 1],
+                    (current - last) * total / available,
+                );
+            }
+            dragging.set(Some((index, Some(current))));
+        })
+        .on_event_cont(EventListener::PointerUp, move |_| {
+            if let Some((index, _)) = dragging.get_untracked() {
+                dragging.set(None);
+                controller.resize(&[
+                    (ids[index].clone(), commit_flexes[index].get_untracked()),
+                    (
+                        ids[index + 1].clone(),
+                        commit_flexes[index + 1].get_untracked(),
+                    ),
+                ]);
+            }
+        })
+        .style(|s| s.size_full())
+        .into_any()
+}
+
+/// 隣り合う2つのペインの比率を `delta` だけ移す（合計は変えない）
+fn resize_adjacent(before: RwSignal<f64>, after: RwSignal<f64>, delta: f64) {
+    let (a, b) = (before.get_untracked(), after.get_untracked());
+    let delta = delta.clamp(MIN_PANE_FLEX - a, b - MIN_PANE_FLEX);
+    before.set(a + delta);
+    after.set(b - delta);
+}
+
+/// ペインの境界
+fn divider(
+    horizontal: bool,
+    index: usize,
+    dragging: RwSignal<Option<(usize, Option<f64>)>>,
+) -> AnyView {
+    empty()
+        .on_event_cont(EventListener::PointerDown, move |_| {
+            dragging.set(Some((index, None)));
+        })
+        .style(move |s| {
+            let theme_arc = get_theme();
+            let theme = theme_arc.read().unwrap();
+            let s = if horizontal {
+                s.width(DIVIDER_SIZE)
+                    .height_full()
+                    .cursor(CursorStyle::ColResize)
+            } else {
+                s.height(DIVIDER_SIZE)
+                    .width_full()
+                    .cursor(CursorStyle::RowResize)
+            };
+            s.flex_shrink(0.0)
+                .hover(|s| s.background(theme.colors.hover))
+        })
+        .into_any()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn two_panes() -> (AppState, String, String) {
+        let mut state = AppState::default();
+        state.add_tab(TabState::new(
+            "left".to_string(),
+            "left".to_string(),
+            PathBuf::from("/left"),
+        ));
+        state.ensure_pane_layout();
+        let left = state.root_pane_id.clone().unwrap();
+        let right = state
+            .split_pane(
+                &left,
+                SplitDirection::Horizontal,
+                TabState::new(
+                    "right".to_string(),
+                    "right".to_string(),
+                    PathBuf::from("/right"),
+                ),
+            )
+            .unwrap();
+        (state, left, right)
+    }
+
+    #[test]
+    fn test_pane_command_for_key() {
+        let ctrl = Modifiers::CONTROL;
+        let ctrl_shift = Modifiers::CONTROL | Modifiers::SHIFT;
+
+        assert_eq!(
+            pane_command_for_key(&Key::Named(NamedKey::F5), Modifiers::empty()),
+            Some(PaneCommand::CopyToOther)
+        );
+        assert_eq!(
+            pane_command_for_key(&Key::Named(NamedKey::F6), Modifiers::empty()),
+            Some(PaneCommand::MoveToOther)
+        );
+        assert_eq!(
+            pane_command_for_key(&Key::Character("\\".into()), ctrl),
+            Some(PaneCommand::SplitHorizontal)
+        );
+        assert_eq!(
+            pane_command_for_key(&Key::Character("|".into()), ctrl_shift),
+            Some(PaneCommand::SplitVertical)
+        );
+        assert_eq!(
+            pane_command_for_key(&Key::Character("W".into()), ctrl_shift),
+            Some(PaneCommand::ClosePane)
+        );
+        // Ctrl+W はタブを閉じる操作
+        assert_eq!(
+            pane_command_for_key(&Key::Character("w".into()), ctrl),
+            None
+        );
+    }
+
+    #[test]
+    fn test_pane_transfer_job() {
+        let (mut state, left, right) = two_panes();
+        assert!(pane_transfer_job(&state, &left, PaneTransfer::Copy).is_err());
+
+        state
+            .update_tab("left", |tab| {
+                tab.selected_paths = vec![PathBuf::from("/left/a.txt")]
+            })
+            .unwrap();
+        let job = pane_transfer_job(&state, &left, PaneTransfer::Move).unwrap();
+        assert!(matches!(
+            job,
+            JobKind::Move { sources, destination }
+                if sources == vec![PathBuf::from("/left/a.txt")]
+                    && destination.as_path() == std::path::Path::new("/right")
+        ));
+
+        // 転送先が同じディレクトリなら何もしない
+        state
+            .update_tab("right", |tab| tab.current_path = PathBuf::from("/left"))
+            .unwrap();
+        assert!(pane_transfer_job(&state, &left, PaneTransfer::Copy).is_err());
+        assert!(pane_transfer_job(&state, &right, PaneTransfer::Copy).is_err());
+    }
+
+    #[test]
+    fn test_resize_adjacent_keeps_minimum() {
+        let before = RwSignal::new(1.0);
+        let after = RwSignal::new(1.0);
+
+        resize_adjacent(before, after, 0.5);
+        assert_eq!((before.get(), after.get()), (1.5, 0.5));
+
+        resize_adjacent(before, after, 5.0);
+        assert!((after.get() - MIN_PANE_FLEX).abs() < 1e-9);
+        assert!((before.get() + after.get() - 2.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,408 @@
+//! クイックオープン
+//!
+//! Ctrl+P で開くポップアップから、最近開いた場所・ブックマーク・索引済みの
+//! パスをあいまい検索し、アクティブなタブで開きます。
+
+use chrono::Utc;
+use floem::action::debounce_action;
+use floem::event::{Event, EventListener};
+use floem::ext_event::create_ext_action;
+use floem::keyboard::{Key, Modifiers, NamedKey};
+use floem::prelude::*;
+use floem::reactive::Scope;
+use floem::style::Position;
+use floem::text::Weight;
+use rust_explorer_core::{
+    Bookmarks, LocationHistory, QuickOpen, QuickOpenMatch, QuickOpenSource, SearchIndexer,
+    StateManager,
+};
+use rust_explorer_utils::AppError;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::FileNavigationManager;
+
+/// 一度に表示する候補の数
+const QUICK_OPEN_LIMIT: usize = 50;
+
+/// 入力が止まってから候補を探すまでの時間
+const QUICK_OPEN_DEBOUNCE: Duration = Duration::from_millis(80);
+
+/// 照合する候補を集める関数
+type QuickOpenCandidates = Rc<dyn Fn() -> QuickOpen>;
+
+/// クイックオープンを開くキーか（Ctrl+P）
+pub fn is_quick_open_key(key: &Key, modifiers: Modifiers) -> bool {
+    modifiers.control()
+        && !modifiers.alt()
+        && !modifiers.shift()
+        && matches!(key, Key::Character(c) if c.eq_ignore_ascii_case("p"))
+}
+
+/// 候補の出どころの表示名
+pub fn quick_open_source_label(source: QuickOpenSource) -> &'static str {
+    match source {
+        QuickOpenSource::Recent => "最近",
+        QuickOpenSource::Bookmark => "ブックマーク",
+        QuickOpenSource::Indexed => "索引",
+    }
+}
+
+/// 候補の名前と親ディレクトリの表示
+pub fn describe_quick_open_match(found: &QuickOpenMatch) -> (String, String) {
+    let name = found
+        .path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| found.path.display().to_string());
+    let parent = found
+        .path
+        .parent()
+        .map(|parent| parent.display().to_string())
+        .unwrap_or_default();
+    (name, parent)
+}
+
+/// セッションの移動履歴・訪れた場所の履歴・ブックマークから照合する候補を集める
+pub fn quick_open_candidates(
+    state_manager: &StateManager,
+    bookmarks: Option<&Bookmarks>,
+    history: Option<&LocationHistory>,
+) -> QuickOpen {
+    let mut quick_open = state_manager
+        .get_state()
+        .map(|state| QuickOpen::from_state(&state))
+        .unwrap_or_default();
+    if let Some(bookmarks) = bookmarks {
+        quick_open.add_bookmarks(bookmarks);
+    }
+    if let Some(history) = history {
+        quick_open.add_history(history, Utc::now());
+    }
+    quick_open
+}
+
+/// 集めた候補と検索インデックスから入力に一致する場所を探す
+pub fn quick_open_matches(
+    quick_open: &QuickOpen,
+    indexer: Option<&SearchIndexer>,
+    pattern: &str,
+) -> Vec<QuickOpenMatch> {
+    indexer
+        .and_then(|indexer| {
+            indexer
+                .with_index(|index| quick_open.search(pattern, Some(index), QUICK_OPEN_LIMIT))
+                .ok()
+        })
+        .unwrap_or_else(|| quick_open.search(pattern, None, QUICK_OPEN_LIMIT))
+}
+
+/// 表示中のタブの移動先を変えるハンドル
+#[derive(Clone)]
+pub struct TabNavigator {
+    navigation: Arc<FileNavigationManager>,
+    selection: RwSignal<HashSet<PathBuf>>,
+}
+
+impl TabNavigator {
+    pub fn new(
+        navigation: Arc<FileNavigationManager>,
+        selection: RwSignal<HashSet<PathBuf>>,
+    ) -> Self {
+        Self {
+            navigation,
+            selection,
+        }
+    }
+
+    /// ディレクトリへ移動する（ファイルは親ディレクトリで選択して表示する）
+    pub fn open(&self, path: &Path, is_dir: bool) -> Result<(), AppError> {
+        if is_dir {
+            return self.navigation.navigate_to(&path.to_path_buf());
+        }
+        let parent = path
+            .parent()
+            .ok_or_else(|| AppError::InvalidPath(path.to_path_buf()))?;
+        self.navigation.navigate_to(&parent.to_path_buf())?;
+        self.selection.set(HashSet::from([path.to_path_buf()]));
+        Ok(())
+    }
+}
+
+/// 表示中のタブのハンドル（タブIDごと）
+#[derive(Clone, Default)]
+pub struct TabNavigators {
+    navigators: Rc<RefCell<HashMap<String, TabNavigator>>>,
+}
+
+impl TabNavigators {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// タブの内容を作ったときに登録する
+    pub fn register(&self, tab_id: &str, navigator: TabNavigator) {
+        self.navigators
+            .borrow_mut()
+            .insert(tab_id.to_string(), navigator);
+    }
+
+    /// タブの内容を破棄したときに外す（同じタブを作り直した後なら何もしない）
+    pub fn unregister(&self, tab_id: &str, navigator: &TabNavigator) {
+        let mut navigators = self.navigators.borrow_mut();
+        if navigators
+            .get(tab_id)
+            .is_some_and(|current| Arc::ptr_eq(&current.navigation, &navigator.navigation))
+        {
+            navigators.remove(tab_id);
+        }
+    }
+
+    pub fn get(&self, tab_id: &str) -> Option<TabNavigator> {
+        self.navigators.borrow().get(tab_id).cloned()
+    }
+}
+
+/// クイックオープンのポップアップ
+///
+/// `visible` の間だけ表示し、開いたときに `candidates` で候補を集める。
+/// 入力が止まるとバックグラウンドで候補と `indexer` の索引を照合し、
+/// Enter かクリックで選んだ候補を `on_choose` に渡して閉じる。
+pub fn quick_open_popup(
+    visible: RwSignal<bool>,
+    candidates: impl Fn() -> QuickOpen + 'static,
+    indexer: Option<Arc<SearchIndexer>>,
+    on_choose: impl Fn(&QuickOpenMatch) + 'static,
+) -> impl IntoView {
+    let candidates: QuickOpenCandidates = Rc::new(candidates);
+    let on_choose = Rc::new(on_choose);
+
+    dyn_container(
+        move || visible.get(),
+        move |show| {
+            if !show {
+                return empty().into_any();
+            }
+            quick_open_panel(
+                visible,
+                Arc::new(candidates()),
+                indexer.clone(),
+                on_choose.clone(),
+            )
+            .into_any()
+        },
+    )
+    .style(move |s| {
+        s.position(Position::Absolute)
+            .inset_top(48.0)
+            .inset_left_pct(20.0)
+            .width_pct(60.0)
+            .z_index(900)
+            .apply_if(!visible.get(), |s| s.hide())
+    })
+}
+
+/// 入力欄と候補の一覧（開くたびに作り直す）
+fn quick_open_panel(
+    visible: RwSignal<bool>,
+    quick_open: Arc<QuickOpen>,
+    indexer: Option<Arc<SearchIndexer>>,
+    on_choose: Rc<dyn Fn(&QuickOpenMatch)>,
+) -> impl IntoView {
+    let pattern = RwSignal::new(String::new());
+    // 入力が空なら索引は使わないため、最初の候補はすぐに出す
+    let matches = RwSignal::new(quick_open_matches(&quick_open, None, ""));
+    let highlighted = RwSignal::new(0_usize);
+
+    // 索引の照合はUIスレッドを止めないよう別スレッドで行い、最後の入力の結果だけ反映する
+    let scope = Scope::current();
+    let latest = Rc::new(Cell::new(0_u64));
+    debounce_action(pattern, QUICK_OPEN_DEBOUNCE, move || {
+        let search = latest.get() + 1;
+        latest.set(search);
+        let latest = latest.clone();
+        let show = create_ext_action(scope, move |found| {
+            if latest.get() == search {
+                matches.set(found);
+                highlighted.set(0);
+            }
+        });
+        let quick_open = quick_open.clone();
+        let indexer = indexer.clone();
+        let pattern = pattern.get_untracked();
+        std::thread::spawn(move || {
+            show(quick_open_matches(
+                &quick_open,
+                indexer.as_deref(),
+                &pattern,
+            ));
+        });
+    });
+
+    let choose = Rc::new(move |index: usize| {
+        let found = matches.with_untracked(|matches| matches.get(index).cloned());
+        if let Some(found) = found {
+            on_choose(&found);
+            visible.set(false);
+        }
+    });
+    let choose_on_enter = choose.clone();
+
+    v_stack((
+        text_input(pattern)
+            .placeholder("移動先を入力（最近開いた場所・ブックマーク・索引から検索）")
+            .request_focus(|| {})
+            .on_event_cont(EventListener::KeyDown, move |event| {
+                let Event::KeyDown(key_event) = event else {
+                    return;
+                };
+                let len = matches.with_untracked(|matches| matches.len());
+                match &key_event.key.logical_key {
+                    Key::Named(NamedKey::Escape) => visible.set(false),
+                    Key::Named(NamedKey::Enter) => choose_on_enter(highlighted.get_untracked()),
+                    Key::Named(NamedKey::ArrowDown) if len > 0 => {
+                        highlighted.update(|index| *index = (*index + 1) % len)
+                    }
+                    Key::Named(NamedKey::ArrowUp) if len > 0 => {
+                        highlighted.update(|index| *index = (*index + len - 1) % len)
+                    }
+                    _ => {}
+                }
+            })
+            .style(|s| {
+                s.width_full()
+                    .padding(8.0)
+                    .border(1.0)
+                    .border_color(Color::rgb8(209, 213, 219))
+                    .border_radius(4.0)
+            }),
+        dyn_container(
+            move || matches.with(|matches| matches.is_empty()),
+            move |empty_result| {
+                if empty_result {
+                    return label(|| "一致する場所はありません")
+                        .style(|s| s.padding(8.0).color(Color::rgb8(107, 114, 128)))
+                        .into_any();
+                }
+                let choose = choose.clone();
+                scroll(
+                    dyn_stack(
+                        move || matches.get().into_iter().enumerate(),
+                        |(index, found)| (*index, found.path.clone()),
+                        move |(index, found)| {
+                            quick_open_row(index, found, highlighted, choose.clone())
+                        },
+                    )
+                    .style(|s| s.flex_col().width_full()),
+                )
+                .style(|s| s.width_full().max_height(360.0))
+                .into_any()
+            },
+        )
+        .style(|s| s.width_full()),
+    ))
+    .style(|s| {
+        s.width_full()
+            .gap(6.0)
+            .padding(8.0)
+            .background(Color::rgb8(255, 255, 255))
+            .border(1.0)
+            .border_color(Color::rgb8(200, 200, 200))
+            .border_radius(8.0)
+            .box_shadow_blur(12.0)
+            .box_shadow_color(Color::rgba8(0, 0, 0, 40))
+    })
+}
+
+/// 候補の1行（名前・親ディレクトリ・出どころ）
+fn quick_open_row(
+    index: usize,
+    found: QuickOpenMatch,
+    highlighted: RwSignal<usize>,
+    choose: Rc<dyn Fn(usize)>,
+) -> impl IntoView {
+    let (name, parent) = describe_quick_open_match(&found);
+    let icon = if found.is_dir { "📁" } else { "📄" };
+    let source = quick_open_source_label(found.source);
+
+    h_stack((
+        label(move || icon),
+        label(move || name.clone()).style(|s| s.font_weight(Weight::BOLD)),
+        label(move || parent.clone()).style(|s| {
+            s.flex_grow(1.0)
+                .min_width(0.0)
+                .font_size(12.0)
+                .color(Color::rgb8(107, 114, 128))
+        }),
+        label(move || source).style(|s| {
+            s.font_size(11.0)
+                .padding_horiz(6.0)
+                .border_radius(4.0)
+                .background(Color::rgb8(243, 244, 246))
+                .color(Color::rgb8(75, 85, 99))
+        }),
+    ))
+    .on_click_stop(move |_| choose(index))
+    .on_event_cont(EventListener::PointerEnter, move |_| highlighted.set(index))
+    .style(move |s| {
+        s.width_full()
+            .gap(8.0)
+            .items_center()
+            .padding_horiz(8.0)
+            .padding_vert(4.0)
+            .border_radius(4.0)
+            .apply_if(highlighted.get() == index, |s| {
+                s.background(Color::rgb8(219, 234, 254))
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quick_open_key() {
+        let ctrl = Modifiers::CONTROL;
+        assert!(is_quick_open_key(&Key::Character("p".into()), ctrl));
+        assert!(is_quick_open_key(&Key::Character("P".into()), ctrl));
+        assert!(!is_quick_open_key(
+            &Key::Character("p".into()),
+            Modifiers::empty()
+        ));
+        assert!(!is_quick_open_key(
+            &Key::Character("p".into()),
+            ctrl | Modifiers::SHIFT
+        ));
+        assert!(!is_quick_open_key(&Key::Character("o".into()), ctrl));
+    }
+
+    #[test]
+    fn test_describe_quick_open_match() {
+        let found = QuickOpenMatch {
+            path: PathBuf::from("/home/u/code/main.rs"),
+            source: QuickOpenSource::Indexed,
+            is_dir: false,
+            score: 0,
+            positions: Vec::new(),
+        };
+        assert_eq!(
+            describe_quick_open_match(&found),
+            ("main.rs".to_string(), "/home/u/code".to_string())
+        );
+        assert_eq!(quick_open_source_label(found.source), "索引");
+
+        let root = QuickOpenMatch {
+            path: PathBuf::from("/"),
+            ..found
+        };
+        assert_eq!(
+            describe_quick_open_match(&root),
+            ("/".to_string(), String::new())
+        );
+    }
+}
@@ -0,0 +1,326 @@
+//! セッションの復元画面
+//!
+//! セッション状態が読めなかった場合や前回の実行が異常終了した場合に、
+//! 状態ファイルのバックアップから復元するか新しく始めるかを選ぶ画面を提供します。
+
+use crate::theme::get_theme;
+use floem::prelude::*;
+use floem::text::Weight;
+use rust_explorer_config::StateBackup;
+use rust_explorer_core::{AppState, StateDiff};
+use rust_explorer_utils::AppError;
+use std::path::{Path, PathBuf};
+
+/// 一覧に表示するタブのパスの最大数
+const MAX_LISTED_PATHS: usize = 5;
+
+/// 復元画面を表示する理由
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecoveryReason {
+    /// セッション状態が読めなかった（壊れたファイルは隔離先のパスに残す）
+    CorruptState {
+        error: String,
+        quarantined: Option<PathBuf>,
+    },
+    /// 前回の実行が正常に終了しなかった
+    Interrupted,
+}
+
+/// 復元に使えるバックアップ
+#[derive(Debug, Clone)]
+pub struct RecoveryOption {
+    pub backup: StateBackup,
+    pub state: AppState,
+    /// 現在のセッションからの違い（現在のセッションが読めなければ None）
+    pub diff: Option<StateDiff>,
+}
+
+/// 復元画面での選択
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecoveryChoice {
+    /// 読み込めた現在のセッションをそのまま使う
+    Keep,
+    /// 指定した番号のバックアップから復元
+    Backup(usize),
+    /// タブのない新しいセッションで始める
+    StartFresh,
+}
+
+/// セッションの復元候補
+#[derive(Debug, Clone)]
+pub struct SessionRecovery {
+    pub reason: RecoveryReason,
+    pub options: Vec<RecoveryOption>,
+}
+
+impl SessionRecovery {
+    /// 読み込んだバックアップから復元候補を作る
+    ///
+    /// 読めないバックアップは候補から外す。候補がなければ選ぶものがないため None を返す。
+    pub fn new(
+        reason: RecoveryReason,
+        current: Option<&AppState>,
+        backups: impl IntoIterator<Item = (StateBackup, Result<AppState, AppError>)>,
+    ) -> Option<Self> {
+        let options: Vec<_> = backups
+            .into_iter()
+            .filter_map(|(backup, state)| {
+                let state = state.ok()?;
+                let diff = current.map(|current| state.diff(current));
+                Some(RecoveryOption {
+                    backup,
+                    state,
+                    diff,
+                })
+            })
+            .collect();
+
+        (!options.is_empty()).then_some(Self { reason, options })
+    }
+
+    /// 現在のセッションをそのまま使えるか
+    pub fn can_keep(&self) -> bool {
+        self.reason == RecoveryReason::Interrupted
+    }
+
+    /// 選択に応じた状態を作る
+    ///
+    /// ウィンドウの大きさと位置は現在の状態のものを使い、なくなったパスは `fallback` などに置き換える。
+    pub fn resolve(&self, choice: RecoveryChoice, current: AppState, fallback: &Path) -> AppState {
+        let mut state = match choice {
+            RecoveryChoice::Keep => return current,
+            RecoveryChoice::Backup(index) => match self.options.get(index) {
+                Some(option) => option.state.clone(),
+                None => return current,
+            },
+            RecoveryChoice::StartFresh => AppState::default(),
+        };
+        state.restore_missing_paths(fallback);
+        state.window = current.window;
+        state
+    }
+}
+
+/// バックアップの見出し（作成時刻とタブ数）
+pub fn describe_backup(option: &RecoveryOption) -> String {
+    let created = option
+        .backup
+        .created
+        .map(|created| {
+            created
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_else(|| "日時不明".to_string());
+    format!("{}  タブ {} 件", created, option.state.tabs.len())
+}
+
+/// バックアップの内容の要約（現在のセッションとの違い、なければタブのパス）
+pub fn describe_changes(option: &RecoveryOption) -> Vec<String> {
+    let Some(diff) = &option.diff else {
+        let mut lines: Vec<_> = option
+            .state
+            .tabs
+            .iter()
+            .take(MAX_LISTED_PATHS)
+            .map(|tab| tab.current_path.display().to_string())
+            .collect();
+        if option.state.tabs.len() > MAX_LISTED_PATHS {
+            lines.push(format!(
+                "ほか {} 件",
+                option.state.tabs.len() - MAX_LISTED_PATHS
+            ));
+        }
+        return lines;
+    };
+
+    if diff.is_empty() {
+        return vec!["現在のセッションと同じ".to_string()];
+    }
+    let added = diff
+        .added_tabs
+        .iter()
+        .map(|path| format!("+ {}", path.display()));
+    let removed = diff
+        .removed_tabs
+        .iter()
+        .map(|path| format!("- {}", path.display()));
+    let changed = diff
+        .changed_paths
+        .iter()
+        .map(|(from, to)| format!("{} → {}", from.display(), to.display()));
+    added.chain(removed).chain(changed).collect()
+}
+
+/// 復元画面の見出しと説明
+fn reason_message(reason: &RecoveryReason) -> (String, String) {
+    match reason {
+        RecoveryReason::CorruptState { error, quarantined } => (
+            "セッションを読み込めませんでした".to_string(),
+            match quarantined {
+                Some(path) => format!(
+                    "{}\n読めなかったファイルは {} に残しています。",
+                    error,
+                    path.display()
+                ),
+                None => error.clone(),
+            },
+        ),
+        RecoveryReason::Interrupted => (
+            "前回は正常に終了しませんでした".to_string(),
+            "保存されているバックアップからセッションを復元できます。".to_string(),
+        ),
+    }
+}
+
+/// 復元画面
+///
+/// 選択されると `on_choice` を呼ぶ。
+pub fn recovery_screen(
+    recovery: SessionRecovery,
+    on_choice: impl Fn(RecoveryChoice) + Clone + 'static,
+) -> impl IntoView {
+    let (title, message) = reason_message(&recovery.reason);
+    let keep = on_choice.clone();
+    let fresh = on_choice.clone();
+
+    let backups = recovery
+        .options
+        .iter()
+        .enumerate()
+        .map(|(index, option)| {
+            let on_choice = on_choice.clone();
+            let heading = describe_backup(option);
+            let changes = describe_changes(option).join("\n");
+            h_stack((
+                v_stack((
+                    label(move || heading.clone()).style(|s| s.font_weight(Weight::BOLD)),
+                    label(move || changes.clone()).style(|s| {
+                        let theme_arc = get_theme();
+                        let theme = theme_arc.read().unwrap();
+                        s.font_size(theme.typography.body_small)
+                            .color(theme.colors.on_surface_variant)
+                    }),
+                ))
+                .style(|s| s.flex_grow(1.0)),
+                button(label(|| "このバックアップから復元"))
+                    .action(move || on_choice(RecoveryChoice::Backup(index))),
+            ))
+            .style(|s| {
+                let theme_arc = get_theme();
+                let theme = theme_arc.read().unwrap();
+                s.items_center()
+                    .gap(theme.spacing.md)
+                    .padding(theme.spacing.md)
+                    .border(1.0)
+                    .border_radius(theme.border_radius.sm)
+                    .border_color(theme.colors.border)
+            })
+            .into_any()
+        })
+        .collect::<Vec<_>>();
+    let can_keep = recovery.can_keep();
+
+    container(
+        v_stack((
+            label(move || title.clone()).style(|s| s.font_size(18.0).font_weight(Weight::BOLD)),
+            label(move || message.clone()),
+            v_stack_from_iter(backups).style(|s| s.gap(8.0)),
+            h_stack((
+                button(label(|| "前回のセッションを使う"))
+                    .action(move || keep(RecoveryChoice::Keep))
+                    .style(move |s| s.apply_if(!can_keep, |s| s.hide())),
+                button(label(|| "新しく始める")).action(move || fresh(RecoveryChoice::StartFresh)),
+            ))
+            .style(|s| s.gap(8.0)),
+        ))
+        .style(|s| {
+            let theme_arc = get_theme();
+            let theme = theme_arc.read().unwrap();
+            s.gap(theme.spacing.md)
+                .padding(theme.spacing.lg)
+                .min_width(400.0)
+                .max_width(640.0)
+                .color(theme.colors.on_surface)
+        }),
+    )
+    .style(|s| s.size_full().items_center().justify_center())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_explorer_core::TabState;
+
+    fn backup(name: &str, paths: &[&str]) -> (StateBackup, Result<AppState, AppError>) {
+        let mut state = AppState::default();
+        for (i, path) in paths.iter().enumerate() {
+            state.add_tab(TabState::new(
+                format!("tab{}", i),
+                "tab".to_string(),
+                PathBuf::from(path),
+            ));
+        }
+        (StateBackup::from_path(PathBuf::from(name)), Ok(state))
+    }
+
+    #[test]
+    fn test_recovery_options() {
+        let broken = || {
+            (
+                StateBackup::from_path(PathBuf::from("session.json.backup.x")),
+                Err(AppError::Config("broken".to_string())),
+            )
+        };
+        let reason = RecoveryReason::Interrupted;
+        assert!(SessionRecovery::new(reason.clone(), None, vec![broken()]).is_none());
+
+        let current = backup("current", &["/a", "/b"]).1.unwrap();
+        let recovery = SessionRecovery::new(
+            reason,
+            Some(&current),
+            vec![
+                broken(),
+                backup("session.json.backup.20261016_212034123", &["/a", "/c"]),
+            ],
+        )
+        .unwrap();
+
+        // 読めないバックアップは外し、現在のセッションとの違いを示す
+        assert_eq!(recovery.options.len(), 1);
+        assert!(describe_backup(&recovery.options[0]).ends_with("タブ 2 件"));
+        assert_eq!(describe_changes(&recovery.options[0]), vec!["/b → /c"]);
+        assert!(recovery.can_keep());
+    }
+
+    #[test]
+    fn test_resolve_choice() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let gone = temp_dir.path().join("gone");
+        let reason = RecoveryReason::CorruptState {
+            error: "broken".to_string(),
+            quarantined: None,
+        };
+        let recovery =
+            SessionRecovery::new(reason, None, vec![backup("b", &[gone.to_str().unwrap()])])
+                .unwrap();
+        assert!(!recovery.can_keep());
+        assert_eq!(
+            describe_changes(&recovery.options[0]),
+            vec![gone.display().to_string()]
+        );
+
+        let mut current = AppState::default();
+        current.window.width = 900.0;
+
+        // バックアップのなくなったパスは置き換え、ウィンドウは現在のものを使う
+        let state = recovery.resolve(RecoveryChoice::Backup(0), current.clone(), temp_dir.path());
+        assert_eq!(state.tabs[0].current_path, temp_dir.path());
+        assert_eq!(state.window.width, 900.0);
+
+        let state = recovery.resolve(RecoveryChoice::StartFresh, current, temp_dir.path());
+        assert!(state.tabs.is_empty());
+        assert_eq!(state.window.width, 900.0);
+    }
+}
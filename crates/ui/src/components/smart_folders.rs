@@ -0,0 +1,556 @@
+//! スマートフォルダ
+//!
+//! 名前を付けて保存した検索をサイドバーから開き、検索範囲の索引から得た結果を
+//! ディレクトリのようにタブで表示します。定義は設定ディレクトリの JSON に保存します。
+
+use super::error_dialog::display_error_globally;
+use super::sort_filter::{SortFilterUIManager, simple_filter_bar};
+use super::tabs::TabsController;
+use super::user_data::SMART_FOLDERS_SAVE;
+use super::virtual_file_list::virtual_file_list_with_scroll;
+use super::{FileNavigationManager, ModernFileItemConfig};
+use floem::ext_event::create_signal_from_channel;
+use floem::prelude::*;
+use floem::reactive::{RwSignal, create_effect, create_memo};
+use rust_explorer_config::state_helpers;
+use rust_explorer_core::{
+    FileEntry, FileType, SearchIndexer, SmartFolder, SmartFolderResults, SmartFolders,
+    StateManager, TabState, state_utils,
+};
+use rust_explorer_utils::AppError;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// タブに表示する結果の上限
+const MAX_SMART_FOLDER_RESULTS: usize = 10_000;
+
+/// 編集中のスマートフォルダ（`id` が None なら新規）
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SmartFolderDraft {
+    pub id: Option<String>,
+    pub name: String,
+    pub query: String,
+    pub scope: String,
+}
+
+impl SmartFolderDraft {
+    /// `scope` を検索範囲にした新規の下書き
+    pub fn new(query: &str, scope: &Path) -> Self {
+        Self {
+            id: None,
+            name: String::new(),
+            query: query.to_string(),
+            scope: scope.display().to_string(),
+        }
+    }
+
+    /// 保存済みのスマートフォルダを編集する下書き
+    pub fn from_folder(folder: &SmartFolder) -> Self {
+        Self {
+            id: Some(folder.id.clone()),
+            name: folder.name.clone(),
+            query: folder.query.clone(),
+            scope: folder.scope.display().to_string(),
+        }
+    }
+}
+
+/// スマートフォルダの一覧の操作（変更は保存し、開いているタブにも反映する）
+#[derive(Clone)]
+pub struct SmartFoldersController {
+    folders: RwSignal<SmartFolders>,
+    state_manager: StateManager,
+    persist: bool,
+}
+
+impl SmartFoldersController {
+    /// 保存された定義を読み込んで作成
+    pub fn load(state_manager: StateManager) -> Self {
+        let folders = match state_helpers::smart_folders_exist() {
+            Ok(true) => state_helpers::load_smart_folders().unwrap_or_else(|e| {
+                display_error_globally(&e);
+                SmartFolders::new()
+            }),
+            Ok(false) => SmartFolders::new(),
+            Err(e) => {
+                display_error_globally(&e);
+                SmartFolders::new()
+            }
+        };
+        Self {
+            folders: RwSignal::new(folders),
+            state_manager,
+            persist: true,
+        }
+    }
+
+    /// 保存しない一覧で作成
+    pub fn in_memory(state_manager: StateManager, folders: SmartFolders) -> Self {
+        Self {
+            folders: RwSignal::new(folders),
+            state_manager,
+            persist: false,
+        }
+    }
+
+    /// スマートフォルダの一覧
+    pub fn folders(&self) -> RwSignal<SmartFolders> {
+        self.folders
+    }
+
+    /// 新規の下書き（アクティブなタブのパスとフィルタのクエリを引き継ぐ）
+    pub fn new_draft(&self) -> SmartFolderDraft {
+        let active = self
+            .state_manager
+            .get_state()
+            .ok()
+            .and_then(|state| state.get_active_tab().cloned());
+        match active {
+            Some(tab) => SmartFolderDraft::new(
+                tab.filter.query.as_deref().unwrap_or_default(),
+                &tab.current_path,
+            ),
+            None => {
+                SmartFolderDraft::new("", &dirs::home_dir().unwrap_or_else(|| PathBuf::from("/")))
+            }
+        }
+    }
+
+    /// 下書きを保存（新規なら末尾に追加、既存なら名前・クエリ・検索範囲を変更）
+    pub fn save_draft(&self, draft: &SmartFolderDraft) -> Result<SmartFolder, AppError> {
+        let scope = PathBuf::from(draft.scope.trim());
+        let mut folders = self.folders.get_untracked();
+        let id = match &draft.id {
+            Some(id) => {
+                folders.rename(id, &draft.name)?;
+                folders.update(id, &draft.query, scope)?;
+                id.clone()
+            }
+            None => folders.add(&draft.name, &draft.query, scope)?.id.clone(),
+        };
+        self.commit(folders);
+        let saved = self
+            .folders
+            .with_untracked(|folders| folders.get(&id).cloned())
+            .ok_or_else(|| AppError::Internal("Smart folder was not saved".to_string()))?;
+        self.refresh_open_tabs(&saved);
+        Ok(saved)
+    }
+
+    /// 削除（開いているタブは保存時の定義のまま残す）
+    pub fn remove(&self, id: &str) {
+        let mut folders = self.folders.get_untracked();
+        if folders.remove(id).is_some() {
+            self.commit(folders);
+        }
+    }
+
+    /// 表示順を `offset` だけ動かす（端では止まる）
+    pub fn move_by(&self, id: &str, offset: isize) {
+        let mut folders = self.folders.get_untracked();
+        let Some(position) = folders.folders().iter().position(|f| f.id == id) else {
+            return;
+        };
+        let index = position.saturating_add_signed(offset);
+        if index == position || index >= folders.folders().len() {
+            return;
+        }
+        match folders.move_to(id, index) {
+            Ok(()) => self.commit(folders),
+            Err(e) => display_error_globally(&e),
+        }
+    }
+
+    /// スマートフォルダを新しいタブで開く
+    pub fn open(&self, id: &str) {
+        let Some(folder) = self
+            .folders
+            .with_untracked(|folders| folders.get(id).cloned())
+        else {
+            return;
+        };
+        let tab = state_utils::create_smart_folder_tab(&folder);
+        if let Err(e) = self.state_manager.add_tab(tab) {
+            display_error_globally(&e);
+        }
+    }
+
+    /// 一覧を反映し、保存を予約する
+    fn commit(&self, folders: SmartFolders) {
+        if self.persist {
+            SMART_FOLDERS_SAVE.save(folders.clone());
+        }
+        self.folders.set(folders);
+    }
+
+    /// このスマートフォルダを開いているタブの定義を差し替える
+    fn refresh_open_tabs(&self, folder: &SmartFolder) {
+        let tab_ids: Vec<String> = self
+            .state_manager
+            .get_state()
+            .map(|state| {
+                state
+                    .tabs
+                    .iter()
+                    .filter(|tab| tab.smart_folder.as_ref().is_some_and(|f| f.id == folder.id))
+                    .map(|tab| tab.id.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+        for tab_id in tab_ids {
+            let result = self.state_manager.update_tab(&tab_id, |tab| {
+                tab.name = folder.name.clone();
+                tab.current_path = folder.scope.clone();
+                tab.smart_folder = Some(folder.clone());
+            });
+            if let Err(e) = result {
+                display_error_globally(&e);
+            }
+        }
+    }
+}
+
+/// 結果の件数と索引の作成状況の表示
+pub fn format_smart_folder_status(count: usize, indexing: bool) -> String {
+    let count = if count >= MAX_SMART_FOLDER_RESULTS {
+        format!("{} 件以上", MAX_SMART_FOLDER_RESULTS)
+    } else {
+        format!("{} 件", count)
+    };
+    if indexing {
+        format!("{}（索引を作成中…）", count)
+    } else {
+        count
+    }
+}
+
+/// スマートフォルダの作成・編集フォーム
+///
+/// 保存またはキャンセルすると `editing` を None に戻す。
+pub fn smart_folder_editor(
+    controller: SmartFoldersController,
+    draft: SmartFolderDraft,
+    editing: RwSignal<Option<SmartFolderDraft>>,
+) -> impl IntoView {
+    let name = RwSignal::new(draft.name.clone());
+    let query = RwSignal::new(draft.query.clone());
+    let scope = RwSignal::new(draft.scope.clone());
+    let error = RwSignal::new(None::<String>);
+
+    let save = move || {
+        let draft = SmartFolderDraft {
+            id: draft.id.clone(),
+            name: name.get_untracked(),
+            query: query.get_untracked(),
+            scope: scope.get_untracked(),
+        };
+        match controller.save_draft(&draft) {
+            Ok(_) => editing.set(None),
+            Err(e) => error.set(Some(e.to_string())),
+        }
+    };
+
+    v_stack((
+        text_input(name)
+            .placeholder("名前")
+            .style(editor_input_style),
+        text_input(query)
+            .placeholder("クエリ (例: ext:rs modified:<7d)")
+            .style(editor_input_style),
+        text_input(scope)
+            .placeholder("検索範囲のディレクトリ")
+            .style(editor_input_style),
+        label(move || error.get().unwrap_or_default()).style(move |s| {
+            s.font_size(11.0)
+                .color(Color::rgb8(220, 53, 69))
+                .apply_if(error.with(|error| error.is_none()), |s| s.hide())
+        }),
+        h_stack((
+            button(label(|| "保存")).action(save),
+            button(label(|| "キャンセル")).action(move || editing.set(None)),
+        ))
+        .style(|s| s.gap(6.0).font_size(12.0)),
+    ))
+    .style(|s| {
+        s.gap(4.0)
+            .padding(6.0)
+            .width_full()
+            .background(Color::rgb8(248, 249, 250))
+            .border(1.0)
+            .border_color(Color::rgb8(229, 231, 235))
+            .border_radius(6.0)
+    })
+}
+
+fn editor_input_style(s: floem::style::Style) -> floem::style::Style {
+    s.width_full()
+        .padding(4.0)
+        .font_size(12.0)
+        .border(1.0)
+        .border_color(Color::rgb8(209, 213, 219))
+        .border_radius(4.0)
+}
+
+/// スマートフォルダを開いたタブの内容
+///
+/// 検索範囲の索引が変わるたびに結果を作り直し、タブのソートとフィルタで並べる。
+/// 検索範囲が `search_indexer` の対象に含まれればその索引を検索する。
+/// ディレクトリを開くと通常のタブで表示する。
+pub fn smart_folder_content(
+    controller: TabsController,
+    tab: TabState,
+    search_indexer: Option<Arc<SearchIndexer>>,
+) -> impl IntoView {
+    let tab_id = tab.id.clone();
+
+    // サイドバーで定義が編集されたら結果を作り直す
+    let tabs = controller.tabs().tabs_signal();
+    let definition_tab_id = tab_id.clone();
+    let definition = create_memo(move |_| {
+        tabs.with(|tabs| {
+            tabs.iter()
+                .find(|tab| tab.id == definition_tab_id)
+                .and_then(|tab| tab.smart_folder.clone())
+        })
+    });
+
+    let found = RwSignal::new(Vec::<FileEntry>::new());
+    let indexing = RwSignal::new(false);
+    let error = RwSignal::new(None::<String>);
+    let results = Rc::new(RefCell::new(None::<SmartFolderResults>));
+    let effect_results = results.clone();
+    create_effect(move |_| {
+        let Some(folder) = definition.get() else {
+            return;
+        };
+        found.set(Vec::new());
+        match SmartFolderResults::start(&folder, search_indexer.as_ref(), MAX_SMART_FOLDER_RESULTS)
+        {
+            Ok(started) => {
+                error.set(None);
+                indexing.set(true);
+                let canceller = started.canceller();
+                let updates = create_signal_from_channel(started.receiver().clone());
+                create_effect(move |_| {
+                    updates.with(|update| {
+                        if let Some(update) = update
+                            && !canceller.is_cancelled()
+                        {
+                            found.set(update.entries.clone());
+                            indexing.set(update.indexing);
+                        }
+                    });
+                });
+                *effect_results.borrow_mut() = Some(started);
+            }
+            Err(e) => {
+                *effect_results.borrow_mut() = None;
+                indexing.set(false);
+                error.set(Some(e.to_string()));
+            }
+        }
+    });
+
+    // ソート・フィルタはタブへ書き戻す
+    let sort_filter_manager = Arc::new(SortFilterUIManager::with_config(
+        tab.sort,
+        tab.filter.clone(),
+    ));
+    let sort_filter_for_tab = sort_filter_manager.clone();
+    let sort_controller = controller.clone();
+    let sort_tab_id = tab_id.clone();
+    create_effect(move |previous: Option<()>| {
+        let sort = sort_filter_for_tab.current_sort_config();
+        let filter = sort_filter_for_tab.current_filter_criteria();
+        if previous.is_some() {
+            sort_controller.update_tab(&sort_tab_id, |tab| {
+                tab.sort = sort;
+                tab.filter = filter;
+            });
+        }
+    });
+
+    // 結果が変わったら並べ直し、結果から消えた項目の選択を外す
+    let selection = RwSignal::new(tab.selected_paths.iter().cloned().collect::<HashSet<_>>());
+    let entries = RwSignal::new(Vec::<FileEntry>::new());
+    let sort_filter_for_list = sort_filter_manager.clone();
+    create_effect(move |_| {
+        sort_filter_for_list.current_sort_config();
+        sort_filter_for_list.current_filter_criteria();
+        let mut listed = found.get();
+        sort_filter_for_list.process_entries(&mut listed);
+        let paths: HashSet<&PathBuf> = listed.iter().map(|entry| &entry.path).collect();
+        if !indexing.get_untracked()
+            && selection.with_untracked(|selection| selection.iter().any(|p| !paths.contains(p)))
+        {
+            selection.update(|selection| selection.retain(|path| paths.contains(path)));
+        }
+        entries.set(listed);
+    });
+
+    let selection_controller = controller.clone();
+    let selection_tab_id = tab_id.clone();
+    create_effect(move |previous: Option<()>| {
+        let selected: Vec<PathBuf> =
+            selection.with(|selection| selection.iter().cloned().collect());
+        if previous.is_some() {
+            selection_controller.update_tab(&selection_tab_id, |tab| {
+                tab.selected_paths = selected;
+            });
+        }
+    });
+
+    let scroll_offset = RwSignal::new(tab.scroll_offset);
+    let scroll_state = controller.state_manager().clone();
+
+    let nav_manager = Arc::new(
+        FileNavigationManager::with_default(tab.current_path.clone()).on_error(|error| {
+            eprintln!("ナビゲーションエラー: {}", error);
+        }),
+    );
+    let open_controller = controller.clone();
+
+    v_stack((
+        // 名前・クエリ・検索範囲と結果の件数
+        h_stack((
+            label(move || {
+                definition
+                    .get()
+                    .map(|folder| format!("スマートフォルダ: {}", folder.name))
+                    .unwrap_or_default()
+            })
+            .style(|s| s.font_size(14.0).font_weight(floem::text::Weight::BOLD)),
+            label(move || {
+                definition
+                    .get()
+                    .map(|folder| folder.query)
+                    .unwrap_or_default()
+            })
+            .style(|s| {
+                s.font_size(12.0)
+                    .font_family("monospace".to_string())
+                    .padding_horiz(6.0)
+                    .background(Color::rgb8(243, 244, 246))
+                    .border_radius(4.0)
+            }),
+            label(move || {
+                definition
+                    .get()
+                    .map(|folder| format!("範囲: {}", folder.scope.display()))
+                    .unwrap_or_default()
+            })
+            .style(|s| s.font_size(12.0).color(Color::rgb8(107, 114, 128))),
+            container("").style(|s| s.flex_grow(1.0)),
+            label(move || match error.get() {
+                Some(error) => error,
+                None => format_smart_folder_status(found.with(Vec::len), indexing.get()),
+            })
+            .style(move |s| {
+                s.font_size(12.0).color(if error.with(Option::is_some) {
+                    Color::rgb8(220, 53, 69)
+                } else {
+                    Color::rgb8(107, 114, 128)
+                })
+            }),
+        ))
+        .style(|s| s.gap(8.0).items_center().width_full().margin_bottom(8.0)),
+        simple_filter_bar(sort_filter_manager.clone()).style(|s| s.margin_bottom(8.0)),
+        // 結果の一覧（ディレクトリは通常のタブで開く）
+        container(virtual_file_list_with_scroll(
+            entries,
+            selection,
+            scroll_offset,
+            ModernFileItemConfig::default(),
+            move |entry| {
+                if entry.file_type == FileType::Directory {
+                    open_controller.open_tab(entry.path.clone(), Vec::new());
+                } else {
+                    nav_manager.handle_double_click(&entry);
+                }
+            },
+        ))
+        .style(|s| {
+            s.size_full()
+                .border(1.0)
+                .border_color(Color::rgb8(200, 200, 200))
+                .border_radius(8.0)
+                .background(Color::rgb8(255, 255, 255))
+        }),
+    ))
+    .on_cleanup(move || {
+        results.borrow_mut().take();
+        // 閉じたタブの場合は書き戻し先がないため失敗しても無視する
+        let _ = scroll_state.update_tab(&tab_id, |tab| {
+            tab.scroll_offset = scroll_offset.get_untracked();
+        });
+    })
+    .style(|s| s.size_full().gap(5.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_smart_folder_status() {
+        assert_eq!(format_smart_folder_status(3, false), "3 件");
+        assert_eq!(
+            format_smart_folder_status(12, true),
+            "12 件（索引を作成中…）"
+        );
+        assert_eq!(
+            format_smart_folder_status(MAX_SMART_FOLDER_RESULTS, false),
+            "10000 件以上"
+        );
+    }
+
+    #[test]
+    fn test_smart_folders_controller() {
+        let state_manager = StateManager::new();
+        let controller =
+            SmartFoldersController::in_memory(state_manager.clone(), SmartFolders::new());
+        let scope = std::env::temp_dir();
+
+        let mut draft = SmartFolderDraft::new("ext:rs", &scope);
+        draft.name = "Rust".to_string();
+        let rust = controller.save_draft(&draft).unwrap();
+        draft.name = "Large".to_string();
+        draft.query = "size:>1GB".to_string();
+        let large = controller.save_draft(&draft).unwrap();
+        draft.query = "size:".to_string();
+        assert!(controller.save_draft(&draft).is_err());
+
+        controller.move_by(&large.id, -1);
+        controller.move_by(&large.id, -1);
+        let names = || {
+            controller.folders().with(|folders| {
+                folders
+                    .folders()
+                    .iter()
+                    .map(|f| f.name.clone())
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(names(), vec!["Large", "Rust"]);
+
+        // 開いたタブは編集した定義に追従する
+        controller.open(&rust.id);
+        let mut edit = SmartFolderDraft::from_folder(&rust);
+        edit.name = "Rust sources".to_string();
+        controller.save_draft(&edit).unwrap();
+        let state = state_manager.get_state().unwrap();
+        let tab = state.get_active_tab().unwrap();
+        assert_eq!(tab.name, "Rust sources");
+        assert_eq!(
+            tab.smart_folder.as_ref().map(|f| f.name.as_str()),
+            Some("Rust sources")
+        );
+        assert_eq!(controller.new_draft(), SmartFolderDraft::new("", &scope));
+
+        controller.remove(&large.id);
+        assert_eq!(names(), vec!["Rust sources"]);
+    }
+}
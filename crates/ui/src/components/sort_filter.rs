@@ -43,6 +43,10 @@ pub struct SortFilterUIManager {
     min_size_input: RwSignal<String>,
     /// 最大サイズ入力
     max_size_input: RwSignal<String>,
+    /// クエリ入力
+    query_input: RwSignal<String>,
+    /// クエリの解析エラー
+    query_error: RwSignal<Option<String>>,
     /// フィルタ変更通知コールバック
     on_filter_change: Option<Box<dyn Fn() + Send + Sync>>,
 }
@@ -50,7 +54,15 @@ pub struct SortFilterUIManager {
 impl SortFilterUIManager {
     /// 新しいUIマネージャーを作成
     pub fn new() -> Self {
-        let manager = Arc::new(std::sync::Mutex::new(FileSortFilterManager::new()));
+        Self::with_config(SortConfig::default(), FilterCriteria::default())
+    }
+
+    /// ソート設定とフィルタ条件を指定してUIマネージャーを作成
+    pub fn with_config(sort: SortConfig, filter: FilterCriteria) -> Self {
+        let mut inner = FileSortFilterManager::new();
+        inner.update_sort_config(sort);
+        inner.update_filter_criteria(filter);
+        let manager = Arc::new(std::sync::Mutex::new(inner));
         let initial_sort = manager.lock().unwrap().sort_config();
         let initial_filter = manager.lock().unwrap().filter_criteria().clone();
 
@@ -74,6 +86,8 @@ impl SortFilterUIManager {
                     .map(|s| s.to_string())
                     .unwrap_or_default(),
             ),
+            query_input: RwSignal::new(initial_filter.query.unwrap_or_default()),
+            query_error: RwSignal::new(None),
             on_filter_change: None,
         }
     }
@@ -156,6 +170,25 @@ impl SortFilterUIManager {
         self.update_filter_criteria(criteria);
     }
 
+    /// クエリを更新
+    ///
+    /// 解析できない場合はエラーを表示し、直前のクエリでの絞り込みを続ける。
+    pub fn update_query(&self, query: String) {
+        self.query_input.set(query.clone());
+        let Ok(mut manager) = self.manager.lock() else {
+            return;
+        };
+        match manager.set_query(&query) {
+            Ok(()) => {
+                self.query_error.set(None);
+                self.filter_criteria.set(manager.filter_criteria().clone());
+                drop(manager);
+                self.notify_change();
+            }
+            Err(e) => self.query_error.set(Some(e.to_string())),
+        }
+    }
+
     /// フィルタをクリア
     pub fn clear_filters(&self) {
         if let Ok(mut manager) = self.manager.lock() {
@@ -166,6 +199,8 @@ impl SortFilterUIManager {
             self.extension_filter_input.set(String::new());
             self.min_size_input.set(String::new());
             self.max_size_input.set(String::new());
+            self.query_input.set(String::new());
+            self.query_error.set(None);
             self.notify_change();
         }
     }
@@ -308,6 +343,7 @@ pub fn filter_toolbar(manager: Arc<SortFilterUIManager>) -> impl IntoView {
             || criteria.extension_filter.is_some()
             || criteria.min_size.is_some()
             || criteria.max_size.is_some()
+            || criteria.query.is_some()
     };
 
     v_stack((
@@ -432,6 +468,39 @@ pub fn filter_toolbar(manager: Arc<SortFilterUIManager>) -> impl IntoView {
             )),
         ))
         .style(|s| s.gap(16).items_end()),
+        // クエリ入力
+        v_stack((
+            label(|| "クエリ (例: ext:rs,toml size:>10MB modified:<7d -path:target)")
+                .style(|s| s.font_size(12)),
+            text_input(manager.query_input)
+                .on_event_stop(floem::event::EventListener::KeyUp, {
+                    let manager = manager.clone();
+                    let input_signal = manager.query_input;
+                    move |_| manager.update_query(input_signal.get())
+                })
+                .style(|s| {
+                    s.width_full()
+                        .padding(6)
+                        .border(1)
+                        .border_color(Color::rgb8(209, 213, 219))
+                        .border_radius(4)
+                }),
+            label({
+                let query_error = manager.query_error;
+                move || query_error.get().unwrap_or_default()
+            })
+            .style({
+                let query_error = manager.query_error;
+                move |s| {
+                    s.font_size(11)
+                        .color(Color::rgb8(239, 68, 68))
+                        .apply_if(query_error.get().is_none(), |s| {
+                            s.display(floem::style::Display::None)
+                        })
+                }
+            }),
+        ))
+        .style(|s| s.gap(4).width_full()),
     ))
     .style(|s| {
         s.gap(12)
@@ -626,4 +695,28 @@ mod tests {
         assert!(!criteria.show_hidden);
         assert!(!manager.has_active_filters());
     }
+
+    #[test]
+    fn test_update_query() {
+        let manager = SortFilterUIManager::new();
+
+        manager.update_query("ext:rs".to_string());
+        assert_eq!(
+            manager.current_filter_criteria().query,
+            Some("ext:rs".to_string())
+        );
+        assert!(manager.query_error.get().is_none());
+
+        // 入力途中の誤りではエラーを表示し、直前のクエリを保つ
+        manager.update_query("ext:rs size:".to_string());
+        assert!(manager.query_error.get().is_some());
+        assert_eq!(
+            manager.current_filter_criteria().query,
+            Some("ext:rs".to_string())
+        );
+
+        manager.clear_filters();
+        assert!(manager.current_filter_criteria().query.is_none());
+        assert!(manager.query_error.get().is_none());
+    }
 }
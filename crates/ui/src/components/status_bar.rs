@@ -3,6 +3,8 @@
 //! アプリケーションのステータスバー部分を提供します。
 
 use floem::prelude::*;
+use floem::reactive::RwSignal;
+use rust_explorer_core::{JobInfo, JobStatus};
 use std::collections::HashMap;
 
 /// ステータスバーコンポーネントの設定
@@ -46,12 +48,36 @@ pub type StatusInfo = HashMap<StatusType, String>;
 
 /// ステータスバーコンポーネントを作成
 pub fn status_bar_component(config: StatusBarConfig, status_info: StatusInfo) -> impl IntoView {
+    status_bar_layout(config, status_info, None)
+}
+
+/// ジョブの進捗を表示するステータスバーコンポーネントを作成
+pub fn status_bar_with_jobs(
+    config: StatusBarConfig,
+    status_info: StatusInfo,
+    jobs: RwSignal<Vec<JobInfo>>,
+) -> impl IntoView {
+    status_bar_layout(config, status_info, Some(jobs))
+}
+
+/// ステータスバーのレイアウト
+fn status_bar_layout(
+    config: StatusBarConfig,
+    status_info: StatusInfo,
+    jobs: Option<RwSignal<Vec<JobInfo>>>,
+) -> impl IntoView {
     let text_color = config.text_color;
     let show_version = config.show_version;
 
     h_stack((
         // 左側エリア
         create_left_area(status_info.clone(), text_color),
+        // ジョブ状況
+        if let Some(jobs) = jobs {
+            create_job_area(jobs, text_color).into_any()
+        } else {
+            container("").into_any()
+        },
         // スペーサー
         container("").style(|s| s.flex_grow(1.0)),
         // 右側エリア
@@ -73,7 +99,8 @@ pub fn default_status_bar() -> impl IntoView {
     let mut status_info = StatusInfo::new();
     status_info.insert(StatusType::Main, "準備完了".to_string());
 
-    status_bar_component(StatusBarConfig::default(), status_info)
+    let jobs = super::job_panel::job_infos_signal();
+    status_bar_with_jobs(StatusBarConfig::default(), status_info, jobs)
 }
 
 /// ファイルエクスプローラー用のステータスバーを作成
@@ -119,6 +146,46 @@ fn create_left_area(status_info: StatusInfo, text_color: Color) -> impl IntoView
     .style(|s| s.items_center())
 }
 
+/// ジョブ状況エリアの作成
+fn create_job_area(jobs: RwSignal<Vec<JobInfo>>, text_color: Color) -> impl IntoView {
+    h_stack((
+        label(|| " | ").style(move |s| s.font_size(12.0).color(text_color.multiply_alpha(0.6))),
+        label(move || jobs.with(|j| job_status_message(j)).unwrap_or_default())
+            .style(move |s| s.font_size(12.0).color(text_color)),
+    ))
+    .style(move |s| {
+        s.items_center()
+            .apply_if(jobs.with(|j| job_status_message(j).is_none()), |s| s.hide())
+    })
+}
+
+/// 実行中ジョブの要約メッセージを作成（実行中のジョブがなければ None）
+pub fn job_status_message(jobs: &[JobInfo]) -> Option<String> {
+    let active: Vec<&JobInfo> = jobs.iter().filter(|j| !j.status.is_finished()).collect();
+    if active.is_empty() {
+        return None;
+    }
+
+    let total_bytes: u64 = active.iter().map(|j| j.progress.total_bytes).sum();
+    let processed_bytes: u64 = active.iter().map(|j| j.progress.processed_bytes).sum();
+    let paused = active
+        .iter()
+        .filter(|j| j.status == JobStatus::Paused)
+        .count();
+
+    let mut message = format!("ジョブ: {}件", active.len());
+    if total_bytes > 0 {
+        message.push_str(&format!(
+            " {}%",
+            (processed_bytes as f64 / total_bytes as f64 * 100.0).round() as u32
+        ));
+    }
+    if paused > 0 {
+        message.push_str(&format!("（{}件一時停止中）", paused));
+    }
+    Some(message)
+}
+
 /// 右側エリアの作成
 fn create_right_area(
     status_info: StatusInfo,
@@ -204,6 +271,36 @@ mod tests {
         assert_eq!(custom, StatusType::Custom("test".to_string()));
     }
 
+    #[test]
+    fn test_job_status_message() {
+        use rust_explorer_core::{JobKind, JobProgress};
+
+        let job = |id, status, processed_bytes| JobInfo {
+            id,
+            kind: JobKind::Delete { paths: vec![] },
+            status,
+            progress: JobProgress {
+                total_bytes: 100,
+                processed_bytes,
+                ..Default::default()
+            },
+        };
+
+        assert_eq!(job_status_message(&[]), None);
+        assert_eq!(
+            job_status_message(&[job(1, JobStatus::Completed, 100)]),
+            None
+        );
+        assert_eq!(
+            job_status_message(&[
+                job(1, JobStatus::Running, 50),
+                job(2, JobStatus::Paused, 0),
+                job(3, JobStatus::Completed, 100),
+            ]),
+            Some("ジョブ: 2件 25%（1件一時停止中）".to_string())
+        );
+    }
+
     #[test]
     fn test_file_explorer_status_creation() {
         let mut status_info = StatusInfo::new();
@@ -0,0 +1,486 @@
+//! タブ
+//!
+//! タブごとにパス・ナビゲーション履歴・ソートとフィルタ・選択・スクロール位置を持ちます。
+//! タブはペインごとに並び、タブの追加・閉じる・複製・並び替え・閉じたタブを開き直す
+//! 操作はすべて `StateManager` を通して行い、セッションとして自動保存されます。
+
+use crate::state_integration::{ReactiveStateManager, ReactiveTabState};
+use crate::theme::get_theme;
+use floem::event::{Event, EventListener};
+use floem::keyboard::{Key, Modifiers, NamedKey};
+use floem::menu::{Menu, MenuItem};
+use floem::reactive::{RwSignal, SignalGet, SignalUpdate, SignalWith, create_effect, create_memo};
+use floem::views::{
+    Decorators, container, dyn_container, dyn_stack, empty, h_stack, label, v_stack,
+};
+use floem::{AnyView, IntoView};
+use rust_explorer_core::{StateManager, TabState, state_utils};
+use rust_explorer_utils::AppError;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use super::error_dialog::display_error_globally;
+
+/// タブ操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TabCommand {
+    /// 新しいタブを開く
+    New,
+    /// アクティブなタブを閉じる
+    Close,
+    /// アクティブなタブを複製
+    Duplicate,
+    /// 最後に閉じたタブを開き直す
+    ReopenClosed,
+    /// 次のタブへ切り替え
+    Next,
+    /// 前のタブへ切り替え
+    Previous,
+    /// アクティブなタブを左へ移動
+    MoveLeft,
+    /// アクティブなタブを右へ移動
+    MoveRight,
+}
+
+/// キー入力に対応するタブ操作（Ctrl と組み合わせたものだけ）
+pub fn tab_command_for_key(key: &Key, modifiers: Modifiers) -> Option<TabCommand> {
+    if !modifiers.control() || modifiers.alt() {
+        return None;
+    }
+    let shift = modifiers.shift();
+    match key {
+        Key::Character(c) if c.eq_ignore_ascii_case("t") => Some(if shift {
+            TabCommand::ReopenClosed
+        } else {
+            TabCommand::New
+        }),
+        Key::Character(c) if c.eq_ignore_ascii_case("w") && !shift => Some(TabCommand::Close),
+        Key::Named(NamedKey::Tab) => Some(if shift {
+            TabCommand::Previous
+        } else {
+            TabCommand::Next
+        }),
+        Key::Named(NamedKey::PageDown) => Some(if shift {
+            TabCommand::MoveRight
+        } else {
+            TabCommand::Next
+        }),
+        Key::Named(NamedKey::PageUp) => Some(if shift {
+            TabCommand::MoveLeft
+        } else {
+            TabCommand::Previous
+        }),
+        _ => None,
+    }
+}
+
+/// `offset` だけ隣のタブの位置（端では反対側へ回り込む）
+pub fn wrapped_index(len: usize, index: usize, offset: isize) -> usize {
+    if len == 0 {
+        return 0;
+    }
+    (index as isize + offset).rem_euclid(len as isize) as usize
+}
+
+/// 1つのペインのタブの管理
+///
+/// タブの操作を `StateManager` へ反映し、ペインのタブ一覧のシグナルを最新に保つ。
+#[derive(Clone)]
+pub struct TabsController {
+    manager: ReactiveStateManager,
+    pane_id: String,
+    tabs: ReactiveTabState,
+    /// 新しいタブで開くディレクトリ
+    default_path: Rc<dyn Fn() -> PathBuf>,
+}
+
+impl TabsController {
+    /// `pane_id` のペインのタブの管理を作成
+    pub fn new(
+        manager: ReactiveStateManager,
+        pane_id: String,
+        default_path: Rc<dyn Fn() -> PathBuf>,
+    ) -> Self {
+        let reactive_state = manager.reactive_state();
+        let tabs = ReactiveTabState::new(Vec::new(), None);
+        reactive_state.with_untracked(|state| tabs.sync_pane(state, &pane_id));
+        let sync_tabs = tabs.clone();
+        let sync_pane_id = pane_id.clone();
+        create_effect(move |_| {
+            reactive_state.with(|state| sync_tabs.sync_pane(state, &sync_pane_id))
+        });
+
+        Self {
+            manager,
+            pane_id,
+            tabs,
+            default_path,
+        }
+    }
+
+    /// ペインID
+    pub fn pane_id(&self) -> &str {
+        &self.pane_id
+    }
+
+    /// ペインのタブ一覧のリアクティブ状態
+    pub fn tabs(&self) -> &ReactiveTabState {
+        &self.tabs
+    }
+
+    /// 内部の状態管理マネージャー
+    pub fn state_manager(&self) -> &StateManager {
+        self.manager.state_manager()
+    }
+
+    /// 指定したタブの状態
+    pub fn tab(&self, tab_id: &str) -> Option<TabState> {
+        self.state_manager()
+            .get_state()
+            .ok()
+            .and_then(|state| state.tabs.into_iter().find(|t| t.id == tab_id))
+    }
+
+    /// タブ操作を実行
+    pub fn run(&self, command: TabCommand) {
+        let active = self.tabs.active_tab_id_signal().get_untracked();
+        match (command, active) {
+            (TabCommand::New, _) => self.new_tab(),
+            (TabCommand::ReopenClosed, _) => self.reopen_closed_tab(),
+            (TabCommand::Close, Some(id)) => self.close_tab(&id),
+            (TabCommand::Duplicate, Some(id)) => self.duplicate_tab(&id),
+            (TabCommand::Next, Some(id)) => self.activate_relative(&id, 1),
+            (TabCommand::Previous, Some(id)) => self.activate_relative(&id, -1),
+            (TabCommand::MoveLeft, Some(id)) => self.move_relative(&id, -1),
+            (TabCommand::MoveRight, Some(id)) => self.move_relative(&id, 1),
+            (_, None) => {}
+        }
+    }
+
+    /// 新しいタブを既定のディレクトリで開く
+    pub fn new_tab(&self) {
+        let tab = self.default_tab();
+        self.apply(|manager| manager.add_tab(tab));
+    }
+
+    /// `path` を開いたタブを追加し、`selected` を選択した状態にする
+    pub fn open_tab(&self, path: PathBuf, selected: Vec<PathBuf>) {
+        let mut tab = self.tab_at(path);
+        tab.selected_paths = selected;
+        self.apply(|manager| manager.add_tab(tab));
+    }
+
+    /// タブを閉じる
+    ///
+    /// ペインの最後のタブを閉じた場合、他にペインがあればペインを閉じ、
+    /// なければ既定のディレクトリで新しいタブを開く。
+    pub fn close_tab(&self, tab_id: &str) {
+        let pane_id = self.pane_id.clone();
+        let default_tab = self.default_tab();
+        self.apply(|manager| {
+            manager.remove_tab(tab_id)?;
+            let state = manager.state_manager().get_state()?;
+            if !state.pane_tabs(&pane_id).is_empty() {
+                return Ok(());
+            }
+            if state.leaf_pane_ids().len() > 1 {
+                manager.close_pane(&pane_id)
+            } else {
+                manager.add_tab(default_tab)
+            }
+        });
+    }
+
+    /// タブを複製
+    pub fn duplicate_tab(&self, tab_id: &str) {
+        self.apply(|manager| manager.duplicate_tab(tab_id).map(|_| ()));
+    }
+
+    /// タブを指定位置へ移動
+    pub fn move_tab(&self, tab_id: &str, index: usize) {
+        self.apply(|manager| manager.move_tab(tab_id, index));
+    }
+
+    /// 最後に閉じたタブを開き直す
+    pub fn reopen_closed_tab(&self) {
+        self.apply(|manager| manager.reopen_closed_tab().map(|_| ()));
+    }
+
+    /// タブをアクティブにする
+    pub fn activate(&self, tab_id: &str) {
+        self.apply(|manager| manager.set_active_tab(tab_id));
+    }
+
+    /// タブの内容を更新（閉じたタブへの更新は無視する）
+    pub fn update_tab<F>(&self, tab_id: &str, updater: F)
+    where
+        F: FnOnce(&mut TabState),
+    {
+        if self.manager.update_tab(tab_id, updater).is_ok() {
+            self.manager.refresh();
+        }
+    }
+
+    /// このペインに開く既定のディレクトリのタブ
+    fn default_tab(&self) -> TabState {
+        self.tab_at((self.default_path)())
+    }
+
+    /// このペインに開く `path` のタブ
+    fn tab_at(&self, path: PathBuf) -> TabState {
+        let mut tab = state_utils::create_default_tab(path);
+        tab.pane_id = Some(self.pane_id.clone());
+        tab
+    }
+
+    /// ペインのタブをタブ全体の並びでの位置へ移動
+    fn move_to_tab_position(&self, tab_id: &str, target_id: &str) {
+        let index = self
+            .manager
+            .reactive_state()
+            .with_untracked(|state| state.tabs.iter().position(|t| t.id == target_id));
+        if let Some(index) = index {
+            self.move_tab(tab_id, index);
+        }
+    }
+
+    /// 隣のタブをアクティブにする
+    fn activate_relative(&self, tab_id: &str, offset: isize) {
+        let next = self.tabs.tabs_signal().with_untracked(|tabs| {
+            let index = tabs.iter().position(|t| t.id == tab_id)?;
+            Some(tabs[wrapped_index(tabs.len(), index, offset)].id.clone())
+        });
+        if let Some(next) = next {
+            self.activate(&next);
+        }
+    }
+
+    /// タブを隣へ移動（端では止まる）
+    fn move_relative(&self, tab_id: &str, offset: isize) {
+        let target = self.tabs.tabs_signal().with_untracked(|tabs| {
+            let index = tabs.iter().position(|t| t.id == tab_id)?;
+            let target = tabs.get(index.checked_add_signed(offset)?)?;
+            Some(target.id.clone())
+        });
+        if let Some(target) = target {
+            self.move_to_tab_position(tab_id, &target);
+        }
+    }
+
+    /// 状態管理マネージャーへの操作を実行し、成功したらすぐに反映
+    fn apply(&self, operation: impl FnOnce(&ReactiveStateManager) -> Result<(), AppError>) {
+        match operation(&self.manager) {
+            Ok(()) => self.manager.refresh(),
+            Err(e) => display_error_globally(&e),
+        }
+    }
+}
+
+/// タブストリップを作成
+///
+/// クリックで切り替え、ドラッグで並び替え、右クリックで複製などのメニューを開く。
+pub fn tab_strip(controller: TabsController) -> impl IntoView {
+    let tabs = controller.tabs().tabs_signal();
+    let active_tab_id = controller.tabs().active_tab_id_signal();
+    let dragging = RwSignal::new(None::<String>);
+    let new_tab = controller.clone();
+
+    h_stack((
+        dyn_stack(
+            move || tabs.get(),
+            |tab| (tab.id.clone(), tab.name.clone()),
+            move |tab| tab_button(controller.clone(), tab, active_tab_id, dragging),
+        )
+        .style(|s| s.gap(2.0).min_width(0.0)),
+        label(|| "+")
+            .on_click_stop(move |_| new_tab.new_tab())
+            .style(|s| {
+                let theme_arc = get_theme();
+                let theme = theme_arc.read().unwrap();
+                s.padding_horiz(theme.spacing.md)
+                    .padding_vert(theme.spacing.xs)
+                    .border_radius(theme.border_radius.sm)
+                    .color(theme.colors.on_surface_variant)
+                    .hover(|s| s.background(theme.colors.hover))
+            }),
+    ))
+    .style(|s| {
+        let theme_arc = get_theme();
+        let theme = theme_arc.read().unwrap();
+        s.width_full()
+            .items_center()
+            .gap(theme.spacing.xs)
+            .padding_bottom(theme.spacing.xs)
+            .border_bottom(1.0)
+            .border_color(theme.colors.border)
+    })
+}
+
+/// タブストリップの1つのタブ
+fn tab_button(
+    controller: TabsController,
+    tab: TabState,
+    active_tab_id: RwSignal<Option<String>>,
+    dragging: RwSignal<Option<String>>,
+) -> impl IntoView {
+    let id = tab.id.clone();
+    let is_active = {
+        let id = id.clone();
+        move || active_tab_id.with(|active| active.as_deref() == Some(id.as_str()))
+    };
+
+    let close = {
+        let controller = controller.clone();
+        let id = id.clone();
+        label(|| "×")
+            .on_click_stop(move |_| controller.close_tab(&id))
+            .style(|s| {
+                let theme_arc = get_theme();
+                let theme = theme_arc.read().unwrap();
+                s.padding_horiz(theme.spacing.xs)
+                    .border_radius(theme.border_radius.sm)
+                    .color(theme.colors.on_surface_variant)
+                    .hover(|s| s.background(theme.colors.hover))
+            })
+    };
+
+    let activate = controller.clone();
+    let drag_id = id.clone();
+    let drop_target = controller.clone();
+    let drop_id = id.clone();
+    let menu = controller;
+    let menu_id = id.clone();
+
+    h_stack((
+        label(move || tab.name.clone()).style(|s| s.min_width(0.0).text_ellipsis()),
+        close,
+    ))
+    .on_click_stop(move |_| activate.activate(&id))
+    .draggable()
+    .on_event_stop(EventListener::DragStart, move |_| {
+        dragging.set(Some(drag_id.clone()));
+    })
+    .on_event_stop(EventListener::DragEnd, move |_| dragging.set(None))
+    .on_event_stop(EventListener::Drop, move |_| {
+        if let Some(dragged) = dragging.get_untracked()
+            && dragged != drop_id
+        {
+            // ドロップ先のタブの位置へ移動
+            drop_target.move_to_tab_position(&dragged, &drop_id);
+        }
+        dragging.set(None);
+    })
+    .context_menu(move || {
+        let (duplicate, close, reopen) = (menu.clone(), menu.clone(), menu.clone());
+        let (duplicate_id, close_id) = (menu_id.clone(), menu_id.clone());
+        Menu::new("")
+            .entry(
+                MenuItem::new("タブを複製").action(move || duplicate.duplicate_tab(&duplicate_id)),
+            )
+            .entry(MenuItem::new("タブを閉じる").action(move || close.close_tab(&close_id)))
+            .separator()
+            .entry(MenuItem::new("閉じたタブを開き直す").action(move || reopen.reopen_closed_tab()))
+    })
+    .style(move |s| {
+        let theme_arc = get_theme();
+        let theme = theme_arc.read().unwrap();
+        let s = s
+            .items_center()
+            .gap(theme.spacing.xs)
+            .max_width(200.0)
+            .padding_horiz(theme.spacing.md)
+            .padding_vert(theme.spacing.xs)
+            .border_radius(theme.border_radius.sm)
+            .font_size(theme.typography.body_small);
+        if is_active() {
+            s.background(theme.colors.selected)
+                .color(theme.colors.on_surface)
+        } else {
+            s.color(theme.colors.on_surface_variant)
+                .hover(|s| s.background(theme.colors.hover))
+        }
+    })
+}
+
+/// タブストリップとアクティブなタブの内容を表示するビューを作成
+///
+/// 内容はアクティブなタブが切り替わるたびに `content` で作り直す。
+/// Ctrl+T・Ctrl+W・Ctrl+Shift+T・Ctrl+Tab などのショートカットを受け付ける。
+pub fn tabbed_view<V: IntoView + 'static>(
+    controller: TabsController,
+    content: impl Fn(TabsController, TabState) -> V + 'static,
+) -> impl IntoView {
+    let active_tab_id = controller.tabs().active_tab_id_signal();
+    // 同じタブのまま状態が同期された場合は作り直さない
+    let active = create_memo(move |_| active_tab_id.get());
+    let content_controller = controller.clone();
+    let key_controller = controller.clone();
+
+    v_stack((
+        tab_strip(controller),
+        container(dyn_container(
+            move || active.get(),
+            move |tab_id| -> AnyView {
+                match tab_id.and_then(|id| content_controller.tab(&id)) {
+                    Some(tab) => content(content_controller.clone(), tab).into_any(),
+                    None => empty().into_any(),
+                }
+            },
+        ))
+        .style(|s| s.size_full().min_height(0.0)),
+    ))
+    .on_event_cont(EventListener::KeyDown, move |event| {
+        if let Event::KeyDown(key_event) = event
+            && let Some(command) =
+                tab_command_for_key(&key_event.key.logical_key, key_event.modifiers)
+        {
+            key_controller.run(command);
+        }
+    })
+    .style(|s| s.size_full().gap(8.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tab_command_for_key() {
+        let ctrl = Modifiers::CONTROL;
+        let ctrl_shift = Modifiers::CONTROL | Modifiers::SHIFT;
+
+        assert_eq!(
+            tab_command_for_key(&Key::Character("t".into()), ctrl),
+            Some(TabCommand::New)
+        );
+        // Shift 併用時は大文字で届くことがある
+        assert_eq!(
+            tab_command_for_key(&Key::Character("T".into()), ctrl_shift),
+            Some(TabCommand::ReopenClosed)
+        );
+        assert_eq!(
+            tab_command_for_key(&Key::Character("w".into()), ctrl),
+            Some(TabCommand::Close)
+        );
+        assert_eq!(
+            tab_command_for_key(&Key::Named(NamedKey::Tab), ctrl_shift),
+            Some(TabCommand::Previous)
+        );
+        assert_eq!(
+            tab_command_for_key(&Key::Named(NamedKey::PageDown), ctrl_shift),
+            Some(TabCommand::MoveRight)
+        );
+        assert_eq!(
+            tab_command_for_key(&Key::Character("t".into()), Modifiers::empty()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_wrapped_index() {
+        assert_eq!(wrapped_index(3, 2, 1), 0);
+        assert_eq!(wrapped_index(3, 0, -1), 2);
+        assert_eq!(wrapped_index(3, 1, 1), 2);
+        assert_eq!(wrapped_index(0, 0, 1), 0);
+    }
+}
@@ -0,0 +1,430 @@
+//! タグ（色ラベル）
+//!
+//! ファイルやフォルダに付けたタグの付け外し、色のチップ表示、タグごとの
+//! 仮想フォルダ（タグの付いた項目の一覧タブ）を提供します。タグは状態ディレクトリの
+//! JSON に保存スレッドで保存し、設定に応じて拡張属性 `user.xdg.tags` にも書き出します。
+
+use super::error_dialog::display_error_globally;
+use super::sort_filter::{SortFilterUIManager, simple_filter_bar};
+use super::tabs::TabsController;
+use super::user_data::{TAGS_SAVE, follow_job_moves};
+use super::virtual_file_list::virtual_file_list_with_scroll;
+use super::{FileNavigationManager, ModernFileItemConfig};
+use floem::menu::{Menu, MenuItem};
+use floem::prelude::*;
+use floem::reactive::{RwSignal, create_effect, create_memo};
+use rust_explorer_config::state_helpers;
+use rust_explorer_core::{
+    FileEntry, FileType, StateManager, TabState, TagColor, TagStore, state_utils, write_xattr_tags,
+};
+use rust_explorer_utils::AppError;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// タグの色をfloemの色に変換
+pub fn tag_color(color: TagColor) -> Color {
+    let (r, g, b) = color.rgb();
+    Color::rgb8(r, g, b)
+}
+
+/// タグの定義と割り当ての操作（変更は保存し、設定に応じて拡張属性にも書き出す）
+#[derive(Clone)]
+pub struct TagsController {
+    store: RwSignal<TagStore>,
+    state_manager: StateManager,
+    persist: bool,
+    mirror_xattr: bool,
+}
+
+impl TagsController {
+    /// 保存されたタグを読み込んで作成し、アプリ内での移動に追随させる
+    pub fn load(state_manager: StateManager, mirror_xattr: bool) -> Self {
+        let store = match state_helpers::tags_exist() {
+            Ok(true) => state_helpers::load_tags().unwrap_or_else(|e| {
+                display_error_globally(&e);
+                TagStore::new()
+            }),
+            Ok(false) => TagStore::new(),
+            Err(e) => {
+                display_error_globally(&e);
+                TagStore::new()
+            }
+        };
+        let controller = Self {
+            store: RwSignal::new(store),
+            state_manager,
+            persist: true,
+            mirror_xattr,
+        };
+        // 移動ジョブで移動した項目のタグを付け替える
+        let moved = controller.clone();
+        follow_job_moves(move |from, to| moved.relocate(from, to));
+        controller
+    }
+
+    /// 保存しないタグで作成
+    pub fn in_memory(state_manager: StateManager, store: TagStore) -> Self {
+        Self {
+            store: RwSignal::new(store),
+            state_manager,
+            persist: false,
+            mirror_xattr: false,
+        }
+    }
+
+    /// タグの定義と割り当て
+    pub fn store(&self) -> RwSignal<TagStore> {
+        self.store
+    }
+
+    /// タグを定義する
+    pub fn define(&self, name: &str, color: TagColor) -> Result<(), AppError> {
+        let mut store = self.store.get_untracked();
+        store.define(name, color)?;
+        self.commit(store);
+        Ok(())
+    }
+
+    /// タグの色を変える
+    pub fn recolor(&self, name: &str, color: TagColor) {
+        let mut store = self.store.get_untracked();
+        match store.recolor(name, color) {
+            Ok(()) => self.commit(store),
+            Err(e) => display_error_globally(&e),
+        }
+    }
+
+    /// タグを削除する（割り当ても外す）
+    pub fn remove_tag(&self, name: &str) {
+        let mut store = self.store.get_untracked();
+        match store.remove_tag(name) {
+            Ok(affected) => {
+                self.commit(store);
+                self.mirror(&affected);
+            }
+            Err(e) => display_error_globally(&e),
+        }
+    }
+
+    /// 項目にタグを付け外しする（すべてに付いていれば外し、そうでなければ付ける）
+    pub fn toggle(&self, paths: &[PathBuf], name: &str) {
+        if paths.is_empty() {
+            return;
+        }
+        let mut store = self.store.get_untracked();
+        let Some(tag_name) = store.tag(name).map(|tag| tag.name.clone()) else {
+            return;
+        };
+        let tagged = paths
+            .iter()
+            .all(|path| store.names_for(path).contains(&tag_name));
+        let result = paths.iter().try_for_each(|path| {
+            if tagged {
+                store.unassign(path, name);
+                Ok(())
+            } else {
+                store.assign(path, name)
+            }
+        });
+        match result {
+            Ok(()) => {
+                self.commit(store);
+                self.mirror(paths);
+            }
+            Err(e) => display_error_globally(&e),
+        }
+    }
+
+    /// 名前変更・移動した項目のタグを付け替える
+    pub fn relocate(&self, from: &std::path::Path, to: &std::path::Path) {
+        let mut store = self.store.get_untracked();
+        if store.relocate(from, to) {
+            self.commit(store);
+        }
+    }
+
+    /// タグの付いた項目を一覧するタブを開く
+    pub fn open(&self, name: &str) {
+        let Some(tag) = self.store.with_untracked(|store| store.tag(name).cloned()) else {
+            return;
+        };
+        let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("/"));
+        let tab = state_utils::create_tag_tab(&tag, home);
+        if let Err(e) = self.state_manager.add_tab(tab) {
+            display_error_globally(&e);
+        }
+    }
+
+    /// 反映し、保存を予約する
+    fn commit(&self, store: TagStore) {
+        if self.persist {
+            TAGS_SAVE.save(store.clone());
+        }
+        self.store.set(store);
+    }
+
+    /// 項目のタグを拡張属性に書き出す（失敗は最初の1件だけ表示する）
+    fn mirror(&self, paths: &[PathBuf]) {
+        if !self.mirror_xattr {
+            return;
+        }
+        let failed = self.store.with_untracked(|store| {
+            paths
+                .iter()
+                .filter(|path| path.exists())
+                .find_map(|path| write_xattr_tags(path, store.names_for(path)).err())
+        });
+        if let Some(e) = failed {
+            display_error_globally(&e);
+        }
+    }
+}
+
+/// 項目に付いたタグの色のチップ
+pub fn tag_chips(tags: RwSignal<TagStore>, path: PathBuf) -> impl IntoView {
+    dyn_stack(
+        move || {
+            tags.with(|store| {
+                store
+                    .tags_for(&path)
+                    .into_iter()
+                    .map(|tag| (tag.name.clone(), tag.color))
+                    .collect::<Vec<_>>()
+            })
+        },
+        |(name, color)| (name.clone(), *color),
+        |(_, color)| {
+            empty().style(move |s| {
+                s.width(10.0)
+                    .height(10.0)
+                    .border_radius(5.0)
+                    .background(tag_color(color))
+            })
+        },
+    )
+    .style(|s| s.gap(3.0).items_center())
+}
+
+/// 選択した項目にタグを付け外しするメニュー（付いているタグには ✓ を付ける）
+pub fn tag_menu(controller: &TagsController, paths: Vec<PathBuf>) -> Menu {
+    let store = controller.store().get_untracked();
+    let mut menu = Menu::new("");
+    if store.tags().is_empty() {
+        return menu
+            .entry(MenuItem::new("タグがありません（サイドバーで作成できます）").enabled(false));
+    }
+    for tag in store.tags() {
+        let tagged = !paths.is_empty()
+            && paths
+                .iter()
+                .all(|path| store.names_for(path).contains(&tag.name));
+        let label = format!("{} {}", if tagged { "✓" } else { "　" }, tag.name);
+        let toggle = controller.clone();
+        let name = tag.name.clone();
+        let paths = paths.clone();
+        menu = menu.entry(
+            MenuItem::new(label)
+                .enabled(!paths.is_empty())
+                .action(move || toggle.toggle(&paths, &name)),
+        );
+    }
+    menu
+}
+
+/// タグの付いた項目を一覧するタブの内容
+///
+/// タグの割り当てが変わると一覧を作り直す。存在しなくなった項目は表示しない。
+pub fn tag_content(
+    controller: TabsController,
+    tab: TabState,
+    tags: TagsController,
+) -> impl IntoView {
+    let tab_id = tab.id.clone();
+    let tag_name = tab.tag.clone().unwrap_or_default();
+    let store = tags.store();
+
+    let definition_name = tag_name.clone();
+    let definition = create_memo(move |_| store.with(|store| store.tag(&definition_name).cloned()));
+    let found_name = tag_name.clone();
+    let found = create_memo(move |_| store.with(|store| store.paths_with(&found_name)));
+
+    // ソート・フィルタはタブへ書き戻す
+    let sort_filter_manager = Arc::new(SortFilterUIManager::with_config(
+        tab.sort,
+        tab.filter.clone(),
+    ));
+    let sort_filter_for_tab = sort_filter_manager.clone();
+    let sort_controller = controller.clone();
+    let sort_tab_id = tab_id.clone();
+    create_effect(move |previous: Option<()>| {
+        let sort = sort_filter_for_tab.current_sort_config();
+        let filter = sort_filter_for_tab.current_filter_criteria();
+        if previous.is_some() {
+            sort_controller.update_tab(&sort_tab_id, |tab| {
+                tab.sort = sort;
+                tab.filter = filter;
+            });
+        }
+    });
+
+    // 一覧が変わったら並べ直し、一覧から消えた項目の選択を外す
+    let selection = RwSignal::new(tab.selected_paths.iter().cloned().collect::<HashSet<_>>());
+    let entries = RwSignal::new(Vec::<FileEntry>::new());
+    let sort_filter_for_list = sort_filter_manager.clone();
+    create_effect(move |_| {
+        sort_filter_for_list.current_sort_config();
+        sort_filter_for_list.current_filter_criteria();
+        let mut listed: Vec<FileEntry> = found.with(|paths| {
+            paths
+                .iter()
+                .filter_map(|path| FileEntry::from_path(path))
+                .collect()
+        });
+        sort_filter_for_list.process_entries(&mut listed);
+        let paths: HashSet<&PathBuf> = listed.iter().map(|entry| &entry.path).collect();
+        if selection.with_untracked(|selection| selection.iter().any(|p| !paths.contains(p))) {
+            selection.update(|selection| selection.retain(|path| paths.contains(path)));
+        }
+        entries.set(listed);
+    });
+
+    let selection_controller = controller.clone();
+    let selection_tab_id = tab_id.clone();
+    create_effect(move |previous: Option<()>| {
+        let selected: Vec<PathBuf> =
+            selection.with(|selection| selection.iter().cloned().collect());
+        if previous.is_some() {
+            selection_controller.update_tab(&selection_tab_id, |tab| {
+                tab.selected_paths = selected;
+            });
+        }
+    });
+
+    let scroll_offset = RwSignal::new(tab.scroll_offset);
+    let scroll_state = controller.state_manager().clone();
+
+    let nav_manager = Arc::new(
+        FileNavigationManager::with_default(tab.current_path.clone()).on_error(|error| {
+            eprintln!("ナビゲーションエラー: {}", error);
+        }),
+    );
+    let open_controller = controller.clone();
+    let menu_tags = tags.clone();
+
+    v_stack((
+        // タグの色・名前と件数
+        h_stack((
+            empty().style(move |s| {
+                let color = definition
+                    .get()
+                    .map(|tag| tag_color(tag.color))
+                    .unwrap_or(Color::TRANSPARENT);
+                s.width(12.0)
+                    .height(12.0)
+                    .border_radius(6.0)
+                    .background(color)
+            }),
+            label(move || format!("タグ: {}", tag_name))
+                .style(|s| s.font_size(14.0).font_weight(floem::text::Weight::BOLD)),
+            container("").style(|s| s.flex_grow(1.0)),
+            label(move || match definition.get() {
+                Some(_) => format!("{} 件", entries.with(Vec::len)),
+                None => "タグは削除されました".to_string(),
+            })
+            .style(|s| s.font_size(12.0).color(Color::rgb8(107, 114, 128))),
+            label(|| "タグ ▾")
+                .popout_menu(move || {
+                    let paths = selection.with_untracked(|s| s.iter().cloned().collect());
+                    tag_menu(&menu_tags, paths)
+                })
+                .style(|s| s.font_size(12.0).padding_horiz(6.0)),
+        ))
+        .style(|s| s.gap(8.0).items_center().width_full().margin_bottom(8.0)),
+        simple_filter_bar(sort_filter_manager.clone()).style(|s| s.margin_bottom(8.0)),
+        // 一覧（ディレクトリは通常のタブで開く）
+        container(virtual_file_list_with_scroll(
+            entries,
+            selection,
+            scroll_offset,
+            ModernFileItemConfig {
+                tags: Some(store),
+                ..ModernFileItemConfig::default()
+            },
+            move |entry| {
+                if entry.file_type == FileType::Directory {
+                    open_controller.open_tab(entry.path.clone(), Vec::new());
+                } else {
+                    nav_manager.handle_double_click(&entry);
+                }
+            },
+        ))
+        .style(|s| {
+            s.size_full()
+                .border(1.0)
+                .border_color(Color::rgb8(200, 200, 200))
+                .border_radius(8.0)
+                .background(Color::rgb8(255, 255, 255))
+        }),
+    ))
+    .on_cleanup(move || {
+        // 閉じたタブの場合は書き戻し先がないため失敗しても無視する
+        let _ = scroll_state.update_tab(&tab_id, |tab| {
+            tab.scroll_offset = scroll_offset.get_untracked();
+        });
+    })
+    .style(|s| s.size_full().gap(5.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tags_controller() {
+        let state_manager = StateManager::new();
+        let controller = TagsController::in_memory(state_manager.clone(), TagStore::new());
+        controller.define("Work", TagColor::Blue).unwrap();
+        assert!(controller.define("work", TagColor::Red).is_err());
+
+        let a = PathBuf::from("/docs/a.txt");
+        let b = PathBuf::from("/docs/b.txt");
+        controller.toggle(std::slice::from_ref(&a), "Work");
+        // 一部にだけ付いていればすべてに付ける
+        controller.toggle(&[a.clone(), b.clone()], "Work");
+        let store = controller.store().get_untracked();
+        assert_eq!(store.paths_with("Work"), vec![a.clone(), b.clone()]);
+        // すべてに付いていれば外す
+        controller.toggle(&[a.clone(), b.clone()], "Work");
+        assert!(
+            controller
+                .store()
+                .get_untracked()
+                .paths_with("Work")
+                .is_empty()
+        );
+
+        controller.toggle(std::slice::from_ref(&a), "Work");
+        controller.relocate(&PathBuf::from("/docs"), &PathBuf::from("/archive"));
+        assert_eq!(
+            controller.store().get_untracked().paths_with("Work"),
+            vec![PathBuf::from("/archive/a.txt")]
+        );
+
+        controller.recolor("Work", TagColor::Green);
+        controller.open("Work");
+        let state = state_manager.get_state().unwrap();
+        assert_eq!(
+            state.tabs.last().and_then(|tab| tab.tag.as_deref()),
+            Some("Work")
+        );
+
+        controller.remove_tag("Work");
+        assert!(controller.store().get_untracked().tags().is_empty());
+    }
+
+    #[test]
+    fn test_tag_color() {
+        assert_eq!(tag_color(TagColor::Red), Color::rgb8(239, 68, 68));
+    }
+}
@@ -0,0 +1,213 @@
+//! ファイル操作の元に戻す・やり直し
+//!
+//! 移動ジョブで移動した項目を元に戻す履歴に記録します（画面から名前の変更や
+//! ゴミ箱への移動を行う操作はまだないため、記録するのは移動ジョブだけです）。
+//! 元に戻す・やり直しはファイルの移動を伴うため、UIスレッドを止めないよう
+//! 別スレッドで行います。
+
+use super::error_dialog::display_error_globally;
+use super::job_panel::global_job_manager;
+use floem::ext_event::create_ext_action;
+use floem::keyboard::{Key, Modifiers};
+use floem::reactive::Scope;
+use rust_explorer_config::state_helpers;
+use rust_explorer_core::{FileSystemManager, JobEvent, JournalEntry, UndoJournal};
+use rust_explorer_utils::AppError;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// 元に戻す・やり直しの操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UndoCommand {
+    /// 直前の操作を元に戻す
+    Undo,
+    /// 元に戻した操作をやり直す
+    Redo,
+}
+
+/// キー入力に対応する操作（Ctrl+Z で元に戻し、Ctrl+Y か Ctrl+Shift+Z でやり直す）
+pub fn undo_command_for_key(key: &Key, modifiers: Modifiers) -> Option<UndoCommand> {
+    if !modifiers.control() || modifiers.alt() {
+        return None;
+    }
+    match key {
+        Key::Character(c) if c.eq_ignore_ascii_case("z") => Some(if modifiers.shift() {
+            UndoCommand::Redo
+        } else {
+            UndoCommand::Undo
+        }),
+        Key::Character(c) if c.eq_ignore_ascii_case("y") && !modifiers.shift() => {
+            Some(UndoCommand::Redo)
+        }
+        _ => None,
+    }
+}
+
+/// アプリケーションとウィンドウで共有する元に戻す履歴
+///
+/// 移動ジョブのイベントはワーカースレッドから届くため、履歴はロックして共有する。
+/// ファイルを戻している間は履歴のロックを外し、移動の記録を止めない。
+#[derive(Clone, Default)]
+pub struct UndoController {
+    journal: Arc<Mutex<UndoJournal>>,
+    /// 元に戻す・やり直しを1つずつ行うためのロック
+    running: Arc<Mutex<()>>,
+    /// 未保存の変更があるか
+    dirty: Arc<AtomicBool>,
+}
+
+impl UndoController {
+    /// 保存された履歴を読み込んで作成（読めない場合は空の履歴）
+    pub fn load() -> Self {
+        Self::new(state_helpers::load_undo_journal().unwrap_or_default())
+    }
+
+    /// 履歴を指定して作成
+    pub fn new(journal: UndoJournal) -> Self {
+        Self {
+            journal: Arc::new(Mutex::new(journal)),
+            running: Arc::new(Mutex::new(())),
+            dirty: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// 履歴への参照を取得
+    pub fn journal(&self) -> MutexGuard<'_, UndoJournal> {
+        self.journal.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// 履歴への可変参照を取得（変更は次回保存時に永続化される）
+    pub fn journal_mut(&self) -> MutexGuard<'_, UndoJournal> {
+        self.dirty.store(true, Ordering::SeqCst);
+        self.journal()
+    }
+
+    /// 移動ジョブで移動した項目を記録し、ジョブが終わるたびに保存する
+    pub fn record_job_moves(&self) -> Result<(), AppError> {
+        let controller = self.clone();
+        global_job_manager().on_job_event(move |event| match event {
+            JobEvent::Moved { from, to, .. } => controller.journal_mut().record_moved(from, to),
+            JobEvent::Completed(_) | JobEvent::Cancelled(_) | JobEvent::Failed { .. } => {
+                if let Err(e) = controller.save() {
+                    display_error_globally(&e);
+                }
+            }
+            _ => {}
+        })
+    }
+
+    /// 元に戻す・やり直しを行い、終わるまで待って保存する
+    pub fn run(&self, command: UndoCommand) -> Result<JournalEntry, AppError> {
+        let _running = self.running.lock().unwrap_or_else(PoisonError::into_inner);
+        let filesystem = FileSystemManager::new();
+        let manager = global_job_manager();
+        let result = match command {
+            UndoCommand::Undo => {
+                let entry = self.journal_mut().take_undo()?;
+                let result = manager.block_on(entry.revert(&filesystem));
+                self.journal_mut().finish_undo(entry, result)
+            }
+            UndoCommand::Redo => {
+                let entry = self.journal_mut().take_redo()?;
+                let result = manager.block_on(entry.reapply(&filesystem));
+                self.journal_mut().finish_redo(entry, result)
+            }
+        };
+        self.save()?;
+        result
+    }
+
+    /// 元に戻す・やり直す操作があるか
+    pub fn can_run(&self, command: UndoCommand) -> bool {
+        let journal = self.journal();
+        match command {
+            UndoCommand::Undo => journal.can_undo(),
+            UndoCommand::Redo => journal.can_redo(),
+        }
+    }
+
+    /// 別スレッドで元に戻す・やり直しを行い、失敗したらエラーを表示する
+    ///
+    /// 元に戻す・やり直す操作がなければ何もしない。
+    pub fn run_in_background(&self, command: UndoCommand) {
+        if !self.can_run(command) {
+            return;
+        }
+        let show = create_ext_action(
+            Scope::current(),
+            |result: Result<JournalEntry, AppError>| {
+                if let Err(e) = result {
+                    display_error_globally(&e);
+                }
+            },
+        );
+        let controller = self.clone();
+        std::thread::spawn(move || show(controller.run(command)));
+    }
+
+    /// 履歴に変更があれば保存
+    pub fn save(&self) -> Result<(), AppError> {
+        if self.dirty.swap(false, Ordering::SeqCst) {
+            let journal = self.journal();
+            if let Err(e) = state_helpers::save_undo_journal(&*journal) {
+                self.dirty.store(true, Ordering::SeqCst);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_undo_command_for_key() {
+        let ctrl = Modifiers::CONTROL;
+        let ctrl_shift = Modifiers::CONTROL | Modifiers::SHIFT;
+        assert_eq!(
+            undo_command_for_key(&Key::Character("z".into()), ctrl),
+            Some(UndoCommand::Undo)
+        );
+        assert_eq!(
+            undo_command_for_key(&Key::Character("Z".into()), ctrl_shift),
+            Some(UndoCommand::Redo)
+        );
+        assert_eq!(
+            undo_command_for_key(&Key::Character("y".into()), ctrl),
+            Some(UndoCommand::Redo)
+        );
+        assert_eq!(
+            undo_command_for_key(&Key::Character("z".into()), Modifiers::empty()),
+            None
+        );
+        assert_eq!(
+            undo_command_for_key(&Key::Character("z".into()), ctrl | Modifiers::ALT),
+            None
+        );
+    }
+
+    #[test]
+    fn test_undo_controller_shares_journal() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let from = temp_dir.path().join("a.txt");
+        let to = temp_dir.path().join("b.txt");
+        std::fs::write(&from, "content").unwrap();
+        std::fs::rename(&from, &to).unwrap();
+
+        let controller = UndoController::default();
+        assert!(!controller.can_run(UndoCommand::Undo));
+        controller.clone().journal_mut().record_moved(&from, &to);
+        assert!(controller.can_run(UndoCommand::Undo));
+
+        // 保存せずに元に戻す（保存は実際の設定ディレクトリに書くため試さない）
+        let entry = global_job_manager()
+            .block_on(controller.journal().undo(&FileSystemManager::new()))
+            .unwrap();
+        assert!(entry.operation.description().contains("a.txt"));
+        assert!(Path::new(&from).exists());
+        assert!(controller.journal().can_redo());
+    }
+}
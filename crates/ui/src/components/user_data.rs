@@ -0,0 +1,64 @@
+//! サイドバーで編集するデータの保存と、移動した場所への追随
+//!
+//! タグ・ブックマーク・スマートフォルダ・訪れた場所の履歴は、変更のたびにUIスレッドで
+//! 書き込まないよう、どれも保存スレッドでまとめて保存します。終了時は `flush_user_data`
+//! で保存を待っている内容を書き出します。移動ジョブで移動した場所への追随は、
+//! ジョブパネルと同じジョブイベントの購読を共有します。
+
+use super::job_panel::job_events_signal;
+use floem::reactive::{SignalWith, create_effect};
+use rust_explorer_config::{DeferredSave, state_helpers};
+use rust_explorer_core::{Bookmarks, JobEvent, LocationHistory, SmartFolders, TagStore};
+use rust_explorer_utils::AppError;
+use std::path::Path;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+/// 変更をまとめて保存するまでの時間
+const SAVE_DELAY: Duration = Duration::from_secs(2);
+
+/// タグの保存
+pub(crate) static TAGS_SAVE: LazyLock<DeferredSave<TagStore>> =
+    LazyLock::new(|| DeferredSave::new(SAVE_DELAY, state_helpers::save_tags::<TagStore>));
+
+/// ブックマークの保存
+pub(crate) static BOOKMARKS_SAVE: LazyLock<DeferredSave<Bookmarks>> =
+    LazyLock::new(|| DeferredSave::new(SAVE_DELAY, state_helpers::save_bookmarks::<Bookmarks>));
+
+/// スマートフォルダの保存
+pub(crate) static SMART_FOLDERS_SAVE: LazyLock<DeferredSave<SmartFolders>> = LazyLock::new(|| {
+    DeferredSave::new(
+        SAVE_DELAY,
+        state_helpers::save_smart_folders::<SmartFolders>,
+    )
+});
+
+/// 訪れた場所の履歴の保存
+pub(crate) static HISTORY_SAVE: LazyLock<DeferredSave<LocationHistory>> =
+    LazyLock::new(|| DeferredSave::new(SAVE_DELAY, state_helpers::save_history::<LocationHistory>));
+
+/// 保存を待っている内容をすべて書き出す（終了時に呼ぶ）
+///
+/// 保存スレッドが書き込み中のものは、その書き込みが終わるまで待つ。
+/// 1つが失敗しても残りは書き出し、最初のエラーを返す。
+pub fn flush_user_data() -> Result<(), AppError> {
+    let results = [
+        TAGS_SAVE.flush(),
+        BOOKMARKS_SAVE.flush(),
+        SMART_FOLDERS_SAVE.flush(),
+        HISTORY_SAVE.flush(),
+    ];
+    results.into_iter().collect()
+}
+
+/// 移動ジョブで項目が移動するたびに `relocate(移動元, 移動先)` を呼ぶ
+pub fn follow_job_moves(relocate: impl Fn(&Path, &Path) + 'static) {
+    let events = job_events_signal();
+    create_effect(move |_| {
+        events.with(|event| {
+            if let Some(JobEvent::Moved { from, to, .. }) = event {
+                relocate(from, to);
+            }
+        });
+    });
+}
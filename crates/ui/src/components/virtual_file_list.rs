@@ -0,0 +1,352 @@
+//! 仮想化されたファイル一覧
+//!
+//! 表示範囲にある行だけビューを作成するため、項目数が多いディレクトリでも
+//! スクロールが重くなりません。選択はパスで管理し、並べ替えや差分の反映で
+//! 項目の位置が変わっても選択が保たれます。
+
+use floem::View;
+use floem::event::{Event, EventListener};
+use floem::kurbo::Point;
+use floem::reactive::{RwSignal, SignalGet, SignalUpdate, SignalWith};
+use floem::views::{
+    Decorators, VirtualDirection, VirtualItemSize, VirtualVector, h_stack_from_iter, scroll,
+    virtual_stack,
+};
+use rust_explorer_core::{DirectoryChange, DirectoryDelta, FileEntry};
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::SystemTime;
+
+use super::modern_file_item::{FileItemDisplayMode, ModernFileItemConfig, modern_file_item_view};
+
+/// グリッド表示のセルの幅と高さ（アイテム 120px と余白）
+pub const GRID_CELL_SIZE: f64 = 128.0;
+
+/// 表示モードごとの1行の高さ
+pub fn row_height(mode: FileItemDisplayMode) -> f64 {
+    match mode {
+        FileItemDisplayMode::List => 40.0,
+        FileItemDisplayMode::Grid => GRID_CELL_SIZE,
+        FileItemDisplayMode::Compact => 28.0,
+    }
+}
+
+/// 表示幅に収まるグリッドの列数（最低1列）
+pub fn grid_columns(width: f64) -> usize {
+    ((width / GRID_CELL_SIZE).floor() as usize).max(1)
+}
+
+/// 1つのセル（項目と選択状態）
+#[derive(Debug, Clone)]
+pub struct FileCell {
+    pub entry: FileEntry,
+    pub selected: bool,
+}
+
+/// 1行分のセル（リスト表示では1つ、グリッド表示では列数分）
+#[derive(Debug, Clone)]
+pub struct FileRow {
+    /// 行の先頭の項目のインデックス
+    pub start: usize,
+    pub cells: Vec<FileCell>,
+}
+
+/// 行のビューを作り直すか判定するためのキー
+type CellKey = (PathBuf, u64, Option<SystemTime>, bool);
+
+impl FileRow {
+    /// 行の内容を識別するキー（メタデータや選択が変わった行だけ作り直す）
+    fn key(&self) -> Vec<CellKey> {
+        self.cells
+            .iter()
+            .map(|cell| {
+                (
+                    cell.entry.path.clone(),
+                    cell.entry.size,
+                    cell.entry.modified,
+                    cell.selected,
+                )
+            })
+            .collect()
+    }
+}
+
+/// 一覧を行単位で切り出すビュー（表示範囲の分だけ項目を複製する）
+pub struct FileRows {
+    entries: RwSignal<Vec<FileEntry>>,
+    selection: RwSignal<HashSet<PathBuf>>,
+    columns: usize,
+}
+
+impl FileRows {
+    pub fn new(
+        entries: RwSignal<Vec<FileEntry>>,
+        selection: RwSignal<HashSet<PathBuf>>,
+        columns: usize,
+    ) -> Self {
+        Self {
+            entries,
+            selection,
+            columns: columns.max(1),
+        }
+    }
+}
+
+impl VirtualVector<FileRow> for FileRows {
+    fn total_len(&self) -> usize {
+        self.entries
+            .with(|entries| entries.len().div_ceil(self.columns))
+    }
+
+    fn slice(&mut self, range: Range<usize>) -> impl Iterator<Item = FileRow> {
+        let columns = self.columns;
+        let rows: Vec<FileRow> = self.entries.with(|entries| {
+            self.selection.with(|selection| {
+                let start = (range.start * columns).min(entries.len());
+                let end = (range.end * columns).min(entries.len());
+                entries[start..end]
+                    .chunks(columns)
+                    .enumerate()
+                    .map(|(i, chunk)| FileRow {
+                        start: start + i * columns,
+                        cells: chunk
+                            .iter()
+                            .map(|entry| FileCell {
+                                selected: selection.contains(&entry.path),
+                                entry: entry.clone(),
+                            })
+                            .collect(),
+                    })
+                    .collect()
+            })
+        });
+        rows.into_iter()
+    }
+}
+
+/// クリックで選択を更新（Ctrl 併用で追加・解除）
+fn select_on_click(selection: RwSignal<HashSet<PathBuf>>, path: PathBuf, event: &Event) {
+    let toggle = matches!(event, Event::PointerUp(e) if e.modifiers.control());
+    selection.update(|selection| {
+        if toggle {
+            if !selection.remove(&path) {
+                selection.insert(path);
+            }
+        } else {
+            selection.clear();
+            selection.insert(path);
+        }
+    });
+}
+
+/// 仮想化ファイル一覧ビューを作成
+///
+/// 項目のダブルクリックで `on_open` が呼ばれる。
+pub fn virtual_file_list(
+    entries: RwSignal<Vec<FileEntry>>,
+    selection: RwSignal<HashSet<PathBuf>>,
+    config: ModernFileItemConfig,
+    on_open: impl Fn(FileEntry) + 'static,
+) -> impl View {
+    virtual_file_list_with_scroll(entries, selection, RwSignal::new(0.0), config, on_open)
+}
+
+/// スクロール位置を `scroll_offset` と同期する仮想化ファイル一覧ビューを作成
+///
+/// 作成時に `scroll_offset` の位置までスクロールし、以降のスクロールを書き戻す。
+pub fn virtual_file_list_with_scroll(
+    entries: RwSignal<Vec<FileEntry>>,
+    selection: RwSignal<HashSet<PathBuf>>,
+    scroll_offset: RwSignal<f64>,
+    config: ModernFileItemConfig,
+    on_open: impl Fn(FileEntry) + 'static,
+) -> impl View {
+    let mode = config.display_mode;
+    let on_open = Rc::new(on_open);
+    // グリッドの列数は表示幅から求める（リスト表示は常に1列）
+    let columns = RwSignal::new(1usize);
+
+    let stack = virtual_stack(
+        VirtualDirection::Vertical,
+        VirtualItemSize::Fixed(Box::new(move || row_height(mode))),
+        move || {
+            let columns = match mode {
+                FileItemDisplayMode::Grid => columns.get(),
+                _ => 1,
+            };
+            FileRows::new(entries, selection, columns)
+        },
+        FileRow::key,
+        move |row| {
+            let config = config.clone();
+            let on_open = on_open.clone();
+            h_stack_from_iter(row.cells.into_iter().map(move |cell| {
+                let path = cell.entry.path.clone();
+                let drag_path = cell.entry.path.clone();
+                let entry_for_open = cell.entry.clone();
+                let on_open = on_open.clone();
+                let drag_paths = config.drag_paths;
+                let item =
+                    modern_file_item_view(cell.entry, cell.selected, config.clone())
+                        .on_click_stop(move |event| select_on_click(selection, path.clone(), event))
+                        .on_double_click_stop(move |_| on_open(entry_for_open.clone()))
+                        .on_event_stop(EventListener::DragStart, move |_| {
+                            if let Some(drag_paths) = drag_paths {
+                                drag_paths.set(selection.with_untracked(|selection| {
+                                    dragged_paths(selection, &drag_path)
+                                }));
+                            }
+                        })
+                        .on_event_stop(EventListener::DragEnd, move |_| {
+                            if let Some(drag_paths) = drag_paths {
+                                drag_paths.set(Vec::new());
+                            }
+                        });
+                if drag_paths.is_some() {
+                    item.id().draggable();
+                }
+                item.style(move |s| match mode {
+                    FileItemDisplayMode::Grid => s
+                        .width(GRID_CELL_SIZE)
+                        .height(GRID_CELL_SIZE)
+                        .items_center()
+                        .justify_center(),
+                    _ => s.width_full(),
+                })
+            }))
+            .style(move |s| s.width_full().height(row_height(mode)))
+        },
+    )
+    .style(|s| s.flex_col().width_full());
+
+    // 一覧は段階的に届くため、保存された位置まで行が揃ってから一度だけスクロールする
+    let pending_offset = RwSignal::new(Some(scroll_offset.get_untracked()).filter(|y| *y > 0.0));
+    let viewport_height = RwSignal::new(0.0);
+    scroll(stack)
+        .scroll_to(move || {
+            let target = pending_offset.get_untracked()?;
+            let rows = entries.with(|entries| entries.len().div_ceil(columns.get_untracked()));
+            let reachable = rows as f64 * row_height(mode) >= target + viewport_height.get();
+            reachable.then(|| {
+                pending_offset.set(None);
+                Point::new(0.0, target)
+            })
+        })
+        .on_scroll(move |viewport| {
+            if scroll_offset.get_untracked() != viewport.y0 {
+                scroll_offset.set(viewport.y0);
+            }
+        })
+        .on_resize(move |rect| {
+            let count = grid_columns(rect.width());
+            if columns.get_untracked() != count {
+                columns.set(count);
+            }
+            viewport_height.set(rect.height());
+        })
+        .style(|s| s.size_full())
+}
+
+/// ドラッグする項目（選択中の項目をドラッグしたら選択中のすべて、そうでなければその項目だけ）
+pub fn dragged_paths(selection: &HashSet<PathBuf>, path: &PathBuf) -> Vec<PathBuf> {
+    if !selection.contains(path) {
+        return vec![path.clone()];
+    }
+    let mut paths: Vec<PathBuf> = selection.iter().cloned().collect();
+    paths.sort();
+    paths
+}
+
+/// 差分の反映後も残っている項目だけ選択を引き継ぐ（名前変更は新しいパスへ移す）
+pub fn reconcile_selection(
+    selection: &mut HashSet<PathBuf>,
+    entries: &[FileEntry],
+    delta: &DirectoryDelta,
+) {
+    for change in &delta.changes {
+        if let DirectoryChange::Renamed { from, to } = change
+            && selection.remove(from)
+        {
+            selection.insert(to.clone());
+        }
+    }
+    let present: HashSet<&PathBuf> = entries.iter().map(|entry| &entry.path).collect();
+    selection.retain(|path| present.contains(path));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_explorer_core::FileType;
+
+    fn entry(name: &str) -> FileEntry {
+        FileEntry {
+            name: name.to_string(),
+            path: PathBuf::from("/dir").join(name),
+            file_type: FileType::File,
+            size: 0,
+            modified: None,
+        }
+    }
+
+    #[test]
+    fn test_grid_columns() {
+        assert_eq!(grid_columns(0.0), 1);
+        assert_eq!(grid_columns(GRID_CELL_SIZE * 3.5), 3);
+    }
+
+    #[test]
+    fn test_rows_slice_only_requested_range() {
+        let entries = RwSignal::new((0..100_000).map(|i| entry(&format!("{i}"))).collect());
+        let selection = RwSignal::new(HashSet::from([PathBuf::from("/dir/50001")]));
+
+        let mut rows = FileRows::new(entries, selection, 1);
+        assert_eq!(rows.total_len(), 100_000);
+        let slice: Vec<FileRow> = rows.slice(50_000..50_003).collect();
+        assert_eq!(slice.len(), 3);
+        assert_eq!(slice[1].start, 50_001);
+        assert!(slice[1].cells[0].selected);
+        assert!(!slice[0].cells[0].selected);
+
+        // グリッドは列数ごとにまとめ、最後の行は端数になる
+        let mut grid = FileRows::new(entries, selection, 3);
+        assert_eq!(grid.total_len(), 33_334);
+        let last: Vec<FileRow> = grid.slice(33_333..33_334).collect();
+        assert_eq!(last[0].start, 99_999);
+        assert_eq!(last[0].cells.len(), 1);
+    }
+
+    #[test]
+    fn test_dragged_paths() {
+        let selection = HashSet::from([PathBuf::from("/dir/b"), PathBuf::from("/dir/a")]);
+        assert_eq!(
+            dragged_paths(&selection, &PathBuf::from("/dir/b")),
+            vec![PathBuf::from("/dir/a"), PathBuf::from("/dir/b")]
+        );
+        assert_eq!(
+            dragged_paths(&selection, &PathBuf::from("/dir/c")),
+            vec![PathBuf::from("/dir/c")]
+        );
+    }
+
+    #[test]
+    fn test_reconcile_selection() {
+        let mut selection = HashSet::from([PathBuf::from("/dir/a"), PathBuf::from("/dir/b")]);
+        let entries = vec![entry("c")];
+        let delta = DirectoryDelta {
+            directory: PathBuf::from("/dir"),
+            changes: vec![
+                DirectoryChange::Renamed {
+                    from: PathBuf::from("/dir/a"),
+                    to: PathBuf::from("/dir/c"),
+                },
+                DirectoryChange::Removed(PathBuf::from("/dir/b")),
+            ],
+        };
+
+        reconcile_selection(&mut selection, &entries, &delta);
+        assert_eq!(selection, HashSet::from([PathBuf::from("/dir/c")]));
+    }
+}
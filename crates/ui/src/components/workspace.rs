@@ -0,0 +1,98 @@
+//! ワークスペース
+//!
+//! タブとペインの構成に名前を付けて保存し、あとで切り替えられるようにします。
+
+use crate::theme::get_theme;
+use floem::IntoView;
+use floem::event::{Event, EventListener};
+use floem::keyboard::{Key, NamedKey};
+use floem::menu::{Menu, MenuItem};
+use floem::reactive::{RwSignal, SignalGet, SignalUpdate};
+use floem::views::{Decorators, button, h_stack, label, text_input};
+
+use super::pane::PanesController;
+
+/// ワークスペースの切り替えと保存のバー
+///
+/// 名前をクリックすると保存されたワークスペースの一覧を開く。
+pub fn workspace_bar(controller: PanesController) -> impl IntoView {
+    let name_input = RwSignal::new(String::new());
+    let current = controller.clone();
+    let menu_controller = controller.clone();
+    let enter_controller = controller.clone();
+
+    let save = move |controller: &PanesController| {
+        let name = name_input.get_untracked().trim().to_string();
+        if !name.is_empty() {
+            controller.save_workspace(&name);
+            name_input.set(String::new());
+        }
+    };
+
+    h_stack((
+        label(|| "ワークスペース:"),
+        label(move || {
+            current
+                .workspace_name()
+                .unwrap_or_else(|| "（名前なし）".to_string())
+        })
+        .popout_menu(move || workspace_menu(&menu_controller))
+        .style(|s| {
+            let theme_arc = get_theme();
+            let theme = theme_arc.read().unwrap();
+            s.padding_horiz(theme.spacing.md)
+                .padding_vert(theme.spacing.xs)
+                .border_radius(theme.border_radius.sm)
+                .color(theme.colors.on_surface)
+                .hover(|s| s.background(theme.colors.hover))
+        }),
+        text_input(name_input)
+            .placeholder("名前を付けて保存")
+            .on_event_cont(EventListener::KeyDown, move |event| {
+                if let Event::KeyDown(key_event) = event
+                    && key_event.key.logical_key == Key::Named(NamedKey::Enter)
+                {
+                    save(&enter_controller);
+                }
+            })
+            .style(|s| {
+                let theme_arc = get_theme();
+                let theme = theme_arc.read().unwrap();
+                s.width(160.0)
+                    .padding(theme.spacing.xs)
+                    .border(1.0)
+                    .border_radius(theme.border_radius.sm)
+                    .border_color(theme.colors.border)
+            }),
+        button(label(|| "保存")).action(move || save(&controller)),
+    ))
+    .style(|s| {
+        let theme_arc = get_theme();
+        let theme = theme_arc.read().unwrap();
+        s.items_center()
+            .gap(theme.spacing.xs)
+            .font_size(theme.typography.body_small)
+            .color(theme.colors.on_surface_variant)
+    })
+}
+
+/// 保存されたワークスペースを開く・削除するメニュー
+fn workspace_menu(controller: &PanesController) -> Menu {
+    let names = controller.workspace_names();
+    let mut menu = Menu::new("");
+    if names.is_empty() {
+        menu = menu.entry(MenuItem::new("保存されたワークスペースはありません").enabled(false));
+    }
+    for name in names {
+        let open = controller.clone();
+        menu = menu.entry(MenuItem::new(name.clone()).action(move || open.open_workspace(&name)));
+    }
+    if let Some(current) = controller.workspace_name() {
+        let delete = controller.clone();
+        menu = menu.separator().entry(
+            MenuItem::new(format!("「{}」を削除", current))
+                .action(move || delete.delete_workspace(&current)),
+        );
+    }
+    menu
+}
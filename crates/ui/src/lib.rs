@@ -7,12 +7,14 @@
 pub mod app;
 pub mod components;
 pub mod layout;
+pub mod settings_reload;
 pub mod state_integration;
 pub mod theme;
 pub mod window;
 
 pub use app::App;
 pub use layout::{LayoutConfig, ResponsiveLayoutManager, ScreenSizeCategory};
+pub use settings_reload::watch_settings_file;
 pub use state_integration::{
     ReactiveStateManager, ReactiveTabState, ReactiveUiState, ReactiveWindowState, reactive_utils,
 };
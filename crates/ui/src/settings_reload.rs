@@ -0,0 +1,150 @@
+//! 設定ファイルの再読み込み
+//!
+//! 設定ディレクトリを監視し、`settings.json` が外部で編集されたら読み直して
+//! 実行中の設定へ反映します。不正な内容はエラー表示し、直前の設定を使い続けます。
+
+use crate::components::{
+    DirectoryWatch, directory_updates_signal, display_error_globally, global_directory_watcher,
+};
+use crate::theme::{ThemeVariant, switch_theme};
+use floem::reactive::{SignalWith, create_effect};
+use rust_explorer_config::{SETTINGS_FILE_NAME, Settings, SettingsChange, app_paths};
+use rust_explorer_core::{DirectoryChange, DirectoryDelta};
+use rust_explorer_utils::AppError;
+use std::cell::RefCell;
+use std::path::Path;
+use std::rc::Rc;
+
+/// 差分が設定ファイルの作成・更新を含むか（削除は無視して直前の設定を保つ）
+pub fn affects_settings_file(delta: &DirectoryDelta, settings_file: &Path) -> bool {
+    delta.changes.iter().any(|change| match change {
+        DirectoryChange::Added(path) | DirectoryChange::Modified(path) => path == settings_file,
+        DirectoryChange::Renamed { to, .. } => to == settings_file,
+        DirectoryChange::Removed(_) => false,
+    })
+}
+
+/// 変更を画面に反映（テーマ以外は `watch_settings_file` の呼び出し側と設定を参照する側が使う）
+pub fn apply_settings_changes(changes: &[SettingsChange]) {
+    for change in changes {
+        if let SettingsChange::Theme { dark } = change {
+            switch_theme(if *dark {
+                ThemeVariant::Dark
+            } else {
+                ThemeVariant::Light
+            });
+        }
+    }
+}
+
+/// 読み直した設定を実行中の設定へ取り込み、反映した変更を返す
+///
+/// 読み直しに失敗した場合はエラーを表示し、実行中の設定は変更しない。
+pub fn apply_reloaded_settings(
+    settings: &RefCell<Settings>,
+    reloaded: Result<Settings, AppError>,
+) -> Vec<SettingsChange> {
+    match reloaded {
+        Ok(reloaded) => {
+            let changes = settings.borrow_mut().apply_reloaded(&reloaded);
+            apply_settings_changes(&changes);
+            changes
+        }
+        Err(error) => {
+            display_error_globally(&error);
+            Vec::new()
+        }
+    }
+}
+
+/// 設定ファイルの監視を開始（監視できなければ None）
+///
+/// 反映した変更は `on_change` に渡す。返されたハンドルを破棄すると監視を終了する。
+pub fn watch_settings_file(
+    settings: Rc<RefCell<Settings>>,
+    on_change: impl Fn(&[SettingsChange]) + 'static,
+) -> Option<DirectoryWatch> {
+    let watcher = global_directory_watcher()?;
+    let updates = directory_updates_signal()?;
+    let config_dir = app_paths().config_dir();
+    // 初回起動時は設定ディレクトリがまだないため作成してから監視する
+    std::fs::create_dir_all(&config_dir).ok()?;
+    let settings_file = config_dir.join(SETTINGS_FILE_NAME);
+
+    let mut watch = DirectoryWatch::new(watcher);
+    watch.retarget(&config_dir);
+
+    create_effect(move |_| {
+        updates.with(|delta| {
+            if let Some(delta) = delta
+                && delta.directory == config_dir
+                && affects_settings_file(delta, &settings_file)
+            {
+                let changes = apply_reloaded_settings(&settings, Settings::reload());
+                if !changes.is_empty() {
+                    on_change(&changes);
+                }
+            }
+        });
+    });
+
+    Some(watch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_affects_settings_file() {
+        let file = PathBuf::from("/config/settings.json");
+        let delta = |change| DirectoryDelta {
+            directory: PathBuf::from("/config"),
+            changes: vec![change],
+        };
+
+        assert!(affects_settings_file(
+            &delta(DirectoryChange::Modified(file.clone())),
+            &file
+        ));
+        // エディタの一時ファイルからの置き換え
+        assert!(affects_settings_file(
+            &delta(DirectoryChange::Renamed {
+                from: PathBuf::from("/config/.settings.json.swp"),
+                to: file.clone(),
+            }),
+            &file
+        ));
+        assert!(!affects_settings_file(
+            &delta(DirectoryChange::Removed(file.clone())),
+            &file
+        ));
+        assert!(!affects_settings_file(
+            &delta(DirectoryChange::Modified(PathBuf::from(
+                "/config/other.json"
+            ))),
+            &file
+        ));
+    }
+
+    #[test]
+    fn test_invalid_reload_keeps_settings() {
+        let settings = RefCell::new(Settings::default());
+
+        let changes = apply_reloaded_settings(
+            &settings,
+            Err(AppError::Config("Invalid value".to_string())),
+        );
+        assert!(changes.is_empty());
+        assert_eq!(*settings.borrow(), Settings::default());
+
+        let reloaded = Settings {
+            min_window_width: 500,
+            ..Settings::default()
+        };
+        let changes = apply_reloaded_settings(&settings, Ok(reloaded));
+        assert_eq!(changes.len(), 1);
+        assert_eq!(settings.borrow().min_window_size(), (500, 600));
+    }
+}
@@ -1,7 +1,8 @@
 //! floem RwSignalを使ったUI状態統合
 
-use floem::reactive::{RwSignal, SignalGet, SignalUpdate};
-use rust_explorer_core::{AppState, StateManager, TabState, UiState, WindowState};
+use floem::ext_event::create_signal_from_channel;
+use floem::reactive::{RwSignal, SignalGet, SignalUpdate, SignalWith, create_effect};
+use rust_explorer_core::{AppState, SplitDirection, StateManager, TabState, UiState, WindowState};
 use rust_explorer_utils::AppError;
 
 /// UIとfloemの状態を結合するリアクティブラッパー
@@ -16,8 +17,12 @@ pub struct ReactiveStateManager {
 impl ReactiveStateManager {
     /// 新しいリアクティブ状態管理マネージャーを作成
     pub fn new(initial_state: AppState) -> Self {
-        let state_manager = StateManager::with_state(initial_state.clone());
-        let reactive_state = RwSignal::new(initial_state);
+        Self::from_state_manager(StateManager::with_state(initial_state))
+    }
+
+    /// 既存の状態管理マネージャーをリアクティブにする（アプリ本体と状態を共有する場合に使う）
+    pub fn from_state_manager(state_manager: StateManager) -> Self {
+        let reactive_state = RwSignal::new(state_manager.get_state().unwrap_or_default());
 
         let manager = Self {
             state_manager,
@@ -45,6 +50,13 @@ impl ReactiveStateManager {
         &self.state_manager
     }
 
+    /// 最新の状態をすぐにリアクティブ状態へ反映（UIスレッドからの変更直後に使う）
+    pub fn refresh(&self) {
+        if let Ok(state) = self.state_manager.get_state() {
+            self.reactive_state.set(state);
+        }
+    }
+
     /// ウィンドウ状態を更新
     pub fn update_window_state(&self, window_state: WindowState) -> Result<(), AppError> {
         self.state_manager.update_window_state(window_state)
@@ -65,22 +77,98 @@ impl ReactiveStateManager {
         self.state_manager.set_active_tab(tab_id)
     }
 
+    /// タブを複製
+    pub fn duplicate_tab(&self, tab_id: &str) -> Result<TabState, AppError> {
+        self.state_manager.duplicate_tab(tab_id)
+    }
+
+    /// タブを指定位置へ移動
+    pub fn move_tab(&self, tab_id: &str, index: usize) -> Result<(), AppError> {
+        self.state_manager.move_tab(tab_id, index)
+    }
+
+    /// 最後に閉じたタブを開き直す
+    pub fn reopen_closed_tab(&self) -> Result<Option<TabState>, AppError> {
+        self.state_manager.reopen_closed_tab()
+    }
+
+    /// タブを更新
+    pub fn update_tab<F>(&self, tab_id: &str, updater: F) -> Result<(), AppError>
+    where
+        F: FnOnce(&mut TabState),
+    {
+        self.state_manager.update_tab(tab_id, updater)
+    }
+
+    /// ペインのレイアウトを整える
+    pub fn ensure_pane_layout(&self) -> Result<(), AppError> {
+        self.state_manager.ensure_pane_layout()
+    }
+
+    /// ペインを分割
+    pub fn split_pane(
+        &self,
+        pane_id: &str,
+        direction: SplitDirection,
+        tab: TabState,
+    ) -> Result<String, AppError> {
+        self.state_manager.split_pane(pane_id, direction, tab)
+    }
+
+    /// ペインを閉じる
+    pub fn close_pane(&self, pane_id: &str) -> Result<(), AppError> {
+        self.state_manager.close_pane(pane_id)
+    }
+
+    /// ペインの大きさを変更
+    pub fn resize_pane(&self, pane_id: &str, flex: f64) -> Result<(), AppError> {
+        self.state_manager.resize_pane(pane_id, flex)
+    }
+
+    /// ペインにフォーカス
+    pub fn focus_pane(&self, pane_id: &str) -> Result<(), AppError> {
+        self.state_manager.focus_pane(pane_id)
+    }
+
+    /// タブを別のペインへ移動
+    pub fn move_tab_to_pane(&self, tab_id: &str, pane_id: &str) -> Result<(), AppError> {
+        self.state_manager.move_tab_to_pane(tab_id, pane_id)
+    }
+
+    /// 状態全体を置き換える
+    pub fn replace_state(&self, state: AppState) -> Result<(), AppError> {
+        self.state_manager.replace_state(state)
+    }
+
     /// UI状態を更新
     pub fn update_ui_state(&self, ui_state: UiState) -> Result<(), AppError> {
         self.state_manager.update_ui_state(ui_state)
     }
 
     /// 状態変更イベントの同期設定
+    ///
+    /// コールバックは任意のスレッドから呼ばれうるため、イベントをチャネル経由で
+    /// UIスレッドへ渡し、そこで最新の状態を読み直す。
     fn setup_state_sync(&self) {
-        let _reactive_state = self.reactive_state;
+        let (sender, receiver) = crossbeam_channel::unbounded();
 
-        if let Err(e) = self.state_manager.on_state_change(move |_event| {
-            // 状態が変更されたときにリアクティブ状態を更新
-            // 注意: ここでstate_managerから最新の状態を取得する必要がある
-            // 実装上の制約で直接アクセスできないため、別の方法を検討
+        if let Err(e) = self.state_manager.on_state_change(move |event| {
+            let _ = sender.send(event.clone());
         }) {
             eprintln!("Failed to setup state sync: {}", e);
+            return;
         }
+
+        let events = create_signal_from_channel(receiver);
+        let state_manager = self.state_manager.clone();
+        let reactive_state = self.reactive_state;
+        create_effect(move |_| {
+            if events.with(|event| event.is_some())
+                && let Ok(state) = state_manager.get_state()
+            {
+                reactive_state.set(state);
+            }
+        });
     }
 }
 
@@ -210,6 +298,28 @@ impl ReactiveTabState {
         }
     }
 
+    /// アプリケーション状態のタブ一覧とアクティブタブを反映
+    pub fn sync(&self, state: &AppState) {
+        self.signal.set(state.tabs.clone());
+        self.active_tab_id.set(state.active_tab_id.clone());
+    }
+
+    /// アプリケーション状態のうち1つのペインのタブとアクティブタブを反映
+    pub fn sync_pane(&self, state: &AppState, pane_id: &str) {
+        let tabs: Vec<TabState> = state.pane_tabs(pane_id).into_iter().cloned().collect();
+        let active_tab_id = state.pane(pane_id).and_then(|p| p.active_tab_id.clone());
+        // 変わっていなければ書き込まず、他のペインの変更でタブを作り直さない
+        if self.signal.with_untracked(|current| *current != tabs) {
+            self.signal.set(tabs);
+        }
+        if self
+            .active_tab_id
+            .with_untracked(|current| *current != active_tab_id)
+        {
+            self.active_tab_id.set(active_tab_id);
+        }
+    }
+
     pub fn set_active_tab(&self, tab_id: &str) {
         let tab_id = tab_id.to_string();
 
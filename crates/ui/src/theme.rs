@@ -340,18 +340,31 @@ use std::sync::{Arc, RwLock};
 
 static GLOBAL_THEME: std::sync::OnceLock<Arc<RwLock<Theme>>> = std::sync::OnceLock::new();
 
+thread_local! {
+    /// テーマの変更通知（どのビューのスコープにも属さず、破棄されない）
+    static THEME_CHANGED: floem::reactive::Trigger =
+        floem::reactive::Scope::new().create_trigger();
+}
+
 /// グローバルテーマを取得
+///
+/// スタイルの中で呼ぶとテーマの変更を購読し、`set_theme` で再スタイルされる。
 pub fn get_theme() -> Arc<RwLock<Theme>> {
-    GLOBAL_THEME
-        .get_or_init(|| Arc::new(RwLock::new(Theme::default())))
-        .clone()
+    THEME_CHANGED.with(|trigger| trigger.track());
+    global_theme().clone()
+}
+
+/// グローバルテーマの本体（変更を購読しない）
+fn global_theme() -> &'static Arc<RwLock<Theme>> {
+    GLOBAL_THEME.get_or_init(|| Arc::new(RwLock::new(Theme::default())))
 }
 
 /// グローバルテーマを設定
 pub fn set_theme(theme: Theme) {
-    if let Ok(mut global_theme) = get_theme().write() {
+    if let Ok(mut global_theme) = global_theme().write() {
         *global_theme = theme;
     }
+    THEME_CHANGED.with(|trigger| trigger.notify());
 }
 
 /// テーマバリアントを切り替え
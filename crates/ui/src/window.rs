@@ -12,6 +12,7 @@
 //!
 //! ### 実装された解決策
 //! - リサイズイベントハンドラーでの最小サイズチェック
+//! - ルートビューのレイアウトへの最小サイズの適用（設定の再読み込みにも追従）
 //! - 制約違反時の警告メッセージ表示
 //! - ユーザーインターフェースでの制限事項の明示
 //!
@@ -20,21 +21,36 @@
 //! - floem-winit直接使用による高度な制御
 //! - カスタムウィンドウマネージャーの実装
 
+use crate::components::main_content::default_directory;
 use crate::components::{
-    default_main_content, default_modern_header, default_modern_sidebar, default_status_bar,
+    BookmarksController, HistoryController, MainContentConfig, ModernSidebar, RecoveryChoice,
+    SessionRecovery, SmartFoldersController, TagsController, UndoController, default_job_panel,
+    default_modern_header, default_status_bar, display_error_globally, main_content_component,
+    recovery_screen,
 };
+use crate::settings_reload::watch_settings_file;
 use floem::event::{Event, EventListener};
-use floem::kurbo::Size;
+use floem::kurbo::{Point, Size};
 use floem::prelude::*;
 use floem::window::WindowConfig;
-use rust_explorer_config::Settings;
+use rust_explorer_config::{Settings, SettingsChange};
+use rust_explorer_core::{SearchIndexer, StateManager};
 use rust_explorer_utils::AppError;
 use std::cell::RefCell;
 use std::rc::Rc;
+use std::sync::Arc;
 
 /// メインウィンドウの状態
 pub struct WindowState {
     pub settings: Rc<RefCell<Settings>>,
+    /// タブ・ペイン・ウィンドウ位置などのセッション状態
+    pub state_manager: StateManager,
+    /// 起動時に選んでもらうセッションの復元候補
+    pub recovery: Option<SessionRecovery>,
+    /// クイックオープンで使う検索インデックス
+    pub search_indexer: Option<Arc<SearchIndexer>>,
+    /// キー操作で元に戻す・やり直す履歴
+    pub undo: UndoController,
 }
 
 /// メインウィンドウ
@@ -44,19 +60,55 @@ pub struct MainWindow {
 
 impl MainWindow {
     /// 新しいメインウィンドウを作成
-    pub fn new(settings: &Settings) -> Result<Self, AppError> {
+    ///
+    /// 設定はアプリケーションと共有し、再読み込みした内容が終了時の保存にも残るようにする。
+    pub fn new(settings: Rc<RefCell<Settings>>) -> Result<Self, AppError> {
         Ok(MainWindow {
             window_state: WindowState {
-                settings: Rc::new(RefCell::new(settings.clone())),
+                settings,
+                state_manager: StateManager::new(),
+                recovery: None,
+                search_indexer: None,
+                undo: UndoController::default(),
             },
         })
     }
 
+    /// 復元したセッション状態を使う（終了時に同じマネージャーから保存する）
+    pub fn with_state_manager(mut self, state_manager: StateManager) -> Self {
+        self.window_state.state_manager = state_manager;
+        self
+    }
+
+    /// ファイル一覧の前に復元画面を表示する
+    pub fn with_recovery(mut self, recovery: Option<SessionRecovery>) -> Self {
+        self.window_state.recovery = recovery;
+        self
+    }
+
+    /// クイックオープンで検索インデックスの項目も探す
+    pub fn with_search_indexer(mut self, search_indexer: Option<Arc<SearchIndexer>>) -> Self {
+        self.window_state.search_indexer = search_indexer;
+        self
+    }
+
+    /// 元に戻す履歴をアプリケーションと共有する
+    pub fn with_undo(mut self, undo: UndoController) -> Self {
+        self.window_state.undo = undo;
+        self
+    }
+
     /// メインウィンドウのfloemビューを作成
     pub fn create_view(&self) -> impl IntoView {
         let settings = self.window_state.settings.clone();
 
-        main_window_view(settings)
+        main_window_view(
+            settings,
+            self.window_state.state_manager.clone(),
+            self.window_state.recovery.clone(),
+            self.window_state.search_indexer.clone(),
+            self.window_state.undo.clone(),
+        )
     }
 
     /// ウィンドウ設定を作成
@@ -100,37 +152,147 @@ impl MainWindow {
     /// アプリケーションを起動
     pub fn launch(self) -> Result<(), AppError> {
         let settings = self.window_state.settings.clone();
+        let state_manager = self.window_state.state_manager.clone();
+        let recovery = self.window_state.recovery;
+        let search_indexer = self.window_state.search_indexer;
+        let undo = self.window_state.undo;
 
-        floem::launch(move || main_window_view(settings));
+        floem::launch(move || {
+            main_window_view(settings, state_manager, recovery, search_indexer, undo)
+        });
 
         Ok(())
     }
 }
 
 /// メインウィンドウのビュー
-fn main_window_view(settings: Rc<RefCell<Settings>>) -> impl IntoView {
+fn main_window_view(
+    settings: Rc<RefCell<Settings>>,
+    state_manager: StateManager,
+    recovery: Option<SessionRecovery>,
+    search_indexer: Option<Arc<SearchIndexer>>,
+    undo: UndoController,
+) -> impl IntoView {
     let settings_clone = settings.clone();
+    let settings_for_move = settings.clone();
+    let state_for_resize = state_manager.clone();
+    let state_for_move = state_manager.clone();
+    // 外部で編集された設定ファイルを再起動せずに反映
+    let history = HistoryController::load(&settings.borrow().history_exclusions);
+    let min_size = RwSignal::new(settings.borrow().min_window_size());
+    let settings_watch = RefCell::new(watch_settings_file(settings.clone(), move |changes| {
+        for change in changes {
+            match change {
+                SettingsChange::MinWindowSize { width, height } => min_size.set((*width, *height)),
+                SettingsChange::HistoryExclusions(patterns) => history.set_exclusions(patterns),
+                _ => {}
+            }
+        }
+    }));
+    let bookmarks = BookmarksController::load();
+    let tags = TagsController::load(
+        state_manager.clone(),
+        settings.borrow().mirror_tags_to_xattr,
+    );
 
     v_stack((
         // モダンヘッダー部分
         default_modern_header(),
         // モダンメインコンテンツ部分（サイドバー + コンテンツ）
         h_stack((
-            // モダンサイドバー
-            default_modern_sidebar(),
-            // メインコンテンツ
-            default_main_content(settings_clone),
+            // モダンサイドバー（ブックマークと最近の場所はアクティブなタブで、
+            // スマートフォルダとタグは新しいタブとして開く）
+            ModernSidebar::with_default()
+                .with_bookmarks(bookmarks.clone())
+                .with_history(history)
+                .with_smart_folders(SmartFoldersController::load(state_manager.clone()))
+                .with_tags(tags.clone())
+                .build(),
+            // メインコンテンツ（復元候補があれば先に選んでもらう）
+            recoverable_main_content(settings_clone, state_manager, recovery, move || {
+                MainContentConfig {
+                    search_indexer: search_indexer.clone(),
+                    tags: Some(tags.clone()),
+                    bookmarks: Some(bookmarks.clone()),
+                    history: Some(history),
+                    undo: Some(undo.clone()),
+                    ..MainContentConfig::default()
+                }
+            }),
         ))
         .style(|s| s.flex().height_full()),
+        // ファイル操作ジョブパネル（ジョブがない間は非表示）
+        default_job_panel(),
         // ステータスバー部分
         default_status_bar(),
     ))
-    .style(|s| s.size_full().flex_col())
+    .style(move |s| {
+        // 最小サイズより小さいウィンドウでは画面を縮めずに切り取る
+        let (min_width, min_height) = min_size.get();
+        s.size_full()
+            .flex_col()
+            .min_width(min_width as f64)
+            .min_height(min_height as f64)
+    })
     .on_event_stop(EventListener::WindowResized, move |event| {
         if let Event::WindowResized(new_size) = event {
             handle_window_resize(&settings, *new_size);
+            record_window_geometry(&state_for_resize, &settings.borrow());
+        }
+    })
+    .on_event_stop(EventListener::WindowMoved, move |event| {
+        if let Event::WindowMoved(position) = event {
+            handle_window_move(&settings_for_move, *position);
+            record_window_geometry(&state_for_move, &settings_for_move.borrow());
         }
     })
+    .on_cleanup(move || {
+        settings_watch.borrow_mut().take();
+    })
+}
+
+/// 復元画面で選ばれた状態に切り替えてからメインコンテンツを表示する
+///
+/// メインコンテンツは表示するたびに `content_config` の設定で作る。
+fn recoverable_main_content(
+    settings: Rc<RefCell<Settings>>,
+    state_manager: StateManager,
+    recovery: Option<SessionRecovery>,
+    content_config: impl Fn() -> MainContentConfig + 'static,
+) -> impl IntoView {
+    let pending = RwSignal::new(recovery);
+
+    dyn_container(
+        move || pending.with(|recovery| recovery.is_some()),
+        move |recovering| {
+            if !recovering {
+                return main_content_component(
+                    content_config(),
+                    settings.clone(),
+                    state_manager.clone(),
+                )
+                .into_any();
+            }
+            let Some(recovery) = pending.get_untracked() else {
+                return empty().into_any();
+            };
+            let settings = settings.clone();
+            let state_manager = state_manager.clone();
+            let chosen = recovery.clone();
+            recovery_screen(recovery, move |choice: RecoveryChoice| {
+                let fallback = default_directory(&settings.borrow());
+                let result = state_manager.get_state().and_then(|current| {
+                    state_manager.replace_state(chosen.resolve(choice, current, &fallback))
+                });
+                if let Err(e) = result {
+                    display_error_globally(&e);
+                }
+                pending.set(None);
+            })
+            .into_any()
+        },
+    )
+    .style(|s| s.size_full())
 }
 
 /// ウィンドウリサイズイベントを処理
@@ -151,15 +313,51 @@ fn handle_window_resize(settings: &Rc<RefCell<Settings>>, new_size: Size) {
     // 現在は実際のサイズで設定を更新し、制約は将来のfloem更新で対応予定
     drop(settings_ref);
     let mut settings_mut = settings.borrow_mut();
+    let (x, y) = settings_mut.window_position();
+    let maximized = settings_mut.is_window_maximized();
     settings_mut.update_window_state(
         new_size.width as u32,
         new_size.height as u32,
-        None,
-        None,
-        false,
+        x,
+        y,
+        maximized,
+    );
+}
+
+/// ウィンドウの移動を設定へ反映
+fn handle_window_move(settings: &Rc<RefCell<Settings>>, position: Point) {
+    let mut settings = settings.borrow_mut();
+    let (width, height) = (settings.window_width(), settings.window_height());
+    let maximized = settings.is_window_maximized();
+    settings.update_window_state(
+        width,
+        height,
+        Some(position.x as i32),
+        Some(position.y as i32),
+        maximized,
     );
 }
 
+/// 設定のウィンドウの大きさと位置をセッション状態の形にする
+pub(crate) fn session_window_state(settings: &Settings) -> rust_explorer_core::WindowState {
+    let (x, y) = settings.window_position();
+    rust_explorer_core::WindowState {
+        width: settings.window_width() as f64,
+        height: settings.window_height() as f64,
+        x: x.map(f64::from),
+        y: y.map(f64::from),
+        maximized: settings.is_window_maximized(),
+        minimized: false,
+    }
+}
+
+/// ウィンドウの大きさと位置をセッション状態へ記録
+fn record_window_geometry(state_manager: &StateManager, settings: &Settings) {
+    if let Err(e) = state_manager.update_window_state(session_window_state(settings)) {
+        eprintln!("ウィンドウ状態の記録エラー: {}", e);
+    }
+}
+
 // レイアウトコンポーネントは crate::components モジュールに移動されました
 // create_header() -> components::header::default_header()
 // create_main_content() -> components::main_content::default_main_content()
@@ -173,14 +371,14 @@ mod tests {
     #[test]
     fn test_main_window_creation() {
         let settings = Settings::default();
-        let result = MainWindow::new(&settings);
+        let result = MainWindow::new(Rc::new(RefCell::new(settings)));
         assert!(result.is_ok());
     }
 
     #[test]
     fn test_window_config_creation() {
         let settings = Settings::default();
-        let window = MainWindow::new(&settings).unwrap();
+        let window = MainWindow::new(Rc::new(RefCell::new(settings))).unwrap();
         let _config = window.create_window_config();
 
         // 設定が正しく反映されているかテスト
@@ -194,6 +392,10 @@ mod tests {
         let settings = Settings::default();
         let window_state = WindowState {
             settings: Rc::new(RefCell::new(settings)),
+            state_manager: StateManager::new(),
+            recovery: None,
+            search_indexer: None,
+            undo: UndoController::default(),
         };
 
         let settings_ref = window_state.settings.borrow();
@@ -206,7 +408,7 @@ mod tests {
         let mut settings = Settings::default();
         settings.update_window_state(1024, 768, Some(100), Some(50), false);
 
-        let window = MainWindow::new(&settings).unwrap();
+        let window = MainWindow::new(Rc::new(RefCell::new(settings))).unwrap();
         let _config = window.create_window_config();
 
         // カスタム設定での作成が成功することを確認
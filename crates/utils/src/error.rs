@@ -97,6 +97,77 @@ pub enum AppError {
     #[error("Invalid path: {0}")]
     InvalidPath(PathBuf),
 
+    /// 操作対象が存在しないエラー
+    #[error("Path not found: {0}")]
+    NotFound(PathBuf),
+
+    /// 操作先が既に存在するエラー
+    #[error("Path already exists: {0}")]
+    AlreadyExists(PathBuf),
+
+    /// ディレクトリが空でないエラー
+    #[error("Directory is not empty: {0}")]
+    DirectoryNotEmpty(PathBuf),
+
+    /// 実行できないファイル操作（自身の配下へのコピーなど）
+    #[error("Invalid file operation: {0}")]
+    InvalidOperation(String),
+
+    /// コピー失敗
+    #[error("Failed to copy {from} to {to}: {source}")]
+    CopyFailed {
+        from: PathBuf,
+        to: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// 移動失敗
+    #[error("Failed to move {from} to {to}: {source}")]
+    MoveFailed {
+        from: PathBuf,
+        to: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// 削除失敗
+    #[error("Failed to delete {path}: {source}")]
+    DeleteFailed {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// リネーム失敗
+    #[error("Failed to rename {from} to {to}: {source}")]
+    RenameFailed {
+        from: PathBuf,
+        to: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// ゴミ箱への移動失敗
+    #[error("Failed to move {path} to the trash: {source}")]
+    TrashFailed {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// 元に戻す・やり直しができない（ファイルがその後変更された等）
+    #[error("Cannot undo or redo: {0}")]
+    UndoRefused(String),
+
+    /// 作成失敗
+    #[error("Failed to create {path}: {source}")]
+    CreateFailed {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
     /// JSON設定エラー
     #[error("JSON serialization/deserialization error: {0}")]
     Json(#[from] serde_json::Error),
@@ -151,6 +222,17 @@ impl AppError {
             AppError::Network(_) | AppError::Timeout(_) => ErrorSeverity::Error,
             AppError::Ui(_) | AppError::InvalidInput(_) => ErrorSeverity::Warning,
             AppError::InvalidPath(_) => ErrorSeverity::Warning,
+            AppError::NotFound(_)
+            | AppError::AlreadyExists(_)
+            | AppError::DirectoryNotEmpty(_)
+            | AppError::InvalidOperation(_)
+            | AppError::UndoRefused(_) => ErrorSeverity::Warning,
+            AppError::CopyFailed { .. }
+            | AppError::MoveFailed { .. }
+            | AppError::DeleteFailed { .. }
+            | AppError::RenameFailed { .. }
+            | AppError::TrashFailed { .. }
+            | AppError::CreateFailed { .. } => ErrorSeverity::Error,
             AppError::Internal(_) => ErrorSeverity::Error,
             AppError::WithMetadata { metadata, .. } => metadata.severity,
         }
@@ -159,9 +241,20 @@ impl AppError {
     /// エラーカテゴリを取得
     pub fn category(&self) -> ErrorCategory {
         match self {
-            AppError::FileSystem(_) | AppError::FileSystemCustom(_) | AppError::InvalidPath(_) => {
-                ErrorCategory::FileSystem
-            }
+            AppError::FileSystem(_)
+            | AppError::FileSystemCustom(_)
+            | AppError::InvalidPath(_)
+            | AppError::NotFound(_)
+            | AppError::AlreadyExists(_)
+            | AppError::DirectoryNotEmpty(_)
+            | AppError::InvalidOperation(_)
+            | AppError::UndoRefused(_)
+            | AppError::CopyFailed { .. }
+            | AppError::MoveFailed { .. }
+            | AppError::DeleteFailed { .. }
+            | AppError::RenameFailed { .. }
+            | AppError::TrashFailed { .. }
+            | AppError::CreateFailed { .. } => ErrorCategory::FileSystem,
             AppError::Config(_) | AppError::Json(_) => ErrorCategory::Configuration,
             AppError::Ui(_) => ErrorCategory::UserInterface,
             AppError::Network(_) => ErrorCategory::Network,
@@ -181,6 +274,21 @@ impl AppError {
             AppError::Config(_) => "設定ファイルに問題があります。".to_string(),
             AppError::Ui(_) => "画面表示でエラーが発生しました。".to_string(),
             AppError::InvalidPath(_) => "指定されたパスが無効です。".to_string(),
+            AppError::NotFound(_) => {
+                "指定されたファイルまたはフォルダが見つかりません。".to_string()
+            }
+            AppError::AlreadyExists(_) => "同じ名前の項目が既に存在します。".to_string(),
+            AppError::DirectoryNotEmpty(_) => "フォルダが空ではありません。".to_string(),
+            AppError::InvalidOperation(msg) => msg.clone(),
+            AppError::UndoRefused(_) => {
+                "ファイルがその後変更されたため、操作を元に戻せません。".to_string()
+            }
+            AppError::CopyFailed { .. } => "コピーに失敗しました。".to_string(),
+            AppError::MoveFailed { .. } => "移動に失敗しました。".to_string(),
+            AppError::DeleteFailed { .. } => "削除に失敗しました。".to_string(),
+            AppError::RenameFailed { .. } => "名前の変更に失敗しました。".to_string(),
+            AppError::TrashFailed { .. } => "ゴミ箱への移動に失敗しました。".to_string(),
+            AppError::CreateFailed { .. } => "作成に失敗しました。".to_string(),
             AppError::Json(_) => "データの読み書きでエラーが発生しました。".to_string(),
             AppError::OutOfMemory => {
                 "メモリ不足です。アプリケーションを再起動してください。".to_string()
@@ -1,19 +1,22 @@
 #![allow(clippy::result_large_err)]
 
+use rust_explorer_config::{AppPaths, init_app_paths};
 use rust_explorer_ui::App;
 use rust_explorer_utils::{
     AppResult, LogConfig, LogLevel, LogOutput, LogRotation, PanicHandlerConfig, PerformanceTimer,
     PostPanicAction, init_logging, init_panic_handler,
 };
-use std::path::PathBuf;
 use tracing::{error, info};
 
 fn main() -> AppResult<()> {
+    // 保存先の決定（--portable またはマーカーファイルでポータブルモード）
+    let paths = init_app_paths(AppPaths::detect());
+
     // ログシステムの初期化
     let log_config = LogConfig {
         level: LogLevel::Info,
         output: LogOutput::Rolling {
-            directory: PathBuf::from("./logs"),
+            directory: paths.log_dir(),
             file_prefix: "rust-explorer".to_string(),
             rotation: LogRotation::Daily,
         },
@@ -29,7 +32,7 @@ fn main() -> AppResult<()> {
     // パニックハンドラーの初期化
     let panic_config = PanicHandlerConfig {
         save_crash_reports: true,
-        crash_reports_dir: PathBuf::from("./crash_reports"),
+        crash_reports_dir: paths.crash_reports_dir(),
         include_backtrace: true,
         include_system_info: true,
         post_panic_action: PostPanicAction::Exit,
@@ -45,6 +48,7 @@ fn main() -> AppResult<()> {
     info!(
         version = env!("CARGO_PKG_VERSION"),
         config = "log_level=Info, crash_reports=enabled",
+        portable = paths.is_portable(),
         "Application starting"
     );
 